@@ -0,0 +1,49 @@
+use postflop_solver::*;
+use std::time::Instant;
+
+// Measures the time per iteration on a tree with many all-in lines.
+//
+// Run with `cargo run --release --example allin_runouts`.
+fn main() {
+    let oop_range = "66+,A8s+,A5s-A4s,AJo+,K9s+,KQo,QTs+,JTs,96s+,85s+,75s+,65s,54s";
+    let ip_range = "QQ-22,AQs-A2s,ATo+,K5s+,KJo+,Q8s+,J8s+,T7s+,96s+,86s+,75s+,64s+,53s+";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    // small stack and large raises: most raising lines end with an all-in on the flop, so the
+    // turn and river are run out without betting
+    let bet_sizes = BetSizeCandidates::try_from(("50%, a", "3x, a")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Flop,
+        starting_pot: 100,
+        effective_stack: 300,
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        max_num_bets: [5, 5, 5],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    let num_iterations = 30;
+    let start = Instant::now();
+    for t in 0..num_iterations {
+        solve_step(&game, t);
+    }
+    let elapsed = start.elapsed();
+    finalize(&mut game);
+
+    println!(
+        "{num_iterations} iterations: {:.2?} ({:.2?} per iteration)",
+        elapsed,
+        elapsed / num_iterations
+    );
+    println!("exploitability: {:.4}", compute_exploitability(&game));
+}
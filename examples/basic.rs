@@ -10,6 +10,12 @@ fn main() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: NOT_DEALT,
+        game_type: GameType::Holdem,
+        dead_cards: Vec::new(),
+        use_isomorphism: true,
+        turn_abstraction: None,    // no turn card abstraction
+        river_abstraction: None,   // no river card abstraction
+        precompute_strength: true, // precompute the hand strength tables
     };
 
     // bet sizes -> 60% of the pot, geometric size, and all-in
@@ -19,18 +25,31 @@ fn main() {
 
     let tree_config = TreeConfig {
         initial_state: BoardState::Turn,
+        ip_first: false, // OOP acts first
         starting_pot: 200,
+        initial_invested: [0, 0], // split the starting pot equally
         effective_stack: 900,
+        big_blind: 10, // used to specify and report amounts in big blinds
         rake_rate: 0.0,
         rake_cap: 0.0,
+        no_rake_check_down: false,
+        no_rake_fold: false,
+        payoff_model: PayoffModel::ChipEv, // value chips linearly
         flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()], // [OOP, IP]
         turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
         river_bet_sizes: [bet_sizes.clone(), bet_sizes],
         turn_donk_sizes: None, // use default bet sizes
         river_donk_sizes: Some(DonkSizeCandidates::try_from("50%").unwrap()),
-        add_allin_threshold: 1.5, // add all-in if (maximum bet size) <= 1.5x pot
+        bet_sizes_fn: None,          // use the bet sizes of each street
+        max_num_bets: [0, 0, 0],     // no limit on the number of bets
+        max_num_raises: [0, 0, 0],   // no limit on the number of raises
+        bet_rounding: 0,             // do not round bet amounts
+        min_bet_ratio: 0.0,          // no minimum bet size other than the rules
+        add_allin_threshold: 1.5,    // add all-in if (maximum bet size) <= 1.5x pot
         force_allin_threshold: 0.15, // force all-in if (SPR after the opponent's call) <= 0.15
         merging_threshold: 0.1,
+        max_num_bet_sizes: 0,   // no limit on the number of bet sizes
+        max_depth_street: None, // build the full tree
     };
 
     // build the game tree
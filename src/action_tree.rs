@@ -1,8 +1,19 @@
 use crate::bet_size::*;
 use crate::mutex_like::*;
+use crate::range::card_to_string;
+use std::fmt;
+use std::sync::Arc;
 
 #[cfg(feature = "bincode")]
-use bincode::{Decode, Encode};
+use bincode::{
+    de::{BorrowDecoder, Decoder},
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+    BorrowDecode, Decode, Encode,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 pub(crate) const PLAYER_OOP: u8 = 0;
 pub(crate) const PLAYER_IP: u8 = 1;
@@ -42,10 +53,30 @@ pub enum Action {
     Chance(u8),
 }
 
+impl fmt::Display for Action {
+    /// Formats the action as a human-readable label (e.g., `"Check"`, `"Bet 50"`, `"Chance Qc"`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Action::None => write!(f, "None"),
+            Action::Fold => write!(f, "Fold"),
+            Action::Check => write!(f, "Check"),
+            Action::Call => write!(f, "Call"),
+            Action::Bet(amount) => write!(f, "Bet {amount}"),
+            Action::Raise(amount) => write!(f, "Raise {amount}"),
+            Action::AllIn(amount) => write!(f, "All-in {amount}"),
+            Action::Chance(card) => match card_to_string(card) {
+                Ok(card) => write!(f, "Chance {card}"),
+                Err(_) => write!(f, "Chance {card}"),
+            },
+        }
+    }
+}
+
 /// An enum representing the board state.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BoardState {
     #[default]
     Flop = 0,
@@ -53,6 +84,118 @@ pub enum BoardState {
     River = 2,
 }
 
+/// An enum representing how the chips won at the terminal nodes are valued.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PayoffModel {
+    /// Chips are valued linearly (default).
+    #[default]
+    ChipEv,
+
+    /// Chips are converted to the tournament equity by the Independent Chip Model
+    /// (Malmuth-Harville).
+    ///
+    /// `stacks` is the stacks of all remaining players at the beginning of the game tree,
+    /// excluding the starting pot: `stacks[0]` is OOP, `stacks[1]` is IP, and the rest are the
+    /// players not involved in the hand. `payouts` is the prize of each finishing place.
+    ///
+    /// The resulting equity is scaled so that the total prize equals the total chips, i.e.,
+    /// the payoffs are expressed in chip equivalents.
+    Icm { stacks: Vec<i32>, payouts: Vec<f64> },
+}
+
+/// Information about a player node passed to [`BetSizesFn`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeContext {
+    /// Street of the node.
+    pub board_state: BoardState,
+
+    /// Player to act (`0` for OOP, `1` for IP).
+    pub player: usize,
+
+    /// Number of bets (the first bet and raises) on the current street before the node.
+    pub num_bets: i32,
+
+    /// Pot size after calling the previous bet, which is the reference of the pot-relative sizes.
+    pub pot: i32,
+}
+
+/// Function that determines the bet size candidates at each player node.
+///
+/// If specified in the [`TreeConfig`], the returned candidates are used instead of the bet size
+/// candidates of the street and the player, so that the sizes can depend on, e.g., the number
+/// of the previous bets. The returned candidates are checked in the same way as the bet size
+/// candidates of the configuration (e.g., the first bet sizes must not contain
+/// [`BetSize::PrevBetRelative`]), and building the tree fails if they are invalid.
+///
+/// This function cannot be serialized: the JSON serialization skips it, and saving a game with
+/// this function to a file fails.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+/// use std::sync::Arc;
+///
+/// // three sizes when leading, one size facing a bet
+/// let bet_sizes_fn = BetSizesFn(Arc::new(|context: &NodeContext| {
+///     match context.num_bets {
+///         0 => ("33%, 75%, 150%", "").try_into().unwrap(),
+///         _ => ("", "3x").try_into().unwrap(),
+///     }
+/// }));
+///
+/// let tree_config = TreeConfig {
+///     starting_pot: 100,
+///     effective_stack: 1000,
+///     bet_sizes_fn: Some(bet_sizes_fn),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct BetSizesFn(pub Arc<dyn Fn(&NodeContext) -> BetSizeCandidates + Send + Sync>);
+
+impl fmt::Debug for BetSizesFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BetSizesFn(..)")
+    }
+}
+
+impl PartialEq for BetSizesFn {
+    /// Two functions are equal only if they are the same instance.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Encode for BetSizesFn {
+    #[inline]
+    fn encode<E: Encoder>(&self, _encoder: &mut E) -> Result<(), EncodeError> {
+        Err(EncodeError::Other("bet size function cannot be serialized"))
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Decode for BetSizesFn {
+    #[inline]
+    fn decode<D: Decoder>(_decoder: &mut D) -> Result<Self, DecodeError> {
+        Err(DecodeError::Other(
+            "bet size function cannot be deserialized",
+        ))
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<'de> BorrowDecode<'de> for BetSizesFn {
+    #[inline]
+    fn borrow_decode<D: BorrowDecoder<'de>>(_decoder: &mut D) -> Result<Self, DecodeError> {
+        Err(DecodeError::Other(
+            "bet size function cannot be deserialized",
+        ))
+    }
+}
+
 /// A struct containing the game tree configuration.
 ///
 /// # Examples
@@ -64,38 +207,88 @@ pub enum BoardState {
 ///
 /// let tree_config = TreeConfig {
 ///     initial_state: BoardState::Turn,
+///     ip_first: false,
 ///     starting_pot: 200,
+///     initial_invested: [0, 0],
 ///     effective_stack: 900,
+///     big_blind: 10,
 ///     rake_rate: 0.05,
 ///     rake_cap: 30.0,
+///     no_rake_check_down: false,
+///     no_rake_fold: false,
+///     payoff_model: PayoffModel::ChipEv,
 ///     flop_bet_sizes: Default::default(),
 ///     turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
 ///     river_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
 ///     turn_donk_sizes: None,
 ///     river_donk_sizes: Some(donk_sizes),
+///     bet_sizes_fn: None,
+///     max_num_bets: [0, 0, 0],
+///     max_num_raises: [0, 0, 0],
+///     bet_rounding: 0,
+///     min_bet_ratio: 0.0,
 ///     add_allin_threshold: 1.5,
 ///     force_allin_threshold: 0.15,
 ///     merging_threshold: 0.1,
+///     max_num_bet_sizes: 0,
+///     max_depth_street: None,
 /// };
 /// ```
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TreeConfig {
     /// Initial state of the game tree (flop, turn, or river).
     pub initial_state: BoardState,
 
+    /// Whether IP acts first on each street (e.g., in some limped pots). If `false` (default),
+    /// OOP acts first as usual.
+    ///
+    /// The players are still identified as OOP (`0`) and IP (`1`) regardless of this value, so
+    /// the ranges, the bet sizes, and the results of each player are specified and obtained in
+    /// the same way.
+    pub ip_first: bool,
+
     /// Starting pot size. Must be greater than `0`.
     pub starting_pot: i32,
 
+    /// Amount of the starting pot invested by each player [OOP, IP] (set `[0, 0]` to split the
+    /// starting pot equally).
+    ///
+    /// Otherwise, both values must be non-negative, and their sum must be `starting_pot`. The
+    /// rest of the starting pot (e.g., dead money from the blinds) can be included in either value.
+    /// A player who folds forfeits the invested amount, and a player who wins the pot profits by
+    /// the amount invested by the opponent.
+    pub initial_invested: [i32; 2],
+
     /// Initial effective stack. Must be greater than `0`.
+    ///
+    /// If the players have different stacks, specify the smaller one: in a heads-up pot, the
+    /// chips exceeding the covered amount can never be put into the pot.
     pub effective_stack: i32,
 
+    /// Big blind size in chips (set `0` if not used).
+    ///
+    /// Must be positive when bet sizes are specified in big blinds (e.g., `"2.5bb"`), which are
+    /// converted into chips using this value. It is also used to report the results in big blinds.
+    pub big_blind: i32,
+
     /// Rake rate. Must be between `0.0` and `1.0`, inclusive.
     pub rake_rate: f64,
 
     /// Rake cap. Must be non-negative.
     pub rake_cap: f64,
 
+    /// Whether to waive the rake when no chips are put into the pot after the initial state
+    /// (i.e., the pot is checked down to the showdown).
+    pub no_rake_check_down: bool,
+
+    /// Whether to waive the rake when a player folds (i.e., the pot is won without a showdown).
+    pub no_rake_fold: bool,
+
+    /// How the chips won at the terminal nodes are valued.
+    pub payoff_model: PayoffModel,
+
     /// Bet size candidates of each player for the flop.
     pub flop_bet_sizes: [BetSizeCandidates; 2],
 
@@ -111,6 +304,41 @@ pub struct TreeConfig {
     /// Donk size candidates for the river (set `None` to use default sizes).
     pub river_donk_sizes: Option<DonkSizeCandidates>,
 
+    /// Function that determines the bet size candidates at each player node (set `None` to use
+    /// the bet size candidates of each street).
+    ///
+    /// If specified, the `flop_bet_sizes`, `turn_bet_sizes`, and `river_bet_sizes` are ignored.
+    /// The donk size candidates are still used when specified. See [`BetSizesFn`] for details.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub bet_sizes_fn: Option<BetSizesFn>,
+
+    /// Maximum number of bets (the first bet and raises) of each street [flop, turn, river]
+    /// (set `0` for no limit).
+    pub max_num_bets: [i32; 3],
+
+    /// Maximum number of raises (i.e., bets after the first bet) of each street
+    /// [flop, turn, river] (set `0` for no limit).
+    ///
+    /// This is checked independently from `max_num_bets`: for example, `max_num_bets = 0` and
+    /// `max_num_raises = 2` allow the first bet and two raises.
+    pub max_num_raises: [i32; 3],
+
+    /// Round bet and raise amounts to the nearest multiple of this value (set `0` to disable).
+    /// All-in amounts are not affected.
+    ///
+    /// Small bets are not dropped by the rounding: a bet amount below a half of this value is
+    /// rounded up to this value (e.g., a 10% bet into a 40-chip pot is a 10-chip bet when this
+    /// value is `10`).
+    pub bet_rounding: i32,
+
+    /// Minimum amount of the first bet of each street as a ratio to the pot (set `0.0` to
+    /// disable).
+    ///
+    /// Bet candidates below this amount are raised to it (e.g., a 10% bet becomes a 33% bet when
+    /// this value is `0.33`). Raises are not affected because their minimum amount is already
+    /// determined by the previous bet.
+    pub min_bet_ratio: f64,
+
     /// Add all-in action if the ratio of maximum bet size to the pot is below or equal to this
     /// value (set `0.0` to disable).
     pub add_allin_threshold: f64,
@@ -130,6 +358,26 @@ pub struct TreeConfig {
     ///
     /// Personal recommendation: around `0.1`
     pub merging_threshold: f64,
+
+    /// Maximum number of bet and raise sizes at each node (set `0` to disable).
+    ///
+    /// Applied after the merging: while there are more bet or raise actions than this value, the
+    /// closest pair of adjacent amounts (compared in the same way as `merging_threshold`) is found
+    /// and the smaller one is removed. Fold, check, call, and all-in actions are always kept and
+    /// are not counted.
+    pub max_num_bet_sizes: usize,
+
+    /// Street at which the game tree stops growing (set `None` to build the full tree).
+    ///
+    /// When specified, no actions are taken on this street and the later streets: once the
+    /// betting of the previous street is closed, the remaining board cards are dealt and the hand
+    /// goes to a showdown, in the same way as when both players are all-in. That is, the payoff of
+    /// the capped nodes is the range-vs-range equity of the pot. Must be later than
+    /// `initial_state`.
+    ///
+    /// This is a rough approximation that ignores the betting on the later streets (e.g., the
+    /// equity realization of weak hands is overestimated), but the game is solved much faster.
+    pub max_depth_street: Option<BoardState>,
 }
 
 /// A struct representing an abstract game tree.
@@ -155,8 +403,10 @@ pub(crate) struct ActionTreeNode {
 struct BuildTreeInfo {
     prev_action: Action,
     num_bets: i32,
+    num_raises: i32,
     allin_flag: bool,
-    oop_call_flag: bool,
+    first_player: u8,
+    first_call_flag: bool,
     stack: [i32; 2],
     prev_amount: i32,
 }
@@ -177,7 +427,25 @@ impl ActionTree {
             config,
             ..Default::default()
         };
-        ret.build_tree();
+        ret.build_tree()?;
+        Ok(ret)
+    }
+
+    /// Creates a new [`ActionTree`] with the specified configuration, and then applies the given
+    /// added and removed lines in this order.
+    #[inline]
+    pub(crate) fn with_lines(
+        config: TreeConfig,
+        added_lines: &[Vec<Action>],
+        removed_lines: &[Vec<Action>],
+    ) -> Result<Self, String> {
+        let mut ret = Self::new(config)?;
+        for line in added_lines {
+            ret.add_line(line)?;
+        }
+        for line in removed_lines {
+            ret.remove_line(line)?;
+        }
         Ok(ret)
     }
 
@@ -208,6 +476,21 @@ impl ActionTree {
         ret
     }
 
+    /// Returns the nodes where two or more bet size candidates collapsed into the same all-in
+    /// action, together with the affected candidates.
+    ///
+    /// Bet sizes exceeding the stack, or leaving less than `force_allin_threshold` behind, are
+    /// converted into the all-in action, and duplicate actions are removed, so the node may have
+    /// fewer actions than configured (e.g., at a low SPR). Each node is identified by the line of
+    /// actions from the root, excluding the chance actions (same format as [`added_lines`]). The
+    /// candidates are reported as specified, before resolving [`BetSize::SprConditional`].
+    ///
+    /// [`added_lines`]: #method.added_lines
+    #[inline]
+    pub fn collapsed_sizes(&self) -> Vec<(Vec<Action>, Vec<BetSize>)> {
+        Self::collapsed_sizes_of(&self.config, &self.root.lock())
+    }
+
     /// Adds a given line to the action tree.
     ///
     /// - `line` except the last action must exist in the current tree.
@@ -222,7 +505,7 @@ impl ActionTree {
             &mut self.root.lock(),
             line,
             removed_index.is_some(),
-            BuildTreeInfo::new(self.config.effective_stack),
+            self.new_build_tree_info(),
         )?;
         if let Some(index) = removed_index {
             self.removed_lines.remove(index);
@@ -369,7 +652,7 @@ impl ActionTree {
     /// Returns the total bet amount of each player (OOP, IP).
     #[inline]
     pub fn total_bet_amount(&self) -> [i32; 2] {
-        let info = BuildTreeInfo::new(self.config.effective_stack);
+        let info = self.new_build_tree_info();
         self.total_bet_amount_recursive(&self.root.lock(), &self.history, info)
     }
 
@@ -379,6 +662,60 @@ impl ActionTree {
         (self.config, self.added_lines, self.removed_lines, self.root)
     }
 
+    /// Returns the result of [`collapsed_sizes`] of the tree rooted at `root` built with `config`.
+    ///
+    /// [`collapsed_sizes`]: #method.collapsed_sizes
+    #[inline]
+    pub(crate) fn collapsed_sizes_of(
+        config: &TreeConfig,
+        root: &ActionTreeNode,
+    ) -> Vec<(Vec<Action>, Vec<BetSize>)> {
+        let action_tree = Self {
+            config: config.clone(),
+            ..Default::default()
+        };
+        let mut ret = Vec::new();
+        let info = action_tree.new_build_tree_info();
+        action_tree.collapsed_sizes_recursive(root, info, &mut Vec::new(), &mut ret);
+        ret
+    }
+
+    /// Returns the action that the given bet size yields at the node reached by `line`.
+    ///
+    /// The amount is computed in the same way as the bet size candidates of the configuration,
+    /// except that the all-in action is not added and no merging is applied. Chance actions must
+    /// be omitted from the `line`.
+    #[inline]
+    pub(crate) fn bet_size_action(
+        &self,
+        line: &[Action],
+        size: &BetSize,
+    ) -> Result<Action, String> {
+        let info = self.new_build_tree_info();
+        self.bet_size_action_recursive(&self.root.lock(), line, size, info)
+    }
+
+    /// Returns the root node.
+    #[inline]
+    pub(crate) fn root(&self) -> MutexGuardLike<'_, ActionTreeNode> {
+        self.root.lock()
+    }
+
+    /// Returns the player who acts first on each street.
+    #[inline]
+    fn first_player(&self) -> u8 {
+        match self.config.ip_first {
+            false => PLAYER_OOP,
+            true => PLAYER_IP,
+        }
+    }
+
+    /// Creates a new `BuildTreeInfo` for the root node.
+    #[inline]
+    fn new_build_tree_info(&self) -> BuildTreeInfo {
+        BuildTreeInfo::new(self.config.effective_stack, self.first_player())
+    }
+
     /// Returns the reference to the current node.
     #[inline]
     fn current_node(&self) -> &ActionTreeNode {
@@ -386,10 +723,10 @@ impl ActionTree {
             let mut node = &*self.root.lock() as *const ActionTreeNode;
             for action in &self.history {
                 while (*node).is_chance() {
-                    node = &*(*node).children[0].lock();
+                    node = &*(&(*node).children)[0].lock();
                 }
                 let index = (*node).actions.iter().position(|x| x == action).unwrap();
-                node = &*(*node).children[index].lock();
+                node = &*(&(*node).children)[index].lock();
             }
             &*node
         }
@@ -401,12 +738,36 @@ impl ActionTree {
         unsafe {
             let mut node = self.current_node() as *const ActionTreeNode;
             while (*node).is_chance() {
-                node = &*(*node).children[0].lock();
+                node = &*(&(*node).children)[0].lock();
             }
             &*node
         }
     }
 
+    /// Returns the player of the child node reached when `player` takes `action`.
+    ///
+    /// A bet (including raise and all-in) passes the action to the opponent, and a fold leads to
+    /// a terminal node with `PLAYER_FOLD_FLAG | player`. A call, or a check by the player who acts
+    /// last on the street, closes the street: it leads to a chance node that deals the next card,
+    /// except on the river where it leads directly to a showdown terminal node (i.e.,
+    /// `PLAYER_TERMINAL_FLAG` without the fold bits). No intermediate node is created between the
+    /// closing action and the showdown.
+    #[inline]
+    fn next_player(&self, player: u8, board_state: BoardState, action: Action) -> u8 {
+        let player_after_call = match board_state {
+            BoardState::River => PLAYER_TERMINAL_FLAG,
+            _ => PLAYER_CHANCE_FLAG | player,
+        };
+
+        match action {
+            Action::Fold => PLAYER_FOLD_FLAG | player,
+            Action::Check if player == self.first_player() => player ^ 1,
+            Action::Check | Action::Call => player_after_call,
+            Action::Bet(_) | Action::Raise(_) | Action::AllIn(_) => player ^ 1,
+            _ => panic!("Unexpected action: {action:?}"),
+        }
+    }
+
     /// Checks the configuration.
     #[inline]
     fn check_config(config: &TreeConfig) -> Result<(), String> {
@@ -417,6 +778,16 @@ impl ActionTree {
             ));
         }
 
+        if config.initial_invested != [0, 0]
+            && (config.initial_invested.iter().any(|&x| x < 0)
+                || config.initial_invested[0] + config.initial_invested[1] != config.starting_pot)
+        {
+            return Err(format!(
+                "Initial invested amounts must be non-negative and sum up to the starting pot: {:?}",
+                config.initial_invested
+            ));
+        }
+
         if config.effective_stack <= 0 {
             return Err(format!(
                 "Effective stack must be positive: {}",
@@ -424,6 +795,13 @@ impl ActionTree {
             ));
         }
 
+        if config.big_blind < 0 {
+            return Err(format!(
+                "Big blind must be non-negative: {}",
+                config.big_blind
+            ));
+        }
+
         if config.rake_rate < 0.0 {
             return Err(format!(
                 "Rake rate must be non-negative: {}",
@@ -445,6 +823,59 @@ impl ActionTree {
             ));
         }
 
+        if let PayoffModel::Icm { stacks, payouts } = &config.payoff_model {
+            if stacks.len() < 2 || stacks.len() > 10 {
+                return Err(format!(
+                    "Number of ICM stacks must be between 2 and 10: {}",
+                    stacks.len()
+                ));
+            }
+
+            if stacks.iter().any(|&s| s <= 0) {
+                return Err(format!("ICM stacks must be positive: {stacks:?}"));
+            }
+
+            if stacks[0] < config.effective_stack || stacks[1] < config.effective_stack {
+                return Err(format!(
+                    "ICM stacks of OOP and IP must be at least the effective stack: {stacks:?}"
+                ));
+            }
+
+            if payouts.iter().any(|&p| p < 0.0) || payouts.iter().all(|&p| p <= 0.0) {
+                return Err(format!(
+                    "ICM payouts must be non-negative and have a positive value: {payouts:?}"
+                ));
+            }
+        }
+
+        if config.max_num_bets.iter().any(|&n| n < 0) {
+            return Err(format!(
+                "Maximum number of bets must be non-negative: {:?}",
+                config.max_num_bets
+            ));
+        }
+
+        if config.max_num_raises.iter().any(|&n| n < 0) {
+            return Err(format!(
+                "Maximum number of raises must be non-negative: {:?}",
+                config.max_num_raises
+            ));
+        }
+
+        if config.bet_rounding < 0 {
+            return Err(format!(
+                "Bet rounding must be non-negative: {}",
+                config.bet_rounding
+            ));
+        }
+
+        if config.min_bet_ratio < 0.0 {
+            return Err(format!(
+                "Minimum bet ratio must be non-negative: {}",
+                config.min_bet_ratio
+            ));
+        }
+
         if config.add_allin_threshold < 0.0 {
             return Err(format!(
                 "Add all-in threshold must be non-negative: {}",
@@ -466,20 +897,151 @@ impl ActionTree {
             ));
         }
 
+        if let Some(street) = config.max_depth_street {
+            if street <= config.initial_state {
+                return Err(format!(
+                    "Maximum depth street must be later than the initial state: {street:?}"
+                ));
+            }
+        }
+
+        // the pot of the first action of each street is at least the starting pot (when the
+        // previous streets are checked through, the pot is exactly the starting pot)
+        let streets = [
+            (BoardState::Flop, "flop", &config.flop_bet_sizes, &None),
+            (
+                BoardState::Turn,
+                "turn",
+                &config.turn_bet_sizes,
+                &config.turn_donk_sizes,
+            ),
+            (
+                BoardState::River,
+                "river",
+                &config.river_bet_sizes,
+                &config.river_donk_sizes,
+            ),
+        ];
+
+        for (board_state, street, bet_sizes, donk_sizes) in streets {
+            if board_state < config.initial_state {
+                continue;
+            }
+
+            let all_sizes = bet_sizes
+                .iter()
+                .flat_map(|c| c.bet.iter().chain(c.cbet.iter().flatten()).chain(&c.raise))
+                .chain(donk_sizes.iter().flat_map(|d| &d.donk));
+
+            let donk_sizes = donk_sizes.iter().map(|d| ("donk", &d.donk));
+            let sizes = [("OOP", &bet_sizes[0].bet), ("IP", &bet_sizes[1].bet)];
+            let cbet_sizes = [
+                ("OOP cbet", &bet_sizes[0].cbet),
+                ("IP cbet", &bet_sizes[1].cbet),
+            ]
+            .into_iter()
+            .filter_map(|(name, sizes)| sizes.as_ref().map(|sizes| (name, sizes)));
+            let first_bet_sizes = sizes.into_iter().chain(cbet_sizes).chain(donk_sizes);
+
+            Self::check_bet_sizes(
+                config,
+                street,
+                all_sizes,
+                first_bet_sizes,
+                config.starting_pot,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the bet sizes of the given street.
+    ///
+    /// `all_sizes` are all the bet size candidates of the street, and `first_bet_sizes` are the
+    /// named lists of the candidates of the first bet, which are checked against the given `pot`.
+    fn check_bet_sizes<'a>(
+        config: &TreeConfig,
+        street: &str,
+        all_sizes: impl Iterator<Item = &'a BetSize> + Clone,
+        first_bet_sizes: impl Iterator<Item = (&'a str, &'a Vec<BetSize>)>,
+        pot: i32,
+    ) -> Result<(), String> {
+        for size in all_sizes.clone() {
+            if let BetSize::SprConditional(sizes) = size {
+                if sizes.iter().any(|(threshold, _)| threshold.is_nan()) {
+                    return Err(format!("SPR threshold on the {street} must not be NaN"));
+                }
+                if sizes
+                    .iter()
+                    .any(|(_, size)| matches!(size, BetSize::SprConditional(_)))
+                {
+                    return Err(format!(
+                        "SPR-conditional bet size on the {street} must not be nested"
+                    ));
+                }
+            }
+        }
+
+        for size in expand_bet_sizes(all_sizes) {
+            if let &BetSize::BigBlinds(big_blinds) = size {
+                if config.big_blind == 0 {
+                    return Err(format!(
+                        "Big blind must be positive to use bet size {big_blinds}bb on the {street}"
+                    ));
+                }
+                if (big_blinds * config.big_blind as f64).round() < 1.0 {
+                    return Err(format!(
+                        "Bet size {big_blinds}bb on the {street} is below 1 chip"
+                    ));
+                }
+            }
+        }
+
+        for (name, sizes) in first_bet_sizes {
+            for size in expand_bet_sizes(sizes) {
+                match *size {
+                    BetSize::PotRelative(ratio) => {
+                        let amount = pot as f64 * ratio.max(config.min_bet_ratio);
+                        if amount < 1.0 {
+                            return Err(format!(
+                                "Bet size {:.2}% ({name}) on the {street} is below the minimum \
+                                 bet: {amount:.2} chips",
+                                ratio * 100.0
+                            ));
+                        }
+                    }
+                    BetSize::PrevBetRelative(ratio) => {
+                        return Err(format!(
+                            "Bet size {ratio}x ({name}) on the {street} must not be relative to \
+                             the previous bet"
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
         Ok(())
     }
 
     /// Builds the action tree.
     #[inline]
-    fn build_tree(&mut self) {
+    fn build_tree(&mut self) -> Result<(), String> {
         let mut root = self.root.lock();
         *root = ActionTreeNode::default();
+        root.player = self.first_player();
         root.board_state = self.config.initial_state;
-        self.build_tree_recursive(&mut root, BuildTreeInfo::new(self.config.effective_stack));
+        self.build_tree_recursive(&mut root, self.new_build_tree_info())
     }
 
     /// Recursively builds the action tree.
-    fn build_tree_recursive(&self, node: &mut ActionTreeNode, info: BuildTreeInfo) {
+    ///
+    /// Returns an error if the bet size function returns invalid candidates.
+    fn build_tree_recursive(
+        &self,
+        node: &mut ActionTreeNode,
+        info: BuildTreeInfo,
+    ) -> Result<(), String> {
         if node.is_terminal() {
             // do nothing
         } else if node.is_chance() {
@@ -489,9 +1051,15 @@ impl ActionTree {
                 BoardState::River => unreachable!(),
             };
 
-            let next_player = match (info.allin_flag, node.board_state) {
-                (false, _) => PLAYER_OOP,
-                (true, BoardState::Flop) => PLAYER_CHANCE_FLAG | PLAYER_CHANCE,
+            // the board is run out without betting after an all-in or at the maximum depth
+            let is_capped = self
+                .config
+                .max_depth_street
+                .is_some_and(|street| next_state >= street);
+
+            let next_player = match (info.allin_flag || is_capped, next_state) {
+                (false, _) => self.first_player(),
+                (true, BoardState::Turn) => PLAYER_CHANCE_FLAG | PLAYER_CHANCE,
                 (true, _) => PLAYER_TERMINAL_FLAG,
             };
 
@@ -506,20 +1074,30 @@ impl ActionTree {
             self.build_tree_recursive(
                 &mut node.children[0].lock(),
                 info.create_next(0, Action::Chance(0)),
-            );
+            )?;
         } else {
-            self.push_actions(node, &info);
+            self.push_actions(node, &info)?;
             for (action, child) in node.actions.iter().zip(node.children.iter()) {
                 self.build_tree_recursive(
                     &mut child.lock(),
                     info.create_next(node.player, *action),
-                );
+                )?;
             }
         }
+
+        Ok(())
     }
 
     /// Pushes all possible actions to the given node.
-    fn push_actions(&self, node: &mut ActionTreeNode, info: &BuildTreeInfo) {
+    ///
+    /// Returns the bet size candidates that collapsed into the same all-in action, if there are
+    /// two or more such candidates, or an error if the candidates returned by the bet size
+    /// function are invalid.
+    fn push_actions(
+        &self,
+        node: &mut ActionTreeNode,
+        info: &BuildTreeInfo,
+    ) -> Result<Vec<BetSize>, String> {
         let player = node.player;
         let opponent = node.player ^ 1;
 
@@ -538,6 +1116,14 @@ impl ActionTree {
             (pot as f64 * ratio.min(max_ratio)).round() as i32
         };
 
+        let big_blinds_to_chips =
+            |big_blinds: f64| (big_blinds * self.config.big_blind as f64).round() as i32;
+
+        let max_num_bets = self.config.max_num_bets[node.board_state as usize];
+        let max_num_raises = self.config.max_num_raises[node.board_state as usize];
+        let can_raise = (max_num_bets == 0 || info.num_bets < max_num_bets)
+            && (max_num_raises == 0 || info.num_raises < max_num_raises);
+
         let (candidates, donk_candidates, num_remaining_streets) = match node.board_state {
             BoardState::Flop => (&self.config.flop_bet_sizes, &None, 3),
             BoardState::Turn => (&self.config.turn_bet_sizes, &self.config.turn_donk_sizes, 2),
@@ -548,18 +1134,57 @@ impl ActionTree {
             ),
         };
 
+        let custom_candidates = self.config.bet_sizes_fn.as_ref().map(|bet_sizes_fn| {
+            (bet_sizes_fn.0)(&NodeContext {
+                board_state: node.board_state,
+                player: player as usize,
+                num_bets: info.num_bets,
+                pot,
+            })
+        });
+        if let Some(custom) = &custom_candidates {
+            let street = match node.board_state {
+                BoardState::Flop => "flop",
+                BoardState::Turn => "turn",
+                BoardState::River => "river",
+            };
+            let all_sizes = custom
+                .bet
+                .iter()
+                .chain(custom.cbet.iter().flatten())
+                .chain(&custom.raise);
+            let first_bet_sizes = [("bet size function", &custom.bet)].into_iter().chain(
+                custom
+                    .cbet
+                    .iter()
+                    .map(|sizes| ("bet size function cbet", sizes)),
+            );
+            Self::check_bet_sizes(&self.config, street, all_sizes, first_bet_sizes, pot)?;
+        }
+
+        let candidates = custom_candidates
+            .as_ref()
+            .unwrap_or(&candidates[player as usize]);
+
         let mut actions = Vec::new();
 
+        // indices of the actions derived from the bet size candidates, with the sizes
+        let mut sources = Vec::new();
+
         if donk_candidates.is_some()
             && matches!(info.prev_action, Action::Chance(_))
-            && info.oop_call_flag
+            && info.first_call_flag
         {
             // check
             actions.push(Action::Check);
 
             // donk bet
-            for &donk_size in &donk_candidates.as_ref().unwrap().donk {
-                match donk_size {
+            for size in &donk_candidates.as_ref().unwrap().donk {
+                let Some(donk_size) = resolve_bet_size(size, spr_after_call) else {
+                    continue;
+                };
+                let num_actions = actions.len();
+                match *donk_size {
                     BetSize::PotRelative(ratio) => {
                         let amount = (pot as f64 * ratio).round() as i32;
                         actions.push(Action::Bet(amount));
@@ -575,11 +1200,21 @@ impl ActionTree {
                         actions.push(Action::Bet(amount));
                     }
                     BetSize::AllIn => actions.push(Action::AllIn(max_amount)),
+                    BetSize::Constant(amount) => actions.push(Action::Bet(amount)),
+                    BetSize::BigBlinds(big_blinds) => {
+                        actions.push(Action::Bet(big_blinds_to_chips(big_blinds)));
+                    }
+                    BetSize::SprConditional(_) => panic!("Unexpected nested `SprConditional`"),
+                }
+                if actions.len() > num_actions {
+                    sources.push((num_actions, size));
                 }
             }
 
             // all-in
-            if max_amount <= (pot as f64 * self.config.add_allin_threshold).round() as i32 {
+            if candidates.add_all_in
+                || max_amount <= (pot as f64 * self.config.add_allin_threshold).round() as i32
+            {
                 actions.push(Action::AllIn(max_amount));
             }
         } else if matches!(
@@ -589,9 +1224,18 @@ impl ActionTree {
             // check
             actions.push(Action::Check);
 
-            // bet
-            for &bet_size in &candidates[player as usize].bet {
-                match bet_size {
+            // bet (or continuation bet after the opponent checked)
+            let bet_sizes = match (&info.prev_action, &candidates.cbet) {
+                (Action::Check, Some(cbet_sizes)) => cbet_sizes,
+                _ => &candidates.bet,
+            };
+
+            for size in bet_sizes {
+                let Some(bet_size) = resolve_bet_size(size, spr_after_call) else {
+                    continue;
+                };
+                let num_actions = actions.len();
+                match *bet_size {
                     BetSize::PotRelative(ratio) => {
                         let amount = (pot as f64 * ratio).round() as i32;
                         actions.push(Action::Bet(amount));
@@ -607,11 +1251,21 @@ impl ActionTree {
                         actions.push(Action::Bet(amount));
                     }
                     BetSize::AllIn => actions.push(Action::AllIn(max_amount)),
+                    BetSize::Constant(amount) => actions.push(Action::Bet(amount)),
+                    BetSize::BigBlinds(big_blinds) => {
+                        actions.push(Action::Bet(big_blinds_to_chips(big_blinds)));
+                    }
+                    BetSize::SprConditional(_) => panic!("Unexpected nested `SprConditional`"),
+                }
+                if actions.len() > num_actions {
+                    sources.push((num_actions, size));
                 }
             }
 
             // all-in
-            if max_amount <= (pot as f64 * self.config.add_allin_threshold).round() as i32 {
+            if candidates.add_all_in
+                || max_amount <= (pot as f64 * self.config.add_allin_threshold).round() as i32
+            {
                 actions.push(Action::AllIn(max_amount));
             }
         } else {
@@ -621,13 +1275,17 @@ impl ActionTree {
             // call
             actions.push(Action::Call);
 
-            if !info.allin_flag {
+            if !info.allin_flag && can_raise {
                 // raise
-                for &bet_size in &candidates[player as usize].raise {
-                    match bet_size {
+                for size in &candidates.raise {
+                    let Some(bet_size) = resolve_bet_size(size, spr_after_call) else {
+                        continue;
+                    };
+                    let num_actions = actions.len();
+                    match *bet_size {
                         BetSize::PotRelative(ratio) => {
-                            let amount = prev_amount + (pot as f64 * ratio).round() as i32;
-                            actions.push(Action::Raise(amount));
+                            let amount = (pot as f64 * ratio).round() as i32;
+                            actions.push(Action::Raise(prev_amount.saturating_add(amount)));
                         }
                         BetSize::PrevBetRelative(ratio) => {
                             let amount = (prev_amount as f64 * ratio).round() as i32;
@@ -635,7 +1293,7 @@ impl ActionTree {
                         }
                         BetSize::Additive(adder, raise_cap) => {
                             if raise_cap == 0 || info.num_bets <= raise_cap {
-                                actions.push(Action::Raise(prev_amount + adder));
+                                actions.push(Action::Raise(prev_amount.saturating_add(adder)));
                             }
                         }
                         BetSize::Geometric(num_streets, max_ratio) => {
@@ -647,12 +1305,22 @@ impl ActionTree {
                             actions.push(Action::Raise(prev_amount + amount));
                         }
                         BetSize::AllIn => actions.push(Action::AllIn(max_amount)),
+                        BetSize::Constant(amount) => actions.push(Action::Raise(amount)),
+                        BetSize::BigBlinds(big_blinds) => {
+                            actions.push(Action::Raise(big_blinds_to_chips(big_blinds)));
+                        }
+                        BetSize::SprConditional(_) => panic!("Unexpected nested `SprConditional`"),
+                    }
+                    if actions.len() > num_actions {
+                        sources.push((num_actions, size));
                     }
                 }
 
                 // all-in
                 let allin_threshold = pot as f64 * self.config.add_allin_threshold;
-                if max_amount <= prev_amount + allin_threshold.round() as i32 {
+                if candidates.add_all_in
+                    || max_amount <= prev_amount + allin_threshold.round() as i32
+                {
                     actions.push(Action::AllIn(max_amount));
                 }
             }
@@ -665,11 +1333,16 @@ impl ActionTree {
             max_amount <= amount + threshold
         };
 
-        // clamp bet amounts
+        let min_bet_amount = (pot as f64 * self.config.min_bet_ratio).round() as i32;
+
+        // round and clamp bet amounts (overbets are converted to all-in only when they exceed the
+        // stack or the SPR after the call falls within `force_allin_threshold`)
         for action in actions.iter_mut() {
             match *action {
                 Action::Bet(amount) => {
-                    let clamped = amount.clamp(min_amount, max_amount);
+                    let clamped = round_bet_amount(amount, self.config.bet_rounding)
+                        .max(min_bet_amount)
+                        .clamp(min_amount, max_amount);
                     if is_above_threshold(clamped) {
                         *action = Action::AllIn(max_amount);
                     } else if clamped != amount {
@@ -677,7 +1350,8 @@ impl ActionTree {
                     }
                 }
                 Action::Raise(amount) => {
-                    let clamped = amount.clamp(min_amount, max_amount);
+                    let clamped = round_bet_amount(amount, self.config.bet_rounding)
+                        .clamp(min_amount, max_amount);
                     if is_above_threshold(clamped) {
                         *action = Action::AllIn(max_amount);
                     } else if clamped != amount {
@@ -688,6 +1362,13 @@ impl ActionTree {
             }
         }
 
+        // configured sizes that are converted into the same all-in action
+        let collapsed = sources
+            .iter()
+            .filter(|&&(index, _)| matches!(actions[index], Action::AllIn(_)))
+            .map(|&(_, size)| size.clone())
+            .collect::<Vec<_>>();
+
         // remove duplicates
         actions.sort_unstable();
         actions.dedup();
@@ -695,36 +1376,26 @@ impl ActionTree {
         // merge bet actions with close amounts
         actions = merge_bet_actions(actions, pot, prev_amount, self.config.merging_threshold);
 
-        let player_after_call = match node.board_state {
-            BoardState::River => PLAYER_TERMINAL_FLAG,
-            _ => PLAYER_CHANCE_FLAG | player,
-        };
-
-        let player_after_check = match player {
-            PLAYER_OOP => opponent,
-            _ => player_after_call,
-        };
+        // limit the number of bet sizes
+        if self.config.max_num_bet_sizes > 0 {
+            limit_bet_actions(
+                &mut actions,
+                pot,
+                prev_amount,
+                self.config.max_num_bet_sizes,
+            );
+        }
 
         // push actions
         for action in actions {
-            let mut amount = node.amount;
-            let next_player = match action {
-                Action::Fold => PLAYER_FOLD_FLAG | player,
-                Action::Check => player_after_check,
-                Action::Call => {
-                    amount += to_call;
-                    player_after_call
-                }
-                Action::Bet(_) | Action::Raise(_) | Action::AllIn(_) => {
-                    amount += to_call;
-                    opponent
-                }
-                _ => panic!("Unexpected action: {action:?}"),
+            let amount = match action {
+                Action::Fold | Action::Check => node.amount,
+                _ => node.amount + to_call,
             };
 
             node.actions.push(action);
             node.children.push(MutexLike::new(ActionTreeNode {
-                player: next_player,
+                player: self.next_player(player, node.board_state, action),
                 board_state: node.board_state,
                 amount,
                 ..Default::default()
@@ -733,33 +1404,143 @@ impl ActionTree {
 
         node.actions.shrink_to_fit();
         node.children.shrink_to_fit();
+
+        if collapsed.len() >= 2 {
+            Ok(collapsed)
+        } else {
+            Ok(Vec::new())
+        }
     }
 
-    /// Recursive function to enumerate all invalid terminal nodes.
-    fn invalid_terminals_recursive(
+    /// Recursive function to enumerate the nodes with collapsed bet sizes.
+    fn collapsed_sizes_recursive(
+        &self,
         node: &ActionTreeNode,
-        result: &mut Vec<Vec<Action>>,
+        info: BuildTreeInfo,
         line: &mut Vec<Action>,
+        result: &mut Vec<(Vec<Action>, Vec<BetSize>)>,
     ) {
         if node.is_terminal() {
             // do nothing
-        } else if node.children.is_empty() {
-            result.push(line.clone());
         } else if node.is_chance() {
-            Self::invalid_terminals_recursive(&node.children[0].lock(), result, line)
+            if let Some(child) = node.children.first() {
+                let info = info.create_next(0, Action::Chance(0));
+                self.collapsed_sizes_recursive(&child.lock(), info, line, result);
+            }
         } else {
+            // rebuild the actions of the node from the configuration
+            let mut scratch = ActionTreeNode {
+                player: node.player,
+                board_state: node.board_state,
+                amount: node.amount,
+                ..Default::default()
+            };
+            // the tree has been built with the same configuration, so this never fails
+            let collapsed = self.push_actions(&mut scratch, &info).unwrap_or_default();
+            if !collapsed.is_empty() {
+                result.push((line.clone(), collapsed));
+            }
+
             for (&action, child) in node.actions.iter().zip(node.children.iter()) {
                 line.push(action);
-                Self::invalid_terminals_recursive(&child.lock(), result, line);
+                let info = info.create_next(node.player, action);
+                self.collapsed_sizes_recursive(&child.lock(), info, line, result);
                 line.pop();
             }
         }
     }
 
-    /// Recursive function to add a given line to the tree.
-    fn add_line_recursive(
+    /// Recursive function to compute the action of a bet size at the node reached by `line`.
+    fn bet_size_action_recursive(
         &self,
-        node: &mut ActionTreeNode,
+        node: &ActionTreeNode,
+        line: &[Action],
+        size: &BetSize,
+        info: BuildTreeInfo,
+    ) -> Result<Action, String> {
+        if node.is_terminal() {
+            return Err("Unexpected terminal node".to_string());
+        }
+
+        if node.is_chance() {
+            return self.bet_size_action_recursive(
+                &node.children[0].lock(),
+                line,
+                size,
+                info.create_next(0, Action::Chance(0)),
+            );
+        }
+
+        if let Some((&action, rest)) = line.split_first() {
+            let Ok(index) = node.actions.binary_search(&action) else {
+                return Err(format!("Action does not exist: {action:?}"));
+            };
+            return self.bet_size_action_recursive(
+                &node.children[index].lock(),
+                rest,
+                size,
+                info.create_next(node.player, action),
+            );
+        }
+
+        // rebuild the actions of the node with the given size as the only candidate
+        let candidates = BetSizeCandidates {
+            bet: vec![size.clone()],
+            raise: vec![size.clone()],
+            ..Default::default()
+        };
+        let action_tree = Self {
+            config: TreeConfig {
+                turn_donk_sizes: None,
+                river_donk_sizes: None,
+                add_allin_threshold: 0.0,
+                merging_threshold: 0.0,
+                bet_sizes_fn: Some(BetSizesFn(Arc::new(move |_| candidates.clone()))),
+                ..self.config.clone()
+            },
+            ..Default::default()
+        };
+
+        let mut scratch = ActionTreeNode {
+            player: node.player,
+            board_state: node.board_state,
+            amount: node.amount,
+            ..Default::default()
+        };
+        action_tree.push_actions(&mut scratch, &info)?;
+
+        scratch
+            .actions
+            .into_iter()
+            .find(|action| matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_)))
+            .ok_or_else(|| format!("Bet size is not available at the node: {size:?}"))
+    }
+
+    /// Recursive function to enumerate all invalid terminal nodes.
+    fn invalid_terminals_recursive(
+        node: &ActionTreeNode,
+        result: &mut Vec<Vec<Action>>,
+        line: &mut Vec<Action>,
+    ) {
+        if node.is_terminal() {
+            // do nothing
+        } else if node.children.is_empty() {
+            result.push(line.clone());
+        } else if node.is_chance() {
+            Self::invalid_terminals_recursive(&node.children[0].lock(), result, line)
+        } else {
+            for (&action, child) in node.actions.iter().zip(node.children.iter()) {
+                line.push(action);
+                Self::invalid_terminals_recursive(&child.lock(), result, line);
+                line.pop();
+            }
+        }
+    }
+
+    /// Recursive function to add a given line to the tree.
+    fn add_line_recursive(
+        &self,
+        node: &mut ActionTreeNode,
         line: &[Action],
         was_removed: bool,
         info: BuildTreeInfo,
@@ -858,51 +1639,28 @@ impl ActionTree {
             };
         }
 
-        let player_after_call = match node.board_state {
-            BoardState::River => PLAYER_TERMINAL_FLAG,
-            _ => PLAYER_CHANCE_FLAG | player,
-        };
-
-        let player_after_check = match player {
-            PLAYER_OOP => opponent,
-            _ => player_after_call,
+        let amount = match action {
+            Action::Fold | Action::Check => node.amount,
+            _ => node.amount + to_call,
         };
+        let next_player = self.next_player(player, node.board_state, action);
 
-        let mut amount = node.amount;
-        let next_player = match action {
-            Action::Fold => PLAYER_FOLD_FLAG | player,
-            Action::Check => player_after_check,
-            Action::Call => {
-                amount += to_call;
-                player_after_call
-            }
-            Action::Bet(_) | Action::Raise(_) | Action::AllIn(_) => {
-                amount += to_call;
-                opponent
-            }
-            _ => panic!("Unexpected action: {action:?}"),
+        // build the subtree before inserting it so that the tree is unchanged on error
+        let mut child = ActionTreeNode {
+            player: next_player,
+            board_state: node.board_state,
+            amount,
+            ..Default::default()
         };
+        self.build_tree_recursive(&mut child, info.create_next(player, action))?;
 
         let index = search_result.unwrap_err();
         node.actions.insert(index, action);
-        node.children.insert(
-            index,
-            MutexLike::new(ActionTreeNode {
-                player: next_player,
-                board_state: node.board_state,
-                amount,
-                ..Default::default()
-            }),
-        );
+        node.children.insert(index, MutexLike::new(child));
 
         node.actions.shrink_to_fit();
         node.children.shrink_to_fit();
 
-        self.build_tree_recursive(
-            &mut node.children[index].lock(),
-            info.create_next(player, action),
-        );
-
         Ok(is_replaced)
     }
 
@@ -985,12 +1743,14 @@ impl ActionTreeNode {
 
 impl BuildTreeInfo {
     #[inline]
-    fn new(stack: i32) -> Self {
+    fn new(stack: i32, first_player: u8) -> Self {
         Self {
             prev_action: Action::None,
             num_bets: 0,
+            num_raises: 0,
             allin_flag: false,
-            oop_call_flag: false,
+            first_player,
+            first_call_flag: false,
             stack: [stack, stack],
             prev_amount: 0,
         }
@@ -999,24 +1759,29 @@ impl BuildTreeInfo {
     #[inline]
     fn create_next(&self, player: u8, action: Action) -> Self {
         let mut num_bets = self.num_bets;
+        let mut num_raises = self.num_raises;
         let mut allin_flag = self.allin_flag;
-        let mut oop_call_flag = self.oop_call_flag;
+        let mut first_call_flag = self.first_call_flag;
         let mut stack = self.stack;
         let mut prev_amount = self.prev_amount;
 
         match action {
             Action::Check => {
-                oop_call_flag = false;
+                first_call_flag = false;
             }
             Action::Call => {
                 num_bets = 0;
-                oop_call_flag = player == PLAYER_OOP;
+                num_raises = 0;
+                first_call_flag = player == self.first_player;
                 stack[player as usize] = stack[player as usize ^ 1];
                 prev_amount = 0;
             }
             Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => {
                 let to_call = stack[player as usize] - stack[player as usize ^ 1];
                 num_bets += 1;
+                if prev_amount > 0 {
+                    num_raises += 1;
+                }
                 allin_flag = matches!(action, Action::AllIn(_));
                 stack[player as usize] -= amount - prev_amount + to_call;
                 prev_amount = amount;
@@ -1027,8 +1792,10 @@ impl BuildTreeInfo {
         BuildTreeInfo {
             prev_action: action,
             num_bets,
+            num_raises,
             allin_flag,
-            oop_call_flag,
+            first_player: self.first_player,
+            first_call_flag,
             stack,
             prev_amount,
         }
@@ -1060,6 +1827,96 @@ fn count_num_action_nodes_recursive(node: &ActionTreeNode, street: usize, count:
     }
 }
 
+/// Returns the number of storage elements of [flop, turn, river] for
+/// [action nodes, IP counterfactual values, chance nodes].
+pub(crate) fn count_num_elements(
+    node: &ActionTreeNode,
+    num_private_hands: [u64; 2],
+) -> [[u64; 3]; 3] {
+    let mut ret = [[0; 3]; 3];
+    count_num_elements_recursive(node, 0, true, num_private_hands, &mut ret);
+
+    let num_action_nodes = count_num_action_nodes(node);
+    let offset = num_action_nodes.iter().take_while(|&&n| n == 0).count();
+    for count in &mut ret {
+        count.rotate_right(offset);
+    }
+
+    ret
+}
+
+fn count_num_elements_recursive(
+    node: &ActionTreeNode,
+    street: usize,
+    is_street_root: bool,
+    num_private_hands: [u64; 2],
+    count: &mut [[u64; 3]; 3],
+) {
+    if node.is_terminal() {
+        // do nothing
+    } else if node.is_chance() {
+        // consistent with `cfvalue_storage_player()` of `PostFlopNode`
+        match node.player & PLAYER_MASK {
+            PLAYER_OOP => count[2][street] += num_private_hands[PLAYER_IP as usize],
+            PLAYER_IP => count[2][street] += num_private_hands[PLAYER_OOP as usize],
+            _ => {}
+        }
+        let child = &node.children[0].lock();
+        count_num_elements_recursive(child, street + 1, true, num_private_hands, count);
+    } else {
+        let num_actions = node.actions.len() as u64;
+        count[0][street] += num_actions * num_private_hands[node.player as usize];
+        if is_street_root {
+            count[1][street] += num_private_hands[node.player as usize ^ 1];
+        }
+        for child in &node.children {
+            count_num_elements_recursive(&child.lock(), street, false, num_private_hands, count);
+        }
+    }
+}
+
+/// Rounds the bet amount to the nearest multiple of `unit` (`0` means no rounding).
+///
+/// A positive amount is never rounded to zero; it is rounded up to `unit` instead. The rounding
+/// is computed in `i64` and saturates at `i32::MAX`.
+#[inline]
+fn round_bet_amount(amount: i32, unit: i32) -> i32 {
+    match unit {
+        0 => amount,
+        _ if amount <= 0 => amount,
+        _ => {
+            let (amount, unit) = (amount as i64, unit as i64);
+            let rounded = ((amount + unit / 2) / unit * unit).max(unit);
+            rounded.min(i32::MAX as i64) as i32
+        }
+    }
+}
+
+/// Returns the given bet sizes with the SPR-conditional sizes expanded into their inner sizes.
+#[inline]
+fn expand_bet_sizes<'a>(
+    sizes: impl IntoIterator<Item = &'a BetSize>,
+) -> impl Iterator<Item = &'a BetSize> {
+    sizes.into_iter().flat_map(|size| match size {
+        BetSize::SprConditional(sizes) => sizes.iter().map(|(_, size)| size).collect(),
+        _ => vec![size],
+    })
+}
+
+/// Resolves the SPR-conditional bet size with the given SPR.
+///
+/// Returns `None` if no condition matches. Other bet sizes are returned as is.
+#[inline]
+fn resolve_bet_size(size: &BetSize, spr: f64) -> Option<&BetSize> {
+    match size {
+        BetSize::SprConditional(sizes) => sizes
+            .iter()
+            .find(|&&(threshold, _)| spr < threshold)
+            .map(|(_, size)| size),
+        _ => Some(size),
+    }
+}
+
 fn merge_bet_actions(actions: Vec<Action>, pot: i32, offset: i32, param: f64) -> Vec<Action> {
     const EPS: f64 = 1e-12;
 
@@ -1089,3 +1946,821 @@ fn merge_bet_actions(actions: Vec<Action>, pot: i32, offset: i32, param: f64) ->
     ret.reverse();
     ret
 }
+
+/// Removes the bet and raise actions until at most `max_num` of them remain.
+///
+/// The closest pair of adjacent amounts is selected by the ratio of `pot + amount - offset`, and
+/// the smaller one (which is never all-in) is removed. `actions` must be sorted.
+fn limit_bet_actions(actions: &mut Vec<Action>, pot: i32, offset: i32, max_num: usize) {
+    let get_amount = |action: Action| match action {
+        Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => amount,
+        _ => -1,
+    };
+
+    let num_bet_sizes = |actions: &[Action]| {
+        actions
+            .iter()
+            .filter(|action| matches!(action, Action::Bet(_) | Action::Raise(_)))
+            .count()
+    };
+
+    while num_bet_sizes(actions) > max_num {
+        let mut min_ratio = f64::INFINITY;
+        let mut remove_index = 0;
+
+        for i in 0..actions.len() - 1 {
+            let (amount, next_amount) = (get_amount(actions[i]), get_amount(actions[i + 1]));
+            if amount > 0 && next_amount > 0 {
+                let size = (pot + amount - offset) as f64;
+                let next_size = (pot + next_amount - offset) as f64;
+                let ratio = next_size / size;
+                if ratio < min_ratio {
+                    min_ratio = ratio;
+                    remove_index = i;
+                }
+            }
+        }
+
+        actions.remove(remove_index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_bet_size() {
+        let bet_sizes = BetSizeCandidates::try_from(("150, 2000", "400")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 1000,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(150), Action::AllIn(1000)]
+        );
+
+        action_tree.play(Action::Bet(150)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(400)]
+        );
+    }
+
+    #[test]
+    fn allin_only_bet_size() {
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 1000,
+            river_bet_sizes: [
+                BetSizeCandidates::try_from(("allin", "")).unwrap(),
+                BetSizeCandidates::try_from(("150, jam, 2000", "jam")).unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::AllIn(1000)]
+        );
+
+        action_tree.play(Action::AllIn(1000)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call]
+        );
+
+        // the all-in is deduplicated against the size clamped to the stack
+        action_tree.back_to_root();
+        action_tree.play(Action::Check).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(150), Action::AllIn(1000)]
+        );
+
+        action_tree.play(Action::Bet(150)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call]
+        );
+    }
+
+    #[test]
+    fn max_num_bets_per_street() {
+        let bet_sizes = BetSizeCandidates::try_from(("50%", "3x")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Flop,
+            starting_pot: 100,
+            effective_stack: 10000,
+            flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+            turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            max_num_bets: [3, 0, 1],
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        action_tree.play(Action::Bet(50)).unwrap();
+        action_tree.play(Action::Raise(150)).unwrap();
+        action_tree.play(Action::Raise(450)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call]
+        );
+
+        action_tree.back_to_root();
+        for _ in 0..2 {
+            action_tree.play(Action::Check).unwrap();
+        }
+        action_tree.play(Action::Bet(50)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(150)]
+        );
+
+        action_tree.back_to_root();
+        for _ in 0..4 {
+            action_tree.play(Action::Check).unwrap();
+        }
+        action_tree.play(Action::Bet(50)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call]
+        );
+    }
+
+    #[test]
+    fn max_num_raises() {
+        let bet_sizes = BetSizeCandidates::try_from(("50%", "3x")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 10000,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            max_num_bets: [0, 0, 0],
+            max_num_raises: [0, 0, 2],
+            ..Default::default()
+        };
+
+        // the first bet and two raises are allowed
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        action_tree.play(Action::Bet(50)).unwrap();
+        action_tree.play(Action::Raise(150)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(450)]
+        );
+        action_tree.play(Action::Raise(450)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call]
+        );
+
+        // the combined cap is still checked
+        let tree_config = TreeConfig {
+            max_num_bets: [0, 0, 2],
+            ..action_tree.config().clone()
+        };
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        action_tree.play(Action::Bet(50)).unwrap();
+        action_tree.play(Action::Raise(150)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call]
+        );
+    }
+
+    #[test]
+    fn bet_rounding() {
+        let bet_sizes = BetSizeCandidates::try_from(("81.5%, 100%", "2.5x")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 200,
+            effective_stack: 1030,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            bet_rounding: 50,
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(150), Action::Bet(200)]
+        );
+
+        // 150 * 2.5 = 375 -> 400
+        action_tree.play(Action::Bet(150)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(400)]
+        );
+
+        // 400 * 2.5 = 1000 (not rounded to all-in)
+        action_tree.play(Action::Raise(400)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(1000)]
+        );
+    }
+
+    #[test]
+    fn bet_rounding_large_constant() {
+        // rounding a constant size near `i32::MAX` must not overflow
+        let bet_sizes = BetSizeCandidates::try_from(("2147483647", "")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 200,
+            effective_stack: 1030,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            bet_rounding: 10,
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::AllIn(1030)]
+        );
+        assert_eq!(round_bet_amount(i32::MAX - 4, 10), 2147483640);
+        assert_eq!(round_bet_amount(i32::MAX, 10), i32::MAX); // saturated
+    }
+
+    #[test]
+    fn bet_size_below_minimum() {
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 80,
+            effective_stack: 1000,
+            flop_bet_sizes: [("1%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [Default::default(), ("50%, 1%", "").try_into().unwrap()],
+            ..Default::default()
+        };
+
+        // the flop is ignored because the tree starts from the turn
+        let err = ActionTree::new(tree_config.clone()).err().unwrap();
+        assert!(err.contains("1.00% (IP) on the river"), "{err}");
+
+        let tree_config = TreeConfig {
+            river_bet_sizes: Default::default(),
+            river_donk_sizes: Some("2%".try_into().unwrap()),
+            ..tree_config
+        };
+        assert!(ActionTree::new(tree_config.clone()).is_ok());
+    }
+
+    #[test]
+    fn small_bet_with_rounding() {
+        let bet_sizes = BetSizeCandidates::try_from(("10%", "")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 40,
+            effective_stack: 1000,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(4)]
+        );
+
+        // 4 chips are rounded up to 10 chips, not to 0 chips
+        let tree_config = TreeConfig {
+            bet_rounding: 10,
+            ..tree_config
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(10)]
+        );
+    }
+
+    #[test]
+    fn min_bet_ratio() {
+        let bet_sizes = BetSizeCandidates::try_from(("10%, 50%", "3x")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 300,
+            effective_stack: 1000,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            min_bet_ratio: 0.33,
+            ..Default::default()
+        };
+
+        // 10% of the pot (30 chips) is lifted to 33% of the pot (99 chips)
+        let mut action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(99), Action::Bet(150)]
+        );
+
+        // raises are not affected
+        action_tree.play(Action::Bet(99)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(297)]
+        );
+
+        // the floor is clamped to the stack
+        let tree_config = TreeConfig {
+            effective_stack: 60,
+            ..tree_config
+        };
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::AllIn(60)]
+        );
+
+        let tree_config = TreeConfig {
+            min_bet_ratio: -0.1,
+            ..tree_config
+        };
+        assert!(ActionTree::new(tree_config).is_err());
+    }
+
+    #[test]
+    fn max_num_bet_sizes() {
+        let bet_sizes = BetSizeCandidates::try_from(("30%, 33%, 75%, a", "2.5x, 3x, a")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 1000,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            max_num_bet_sizes: 2,
+            ..Default::default()
+        };
+
+        // the smaller one of the near-identical sizes (30% and 33%) is removed
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [
+                Action::Check,
+                Action::Bet(33),
+                Action::Bet(75),
+                Action::AllIn(1000)
+            ]
+        );
+
+        // fold, call, and all-in are kept
+        let tree_config = TreeConfig {
+            max_num_bet_sizes: 1,
+            ..tree_config
+        };
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(75), Action::AllIn(1000)]
+        );
+
+        action_tree.play(Action::Bet(75)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [
+                Action::Fold,
+                Action::Call,
+                Action::Raise(225),
+                Action::AllIn(1000)
+            ]
+        );
+    }
+
+    #[test]
+    fn add_all_in() {
+        let bet_sizes = BetSizeCandidates {
+            add_all_in: true,
+            ..BetSizeCandidates::try_from(("33%", "")).unwrap()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Flop,
+            starting_pot: 100,
+            effective_stack: 1000,
+            flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(33), Action::AllIn(1000)]
+        );
+
+        // the all-in is also available for raises
+        action_tree.play(Action::Bet(33)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::AllIn(1000)]
+        );
+
+        // and for donk bets, using the bet size candidates of the street
+        let donk_config = TreeConfig {
+            turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+            turn_donk_sizes: Some(DonkSizeCandidates::try_from("50%").unwrap()),
+            ..tree_config.clone()
+        };
+        let mut action_tree = ActionTree::new(donk_config).unwrap();
+        action_tree
+            .apply_history(&[Action::Check, Action::Bet(33), Action::Call])
+            .unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(83), Action::AllIn(967)]
+        );
+
+        // deduplicated if a bet size is already converted into an all-in
+        let bet_sizes = BetSizeCandidates {
+            add_all_in: true,
+            ..BetSizeCandidates::try_from(("2000%", "")).unwrap()
+        };
+        let tree_config = TreeConfig {
+            flop_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..tree_config
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::AllIn(1000)]
+        );
+    }
+
+    #[test]
+    fn cbet_sizes() {
+        let oop_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+        let ip_sizes = BetSizeCandidates {
+            cbet: Some(vec![BetSize::PotRelative(0.75)]),
+            ..BetSizeCandidates::try_from(("33%", "")).unwrap()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Flop,
+            starting_pot: 100,
+            effective_stack: 1000,
+            flop_bet_sizes: [oop_sizes.clone(), ip_sizes.clone()],
+            turn_bet_sizes: [oop_sizes, ip_sizes],
+            turn_donk_sizes: Some(DonkSizeCandidates::try_from("40%").unwrap()),
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(50)]
+        );
+
+        // IP uses the cbet sizes after OOP checked
+        action_tree.play(Action::Check).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(75)]
+        );
+
+        // OOP uses the donk sizes after calling the flop bet
+        action_tree.play(Action::Bet(75)).unwrap();
+        action_tree.play(Action::Call).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(100)]
+        );
+
+        action_tree.play(Action::Check).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(188)]
+        );
+
+        // OOP uses the first bet sizes after the flop was checked through
+        action_tree
+            .apply_history(&[Action::Check, Action::Check])
+            .unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(50)]
+        );
+
+        action_tree.play(Action::Check).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(75)]
+        );
+    }
+
+    #[test]
+    fn spr_conditional_bet_size() {
+        let conditional = BetSize::SprConditional(vec![
+            (1.5, BetSize::AllIn),
+            (f64::INFINITY, BetSize::PotRelative(0.66)),
+        ]);
+        let bet_sizes = BetSizeCandidates {
+            bet: vec![conditional.clone()],
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 100,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        // all-in below the SPR threshold
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::AllIn(100)]
+        );
+
+        // pot-relative bet above the SPR threshold
+        let action_tree = ActionTree::new(TreeConfig {
+            effective_stack: 1000,
+            ..tree_config.clone()
+        })
+        .unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(66)]
+        );
+
+        // no bet if no condition matches
+        let bet_sizes = BetSizeCandidates {
+            bet: vec![BetSize::SprConditional(vec![(1.5, BetSize::AllIn)])],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(TreeConfig {
+            effective_stack: 1000,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..tree_config.clone()
+        })
+        .unwrap();
+        assert_eq!(action_tree.available_actions(), [Action::Check]);
+
+        // nested conditions are rejected
+        let bet_sizes = BetSizeCandidates {
+            bet: vec![BetSize::SprConditional(vec![(2.0, conditional)])],
+            ..Default::default()
+        };
+        assert!(ActionTree::new(TreeConfig {
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..tree_config
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn action_display() {
+        assert_eq!(Action::Fold.to_string(), "Fold");
+        assert_eq!(Action::Check.to_string(), "Check");
+        assert_eq!(Action::Call.to_string(), "Call");
+        assert_eq!(Action::Bet(50).to_string(), "Bet 50");
+        assert_eq!(Action::Raise(150).to_string(), "Raise 150");
+        assert_eq!(Action::AllIn(1000).to_string(), "All-in 1000");
+        assert_eq!(Action::Chance(41).to_string(), "Chance Qd");
+    }
+
+    #[test]
+    fn bet_sizes_fn() {
+        let bet_sizes_fn = BetSizesFn(Arc::new(|context: &NodeContext| match context.num_bets {
+            0 => ("25%, 50%, 100%", "").try_into().unwrap(),
+            1 => ("", "3x").try_into().unwrap(),
+            _ => Default::default(),
+        }));
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 1000,
+            bet_sizes_fn: Some(bet_sizes_fn),
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [
+                Action::Check,
+                Action::Bet(25),
+                Action::Bet(50),
+                Action::Bet(100)
+            ]
+        );
+
+        action_tree.play(Action::Bet(50)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(150)]
+        );
+
+        action_tree.play(Action::Raise(150)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call]
+        );
+    }
+
+    #[test]
+    fn bet_sizes_fn_invalid() {
+        let tree_config = |candidates: BetSizeCandidates| TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 1000,
+            bet_sizes_fn: Some(BetSizesFn(Arc::new(move |_| candidates.clone()))),
+            ..Default::default()
+        };
+
+        let invalid_candidates = [
+            // relative to the previous bet in the first bet
+            BetSizeCandidates {
+                bet: vec![BetSize::PrevBetRelative(2.0)],
+                ..Default::default()
+            },
+            // big blinds without the big blind
+            BetSizeCandidates {
+                bet: vec![BetSize::BigBlinds(2.5)],
+                ..Default::default()
+            },
+            // below 1 chip
+            BetSizeCandidates {
+                bet: vec![BetSize::PotRelative(0.001)],
+                ..Default::default()
+            },
+            // nested SPR-conditional size
+            BetSizeCandidates {
+                raise: vec![BetSize::SprConditional(vec![(
+                    2.0,
+                    BetSize::SprConditional(vec![(1.0, BetSize::AllIn)]),
+                )])],
+                ..Default::default()
+            },
+        ];
+
+        for candidates in invalid_candidates {
+            assert!(ActionTree::new(tree_config(candidates)).is_err());
+        }
+
+        // the first bet is checked against the pot of the node
+        let valid_candidates = BetSizeCandidates {
+            bet: vec![BetSize::PotRelative(0.01)],
+            raise: vec![BetSize::PrevBetRelative(2.0)],
+            ..Default::default()
+        };
+        assert!(ActionTree::new(tree_config(valid_candidates)).is_ok());
+    }
+
+    #[test]
+    fn big_blind_sizes() {
+        let bet_sizes = BetSizeCandidates::try_from(("2.5bb", "7.5bb")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 1000,
+            big_blind: 20,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(50)]
+        );
+
+        // raises are the total amount to raise to
+        action_tree.play(Action::Bet(50)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(150)]
+        );
+
+        // the big blind must be set
+        let tree_config = TreeConfig {
+            big_blind: 0,
+            ..tree_config
+        };
+        assert!(ActionTree::new(tree_config.clone()).is_err());
+
+        let tree_config = TreeConfig {
+            big_blind: -20,
+            ..tree_config
+        };
+        assert!(ActionTree::new(tree_config).is_err());
+    }
+
+    #[test]
+    fn effective_stack_covers_larger_stack() {
+        // OOP has 200 chips behind and IP has 960 chips behind
+        let bet_sizes = BetSizeCandidates::try_from(("a", "")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 200,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        action_tree.play(Action::Check).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::AllIn(200)]
+        );
+
+        action_tree.play(Action::AllIn(200)).unwrap();
+        action_tree.play(Action::Call).unwrap();
+        assert_eq!(action_tree.total_bet_amount(), [200, 200]);
+    }
+
+    #[test]
+    fn overbet_sizes() {
+        let bet_sizes = BetSizeCandidates::try_from(("150%, 200%", "200%")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 10000,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            force_allin_threshold: 0.15,
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::Bet(150), Action::Bet(200)]
+        );
+
+        action_tree.play(Action::Bet(200)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::Raise(1200)]
+        );
+
+        // the overbets exceed the stack or are within the force all-in threshold
+        let tree_config = TreeConfig {
+            effective_stack: 180,
+            ..tree_config
+        };
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Check, Action::AllIn(180)]
+        );
+
+        // extremely large ratios do not overflow
+        let bet_sizes = BetSizeCandidates::try_from(("50%", "100000000000%")).unwrap();
+        let tree_config = TreeConfig {
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..tree_config
+        };
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        action_tree.play(Action::Bet(50)).unwrap();
+        assert_eq!(
+            action_tree.available_actions(),
+            [Action::Fold, Action::Call, Action::AllIn(180)]
+        );
+    }
+
+    #[test]
+    fn river_showdown_terminal() {
+        let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+        for ip_first in [false, true] {
+            let tree_config = TreeConfig {
+                initial_state: BoardState::River,
+                ip_first,
+                starting_pot: 100,
+                effective_stack: 1000,
+                river_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+                ..Default::default()
+            };
+            let action_tree = ActionTree::new(tree_config).unwrap();
+            let root = action_tree.root();
+
+            // check-check leads directly to a showdown terminal node
+            let first_check = root.children[0].lock();
+            assert_eq!(root.actions[0], Action::Check);
+            assert!(!first_check.is_terminal());
+            assert_eq!(first_check.actions[0], Action::Check);
+            let showdown = first_check.children[0].lock();
+            assert!(showdown.is_terminal());
+            assert_eq!(showdown.player, PLAYER_TERMINAL_FLAG);
+            assert_ne!(showdown.player & PLAYER_FOLD_FLAG, PLAYER_FOLD_FLAG);
+            assert!(showdown.children.is_empty());
+
+            // so does bet-call, while bet-fold leads to a fold terminal node
+            let bet = root.children[1].lock();
+            let fold = bet.children[0].lock();
+            let call = bet.children[1].lock();
+            assert_eq!(bet.actions[..2], [Action::Fold, Action::Call]);
+            assert_eq!(fold.player & PLAYER_FOLD_FLAG, PLAYER_FOLD_FLAG);
+            assert_eq!(call.player, PLAYER_TERMINAL_FLAG);
+        }
+    }
+}
@@ -1,22 +1,38 @@
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// Bet size candidates for the first bets and raises.
 ///
 /// In the `try_from()` method, multiple bet sizes can be specified using a comma-separated string.
-/// Each element must be a string ending in one of the following characters: %, x, c, r, e, a.
+/// Each element must be a string ending in one of the following characters: %, x, c, r, e, a, bb,
+/// or a bare integer.
 ///
-/// - %: Percentage of the pot. Example: "70%"
+/// - %: Percentage of the pot. Values above 100 are overbets. Example: "70%", "200%"
 /// - x: Multiple of the previous bet. Valid for only raises. Example: "2.5x"
 /// - c: Constant value. Must be an integer. Example: "100c"
 /// - c + r: Constant value with raise cap (for FLHE). Both values must be integers.
-///          Valid only for raises. Example: "20c3r"
+///   Valid only for raises. Example: "20c3r"
 /// - e: Geometric size.
 ///   - e: Same as "3e" for the flop, "2e" for the turn, and "1e" (equivalent to "a") for the river.
 ///   - Xe: The geometric size with X streets remaining. X must be a positive integer. Example: "2e"
 ///   - XeY%: Same as Xe, but the maximum size is Y% of the pot. Example: "3e200%".
 ///   - If specified for raises, the number of previous raises is subtracted from X.
-/// - a: All-in. Example: "a"
+/// - a: All-in. "allin" and "jam" are also accepted. Example: "a"
+/// - bb: Absolute amount in big blinds, converted into chips using the `big_blind` of
+///   [`TreeConfig`] (rounded to the nearest integer). Like the bare integer, the amount is the
+///   total amount to raise to when specified for raises. Example: "2.5bb"
+/// - (no suffix): Absolute amount to bet, or to raise to. Must be a positive integer.
+///   Unlike "c", the amount is not added to the previous bet when specified for raises.
+///   Example: "100"
+///
+/// Empty candidates (the default) allow no bets except the all-in added by the
+/// `add_allin_threshold` of [`TreeConfig`]. Hence, a street is check-only when the candidates of
+/// both players are empty and `add_allin_threshold` is `0.0` (the default).
+///
+/// [`TreeConfig`]: crate::TreeConfig
 ///
 /// # Examples
 /// ```
@@ -39,12 +55,29 @@ use bincode::{Decode, Encode};
 /// ```
 #[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BetSizeCandidates {
     /// Bet size candidates for first bet.
     pub bet: Vec<BetSize>,
 
+    /// Bet size candidates for the first bet after the opponent checked on the same street, e.g.,
+    /// continuation bets of the in-position player (set `None` to use `bet`).
+    ///
+    /// Donk bets into a new street are specified separately by the donk size candidates of
+    /// [`TreeConfig`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cbet: Option<Vec<BetSize>>,
+
     /// Bet size candidates for raise.
     pub raise: Vec<BetSize>,
+
+    /// Whether to always add an all-in action to the first bets (including donk bets) and raises,
+    /// regardless of the `add_allin_threshold` of the tree configuration.
+    ///
+    /// This is equivalent to adding [`BetSize::AllIn`] to both `bet` and `raise`. The all-in
+    /// action is deduplicated if one of the bet sizes is already converted into an all-in.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub add_all_in: bool,
 }
 
 /// Bet size candidates for the donk bets.
@@ -52,13 +85,15 @@ pub struct BetSizeCandidates {
 /// See the [`BetSizeCandidates`] struct for the description and examples.
 #[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DonkSizeCandidates {
     pub donk: Vec<BetSize>,
 }
 
 /// Bet size specification.
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BetSize {
     /// Bet size relative to the current pot size.
     PotRelative(f64),
@@ -74,10 +109,45 @@ pub enum BetSize {
     /// Geometric bet size for `i32` streets with maximum pot-relative size of `f64`.
     ///
     /// If `i32 == 0`, the number of streets is as follows: flop = 3, turn = 2, river = 1.
-    Geometric(i32, f64),
+    Geometric(
+        i32,
+        #[cfg_attr(feature = "serde", serde(with = "max_pot_rel_serde"))] f64,
+    ),
 
     /// Bet size representing all-in.
     AllIn,
+
+    /// Constant bet size in chips (for raises, the total amount to raise to).
+    Constant(i32),
+
+    /// Constant bet size in big blinds (for raises, the total amount to raise to).
+    ///
+    /// Converted into chips using the `big_blind` of the tree configuration.
+    BigBlinds(f64),
+
+    /// Bet size conditional on the stack-to-pot ratio (SPR) at the node.
+    ///
+    /// Each element is a pair of an SPR threshold and a bet size. The size of the first element
+    /// whose threshold is greater than the SPR is used, and no size is used if no element
+    /// matches (use `f64::INFINITY` as the last threshold to specify a fallback). The SPR is the
+    /// effective stack divided by the pot after calling the previous bet, if any. The inner sizes
+    /// must not be `SprConditional`. This variant cannot be parsed from a string.
+    SprConditional(Vec<(f64, BetSize)>),
+}
+
+/// Serializes the unlimited maximum size of [`BetSize::Geometric`] (`f64::INFINITY`) as `null`,
+/// which JSON cannot represent otherwise.
+#[cfg(feature = "serde")]
+mod max_pot_rel_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        Some(*value).filter(|v| v.is_finite()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::INFINITY))
+    }
 }
 
 impl TryFrom<(&str, &str)> for BetSizeCandidates {
@@ -112,7 +182,12 @@ impl TryFrom<(&str, &str)> for BetSizeCandidates {
         bet.sort_unstable_by(|l, r| l.partial_cmp(r).unwrap());
         raise.sort_unstable_by(|l, r| l.partial_cmp(r).unwrap());
 
-        Ok(BetSizeCandidates { bet, raise })
+        Ok(BetSizeCandidates {
+            bet,
+            cbet: None,
+            raise,
+            add_all_in: false,
+        })
     }
 }
 
@@ -167,6 +242,14 @@ fn bet_size_from_str(s: &str, is_raise: bool) -> Result<BetSize, String> {
                 Ok(BetSize::PrevBetRelative(float))
             }
         }
+    } else if let Some(big_blinds) = s_lower.strip_suffix("bb") {
+        // Big blinds
+        let float = parse_float(big_blinds).ok_or(&err_msg)?;
+        if float <= 0.0 {
+            Err(format!("Size in big blinds must be positive: {s}"))
+        } else {
+            Ok(BetSize::BigBlinds(float))
+        }
     } else if s_lower.contains('c') {
         // Additive
         let mut split = s_lower.split('c');
@@ -241,9 +324,18 @@ fn bet_size_from_str(s: &str, is_raise: bool) -> Result<BetSize, String> {
         // Pot relative (must be after the geometric check)
         let float = parse_float(pot_rel).ok_or(&err_msg)?;
         Ok(BetSize::PotRelative(float / 100.0))
-    } else if s_lower == "a" {
+    } else if matches!(s_lower.as_str(), "a" | "allin" | "jam") {
         // All-in
         Ok(BetSize::AllIn)
+    } else if let Some(float) = parse_float(&s_lower) {
+        // Constant (must be the last check)
+        if float.trunc() != float || float == 0.0 {
+            Err(format!("Constant size must be a positive integer: {s}"))
+        } else if float > i32::MAX as f64 {
+            Err(format!("Constant size must be less than 2^31: {s}"))
+        } else {
+            Ok(BetSize::Constant(float as i32))
+        }
     } else {
         // Parse error
         Err(err_msg)
@@ -261,6 +353,7 @@ mod tests {
             ("0%", PotRelative(0.0)),
             ("75%", PotRelative(0.75)),
             ("112.5%", PotRelative(1.125)),
+            ("200%", PotRelative(2.0)),
             ("1.001x", PrevBetRelative(1.001)),
             ("3.5X", PrevBetRelative(3.5)),
             ("0c", Additive(0, 0)),
@@ -274,6 +367,13 @@ mod tests {
             ("100e.5%", Geometric(100, 0.005)),
             ("a", AllIn),
             ("A", AllIn),
+            ("allin", AllIn),
+            ("AllIn", AllIn),
+            ("jam", AllIn),
+            ("1", Constant(1)),
+            ("250", Constant(250)),
+            ("2.5bb", BigBlinds(2.5)),
+            ("10BB", BigBlinds(10.0)),
         ];
 
         for (s, expected) in tests {
@@ -283,12 +383,15 @@ mod tests {
         let error_tests = [
             "", "0", "1.23", "%", "+42%", "-30%", "x", "0x", "1x", "c", "12.3c", "10c10", "42cr",
             "c3r", "0c0r", "123c101r", "1c2r3", "12c3.4r", "0e", "2.7e", "101e", "3e7", "E%",
-            "1e2e3", "bet", "1a", "a1",
+            "1e2e3", "bet", "1a", "a1", "all-in", "jams", "-100", "0.5", "bb", "0bb", "-2bb", "2b",
         ];
 
         for s in error_tests {
             assert!(bet_size_from_str(s, true).is_err());
         }
+
+        // overflows `i32`
+        assert!(bet_size_from_str("2147483648", true).is_err());
     }
 
     #[test]
@@ -299,7 +402,9 @@ mod tests {
                 "",
                 BetSizeCandidates {
                     bet: vec![PotRelative(0.4), PotRelative(0.7)],
+                    cbet: None,
                     raise: Vec::new(),
+                    add_all_in: false,
                 },
             ),
             (
@@ -307,7 +412,29 @@ mod tests {
                 "25%, 2.5x, e200%",
                 BetSizeCandidates {
                     bet: vec![Additive(50, 0), Geometric(0, f64::INFINITY), AllIn],
+                    cbet: None,
                     raise: vec![PotRelative(0.25), PrevBetRelative(2.5), Geometric(0, 2.0)],
+                    add_all_in: false,
+                },
+            ),
+            (
+                "50%,75%",
+                "2.5x,3x",
+                BetSizeCandidates {
+                    bet: vec![PotRelative(0.5), PotRelative(0.75)],
+                    cbet: None,
+                    raise: vec![PrevBetRelative(2.5), PrevBetRelative(3.0)],
+                    add_all_in: false,
+                },
+            ),
+            (
+                "100, 50%",
+                "300, 3x",
+                BetSizeCandidates {
+                    bet: vec![PotRelative(0.5), Constant(100)],
+                    cbet: None,
+                    raise: vec![PrevBetRelative(3.0), Constant(300)],
+                    add_all_in: false,
                 },
             ),
         ];
@@ -316,7 +443,7 @@ mod tests {
             assert_eq!((bet, raise).try_into(), Ok(expected));
         }
 
-        let error_tests = [("2.5x", ""), (",", "")];
+        let error_tests = [("2.5x", ""), ("50%, 3x", "3x"), (",", "")];
 
         for (bet, raise) in error_tests {
             assert!(BetSizeCandidates::try_from((bet, raise)).is_err());
@@ -132,6 +132,7 @@ pub struct BunchingData {
 }
 
 #[inline]
+#[allow(clippy::needless_range_loop)]
 fn mask_to_index(mut mask: u64, k: usize) -> usize {
     let mut index = 0;
     for i in 0..k {
@@ -162,6 +163,7 @@ fn next_combination(mask: u64) -> u64 {
 }
 
 #[inline]
+#[allow(clippy::needless_range_loop)]
 fn compress_mask(mut mask: u64, flop: [u8; 3]) -> u64 {
     assert!(flop[0] < flop[1] && flop[1] < flop[2]);
     for i in 0..3 {
@@ -683,6 +685,7 @@ impl BunchingData {
 
     /* Phase 2: Main process */
 
+    #[allow(clippy::needless_range_loop)]
     fn phase2_process<const K: usize>(&mut self) {
         let (src_len, src_table) = match K {
             2 => (COMB_49_2, &self.sum[2]),
@@ -742,6 +745,7 @@ impl BunchingData {
 
     /* Phase 3: Main process */
 
+    #[allow(clippy::needless_range_loop)]
     fn phase3_process<const N: usize>(&mut self, start_index: usize, end_index: usize) {
         let dst_table = match N {
             4 => &mut self.result4,
@@ -802,6 +806,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn test_next_combination() {
         let seq = [
             0b001111, 0b010111, 0b011011, 0b011101, 0b011110, 0b100111, 0b101011, 0b101101,
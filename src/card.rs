@@ -5,9 +5,67 @@ use std::mem;
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// Constant representing that the card is not yet dealt.
 pub const NOT_DEALT: u8 = 0xff;
 
+/// An enum representing the game type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameType {
+    /// No-limit Texas Hold'em with the standard 52-card deck.
+    #[default]
+    Holdem = 0,
+
+    /// Short-deck (6+) Hold'em: cards 2 through 5 are removed from the deck, a flush beats a full
+    /// house, and A-6-7-8-9 is the lowest straight.
+    ShortDeck = 1,
+}
+
+/// A struct representing the turn card abstraction.
+///
+/// Turn cards are merged into one bucket when they (1) pair the board or not alike, (2) have the
+/// same number of flop cards of their suit (i.e., the same effect on flush draws), and (3) belong
+/// to the same rank group. The ranks are grouped by `rank_group_size` consecutive ranks from the
+/// deuce (e.g., `2` groups 2-3, 4-5, ..., Q-K, and A).
+///
+/// The cards in a bucket share the subtree of the lowest card in the bucket (representative), so
+/// the number of turn chance children shrinks accordingly. The private hands are mapped to the
+/// representative by replacing the merged card with the representative card. This is an
+/// approximation: the card removal effects and hand strengths of the merged cards are those of the
+/// representative card. The error depends on the ranges and grows with the group size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TurnBucketing {
+    /// Number of consecutive ranks grouped into one bucket. Must be in range [`1`, `13`].
+    pub rank_group_size: u8,
+}
+
+/// A struct representing the river card abstraction.
+///
+/// River cards are merged into one bucket in the same way as [`TurnBucketing`], except that the
+/// turn card is also taken into account: river cards are merged when they (1) pair the board
+/// (flop and turn) or not alike, (2) have the same number of board cards of their suit, and (3)
+/// belong to the same rank group.
+///
+/// The river is the last street, so the merged cards affect only the showdown values and the
+/// river strategies. Still, the hand strengths of the merged cards are those of the representative
+/// card, so straights and pairs completed by the merged card are evaluated as if the
+/// representative card were dealt, and the expected values of the two players may not sum exactly
+/// to zero. The error grows with the group size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RiverBucketing {
+    /// Number of consecutive ranks grouped into one bucket. Must be in range [`1`, `13`].
+    pub rank_group_size: u8,
+}
+
 /// A struct containing the card configuration.
 ///
 /// Card ID (u8): `"2c"` => `0`, `"2d"` => `1`, `"2h"` => `2`, ..., `"As"` => `51`.
@@ -24,10 +82,17 @@ pub const NOT_DEALT: u8 = 0xff;
 ///     flop: flop_from_str("Td9d6h").unwrap(),
 ///     turn: card_from_str("Qc").unwrap(),
 ///     river: NOT_DEALT,
+///     game_type: GameType::Holdem,
+///     dead_cards: Vec::new(),
+///     use_isomorphism: true,
+///     turn_abstraction: None,
+///     river_abstraction: None,
+///     precompute_strength: true,
 /// };
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CardConfig {
     /// Initial range of each player.
     pub range: [Range; 2],
@@ -40,6 +105,38 @@ pub struct CardConfig {
 
     /// River card: must be in range [`0`, `52`) or `NOT_DEALT`.
     pub river: u8,
+
+    /// Game type. In short deck, the board cards must not be of rank 2 through 5, and hands
+    /// containing such cards are removed from the ranges.
+    pub game_type: GameType,
+
+    /// Cards known to be dead (e.g., folded by other players): each card must be unique and in
+    /// range [`0`, `52`). They are never dealt as the turn or the river, and hands containing them
+    /// are removed from the ranges.
+    pub dead_cards: Vec<u8>,
+
+    /// Whether to merge isomorphic chances (turn and river deals) into one. Disabling this is only
+    /// useful for verification because it increases the tree size without changing the results.
+    pub use_isomorphism: bool,
+
+    /// Turn card abstraction to merge strategically similar turn cards (set `None` to disable).
+    ///
+    /// When enabled, the turn isomorphism is replaced by the abstraction. Not supported with the
+    /// bunching effect. See [`TurnBucketing`] for details.
+    pub turn_abstraction: Option<TurnBucketing>,
+
+    /// River card abstraction to merge strategically similar river cards (set `None` to disable).
+    ///
+    /// When enabled, the river isomorphism is replaced by the abstraction. Not supported with the
+    /// bunching effect. See [`RiverBucketing`] for details.
+    pub river_abstraction: Option<RiverBucketing>,
+
+    /// Whether to precompute the hand strength tables of all possible boards.
+    ///
+    /// Disabling this computes the hand strengths at each showdown evaluation instead, which saves
+    /// the memory of the tables (several megabytes for a flop game with wide ranges) at the cost
+    /// of much slower solving. The results are identical. Not supported with the bunching effect.
+    pub precompute_strength: bool,
 }
 
 impl Default for CardConfig {
@@ -50,6 +147,12 @@ impl Default for CardConfig {
             flop: [NOT_DEALT; 3],
             turn: NOT_DEALT,
             river: NOT_DEALT,
+            game_type: GameType::Holdem,
+            dead_cards: Vec::new(),
+            use_isomorphism: true,
+            turn_abstraction: None,
+            river_abstraction: None,
+            precompute_strength: true,
         }
     }
 }
@@ -59,6 +162,7 @@ type PrivateCards = [Vec<(u8, u8)>; 2];
 type Indices = [Vec<u16>; 2];
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub(crate) struct StrengthItem {
     pub(crate) strength: u16,
     pub(crate) index: u16,
@@ -75,6 +179,8 @@ type IsomorphismData = (
     [[SwapList; 4]; 4],
 );
 
+type RiverBucketingData = (Vec<Vec<u8>>, Vec<Vec<u8>>, Vec<Vec<SwapList>>);
+
 /// Returns an index of the given card pair.
 ///
 /// `"2d2c"` => `0`, `"2h2c"` => `1`, `"2s2c"` => `2`, ..., `"AsAh"` => `1325`.
@@ -96,7 +202,113 @@ pub(crate) fn index_to_card_pair(index: usize) -> (u8, u8) {
     (card1 as u8, card2 as u8)
 }
 
+/// A struct representing the texture of a board.
+///
+/// See the [`board_texture`] function for the details.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BoardTexture {
+    /// Whether two or more board cards have the same rank.
+    pub paired: bool,
+
+    /// Whether all board cards have the same suit.
+    pub monotone: bool,
+
+    /// Whether the board cards have exactly two different suits.
+    pub two_tone: bool,
+
+    /// Whether three or more board cards have the same suit, i.e., a flush is possible.
+    pub flush_possible: bool,
+
+    /// Whether three or more distinct ranks of the board fit in a straight, i.e., a straight is
+    /// possible.
+    pub straight_possible: bool,
+
+    /// The maximum number of distinct ranks of the board that fit in a straight (A-5 included).
+    pub connectedness: u8,
+}
+
+/// Computes the texture of the given board.
+///
+/// `turn` and `river` can be [`NOT_DEALT`] to compute the texture of the flop or the turn.
+///
+/// # Panics
+/// Panics if the board contains an invalid card ID or duplicate cards.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let flop = flop_from_str("Td9d6h").unwrap();
+/// let texture = board_texture(flop, NOT_DEALT, NOT_DEALT);
+/// assert!(!texture.paired);
+/// assert!(texture.two_tone);
+/// assert!(!texture.flush_possible);
+/// assert!(texture.straight_possible);
+/// assert_eq!(texture.connectedness, 3);
+/// ```
+pub fn board_texture(flop: [u8; 3], turn: u8, river: u8) -> BoardTexture {
+    let mut mask: u64 = 0;
+    let mut rankset = 0u16;
+    let mut suit_count = [0u8; 4];
+    let mut paired = false;
+
+    for card in flop.into_iter().chain([turn, river]) {
+        if card == NOT_DEALT {
+            continue;
+        }
+
+        if card >= 52 || mask & (1 << card) != 0 {
+            panic!("Invalid board: {flop:?}, {turn}, {river}");
+        }
+
+        mask |= 1 << card;
+        paired |= rankset & (1 << (card >> 2)) != 0;
+        rankset |= 1 << (card >> 2);
+        suit_count[card as usize & 3] += 1;
+    }
+
+    if flop.contains(&NOT_DEALT) || (turn == NOT_DEALT && river != NOT_DEALT) {
+        panic!("Invalid board: {flop:?}, {turn}, {river}");
+    }
+
+    let num_suits = suit_count.iter().filter(|&&count| count > 0).count();
+    let max_suit_count = *suit_count.iter().max().unwrap();
+
+    // the ace is also placed below the deuce for the A-5 straight
+    let extended_rankset = (rankset << 1) | (rankset >> 12);
+    let connectedness = (0..10)
+        .map(|low| (extended_rankset >> low) & 0x1f)
+        .map(|window| window.count_ones() as u8)
+        .max()
+        .unwrap();
+
+    BoardTexture {
+        paired,
+        monotone: num_suits == 1,
+        two_tone: num_suits == 2,
+        flush_possible: max_suit_count >= 3,
+        straight_possible: connectedness >= 3,
+        connectedness,
+    }
+}
+
 impl CardConfig {
+    /// Returns the bit mask of the cards removed from the deck.
+    #[inline]
+    pub(crate) fn removed_mask(&self) -> u64 {
+        let dead_mask = self.dead_cards.iter().fold(0, |mask, &c| mask | (1 << c));
+        match self.game_type {
+            GameType::Holdem => dead_mask,
+            GameType::ShortDeck => dead_mask | ((1 << 16) - 1),
+        }
+    }
+
+    /// Returns the number of cards in the deck.
+    #[inline]
+    pub(crate) fn deck_size(&self) -> usize {
+        52 - self.removed_mask().count_ones() as usize
+    }
+
     pub(crate) fn valid_indices(
         &self,
         private_cards: &PrivateCards,
@@ -107,9 +319,12 @@ impl CardConfig {
             Indices::default()
         };
 
+        let removed_mask = self.removed_mask();
+
         let mut ret_turn = vec![Indices::default(); 52];
         for board in 0..52 {
-            if !self.flop.contains(&board)
+            if (1 << board) & removed_mask == 0
+                && !self.flop.contains(&board)
                 && (self.turn == NOT_DEALT || self.turn == board)
                 && self.river == NOT_DEALT
             {
@@ -121,7 +336,8 @@ impl CardConfig {
         let mut ret_river = vec![Indices::default(); 52 * 51 / 2];
         for board1 in 0..52 {
             for board2 in board1 + 1..52 {
-                if !self.flop.contains(&board1)
+                if ((1 << board1) | (1 << board2)) & removed_mask == 0
+                    && !self.flop.contains(&board1)
                     && !self.flop.contains(&board2)
                     && (self.turn == NOT_DEALT || board1 == self.turn || board2 == self.turn)
                     && (self.river == NOT_DEALT || board1 == self.river || board2 == self.river)
@@ -177,62 +393,84 @@ impl CardConfig {
     ) -> Vec<[Vec<StrengthItem>; 2]> {
         let mut ret = vec![Default::default(); 52 * 51 / 2];
 
+        for board1 in 0..52 {
+            for board2 in board1 + 1..52 {
+                ret[card_pair_to_index(board1, board2)] =
+                    self.board_strength(board1, board2, private_cards);
+            }
+        }
+
+        ret
+    }
+
+    /// Computes the hand strengths of the private hands on the board completed by the given two
+    /// cards (the turn and the river in any order).
+    ///
+    /// Returns empty vectors if the board is not possible under the configuration.
+    pub(crate) fn board_strength(
+        &self,
+        board1: u8,
+        board2: u8,
+        private_cards: &PrivateCards,
+    ) -> [Vec<StrengthItem>; 2] {
+        let removed_mask = self.removed_mask();
+
         let mut board = Hand::new();
         for &card in &self.flop {
             board = board.add_card(card as usize);
         }
 
-        for board1 in 0..52 {
-            for board2 in board1 + 1..52 {
-                if !board.contains(board1 as usize)
-                    && !board.contains(board2 as usize)
-                    && (self.turn == NOT_DEALT || board1 == self.turn || board2 == self.turn)
-                    && (self.river == NOT_DEALT || board1 == self.river || board2 == self.river)
-                {
-                    let board = board.add_card(board1 as usize).add_card(board2 as usize);
-                    let mut strength = [
-                        Vec::with_capacity(private_cards[0].len() + 2),
-                        Vec::with_capacity(private_cards[1].len() + 2),
-                    ];
-
-                    for player in 0..2 {
-                        // add the weakest and strongest sentinels
-                        strength[player].push(StrengthItem {
-                            strength: 0,
-                            index: 0,
-                        });
-                        strength[player].push(StrengthItem {
-                            strength: u16::MAX,
-                            index: u16::MAX,
-                        });
-
-                        strength[player].extend(
-                            private_cards[player].iter().enumerate().filter_map(
-                                |(index, &(c1, c2))| {
-                                    let (c1, c2) = (c1 as usize, c2 as usize);
-                                    if board.contains(c1) || board.contains(c2) {
-                                        None
-                                    } else {
-                                        let hand = board.add_card(c1).add_card(c2);
-                                        Some(StrengthItem {
-                                            strength: hand.evaluate() + 1, // +1 to avoid 0
-                                            index: index as u16,
-                                        })
-                                    }
-                                },
-                            ),
-                        );
+        if board1 == board2
+            || ((1 << board1) | (1 << board2)) & removed_mask != 0
+            || board.contains(board1 as usize)
+            || board.contains(board2 as usize)
+            || (self.turn != NOT_DEALT && board1 != self.turn && board2 != self.turn)
+            || (self.river != NOT_DEALT && board1 != self.river && board2 != self.river)
+        {
+            return Default::default();
+        }
+
+        let board = board.add_card(board1 as usize).add_card(board2 as usize);
+        let mut strength = [
+            Vec::with_capacity(private_cards[0].len() + 2),
+            Vec::with_capacity(private_cards[1].len() + 2),
+        ];
 
-                        strength[player].shrink_to_fit();
-                        strength[player].sort_unstable();
+        for player in 0..2 {
+            // add the weakest and strongest sentinels
+            strength[player].push(StrengthItem {
+                strength: 0,
+                index: 0,
+            });
+            strength[player].push(StrengthItem {
+                strength: u16::MAX,
+                index: u16::MAX,
+            });
+
+            strength[player].extend(private_cards[player].iter().enumerate().filter_map(
+                |(index, &(c1, c2))| {
+                    let (c1, c2) = (c1 as usize, c2 as usize);
+                    if board.contains(c1) || board.contains(c2) {
+                        None
+                    } else {
+                        let hand = board.add_card(c1).add_card(c2);
+                        let strength = match self.game_type {
+                            GameType::Holdem => hand.evaluate(),
+                            GameType::ShortDeck => hand.evaluate_short_deck(),
+                        };
+                        Some(StrengthItem {
+                            strength: strength + 1, // +1 to avoid 0
+                            index: index as u16,
+                        })
                     }
+                },
+            ));
 
-                    ret[card_pair_to_index(board1, board2)] = strength;
-                }
-            }
+            strength[player].shrink_to_fit();
+            strength[player].sort_unstable();
         }
 
-        ret
+        strength
     }
 
     pub(crate) fn isomorphism(&self, private_cards: &[Vec<(u8, u8)>; 2]) -> IsomorphismData {
@@ -240,7 +478,8 @@ impl CardConfig {
         let mut next_index = 1;
         'outer: for suit2 in 1..4 {
             for suit1 in 0..suit2 {
-                if self.range[0].is_suit_isomorphic(suit1, suit2)
+                if self.use_isomorphism
+                    && self.range[0].is_suit_isomorphic(suit1, suit2)
                     && self.range[1].is_suit_isomorphic(suit1, suit2)
                 {
                     suit_isomorphism[suit2 as usize] = suit_isomorphism[suit1 as usize];
@@ -251,10 +490,13 @@ impl CardConfig {
             next_index += 1;
         }
 
-        let flop_mask: u64 = (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]);
+        // removed cards are treated in the same way as the board cards
+        let flop_mask: u64 =
+            (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]) | self.removed_mask();
         let mut flop_rankset = [0; 4];
 
-        for &card in &self.flop {
+        // dead cards break the symmetry of the suits in the same way as the flop cards
+        for &card in self.flop.iter().chain(&self.dead_cards) {
             let rank = card >> 2;
             let suit = card & 3;
             flop_rankset[suit as usize] |= 1 << rank;
@@ -267,8 +509,11 @@ impl CardConfig {
         let mut isomorphism_card_turn = Vec::new();
         let mut isomorphism_swap_turn = Default::default();
 
-        // turn isomorphism
-        if self.turn == NOT_DEALT {
+        // turn isomorphism (replaced by the turn abstraction if enabled)
+        // two suits are merged only if they have exactly the same ranks on the board, so swapping
+        // them preserves every hand including flushes (e.g., the third heart on a two-tone flop
+        // with two hearts is never merged with a card of another suit)
+        if self.turn == NOT_DEALT && self.turn_abstraction.is_none() {
             for suit1 in 1..4 {
                 for suit2 in 0..suit1 {
                     if flop_rankset[suit1 as usize] == flop_rankset[suit2 as usize]
@@ -299,8 +544,8 @@ impl CardConfig {
         let mut isomorphism_card_river: [Vec<u8>; 4] = Default::default();
         let mut isomorphism_swap_river: [[SwapList; 4]; 4] = Default::default();
 
-        // river isomorphism
-        if self.river == NOT_DEALT {
+        // river isomorphism (replaced by the river abstraction if enabled)
+        if self.river == NOT_DEALT && self.river_abstraction.is_none() {
             for turn in 0..52 {
                 if (1 << turn) & flop_mask != 0 || (self.turn != NOT_DEALT && self.turn != turn) {
                     continue;
@@ -351,6 +596,132 @@ impl CardConfig {
         )
     }
 
+    /// Computes the turn buckets: returns the reference indices, the merged cards, and the swap
+    /// lists of the merged cards (in the same format as the turn isomorphism).
+    pub(crate) fn turn_bucketing(
+        &self,
+        private_cards: &PrivateCards,
+    ) -> (Vec<u8>, Vec<u8>, Vec<SwapList>) {
+        let Some(TurnBucketing { rank_group_size }) = self.turn_abstraction else {
+            return Default::default();
+        };
+
+        if self.turn != NOT_DEALT {
+            return Default::default();
+        }
+
+        let flop_mask: u64 =
+            (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]) | self.removed_mask();
+
+        Self::bucketing_internal(&self.flop, flop_mask, rank_group_size, private_cards)
+    }
+
+    /// Computes the river buckets for each turn card: returns the reference indices, the merged
+    /// cards, and the swap lists of the merged cards (indexed by the turn card).
+    pub(crate) fn river_bucketing(&self, private_cards: &PrivateCards) -> RiverBucketingData {
+        let Some(RiverBucketing { rank_group_size }) = self.river_abstraction else {
+            return Default::default();
+        };
+
+        if self.river != NOT_DEALT {
+            return Default::default();
+        }
+
+        let mut bucket_ref = vec![Vec::new(); 52];
+        let mut bucket_card = vec![Vec::new(); 52];
+        let mut bucket_swap = vec![Vec::new(); 52];
+
+        let flop_mask: u64 =
+            (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]) | self.removed_mask();
+
+        for turn in 0..52 {
+            if (1 << turn) & flop_mask != 0 || (self.turn != NOT_DEALT && self.turn != turn) {
+                continue;
+            }
+
+            let board = [self.flop[0], self.flop[1], self.flop[2], turn];
+            (
+                bucket_ref[turn as usize],
+                bucket_card[turn as usize],
+                bucket_swap[turn as usize],
+            ) = Self::bucketing_internal(
+                &board,
+                flop_mask | (1 << turn),
+                rank_group_size,
+                private_cards,
+            );
+        }
+
+        (bucket_ref, bucket_card, bucket_swap)
+    }
+
+    /// Merges the cards not in `board_mask` into buckets keyed by the given board.
+    fn bucketing_internal(
+        board: &[u8],
+        board_mask: u64,
+        rank_group_size: u8,
+        private_cards: &PrivateCards,
+    ) -> (Vec<u8>, Vec<u8>, Vec<SwapList>) {
+        let mut bucket_ref = Vec::new();
+        let mut bucket_card = Vec::new();
+        let mut bucket_swap = Vec::new();
+
+        let key = |card: u8| {
+            let pairs_board = board.iter().any(|&c| c >> 2 == card >> 2);
+            let num_suited = board.iter().filter(|&&c| c & 3 == card & 3).count();
+            (pairs_board, num_suited, (card >> 2) / rank_group_size)
+        };
+
+        // representative card and child index of each bucket
+        let mut representatives = Vec::new();
+        let mut counter = 0;
+
+        for card in 0..52 {
+            if (1 << card) & board_mask != 0 {
+                continue;
+            }
+
+            match representatives.iter().find(|&&(k, _, _)| k == key(card)) {
+                Some(&(_, repr_card, index)) => {
+                    bucket_ref.push(index);
+                    bucket_card.push(card);
+                    bucket_swap.push(Self::card_swap_internal(card, repr_card, private_cards));
+                }
+                None => {
+                    representatives.push((key(card), card, counter));
+                    counter += 1;
+                }
+            }
+        }
+
+        (bucket_ref, bucket_card, bucket_swap)
+    }
+
+    /// Returns the swap list that replaces `card1` with `card2` in the private hands.
+    fn card_swap_internal(card1: u8, card2: u8, private_cards: &PrivateCards) -> SwapList {
+        let mut swap_list = SwapList::default();
+
+        for player in 0..2 {
+            let cards = &private_cards[player];
+            for (i, &(c1, c2)) in cards.iter().enumerate() {
+                let other = match (c1 == card1, c2 == card1) {
+                    (true, _) => c2,
+                    (_, true) => c1,
+                    _ => continue,
+                };
+                if other == card2 {
+                    continue;
+                }
+                let hand = (other.min(card2), other.max(card2));
+                if let Ok(index) = cards.binary_search(&hand) {
+                    swap_list[player].push((i as u16, index as u16));
+                }
+            }
+        }
+
+        swap_list
+    }
+
     fn isomorphism_swap_internal(
         swap_list: &mut [SwapList; 4],
         reverse_table: &mut [usize],
@@ -439,4 +810,55 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_board_texture() {
+        let texture = |board: &str| {
+            let flop = flop_from_str(&board[..6]).unwrap();
+            let turn = board
+                .get(6..8)
+                .map_or(NOT_DEALT, |s| card_from_str(s).unwrap());
+            let river = board
+                .get(8..10)
+                .map_or(NOT_DEALT, |s| card_from_str(s).unwrap());
+            board_texture(flop, turn, river)
+        };
+
+        // monotone flop
+        let t = texture("Ah7h2h");
+        assert!(!t.paired);
+        assert!(t.monotone);
+        assert!(!t.two_tone);
+        assert!(t.flush_possible);
+        assert!(!t.straight_possible);
+        assert_eq!(t.connectedness, 2);
+
+        // paired board
+        let t = texture("KcKd7s2c");
+        assert!(t.paired);
+        assert!(!t.monotone);
+        assert!(!t.two_tone);
+        assert!(!t.flush_possible);
+        assert!(!t.straight_possible);
+        assert_eq!(t.connectedness, 1);
+
+        // broadway straight
+        let t = texture("AsKdQh8c2c");
+        assert!(!t.paired);
+        assert!(!t.monotone);
+        assert!(!t.flush_possible);
+        assert!(t.straight_possible);
+        assert_eq!(t.connectedness, 3);
+
+        let t = texture("AhKsQhJhTh");
+        assert!(!t.monotone);
+        assert!(t.two_tone);
+        assert!(t.flush_possible);
+        assert_eq!(t.connectedness, 5);
+
+        // wheel
+        let t = texture("Ac4d3h");
+        assert!(t.straight_possible);
+        assert_eq!(t.connectedness, 3);
+    }
 }
@@ -3,17 +3,20 @@
 //  - Magic number (4 bytes): 90 57 f1 09
 //  - Version number (1 byte): 1
 //  - Compression type (1 byte): 0 (none), 1 (zstd)
-//  - Data type (1 byte): 0 (game), 1 (bunching)
+//  - Data type (1 byte): 0 (game), 1 (bunching), 2 (hand strength)
 //  - Estimated memory usage (`VarIntEncoding`)
 //  - Memo string
 //
 // `VarIntEncoding`: https://github.com/bincode-org/bincode/blob/trunk/docs/spec.md#varintencoding
 
 use crate::bunching::*;
+use crate::card::*;
 use crate::game::*;
 use crate::interface::*;
 use bincode::{Decode, Encode};
-use std::fs::File;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
 use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
@@ -24,6 +27,7 @@ const VERSION: u8 = 1;
 pub enum DataType {
     Game = 0,
     Bunching = 1,
+    HandStrength = 2,
 }
 
 /// A trait for data that can be saved into a file.
@@ -206,11 +210,88 @@ impl FileData for BunchingData {
     }
 }
 
+/// A cached hand strength table with the data it was computed from.
+#[derive(Decode, Encode)]
+pub(crate) struct HandStrengthCache {
+    flop: [u8; 3],
+    turn: u8,
+    river: u8,
+    game_type: GameType,
+    dead_cards: Vec<u8>,
+    private_cards: [Vec<(u8, u8)>; 2],
+    hand_strength: Vec<[Vec<StrengthItem>; 2]>,
+}
+
+impl FileData for HandStrengthCache {
+    fn data_type() -> DataType {
+        DataType::HandStrength
+    }
+
+    fn is_ready_to_save(&self) -> bool {
+        true
+    }
+
+    fn estimated_memory_usage(&self) -> u64 {
+        let num_items = self
+            .hand_strength
+            .iter()
+            .map(|strength| strength[0].len() + strength[1].len())
+            .sum::<usize>();
+        (num_items * std::mem::size_of::<StrengthItem>()) as u64
+    }
+}
+
+/// Returns the hand strength table, loading it from the cache directory `dir` if available.
+///
+/// If the cache is not found or does not match the given cards, the table is computed and saved
+/// to `dir`. Failures on saving the cache are ignored.
+pub(crate) fn hand_strength_with_cache(
+    card_config: &CardConfig,
+    private_cards: &[Vec<(u8, u8)>; 2],
+    dir: &Path,
+) -> Vec<[Vec<StrengthItem>; 2]> {
+    let mut hasher = DefaultHasher::new();
+    card_config.flop.hash(&mut hasher);
+    card_config.turn.hash(&mut hasher);
+    card_config.river.hash(&mut hasher);
+    (card_config.game_type as u8).hash(&mut hasher);
+    card_config.dead_cards.hash(&mut hasher);
+    private_cards.hash(&mut hasher);
+    let path = dir.join(format!("{:016x}.strength", hasher.finish()));
+
+    if let Ok((cache, _)) = load_data_from_file::<HandStrengthCache, _>(&path, None) {
+        if cache.flop == card_config.flop
+            && cache.turn == card_config.turn
+            && cache.river == card_config.river
+            && cache.game_type == card_config.game_type
+            && cache.dead_cards == card_config.dead_cards
+            && cache.private_cards == *private_cards
+        {
+            return cache.hand_strength;
+        }
+    }
+
+    let cache = HandStrengthCache {
+        flop: card_config.flop,
+        turn: card_config.turn,
+        river: card_config.river,
+        game_type: card_config.game_type,
+        dead_cards: card_config.dead_cards.clone(),
+        private_cards: private_cards.clone(),
+        hand_strength: card_config.hand_strength(private_cards),
+    };
+
+    if fs::create_dir_all(dir).is_ok() {
+        let _ = save_data_to_file(&cache, "", &path, None);
+    }
+
+    cache.hand_strength
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::action_tree::*;
-    use crate::card::*;
     use crate::range::*;
     use crate::utility::*;
 
@@ -273,6 +354,69 @@ mod tests {
         assert!((root_ev_ip - 15.0).abs() < 1e-4);
     }
 
+    #[test]
+    fn hand_strength_cache() {
+        let dir = std::env::temp_dir().join("postflop-solver-hand-strength-cache");
+        let _ = fs::remove_dir_all(&dir);
+
+        let card_config = CardConfig {
+            range: ["AA,KK".parse().unwrap(), "QQ,TT".parse().unwrap()],
+            flop: flop_from_str("2c3d7h").unwrap(),
+            turn: card_from_str("8s").unwrap(),
+            river: card_from_str("Jd").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 970,
+            ..Default::default()
+        };
+
+        let new_game = |card_config: &CardConfig| {
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::new();
+            game.set_hand_strength_cache_dir(Some(dir.clone()));
+            game.update_config(card_config.clone(), action_tree)
+                .unwrap();
+            game.cache_normalized_weights();
+            game
+        };
+
+        // miss: the computed table is saved
+        let game = new_game(&card_config);
+        assert!(game.equity(0).iter().all(|&x| x == 1.0));
+        let entries = fs::read_dir(&dir).unwrap().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 1);
+
+        // tamper the cache so that every hand ties
+        let path = entries[0].as_ref().unwrap().path();
+        let mut cache: HandStrengthCache = load_data_from_file(&path, None).unwrap().0;
+        for strength in &mut cache.hand_strength {
+            for items in strength.iter_mut().filter(|items| !items.is_empty()) {
+                let len = items.len();
+                items[1..len - 1]
+                    .iter_mut()
+                    .for_each(|item| item.strength = 1);
+            }
+        }
+        save_data_to_file(&cache, "", &path, None).unwrap();
+
+        // hit: the tampered table is loaded
+        let game = new_game(&card_config);
+        assert!(game.equity(0).iter().all(|&x| x == 0.5));
+
+        // different ranges: the cache is not used
+        let mut card_config = card_config;
+        card_config.range[1] = "QQ".parse().unwrap();
+        let game = new_game(&card_config);
+        assert!(game.equity(0).iter().all(|&x| x == 1.0));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     #[cfg(feature = "zstd")]
     fn save_and_load_file_compressed() {
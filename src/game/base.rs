@@ -1,8 +1,17 @@
 use super::*;
+use crate::bet_size::*;
 use crate::bunching::*;
 use crate::interface::*;
+use crate::range::*;
+use crate::sliceop::*;
 use crate::utility::*;
+use std::borrow::Cow;
+use std::fmt::Write;
 use std::mem::{self, MaybeUninit};
+use std::slice;
+
+#[cfg(feature = "bincode")]
+use crate::file::*;
 
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
@@ -21,7 +30,7 @@ impl Game for PostFlopGame {
     type Node = PostFlopNode;
 
     #[inline]
-    fn root(&self) -> MutexGuardLike<Self::Node> {
+    fn root(&self) -> MutexGuardLike<'_, Self::Node> {
         self.node_arena[0].lock()
     }
 
@@ -52,10 +61,11 @@ impl Game for PostFlopGame {
 
     #[inline]
     fn chance_factor(&self, node: &Self::Node) -> usize {
+        let deck_size = self.card_config.deck_size();
         if node.turn == NOT_DEALT {
-            45 - self.bunching_num_dead_cards
+            deck_size - 7 - self.bunching_num_dead_cards
         } else {
-            44 - self.bunching_num_dead_cards
+            deck_size - 8 - self.bunching_num_dead_cards
         }
     }
 
@@ -78,7 +88,9 @@ impl Game for PostFlopGame {
 
     #[inline]
     fn is_raked(&self) -> bool {
-        self.tree_config.rake_rate > 0.0 && self.tree_config.rake_cap > 0.0
+        // the ICM payoffs are not zero-sum either
+        (self.tree_config.rake_rate > 0.0 && self.tree_config.rake_cap > 0.0)
+            || self.tree_config.payoff_model != PayoffModel::ChipEv
     }
 
     #[inline]
@@ -93,7 +105,13 @@ impl Game for PostFlopGame {
     #[inline]
     fn isomorphic_swap(&self, node: &Self::Node, index: usize) -> &[Vec<(u16, u16)>; 2] {
         if node.turn == NOT_DEALT {
-            &self.isomorphism_swap_turn[self.isomorphism_card_turn[index] as usize & 3]
+            if !self.turn_bucket_swap.is_empty() {
+                &self.turn_bucket_swap[index]
+            } else {
+                &self.isomorphism_swap_turn[self.isomorphism_card_turn[index] as usize & 3]
+            }
+        } else if !self.river_bucket_swap.is_empty() {
+            &self.river_bucket_swap[node.turn as usize][index]
         } else {
             &self.isomorphism_swap_river[node.turn as usize & 3]
                 [self.isomorphism_card_river[node.turn as usize & 3][index] as usize & 3]
@@ -155,6 +173,7 @@ impl PostFlopGame {
         self.check_card_config()?;
         self.init_card_fields();
         self.init_root()?;
+        self.init_payoffs();
 
         self.state = State::TreeBuilt;
 
@@ -164,6 +183,79 @@ impl PostFlopGame {
         Ok(())
     }
 
+    /// Updates the bet sizes and rebuilds the game tree. The solved result will be lost.
+    ///
+    /// Unlike [`update_config`], this method reuses the private hands, the initial weights, and the
+    /// hand strength tables, so it is much faster when only the bet sizes are changed. The other
+    /// fields of the tree configuration are kept, and the added and removed lines are applied to
+    /// the new tree again. The locked strategies are cleared because they refer to the nodes of
+    /// the previous tree.
+    ///
+    /// Returns an error if an added or removed line is not valid in the new tree (e.g., it contains
+    /// a bet size that is no longer available); in that case, the game is not modified.
+    ///
+    /// [`update_config`]: #method.update_config
+    pub fn update_bet_sizes(
+        &mut self,
+        flop_bet_sizes: [BetSizeCandidates; 2],
+        turn_bet_sizes: [BetSizeCandidates; 2],
+        river_bet_sizes: [BetSizeCandidates; 2],
+        max_num_bets: [i32; 3],
+    ) -> Result<(), String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
+
+        let tree_config = TreeConfig {
+            flop_bet_sizes,
+            turn_bet_sizes,
+            river_bet_sizes,
+            max_num_bets,
+            ..self.tree_config.clone()
+        };
+
+        self.rebuild_tree(tree_config)
+    }
+
+    /// Updates the effective stack and rebuilds the game tree. The solved result will be lost.
+    ///
+    /// This is useful to re-solve the same spot with a shorter (or deeper) stack: the maximum
+    /// bet amounts and the all-in actions are determined by the new effective stack, while the
+    /// other fields of the tree configuration are kept. As with [`update_bet_sizes`], the hand
+    /// strength tables are reused, the added and removed lines are applied to the new tree again,
+    /// and the locked strategies are cleared.
+    ///
+    /// Returns an error if an added or removed line is not valid in the new tree (e.g., a bet in
+    /// the line exceeds the new effective stack); in that case, the game is not modified.
+    ///
+    /// [`update_bet_sizes`]: #method.update_bet_sizes
+    pub fn update_effective_stack(&mut self, effective_stack: i32) -> Result<(), String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
+
+        let tree_config = TreeConfig {
+            effective_stack,
+            ..self.tree_config.clone()
+        };
+
+        self.rebuild_tree(tree_config)
+    }
+
+    /// Sets the directory to cache the hand strength tables (set `None` to disable).
+    ///
+    /// The hand strength tables depend only on the board cards and the private hands, so they can
+    /// be reused when solving the same board with the same ranges but different bet sizes. When
+    /// the cache is enabled, [`update_config`] loads the table from the directory if available;
+    /// otherwise, the computed table is saved to the directory.
+    ///
+    /// [`update_config`]: #method.update_config
+    #[cfg(feature = "bincode")]
+    #[inline]
+    pub fn set_hand_strength_cache_dir(&mut self, dir: Option<PathBuf>) {
+        self.hand_strength_cache_dir = dir;
+    }
+
     /// Sets the bunching effect configuration.
     ///
     /// **Warning**: Enabling bunching effect will dramatically slow down the solving process.
@@ -173,6 +265,28 @@ impl PostFlopGame {
             return Err("Game is not successfully initialized".to_string());
         }
 
+        if self.card_config.game_type != GameType::Holdem {
+            return Err("Bunching effect is only supported in Hold'em".to_string());
+        }
+
+        if !self.card_config.dead_cards.is_empty() {
+            return Err("Bunching effect is not supported with dead cards".to_string());
+        }
+
+        if self.card_config.turn_abstraction.is_some() {
+            return Err("Bunching effect is not supported with the turn abstraction".to_string());
+        }
+
+        if self.card_config.river_abstraction.is_some() {
+            return Err("Bunching effect is not supported with the river abstraction".to_string());
+        }
+
+        if !self.card_config.precompute_strength {
+            return Err(
+                "Bunching effect is not supported without precomputed hand strengths".to_string(),
+            );
+        }
+
         if !bunching_data.is_ready() {
             return Err("Bunching configuration is not ready".to_string());
         }
@@ -238,189 +352,1715 @@ impl PostFlopGame {
         &self.private_cards[player]
     }
 
-    /// Returns the estimated memory usage in bytes (uncompressed, compressed).
+    /// Returns the index of the given private hand of the given player in [`private_cards`], or
+    /// `None` if the player does not have the hand in the range.
+    ///
+    /// The order of `card1` and `card2` does not matter.
+    ///
+    /// **Time complexity:** *O*(log #(private hands)).
+    ///
+    /// [`private_cards`]: #method.private_cards
     #[inline]
-    pub fn memory_usage(&self) -> (u64, u64) {
-        if self.state <= State::Uninitialized {
-            panic!("Game is not successfully initialized");
-        }
+    pub fn hand_index(&self, player: usize, card1: u8, card2: u8) -> Option<usize> {
+        let hand = (card1.min(card2), card1.max(card2));
+        self.private_cards(player).binary_search(&hand).ok()
+    }
 
-        let num_elements = 2 * self.num_storage + self.num_storage_ip + self.num_storage_chance;
-        let uncompressed = 4 * num_elements + self.misc_memory_usage;
-        let compressed = 2 * num_elements + self.misc_memory_usage;
+    /// Returns the number of private hands of the given player that do not overlap with the given
+    /// board cards.
+    ///
+    /// Note that the hands overlapping with the flop are already excluded from
+    /// [`private_cards`], so the flop cards do not need to be included in `board`. Panics if a
+    /// card in `board` is not in [0, 52).
+    ///
+    /// **Time complexity:** *O*(#(private hands)).
+    ///
+    /// [`private_cards`]: #method.private_cards
+    pub fn live_combos(&self, player: usize, board: &[u8]) -> usize {
+        let board_mask = board.iter().fold(0u64, |mask, &card| {
+            if 52 <= card {
+                panic!("Invalid board card: {card}");
+            }
+            mask | (1 << card)
+        });
 
-        (uncompressed, compressed)
+        self.private_cards(player)
+            .iter()
+            .filter(|&&(c1, c2)| {
+                let hand_mask: u64 = (1 << c1) | (1 << c2);
+                hand_mask & board_mask == 0
+            })
+            .count()
     }
 
-    /// Returns the estimated additional memory usage in bytes when the bunching effect is enabled.
-    #[inline]
-    pub fn memory_usage_bunching(&self) -> u64 {
-        if self.state <= State::Uninitialized {
-            panic!("Game is not successfully initialized");
+    /// Groups the private hands of the given player into the 13x13 hand grid.
+    ///
+    /// The element at the `j`-th row and the `k`-th column holds the indices into
+    /// [`private_cards`] of the combinations belonging to the cell, in ascending order. As in
+    /// [`strategy_grid`], both rows and columns are ordered from ace to deuce; pairs are on the
+    /// diagonal, suited hands are in the upper triangle (`j < k`), and offsuit hands are in the
+    /// lower triangle (`j > k`). Since the hands overlapping with the flop are excluded from
+    /// [`private_cards`], a cell may contain fewer combinations than the class has.
+    ///
+    /// **Time complexity:** *O*(#(private hands)).
+    ///
+    /// [`private_cards`]: #method.private_cards
+    /// [`strategy_grid`]: #method.strategy_grid
+    pub fn combos_by_class(&self, player: usize) -> [[Vec<usize>; 13]; 13] {
+        let mut ret: [[Vec<usize>; 13]; 13] = Default::default();
+        for (index, &(c1, c2)) in self.private_cards(player).iter().enumerate() {
+            let (row, col) = hand_grid_cell(c1, c2);
+            ret[row][col].push(index);
         }
-
-        self.memory_usage_bunching_internal()
+        ret
     }
 
-    /// Remove lines after building the `PostFlopGame` but before allocating memory.
+    /// Returns the list of the turn/river runouts that are not pruned by the isomorphism.
     ///
-    /// This allows the removal of chance-specific lines (e.g., remove overbets on board-pairing
-    /// turns) which we cannot do while building an action tree.
-    pub fn remove_lines(&mut self, lines: &[Vec<Action>]) -> Result<(), String> {
+    /// Each element is a tuple of `(turn, river, weight)`, where `weight` is the number of actual
+    /// runouts represented by the runout (i.e., the runout and its isomorphic runouts). Therefore,
+    /// the sum of the weights equals the number of all possible runouts. If the turn or the river
+    /// is given by the card configuration, the card is fixed in all runouts.
+    pub fn runouts(&self) -> Vec<(u8, u8, f32)> {
         if self.state <= State::Uninitialized {
-            return Err("Game is not successfully initialized".to_string());
-        } else if self.state >= State::MemoryAllocated {
-            return Err("Game has already been allocated".to_string());
+            panic!("Game is not successfully initialized");
         }
 
-        for line in lines {
-            let mut root = self.root();
-            let info = self.remove_line_recursive(&mut root, line)?;
-            self.num_storage -= info.num_storage;
-            self.num_storage_ip -= info.num_storage_ip;
-            self.num_storage_chance -= info.num_storage_chance;
+        let (turn, river) = (self.card_config.turn, self.card_config.river);
+        if river != NOT_DEALT {
+            return vec![(turn, river, 1.0)];
         }
 
-        Ok(())
-    }
+        let flop = self.card_config.flop;
+        let flop_mask: u64 =
+            (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]) | self.card_config.removed_mask();
 
-    /// Returns whether the memory is allocated.
-    ///
-    /// If the memory is allocated, returns `Some(is_compression_enabled)`;
-    /// otherwise, returns `None`.
-    #[inline]
-    pub fn is_memory_allocated(&self) -> Option<bool> {
-        if self.state <= State::TreeBuilt {
-            None
+        // representative cards and their multiplicities
+        let chances = |mask: u64, skip_cards: &[u8], iso_ref: &[u8]| {
+            let skip_mask: u64 = skip_cards.iter().map(|&card| 1 << card).sum();
+            let mut ret = (0..52)
+                .filter(|&card| (1 << card) & (mask | skip_mask) == 0)
+                .map(|card| (card, 1.0))
+                .collect::<Vec<_>>();
+            for &index in iso_ref {
+                ret[index as usize].1 += 1.0;
+            }
+            ret
+        };
+
+        let turns = if turn == NOT_DEALT {
+            chances(
+                flop_mask,
+                &self.isomorphism_card_turn,
+                &self.isomorphism_ref_turn,
+            )
         } else {
-            Some(self.is_compression_enabled)
+            vec![(turn, 1.0)]
+        };
+
+        let mut ret = Vec::new();
+        for (turn, turn_weight) in turns {
+            let rivers = chances(
+                flop_mask | (1 << turn),
+                self.river_isomorphism_cards(turn),
+                &self.isomorphism_ref_river[turn as usize],
+            );
+            for (river, river_weight) in rivers {
+                ret.push((turn, river, turn_weight * river_weight));
+            }
         }
+
+        ret
     }
 
-    /// Allocates the memory.
-    pub fn allocate_memory(&mut self, enable_compression: bool) {
+    /// Returns the win, tie, and lose masses of each private hand of the given player against the
+    /// opponent's range on the board completed by the given turn and river cards.
+    ///
+    /// Each mass is the sum of the initial weights of the opponent's hands that the hand beats,
+    /// ties with, or loses to, respectively. Opponent's hands that overlap with the hand are
+    /// excluded, and hands that overlap with the board have zero masses. The equity of a hand is
+    /// therefore `(win + tie / 2) / (win + tie + lose)`.
+    ///
+    /// **Time complexity:** *O*(#(OOP private hands) + #(IP private hands)).
+    pub fn outcome_matrix(
+        &self,
+        turn: u8,
+        river: u8,
+        player: usize,
+    ) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
         if self.state <= State::Uninitialized {
             panic!("Game is not successfully initialized");
         }
 
-        if self.state == State::MemoryAllocated
-            && self.storage_mode == BoardState::River
-            && self.is_compression_enabled == enable_compression
-        {
-            return;
+        if 52 <= turn || 52 <= river || turn == river {
+            panic!("Invalid board: turn = {turn}, river = {river}");
         }
 
-        let num_bytes = if enable_compression { 2 } else { 4 };
-        if num_bytes * self.num_storage > isize::MAX as u64
-            || num_bytes * self.num_storage_chance > isize::MAX as u64
+        self.outcome_masses(turn, river, player, &self.initial_weights[player ^ 1])
+    }
+
+    /// Returns the fraction of the pot that each private hand of the given player wins on average
+    /// at the showdown of the board completed by the given turn and river cards (win = 1, tie =
+    /// 0.5, lose = 0).
+    ///
+    /// The opponent's hands are weighted by their reach probabilities at the current node, so the
+    /// return value is the equity of each hand on this exact board against the opponent's range
+    /// reaching the node. If the turn or river card is already dealt at the current node, the
+    /// given card must match it. Hands that overlap with the board, or that have no compatible
+    /// opponent's hands reaching the node, have zero values.
+    ///
+    /// Panics if the game is not successfully initialized or the board is invalid.
+    ///
+    /// **Time complexity:** *O*(#(OOP private hands) + #(IP private hands)).
+    pub fn pot_share(&self, turn: u8, river: u8, player: usize) -> Vec<f32> {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        if 52 <= turn
+            || 52 <= river
+            || turn == river
+            || (self.turn != NOT_DEALT && self.turn != turn)
+            || (self.river != NOT_DEALT && self.river != river)
         {
-            panic!("Memory usage exceeds maximum size");
+            panic!("Invalid board: turn = {turn}, river = {river}");
         }
 
-        self.state = State::MemoryAllocated;
-        self.is_compression_enabled = enable_compression;
+        let (win, tie, lose) = self.outcome_masses(turn, river, player, &self.weights[player ^ 1]);
 
-        self.clear_storage();
+        win.iter()
+            .zip(tie.iter())
+            .zip(lose.iter())
+            .map(|((&win, &tie), &lose)| {
+                let total = win + tie + lose;
+                if total > 0.0 {
+                    (win + 0.5 * tie) / total
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
 
-        let storage_bytes = (num_bytes * self.num_storage) as usize;
-        let storage_ip_bytes = (num_bytes * self.num_storage_ip) as usize;
-        let storage_chance_bytes = (num_bytes * self.num_storage_chance) as usize;
+    /// Internal method for [`outcome_matrix`] and [`pot_share`] with the given opponent's weights.
+    ///
+    /// [`outcome_matrix`]: #method.outcome_matrix
+    /// [`pot_share`]: #method.pot_share
+    fn outcome_masses(
+        &self,
+        turn: u8,
+        river: u8,
+        player: usize,
+        opponent_weights: &[f32],
+    ) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
+        let hand_strength = self.hand_strength_at(turn, river);
+        if hand_strength[0].is_empty() {
+            panic!("Invalid board: turn = {turn}, river = {river}");
+        }
+
+        let player_strength = &hand_strength[player];
+        let opponent_strength = &hand_strength[player ^ 1];
+        let player_cards = &self.private_cards[player];
+        let opponent_cards = &self.private_cards[player ^ 1];
+        let same_hand_index = &self.same_hand_index[player];
+
+        let mut win = vec![0.0; player_cards.len()];
+        let mut tie = vec![0.0; player_cards.len()];
+        let mut lose = vec![0.0; player_cards.len()];
+
+        let valid_player_strength = &player_strength[1..player_strength.len() - 1];
+        let valid_opponent_strength = &opponent_strength[1..opponent_strength.len() - 1];
+
+        let mut weight_sum = 0.0;
+        let mut weight_minus = [0.0; 52];
+        for &StrengthItem { index, .. } in valid_opponent_strength {
+            let weight = opponent_weights[index as usize] as f64;
+            let (c1, c2) = opponent_cards[index as usize];
+            weight_sum += weight;
+            weight_minus[c1 as usize] += weight;
+            weight_minus[c2 as usize] += weight;
+        }
+
+        // `win` accumulates strictly weaker hands, `win_tie` accumulates hands not stronger
+        let mut weight_sum_win = 0.0;
+        let mut weight_sum_win_tie = 0.0;
+        let mut weight_minus_win = [0.0; 52];
+        let mut weight_minus_win_tie = [0.0; 52];
+        let mut i = 1;
+        let mut j = 1;
+
+        for &StrengthItem { strength, index } in valid_player_strength {
+            while opponent_strength[i].strength < strength {
+                let opponent_index = opponent_strength[i].index as usize;
+                let weight = opponent_weights[opponent_index] as f64;
+                let (c1, c2) = opponent_cards[opponent_index];
+                weight_sum_win += weight;
+                weight_minus_win[c1 as usize] += weight;
+                weight_minus_win[c2 as usize] += weight;
+                i += 1;
+            }
 
-        self.storage1 = vec![0; storage_bytes];
-        self.storage2 = vec![0; storage_bytes];
-        self.storage_ip = vec![0; storage_ip_bytes];
-        self.storage_chance = vec![0; storage_chance_bytes];
+            while opponent_strength[j].strength <= strength {
+                let opponent_index = opponent_strength[j].index as usize;
+                let weight = opponent_weights[opponent_index] as f64;
+                let (c1, c2) = opponent_cards[opponent_index];
+                weight_sum_win_tie += weight;
+                weight_minus_win_tie[c1 as usize] += weight;
+                weight_minus_win_tie[c2 as usize] += weight;
+                j += 1;
+            }
 
-        self.allocate_memory_nodes();
+            let index = index as usize;
+            let (c1, c2) = player_cards[index];
+            let same_i = same_hand_index[index];
+            let weight_same = if same_i == u16::MAX {
+                0.0
+            } else {
+                opponent_weights[same_i as usize] as f64
+            };
 
-        self.storage_mode = BoardState::River;
-        self.target_storage_mode = BoardState::River;
+            // inclusion-exclusion principle (the same hand always ties)
+            let weight_total =
+                weight_sum - weight_minus[c1 as usize] - weight_minus[c2 as usize] + weight_same;
+            let weight_win =
+                weight_sum_win - weight_minus_win[c1 as usize] - weight_minus_win[c2 as usize];
+            let weight_win_tie = weight_sum_win_tie
+                - weight_minus_win_tie[c1 as usize]
+                - weight_minus_win_tie[c2 as usize]
+                + weight_same;
+
+            win[index] = weight_win as f32;
+            tie[index] = (weight_win_tie - weight_win) as f32;
+            lose[index] = (weight_total - weight_win_tie) as f32;
+        }
+
+        (win, tie, lose)
     }
 
-    /// Checks the card configuration.
-    pub(crate) fn check_card_config(&mut self) -> Result<(), String> {
-        let config = &self.card_config;
-        let (flop, turn, river) = (config.flop, config.turn, config.river);
-        let range = &config.range;
+    /// Overrides the initial weights (reach probabilities) of the given player.
+    ///
+    /// The `weights` argument must be a slice of the length of `#(private hands)` whose elements
+    /// are between `0.0` and `1.0`, inclusive. This is useful for solving a subgame with the reach
+    /// probabilities obtained from a previous solution. The current node will be reset to the root.
+    ///
+    /// Note that the private hands are determined by the ranges of `CardConfig`; hands with zero
+    /// weight in the range cannot be added by this method.
+    pub fn set_initial_weights(&mut self, player: usize, weights: &[f32]) -> Result<(), String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
 
-        if flop.contains(&NOT_DEALT) {
-            return Err("Flop cards not initialized".to_string());
+        if self.state == State::Solved {
+            return Err("Game has already been solved".to_string());
         }
 
-        if flop.iter().any(|&c| 52 <= c) {
-            return Err(format!("Flop cards must be in [0, 52): flop = {flop:?}"));
+        if self.bunching_num_dead_cards > 0 {
+            return Err("Cannot set initial weights with the bunching effect".to_string());
         }
 
-        if flop[0] == flop[1] || flop[0] == flop[2] || flop[1] == flop[2] {
-            return Err(format!("Flop cards must be unique: flop = {flop:?}"));
+        if weights.len() != self.num_private_hands(player) {
+            return Err(format!(
+                "Invalid length of weights: expected = {}, actual = {}",
+                self.num_private_hands(player),
+                weights.len()
+            ));
         }
 
-        if turn != NOT_DEALT {
-            if 52 <= turn {
-                return Err(format!("Turn card must be in [0, 52): turn = {turn}"));
-            }
+        if weights.iter().any(|w| !(0.0..=1.0).contains(w)) {
+            return Err("Weights must be between 0.0 and 1.0".to_string());
+        }
 
-            if flop.contains(&turn) {
-                return Err(format!(
-                    "Turn card must be different from flop cards: turn = {turn}"
-                ));
-            }
+        let prev_weights = mem::replace(&mut self.initial_weights[player], weights.to_vec());
+        if let Err(err) = self.init_num_combinations() {
+            self.initial_weights[player] = prev_weights;
+            self.init_num_combinations()?;
+            return Err(err);
         }
 
-        if river != NOT_DEALT {
-            if 52 <= river {
-                return Err(format!("River card must be in [0, 52): river = {river}"));
-            }
+        self.back_to_root();
+        Ok(())
+    }
 
-            if flop.contains(&river) {
-                return Err(format!(
-                    "River card must be different from flop cards: river = {river}"
-                ));
-            }
+    /// Returns the estimated memory usage in bytes (uncompressed, compressed).
+    #[inline]
+    pub fn memory_usage(&self) -> (u64, u64) {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
 
-            if turn == river {
-                return Err(format!(
-                    "River card must be different from turn card: river = {river}"
-                ));
-            }
+        let num_elements = 2 * self.num_storage + self.num_storage_ip + self.num_storage_chance;
+        let uncompressed = 4 * num_elements + self.misc_memory_usage;
+        let compressed = 2 * num_elements + self.misc_memory_usage;
 
-            if turn == NOT_DEALT {
-                return Err(format!(
-                    "River card specified without turn card: river = {river}"
-                ));
-            }
+        (uncompressed, compressed)
+    }
+
+    /// Estimates the memory usage in bytes (uncompressed, compressed) of the game that would be
+    /// created by `with_config(card_config, action_tree)`, without building it.
+    ///
+    /// The estimate covers the storage allocated by `allocate_memory()` and the game tree nodes,
+    /// but not the hand strength tables. Therefore, the returned values are slightly smaller than
+    /// those of `memory_usage()`.
+    pub fn estimate_memory(
+        card_config: &CardConfig,
+        action_tree: &ActionTree,
+    ) -> Result<(u64, u64), String> {
+        if !action_tree.invalid_terminals().is_empty() {
+            return Err("Invalid terminal is found in action tree".to_string());
         }
 
-        let expected_state = match (turn != NOT_DEALT, river != NOT_DEALT) {
-            (false, _) => BoardState::Flop,
-            (true, false) => BoardState::Turn,
-            (true, true) => BoardState::River,
+        let mut game = Self {
+            card_config: card_config.clone(),
+            tree_config: action_tree.config().clone(),
+            ..Default::default()
         };
 
-        if self.tree_config.initial_state != expected_state {
-            return Err(format!(
-                "Invalid initial state of `tree_config`: expected = {:?}, actual = {:?}",
-                expected_state, self.tree_config.initial_state
-            ));
-        }
+        game.check_card_config()?;
 
-        if range[0].is_empty() {
-            return Err("OOP range is empty".to_string());
-        }
+        (
+            game.isomorphism_ref_turn,
+            game.isomorphism_card_turn,
+            _,
+            game.isomorphism_ref_river,
+            game.isomorphism_card_river,
+            _,
+        ) = card_config.isomorphism(&game.private_cards);
+
+        if card_config.river_abstraction.is_some() {
+            (game.isomorphism_ref_river, game.river_bucket_card, _) =
+                card_config.river_bucketing(&game.private_cards);
+        }
+
+        let coef = game.street_coefficients();
+        let root = action_tree.root();
+        let num_action_nodes = count_num_action_nodes(&root);
+        let num_private_hands = [
+            game.num_private_hands(0) as u64,
+            game.num_private_hands(1) as u64,
+        ];
+        let num_elements = count_num_elements(&root, num_private_hands);
 
-        if range[1].is_empty() {
-            return Err("IP range is empty".to_string());
+        let mut total_num_nodes = 0;
+        let mut total_num_elements = 0;
+        for street in 0..3 {
+            total_num_nodes += num_action_nodes[street] * coef[street];
+            total_num_elements +=
+                (2 * num_elements[0][street] + num_elements[1][street] + num_elements[2][street])
+                    * coef[street];
         }
 
-        if !range[0].is_valid() {
-            return Err("OOP range is invalid (loaded broken data?)".to_string());
-        }
+        let misc_memory_usage =
+            mem::size_of::<Self>() as u64 + mem::size_of::<PostFlopNode>() as u64 * total_num_nodes;
 
-        if !range[1].is_valid() {
-            return Err("IP range is invalid (loaded broken data?)".to_string());
+        Ok((
+            4 * total_num_elements + misc_memory_usage,
+            2 * total_num_elements + misc_memory_usage,
+        ))
+    }
+
+    /// Returns the estimated additional memory usage in bytes when the bunching effect is enabled.
+    #[inline]
+    pub fn memory_usage_bunching(&self) -> u64 {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
         }
 
-        self.init_hands();
+        self.memory_usage_bunching_internal()
+    }
+
+    /// Calls `f` on every node of the game tree in depth-first order.
+    ///
+    /// The second argument of `f` is the list of actions leading from the root to the node.
+    /// Children of chance nodes are reached by `Action::Chance(card)`; isomorphic chances that
+    /// are merged into another card are not visited.
+    pub fn for_each_node<F: FnMut(&PostFlopNode, &[Action])>(&self, mut f: F) {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        let mut line = Vec::new();
+        Self::for_each_node_recursive(&self.root(), &mut line, &mut f);
+    }
+
+    /// Returns the nodes where two or more bet size candidates collapsed into the same all-in
+    /// action, together with the affected candidates.
+    ///
+    /// Each node is identified by the line of actions from the root, excluding the chance actions.
+    /// See [`ActionTree::collapsed_sizes`] for details.
+    ///
+    /// Panics if the game is not successfully initialized.
+    pub fn collapsed_sizes(&self) -> Vec<(Vec<Action>, Vec<BetSize>)> {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        ActionTree::collapsed_sizes_of(&self.tree_config, &self.action_root.lock())
+    }
+
+    /// Returns the statistics of the game tree.
+    ///
+    /// The `strategy_bytes` field equals the size of the strategy storage allocated by
+    /// `allocate_memory(false)`.
+    pub fn tree_stats(&self) -> TreeStats {
+        let mut stats = TreeStats::default();
+
+        self.for_each_node(|node, _| {
+            stats.num_nodes += 1;
+            if node.is_terminal() {
+                stats.num_terminal += 1;
+            } else if node.is_chance() {
+                stats.num_chance += 1;
+            } else {
+                stats.num_player += 1;
+                stats.strategy_bytes += 4 * node.num_elements as u64;
+            }
+        });
+
+        stats
+    }
+
+    /// Verifies that every chance node covers all cards remaining in the deck.
+    ///
+    /// At each chance node, the cards dealt to the children and the isomorphic cards merged into
+    /// them must be distinct and together make up the deck without the board cards and the
+    /// removed cards (i.e., 49 cards at the turn and 48 cards at the river in Hold'em without
+    /// dead cards). This is an internal invariant intended for debugging; it is violated only if
+    /// the isomorphism over-merges or drops runouts.
+    ///
+    /// Returns an error identifying the line to the first offending chance node.
+    pub fn verify_chance_completeness(&self) -> Result<(), String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
+
+        let mut result = Ok(());
+        self.for_each_node(|node, line| {
+            if result.is_ok() && node.is_chance() {
+                if let Err(err) = self.verify_chance_node(node) {
+                    result = Err(format!("{err}: line = {line:?}"));
+                }
+            }
+        });
+
+        result
+    }
+
+    /// Returns all terminal nodes of the game tree with their outcome types.
+    ///
+    /// Each element is a tuple of `(line, kind)`, where `line` is the list of actions leading from
+    /// the root to the node (chance actions are included as `Action::Chance(card)`, and isomorphic
+    /// chances that are merged into another card are not visited), and `kind` is the outcome type
+    /// of the node. The pot size is computed in the same way as [`node_info`]. The elements are
+    /// sorted in depth-first order.
+    ///
+    /// [`node_info`]: #method.node_info
+    pub fn terminal_nodes(&self) -> Vec<(Vec<Action>, TerminalKind)> {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        let mut ret = Vec::new();
+        self.terminal_nodes_recursive(&self.root(), &mut Vec::new(), [0, 0], &mut ret);
+        ret
+    }
+
+    /// Returns the expected value of the given player for each completed board, conditioned on
+    /// reaching the river with the board.
+    ///
+    /// Each element is a tuple of `(turn, river, ev)`. The boards that cannot be reached (e.g.,
+    /// the players are always folded before the river) are omitted, and the elements are sorted
+    /// by the turn and river cards. The runouts merged by the suit isomorphism are also listed.
+    /// The value is the expected value of the current strategy of both players in the same unit
+    /// as [`compute_current_ev`] (i.e., the bias is subtracted), so the sum of `ev` over the
+    /// boards weighted by the probabilities of reaching them equals the contribution of the river
+    /// subtrees to the expected value of the game. The river subtrees are processed in parallel.
+    ///
+    /// Returns an error if the memory is not yet allocated, the `player` is invalid, or the
+    /// turn abstraction or the bunching effect is enabled.
+    ///
+    /// [`compute_current_ev`]: crate::compute_current_ev
+    pub fn ev_by_board(&self, player: usize) -> Result<Vec<(u8, u8, f32)>, String> {
+        self.check_analysis_supported(player)?;
+
+        // (reach-weighted value, probability) of each board
+        let boards = MutexLike::new(vec![[0.0; 2]; 52 * 52]);
+
+        self.ev_by_board_recursive(
+            &self.root(),
+            player,
+            [
+                self.initial_weights[0].clone(),
+                self.initial_weights[1].clone(),
+            ],
+            &[[0, 1, 2, 3]],
+            &boards,
+        );
+
+        let ret = boards
+            .lock()
+            .iter()
+            .enumerate()
+            .filter(|(_, &[_, prob])| prob > 0.0)
+            .map(|(index, &[value, prob])| {
+                let (turn, river) = ((index / 52) as u8, (index % 52) as u8);
+                (turn, river, (value / prob) as f32)
+            })
+            .collect();
+
+        Ok(ret)
+    }
+
+    /// Returns the probability that each private hand of the given player reaches a showdown under
+    /// the current strategy of both players.
+    ///
+    /// The probability is conditioned on the hand being dealt: it is averaged over the hands of
+    /// the opponent (weighted by their initial weights) and the runouts. The rest of the
+    /// probability corresponds to the hand folding or making the opponent fold, so this value
+    /// decomposes the expected value of a hand into the showdown and fold components. Hands that
+    /// cannot be dealt have the value of `0.0`.
+    ///
+    /// Returns an error if the memory is not yet allocated, the `player` is invalid, or the turn
+    /// abstraction or the bunching effect is enabled.
+    pub fn showdown_frequency(&self, player: usize) -> Result<Vec<f32>, String> {
+        self.check_analysis_supported(player)?;
+
+        let cfreach = &self.initial_weights[player ^ 1];
+        let [reached] =
+            self.accumulate_recursive(&self.root(), player, cfreach, &|node, cfreach| {
+                let is_showdown =
+                    node.is_terminal() && node.player & PLAYER_FOLD_FLAG != PLAYER_FOLD_FLAG;
+                is_showdown.then(|| [self.compatible_reach(player, cfreach, node.turn, node.river)])
+            });
+        let total = self.compatible_reach(player, cfreach, NOT_DEALT, NOT_DEALT);
+
+        let ret = reached
+            .iter()
+            .zip(&total)
+            .map(|(&r, &t)| if t > 0.0 { r / t } else { 0.0 })
+            .collect();
+
+        Ok(ret)
+    }
+
+    /// Returns the expected value of the given hand of the `player` at the root, split into the
+    /// parts collected at the fold terminals and at the showdowns under the current strategy.
+    ///
+    /// The `hand_index` is the index of the hand in [`private_cards`]. The values are in the
+    /// same unit as the [`expected_values`] method: the chips returned to the player at a
+    /// terminal node are attributed to its type, so `total` equals the expected value of the hand
+    /// at the root. Hands that cannot be dealt have all values of `0.0`.
+    ///
+    /// Returns an error if the memory is not yet allocated, the `player` or the `hand_index` is
+    /// invalid, or the card abstraction or the bunching effect is enabled.
+    ///
+    /// [`private_cards`]: #method.private_cards
+    /// [`expected_values`]: #method.expected_values
+    pub fn ev_breakdown(&self, player: usize, hand_index: usize) -> Result<EvBreakdown, String> {
+        self.check_analysis_supported(player)?;
+
+        if hand_index >= self.num_private_hands(player) {
+            return Err(format!("Invalid hand index: {hand_index}"));
+        }
+
+        let cfreach = &self.initial_weights[player ^ 1];
+        let num_hands = self.num_private_hands(player);
+        let invested = (self.initial_invested()[player] / self.num_combinations) as f32;
+
+        // the initial investment of the `player` is added back at the terminal nodes so that the
+        // values are in the same unit as the expected values
+        let [fold, showdown] =
+            self.accumulate_recursive(&self.root(), player, cfreach, &|node, cfreach| {
+                if !node.is_terminal() {
+                    return None;
+                }
+
+                let mut result = Vec::with_capacity(num_hands);
+                self.evaluate_internal(result.spare_capacity_mut(), node, player, cfreach);
+                unsafe { result.set_len(num_hands) };
+
+                let reach = self.compatible_reach(player, cfreach, node.turn, node.river);
+                result
+                    .iter_mut()
+                    .zip(&reach)
+                    .for_each(|(v, &r)| *v += invested * r);
+
+                Some(if node.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG {
+                    [result, vec![0.0; num_hands]]
+                } else {
+                    [vec![0.0; num_hands], result]
+                })
+            });
+        let total = self.compatible_reach(player, cfreach, NOT_DEALT, NOT_DEALT)[hand_index];
+
+        if total == 0.0 {
+            return Ok(EvBreakdown::default());
+        }
+
+        let normalizer = self.num_combinations as f32 / total;
+        let fold_ev = fold[hand_index] * normalizer;
+        let showdown_ev = showdown[hand_index] * normalizer;
+
+        Ok(EvBreakdown {
+            fold_ev,
+            showdown_ev,
+            total: fold_ev + showdown_ev,
+        })
+    }
+
+    /// Returns the aggression frequency of the given player under the current strategy of both
+    /// players.
+    ///
+    /// The aggression frequency is the fraction of the decisions of the `player` in which the
+    /// player bets or raises (including all-in), rather than checks, calls, or folds. Each
+    /// decision node is weighted by the probability that the node is reached, over the initial
+    /// ranges of both players and the runouts. Returns `0.0` if the player has no decision.
+    ///
+    /// Returns an error if the memory is not yet allocated, the `player` is invalid, or the card
+    /// abstraction or the bunching effect is enabled.
+    pub fn aggression_frequency(&self, player: usize) -> Result<f32, String> {
+        self.check_analysis_supported(player)?;
+
+        let cfreach = &self.initial_weights[player ^ 1];
+        // at the decision nodes of the `player`, collect the reach probabilities weighted by the
+        // probabilities of the aggressive actions and those without the weighting
+        let [aggressive, total] =
+            self.accumulate_recursive(&self.root(), player, cfreach, &|node, cfreach| {
+                if node.is_terminal() || node.is_chance() || node.player() != player {
+                    return None;
+                }
+
+                let num_hands = self.num_private_hands(player);
+                let strategy = node_strategy(self, node);
+                let reach = self.compatible_reach(player, cfreach, node.turn, node.river);
+                let mut aggressive = vec![0.0; num_hands];
+
+                for action in 0..node.num_actions() {
+                    if let Action::Bet(_) | Action::Raise(_) | Action::AllIn(_) =
+                        node.play(action).prev_action
+                    {
+                        add_slice(&mut aggressive, row(&strategy, action, num_hands));
+                    }
+                }
+
+                mul_slice(&mut aggressive, &reach);
+                Some([aggressive, reach])
+            });
+
+        let weights = &self.initial_weights[player];
+        let aggressive = inner_product(&aggressive, weights) as f64;
+        let total = inner_product(&total, weights) as f64;
+
+        Ok(if total > 0.0 {
+            (aggressive / total) as f32
+        } else {
+            0.0
+        })
+    }
+
+    /// Estimates the expected value that `player` gains by adding the given bet size at the node
+    /// reached by the given line.
+    ///
+    /// The `line` must include chance actions as `Action::Chance(card)`. A game is built from the
+    /// current configuration with the action of `size` added at the node (since the action tree is
+    /// shared among the runouts, the size is added after every runout), warm-started from the
+    /// solved strategy of this game with [`warm_start_from`], and then the subtree rooted at the
+    /// node is re-solved with [`resolve_subtree`] for `num_iterations` iterations. The return
+    /// value is the difference of the expected values of `player` at the node computed by
+    /// [`compute_ev_at`] (with the size minus without the size). The strategies outside the
+    /// subtree are those of this game, while the locked strategies in the subtree are not carried
+    /// over. Note that the subtree is re-solved without a gadget game, so the value is an estimate
+    /// against the fixed reach probabilities of both players at the node.
+    ///
+    /// This game must be solved, so the peak memory usage is that of this game plus one more game
+    /// of about the same size. If `max_memory_usage` is `Some`, the estimated memory usage of the
+    /// game with the size added is checked against it (in bytes) before anything is allocated.
+    ///
+    /// Returns an error if the game is not solved, the `player` is invalid, the compressed storage,
+    /// the card abstraction, or the bunching effect is enabled, the line is invalid or leads to a
+    /// node that cannot be reached, the size is not available at the node or yields an existing
+    /// action, or the estimated memory usage exceeds `max_memory_usage`.
+    ///
+    /// **Time complexity:** one solve of the subtree for `num_iterations` iterations, in addition
+    /// to the construction of the game with the size added.
+    ///
+    /// [`warm_start_from`]: #method.warm_start_from
+    /// [`resolve_subtree`]: #method.resolve_subtree
+    /// [`compute_ev_at`]: #method.compute_ev_at
+    pub fn size_addition_value(
+        &mut self,
+        line: &[Action],
+        player: usize,
+        size: BetSize,
+        num_iterations: u32,
+        max_memory_usage: Option<u64>,
+    ) -> Result<f32, String> {
+        if self.state != State::Solved {
+            return Err("Game is not solved".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        if self.is_compression_enabled {
+            return Err("Compressed storage is not supported".to_string());
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            return Err("Not supported with the bunching effect".to_string());
+        }
+
+        let base_ev = self.compute_ev_at(line, player)?;
+
+        // the action tree does not contain chance actions
+        let mut tree_line = line
+            .iter()
+            .filter(|action| !matches!(action, Action::Chance(_)))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut extended_tree = ActionTree::with_lines(
+            self.tree_config.clone(),
+            &self.added_lines,
+            &self.removed_lines,
+        )?;
+        let action = extended_tree.bet_size_action(&tree_line, &size)?;
+        tree_line.push(action);
+        extended_tree.add_line(&tree_line)?;
+
+        if let Some(max_memory_usage) = max_memory_usage {
+            let (memory_usage, _) = Self::estimate_memory(&self.card_config, &extended_tree)?;
+            if memory_usage > max_memory_usage {
+                return Err("Estimated memory usage is too large".to_string());
+            }
+        }
+
+        let mut game = Self::with_config(self.card_config.clone(), extended_tree)?;
+        game.allocate_memory(false);
+        game.warm_start_from(self, |line| Some(line.to_vec()))?;
+        game.resolve_subtree(line, num_iterations)?;
+        finalize(&mut game);
+
+        Ok(game.compute_ev_at(line, player)? - base_ev)
+    }
+
+    /// Verifies that the expected values of the two players sum up to the starting pot under the
+    /// current strategy.
+    ///
+    /// This is a cheap invariant of a correctly evaluated game: the chips in the pot are only moved
+    /// between the players, so any mismatch indicates a bug in the evaluation or the reach
+    /// computation. When the game is raked, the sum is only checked to be between the starting pot
+    /// minus the maximum possible rake and the starting pot, because the expected rake depends on
+    /// the strategy.
+    ///
+    /// Returns an error describing the mismatch if the sum deviates by more than `tolerance`.
+    /// Also returns an error if the memory is not yet allocated or the ICM payoff model is used
+    /// (the ICM payoffs are not zero-sum).
+    pub fn verify_zero_sum(&self, tolerance: f32) -> Result<(), String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if self.tree_config.payoff_model != PayoffModel::ChipEv {
+            return Err("Not supported with the ICM payoff model".to_string());
+        }
+
+        let ev = compute_current_ev(self);
+        let invested = self.initial_invested();
+        let sum = ev[0] as f64 + ev[1] as f64 + invested[0] + invested[1];
+
+        let config = &self.tree_config;
+        let expected = config.starting_pot as f64;
+        let max_pot = (config.starting_pot + 2 * config.effective_stack) as f64;
+        let max_rake = (max_pot * config.rake_rate).min(config.rake_cap);
+
+        let tolerance = tolerance as f64;
+        if sum > expected + tolerance || sum < expected - max_rake - tolerance {
+            let expected = if max_rake > 0.0 {
+                format!("[{:.6}, {expected:.6}]", expected - max_rake)
+            } else {
+                format!("{expected:.6}")
+            };
+            return Err(format!(
+                "Sum of expected values does not match the starting pot: \
+                 sum = {sum:.6}, expected = {expected}, tolerance = {tolerance}"
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the game tree in the DOT format of Graphviz.
+    ///
+    /// Each node is labeled with the player to act (or "chance", "fold", and "showdown") and the
+    /// pot size, and each edge is labeled with the action. Nodes deeper than `max_depth` (the
+    /// number of actions from the root, including chance actions) are omitted.
+    pub fn to_dot(&self, max_depth: usize) -> String {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        let mut out = String::from("digraph {\n    node [shape=box];\n");
+        let mut next_id = 0;
+        self.to_dot_recursive(&mut out, &self.root(), &mut next_id, [0, 0], max_depth);
+        out.push_str("}\n");
+        out
+    }
+
+    /// Returns the pot size, the remaining stacks, and the amount to call at the node reached by
+    /// the given line from the root.
+    ///
+    /// Chance actions (i.e., dealing turn and river cards) must be omitted from the `actions`.
+    pub fn node_info(&self, actions: &[Action]) -> Result<NodeInfo, String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
+
+        let (_, total_bet_amount) = self.node_and_bet_amount(actions)?;
+        Ok(self.node_info_internal(total_bet_amount))
+    }
+
+    /// Returns the action leading to the `child_index`-th child of the node reached by the given
+    /// line from the root, the [`NodeInfo`] of the child, and the [`NodeRole`] of the child (i.e.,
+    /// the player to act at the child, or the kind of the child if no player acts).
+    ///
+    /// The `actions` follow the same rule as [`node_info`]. If the node is a chance node, the
+    /// action is the dealt card and the [`NodeInfo`] is the same as that of the node. Returns an
+    /// error if the node is terminal or `child_index` is out of range.
+    ///
+    /// [`node_info`]: #method.node_info
+    pub fn child_info(
+        &self,
+        actions: &[Action],
+        child_index: usize,
+    ) -> Result<(Action, NodeInfo, NodeRole), String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
+
+        let (node, total_bet_amount) = self.node_and_bet_amount(actions)?;
+
+        if node.is_terminal() {
+            return Err("Terminal node has no children".to_string());
+        }
+
+        if child_index >= node.num_children as usize {
+            return Err(format!("Invalid child index: {child_index}"));
+        }
+
+        let child = node.children()[child_index].lock();
+        let action = child.prev_action;
+        let total_bet_amount = next_bet_amount(node, action, total_bet_amount);
+        Ok((
+            action,
+            self.node_info_internal(total_bet_amount),
+            child.role(),
+        ))
+    }
+
+    /// Returns the stack-to-pot ratio (SPR) at the node reached by the given line from the root.
+    ///
+    /// The SPR is the smaller remaining stack of the two players divided by the pot size, where
+    /// both values are those returned by [`node_info`] (i.e., a pending bet is included in the
+    /// pot). The `actions` follow the same rule as [`node_info`].
+    ///
+    /// [`node_info`]: #method.node_info
+    pub fn spr_at(&self, actions: &[Action]) -> Result<f32, String> {
+        let info = self.node_info(actions)?;
+        Ok(info.oop_stack.min(info.ip_stack) as f32 / info.pot as f32)
+    }
+
+    /// Returns the cards that may be dealt at the chance node reached by the given line from the
+    /// root.
+    ///
+    /// Unlike [`node_info`], chance actions must be included in the `actions` as
+    /// `Action::Chance(card)`, where `card` must be a representative card (i.e., not isomorphic to
+    /// another card). Each element of the return value is a tuple of `(card, representative)`:
+    /// `representative` is `None` if the card has its own child node, and otherwise, it is the
+    /// card of the child node that is shared by the isomorphic card. The elements are sorted by
+    /// the card.
+    ///
+    /// [`node_info`]: #method.node_info
+    pub fn chance_actions(&self, actions: &[Action]) -> Result<Vec<(u8, Option<u8>)>, String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
+
+        let node = self
+            .node_by_line(actions)
+            .ok_or_else(|| format!("Invalid line: {actions:?}"))?;
+
+        if !node.is_chance() {
+            return Err("Not a chance node".to_string());
+        }
+
+        let cards = node
+            .children()
+            .iter()
+            .map(|child| match child.lock().prev_action {
+                Action::Chance(card) => card,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        let (iso_card, iso_ref): (&[u8], &[u8]) = if node.turn == NOT_DEALT {
+            (&self.isomorphism_card_turn, &self.isomorphism_ref_turn)
+        } else {
+            (
+                self.river_isomorphism_cards(node.turn),
+                &self.isomorphism_ref_river[node.turn as usize],
+            )
+        };
+
+        let mut ret = cards.iter().map(|&card| (card, None)).collect::<Vec<_>>();
+        for (&card, &index) in iso_card.iter().zip(iso_ref) {
+            ret.push((card, Some(cards[index as usize])));
+        }
+
+        ret.sort_unstable();
+        Ok(ret)
+    }
+
+    /// Returns the strategy of the player node right after dealing the `card` at the chance node
+    /// reached by the `prefix` from the root.
+    ///
+    /// The `prefix` follows the same rule as [`chance_actions`]. Unlike the `prefix`, the `card`
+    /// may be isomorphic to another card: in that case, the strategy of the representative node
+    /// is permuted so that the hands are oriented to the suit of the requested `card`. The return
+    /// value is in the same format as [`strategy`].
+    ///
+    /// Returns an error if the `card` cannot be dealt or the next node is not a player node (i.e.,
+    /// the players are all-in). Also, returns an error if the memory is not yet allocated.
+    ///
+    /// [`chance_actions`]: #method.chance_actions
+    /// [`strategy`]: #method.strategy
+    pub fn strategy_at_card(&self, prefix: &[Action], card: u8) -> Result<Vec<f32>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if self.storage_mode != BoardState::River {
+            return Err("Storage mode is not compatible".to_string());
+        }
+
+        let node = self
+            .node_by_line(prefix)
+            .ok_or_else(|| format!("Invalid line: {prefix:?}"))?;
+
+        if !node.is_chance() {
+            return Err("Not a chance node".to_string());
+        }
+
+        let children = node.children();
+
+        let (iso_card, iso_ref): (&[u8], &[u8]) = if node.turn == NOT_DEALT {
+            (&self.isomorphism_card_turn, &self.isomorphism_ref_turn)
+        } else {
+            (
+                self.river_isomorphism_cards(node.turn),
+                &self.isomorphism_ref_river[node.turn as usize],
+            )
+        };
+
+        let position = children
+            .iter()
+            .position(|child| child.lock().prev_action == Action::Chance(card));
+
+        let (child_index, isomorphic_index) = match position {
+            Some(index) => (index, None),
+            None => {
+                let i = iso_card
+                    .iter()
+                    .position(|&c| c == card)
+                    .ok_or_else(|| format!("Card cannot be dealt: {card}"))?;
+                (iso_ref[i] as usize, Some(i))
+            }
+        };
+
+        let child = children[child_index].lock();
+        if child.is_terminal() || child.is_chance() {
+            return Err("Not a player node".to_string());
+        }
+
+        let player = child.player();
+        let num_hands = self.num_private_hands(player);
+
+        let mut ret = if self.is_compression_enabled {
+            normalized_strategy_compressed(child.strategy_compressed(), child.num_actions())
+        } else {
+            normalized_strategy(child.strategy(), child.num_actions())
+        };
+
+        apply_locking_strategy(&mut ret, self.locking_strategy(&child));
+
+        if let Some(i) = isomorphic_index {
+            let swap_list = &self.isomorphic_swap(node, i)[player];
+            ret.chunks_exact_mut(num_hands).for_each(|row| {
+                apply_swap(row, swap_list);
+            });
+        }
+
+        Ok(ret)
+    }
+
+    /// Returns the cumulative regrets of the player node reached by the given line (including
+    /// chance actions) from the root.
+    ///
+    /// The return value is a copy of the raw values used by the solver, i.e., they are not
+    /// normalized and are discounted as specified by the CFR variant. The layout is the same as
+    /// [`strategy`]: the regret of the `i`-th action of the `j`-th hand is stored at index
+    /// `i * num_private_hands + j`. This is intended for debugging the solver (e.g., to detect
+    /// NaNs or runaway magnitudes); use [`solve_step`] instead of [`solve`] to inspect the values
+    /// during the solving.
+    ///
+    /// Returns an error if the memory is not yet allocated, if the game is already solved (the
+    /// regrets are overwritten by the counterfactual values at the end of the solving), or if
+    /// the line does not lead to a player node.
+    ///
+    /// [`strategy`]: #method.strategy
+    /// [`solve_step`]: crate::solve_step
+    /// [`solve`]: crate::solve
+    pub fn cum_regret_at(&self, actions: &[Action]) -> Result<Vec<f32>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if self.state == State::Solved {
+            return Err("Regrets are not available after the game is solved".to_string());
+        }
+
+        if self.storage_mode != BoardState::River {
+            return Err("Storage mode is not compatible".to_string());
+        }
+
+        let node = self
+            .node_by_line(actions)
+            .ok_or_else(|| format!("Invalid line: {actions:?}"))?;
+
+        if node.is_terminal() || node.is_chance() {
+            return Err("Not a player node".to_string());
+        }
+
+        if self.is_compression_enabled {
+            let decoder = node.regret_scale() / i16::MAX as f32;
+            let src = node.regrets_compressed();
+            Ok(src.iter().map(|&x| x as f32 * decoder).collect())
+        } else {
+            Ok(node.regrets().to_vec())
+        }
+    }
+
+    /// Remove lines after building the `PostFlopGame` but before allocating memory.
+    ///
+    /// This allows the removal of chance-specific lines (e.g., remove overbets on board-pairing
+    /// turns) which we cannot do while building an action tree.
+    pub fn remove_lines(&mut self, lines: &[Vec<Action>]) -> Result<(), String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        } else if self.state >= State::MemoryAllocated {
+            return Err("Game has already been allocated".to_string());
+        }
+
+        for line in lines {
+            let mut root = self.root();
+            let info = self.remove_line_recursive(&mut root, line)?;
+            self.num_storage -= info.num_storage;
+            self.num_storage_ip -= info.num_storage_ip;
+            self.num_storage_chance -= info.num_storage_chance;
+        }
+
+        Ok(())
+    }
+
+    /// Restricts the actions of the player node reached by the given line to `allowed` after
+    /// building the `PostFlopGame` but before allocating memory.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The other actions of the
+    /// node are removed with their subtrees by [`remove_lines`], so the storage of the node is
+    /// allocated only for the allowed actions.
+    ///
+    /// Returns an error if the line does not reach a player node, or if `allowed` is empty or
+    /// contains an action that does not exist at the node.
+    ///
+    /// [`remove_lines`]: #method.remove_lines
+    pub fn restrict_actions(
+        &mut self,
+        actions: &[Action],
+        allowed: &[Action],
+    ) -> Result<(), String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        } else if self.state >= State::MemoryAllocated {
+            return Err("Game has already been allocated".to_string());
+        }
+
+        if allowed.is_empty() {
+            return Err("No actions are allowed".to_string());
+        }
+
+        let node = self
+            .node_by_line(actions)
+            .ok_or_else(|| format!("Invalid line: {actions:?}"))?;
+
+        if node.is_terminal() || node.is_chance() {
+            return Err("Line does not reach a player node".to_string());
+        }
+
+        let existing = node
+            .children()
+            .iter()
+            .map(|child| child.lock().prev_action)
+            .collect::<Vec<_>>();
+
+        if let Some(action) = allowed.iter().find(|action| !existing.contains(action)) {
+            return Err(format!("Action does not exist: {action:?}"));
+        }
+
+        let lines = existing
+            .into_iter()
+            .filter(|action| !allowed.contains(action))
+            .map(|action| [actions, &[action]].concat())
+            .collect::<Vec<_>>();
+
+        self.remove_lines(&lines)
+    }
+
+    /// Returns whether the memory is allocated.
+    ///
+    /// If the memory is allocated, returns `Some(is_compression_enabled)`;
+    /// otherwise, returns `None`.
+    #[inline]
+    pub fn is_memory_allocated(&self) -> Option<bool> {
+        if self.state <= State::TreeBuilt {
+            None
+        } else {
+            Some(self.is_compression_enabled)
+        }
+    }
+
+    /// Allocates the memory.
+    ///
+    /// Building the game (e.g., by [`with_config`]) constructs only the skeleton of the game tree,
+    /// so the size of the tree can be examined with [`tree_stats`] and [`memory_usage`] before
+    /// paying the memory cost. The solver requires this method to have been called.
+    ///
+    /// The strategies, regrets, and counterfactual values of all nodes are not allocated per
+    /// node: they live in a few contiguous buffers whose sizes are computed when building the
+    /// tree, and each node only holds pointers into them. Hence, this method performs a constant
+    /// number of allocations regardless of the size of the tree.
+    ///
+    /// [`with_config`]: #method.with_config
+    /// [`tree_stats`]: #method.tree_stats
+    /// [`memory_usage`]: #method.memory_usage
+    pub fn allocate_memory(&mut self, enable_compression: bool) {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        if self.state == State::MemoryAllocated
+            && self.storage_mode == BoardState::River
+            && self.is_compression_enabled == enable_compression
+        {
+            return;
+        }
+
+        let num_bytes = if enable_compression { 2 } else { 4 };
+        if num_bytes * self.num_storage > isize::MAX as u64
+            || num_bytes * self.num_storage_chance > isize::MAX as u64
+        {
+            panic!("Memory usage exceeds maximum size");
+        }
+
+        self.state = State::MemoryAllocated;
+        self.is_compression_enabled = enable_compression;
+
+        self.clear_storage();
+
+        let storage_bytes = (num_bytes * self.num_storage) as usize;
+        let storage_ip_bytes = (num_bytes * self.num_storage_ip) as usize;
+        let storage_chance_bytes = (num_bytes * self.num_storage_chance) as usize;
+
+        self.storage1 = vec![0; storage_bytes];
+        self.storage2 = vec![0; storage_bytes];
+        self.storage_ip = vec![0; storage_ip_bytes];
+        self.storage_chance = vec![0; storage_chance_bytes];
+
+        self.allocate_memory_nodes();
+
+        self.storage_mode = BoardState::River;
+        self.target_storage_mode = BoardState::River;
+    }
+
+    /// Compresses the storage of the solved game to halve its memory usage.
+    ///
+    /// The solver keeps accumulating the regrets and the strategy in `f32` during solving; this
+    /// method converts the finalized game into the same 16-bit fixed-point representation used
+    /// when the memory is allocated with `enable_compression = true`. The strategy of each node is
+    /// normalized and stored as `u16` values, and the counterfactual values are stored as `i16`
+    /// values, both with a per-node scale. The results are dequantized on read, so the methods of
+    /// the interpreter (e.g., [`strategy`] and [`expected_values`]) work as before. The loss of
+    /// accuracy is bounded by the quantization step of each node (i.e., `scale / 65535` for the
+    /// strategy and `scale / 32767` for the counterfactual values).
+    ///
+    /// Does nothing if the compression is already enabled. Panics if the game is not solved or the
+    /// storage mode is not `BoardState::River`.
+    ///
+    /// [`strategy`]: #method.strategy
+    /// [`expected_values`]: #method.expected_values
+    pub fn compress_storage(&mut self) {
+        if self.state != State::Solved {
+            panic!("Game is not solved");
+        }
+
+        if self.storage_mode != BoardState::River {
+            panic!("Storage mode is not compatible");
+        }
+
+        if self.is_compression_enabled {
+            return;
+        }
+
+        let mut storage1 = vec![0u8; 2 * self.num_storage as usize];
+        let mut storage2 = vec![0u8; 2 * self.num_storage as usize];
+        let mut storage_ip = vec![0u8; 2 * self.num_storage_ip as usize];
+        let mut storage_chance = vec![0u8; 2 * self.num_storage_chance as usize];
+
+        let mut action_counter = 0;
+        let mut ip_counter = 0;
+        let mut chance_counter = 0;
+
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            if node.is_terminal() {
+                // do nothing
+            } else if node.is_chance() {
+                let len = node.num_elements as usize;
+                let dst = &mut storage_chance[chance_counter..chance_counter + 2 * len];
+                let dst = unsafe { slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut i16, len) };
+                let scale = encode_signed_slice(dst, node.cfvalues_chance());
+                node.set_cfvalue_chance_scale(scale);
+                chance_counter += 2 * len;
+            } else {
+                let len = node.num_elements as usize;
+                let len_ip = node.num_elements_ip as usize;
+
+                let strategy = normalized_strategy(node.strategy(), node.num_actions());
+                let dst = &mut storage1[action_counter..action_counter + 2 * len];
+                let dst = unsafe { slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut u16, len) };
+                let scale = encode_unsigned_slice(dst, &strategy);
+                node.set_strategy_scale(scale);
+
+                let dst = &mut storage2[action_counter..action_counter + 2 * len];
+                let dst = unsafe { slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut i16, len) };
+                let scale = encode_signed_slice(dst, node.cfvalues());
+                node.set_cfvalue_scale(scale);
+
+                let dst = &mut storage_ip[ip_counter..ip_counter + 2 * len_ip];
+                let dst =
+                    unsafe { slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut i16, len_ip) };
+                let scale = encode_signed_slice(dst, node.cfvalues_ip());
+                node.set_cfvalue_ip_scale(scale);
+
+                action_counter += 2 * len;
+                ip_counter += 2 * len_ip;
+            }
+        }
+
+        self.storage1 = storage1;
+        self.storage2 = storage2;
+        self.storage_ip = storage_ip;
+        self.storage_chance = storage_chance;
+        self.is_compression_enabled = true;
+
+        self.allocate_memory_nodes();
+    }
+
+    /// Clears the strategy and the other values accumulated by the solver without rebuilding the
+    /// game tree.
+    ///
+    /// The allocated memory, the hand strength, and the locked strategies are preserved, so the
+    /// game can be solved again from scratch (e.g., to compare different solver parameters).
+    pub fn clear_strategy(&mut self) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.storage_mode != BoardState::River {
+            panic!("Storage mode is not compatible");
+        }
+
+        self.state = State::MemoryAllocated;
+
+        self.storage1.fill(0);
+        self.storage2.fill(0);
+        self.storage_ip.fill(0);
+        self.storage_chance.fill(0);
+
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            node.scale1 = 0.0;
+            node.scale2 = 0.0;
+            node.scale3 = 0.0;
+        }
+
+        let history = self.action_history.clone();
+        self.apply_history(&history);
+    }
+
+    /// Initializes the strategy from another game to warm-start the solver.
+    ///
+    /// For each player node of this game, `map` receives the line leading to the node (chance
+    /// actions are included as `Action::Chance(card)`) and returns the corresponding line in
+    /// `other`, or `None` to skip the node. If the corresponding node exists in `other` and has the
+    /// same player and the same actions, its cumulative strategy is copied, and so are the
+    /// cumulative regrets if `other` is not finalized yet. The private hands are matched by their
+    /// cards; the hands that do not exist in `other` are initialized with zeros. Locked nodes are
+    /// not changed.
+    ///
+    /// Note that the `solve` function starts from the iteration 0, where the discount factors of
+    /// DCFR discard the accumulated values. To take advantage of the copied values, continue the
+    /// computation by calling `solve_step` from a positive iteration.
+    pub fn warm_start_from<F: Fn(&[Action]) -> Option<Vec<Action>>>(
+        &mut self,
+        other: &PostFlopGame,
+        map: F,
+    ) -> Result<(), String> {
+        if self.state <= State::Uninitialized || other.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
+
+        if self.state == State::Solved {
+            return Err("Game is already solved".to_string());
+        }
+
+        if self.state < State::MemoryAllocated || other.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if self.storage_mode != BoardState::River || other.storage_mode != BoardState::River {
+            return Err("Storage mode is not compatible".to_string());
+        }
+
+        if self.is_compression_enabled || other.is_compression_enabled {
+            return Err("Compressed storage is not supported".to_string());
+        }
+
+        let hand_map = [0, 1].map(|player| {
+            self.private_cards[player]
+                .iter()
+                .map(|&(c1, c2)| other.hand_index(player, c1, c2))
+                .collect::<Vec<_>>()
+        });
+
+        let copy_regrets = other.state != State::Solved;
+        let mut line = Vec::new();
+        let mut root = self.root();
+        Self::warm_start_recursive(&mut root, &mut line, other, &map, &hand_map, copy_regrets);
+
+        Ok(())
+    }
+
+    /// Exports the normalized strategy of all player nodes into a compact binary format.
+    ///
+    /// Unlike saving the whole game, only the strategy is exported, so the consumer must construct
+    /// the identical tree from the same configurations and then call [`import_strategy`]. The
+    /// format consists of the number of player nodes, the number of actions of each player node,
+    /// and the strategy of each player node, all in little-endian and in the node order of the
+    /// tree. Locked strategies are exported as they are.
+    ///
+    /// Panics if the memory is not yet allocated or the storage mode is not `BoardState::River`.
+    ///
+    /// [`import_strategy`]: #method.import_strategy
+    pub fn export_strategy(&self) -> Vec<u8> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.storage_mode != BoardState::River {
+            panic!("Storage mode is not compatible");
+        }
+
+        let player_nodes = self.player_nodes();
+
+        let mut ret = Vec::new();
+        ret.extend_from_slice(&(player_nodes.len() as u32).to_le_bytes());
+
+        for node in &player_nodes {
+            ret.extend_from_slice(&(node.num_actions() as u32).to_le_bytes());
+        }
+
+        for node in &player_nodes {
+            let mut strategy = if self.is_compression_enabled {
+                normalized_strategy_compressed(node.strategy_compressed(), node.num_actions())
+            } else {
+                normalized_strategy(node.strategy(), node.num_actions())
+            };
+
+            apply_locking_strategy(&mut strategy, self.locking_strategy(node));
+            strategy
+                .iter()
+                .for_each(|v| ret.extend_from_slice(&v.to_le_bytes()));
+        }
+
+        ret
+    }
+
+    /// Imports the strategy exported by [`export_strategy`].
+    ///
+    /// The tree must have the same shape as that of the exported game; otherwise, returns an error
+    /// without modifying the strategy. After importing, call the [`finalize`] function to compute
+    /// the expected values.
+    ///
+    /// [`export_strategy`]: #method.export_strategy
+    /// [`finalize`]: ../fn.finalize.html
+    pub fn import_strategy(&mut self, bytes: &[u8]) -> Result<(), String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if self.state == State::Solved {
+            return Err("Game is already solved".to_string());
+        }
+
+        if self.storage_mode != BoardState::River {
+            return Err("Storage mode is not compatible".to_string());
+        }
+
+        if !bytes.len().is_multiple_of(4) {
+            return Err("Invalid length of strategy data".to_string());
+        }
+
+        let mut words = bytes.chunks_exact(4).map(|chunk| chunk.try_into().unwrap());
+
+        let mut player_nodes = self.player_nodes();
+
+        let num_nodes = words.next().map(u32::from_le_bytes);
+        if num_nodes != Some(player_nodes.len() as u32) {
+            return Err(format!(
+                "Number of nodes mismatch: expected = {}, actual = {num_nodes:?}",
+                player_nodes.len()
+            ));
+        }
+
+        for node in &player_nodes {
+            let num_actions = words.next().map(u32::from_le_bytes);
+            if num_actions != Some(node.num_actions() as u32) {
+                return Err(format!(
+                    "Number of actions mismatch: expected = {}, actual = {num_actions:?}",
+                    node.num_actions()
+                ));
+            }
+        }
+
+        let expected_len = player_nodes
+            .iter()
+            .map(|node| node.num_elements as usize)
+            .sum::<usize>();
+        if words.len() != expected_len {
+            return Err(format!(
+                "Invalid length of strategy data: expected = {expected_len}, actual = {}",
+                words.len()
+            ));
+        }
+
+        for node in &mut player_nodes {
+            let strategy = words
+                .by_ref()
+                .take(node.num_elements as usize)
+                .map(f32::from_le_bytes)
+                .collect::<Vec<_>>();
+
+            if self.is_compression_enabled {
+                let scale = encode_unsigned_slice(node.strategy_compressed_mut(), &strategy);
+                node.set_strategy_scale(scale);
+            } else {
+                node.strategy_mut().copy_from_slice(&strategy);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Normalizes the stored strategy of the node reached by the given line from the root and all
+    /// of its descendants.
+    ///
+    /// Chance actions must be included in the `actions` as `Action::Chance(card)`. The strategy
+    /// stored in each node is the cumulative strategy, which is normalized on the fly whenever it
+    /// is read (e.g., by [`strategy`], [`compute_exploitability`], and [`compute_current_ev`]), so
+    /// there is no global normalization step that must run before computing the expected values,
+    /// and calling this method does not change any of the computed results. It is useful when the
+    /// raw storage is consumed directly, e.g., after re-solving a subgame. Note that if the solving
+    /// is continued after calling this method, the accumulated weight of the cumulative strategy
+    /// of the normalized nodes is reset.
+    ///
+    /// [`strategy`]: #method.strategy
+    /// [`compute_exploitability`]: ../fn.compute_exploitability.html
+    /// [`compute_current_ev`]: ../fn.compute_current_ev.html
+    pub fn normalize_subtree(&mut self, actions: &[Action]) -> Result<(), String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if self.storage_mode != BoardState::River {
+            return Err("Storage mode is not compatible".to_string());
+        }
+
+        if self.node_by_line(actions).is_none() {
+            return Err(format!("Invalid line: {actions:?}"));
+        }
+
+        self.normalize_subtree_recursive(&mut self.root(), actions);
+
+        Ok(())
+    }
+
+    /// Checks the card configuration.
+    pub(crate) fn check_card_config(&mut self) -> Result<(), String> {
+        let config = &self.card_config;
+        let (flop, turn, river) = (config.flop, config.turn, config.river);
+        let range = &config.range;
+
+        if flop.contains(&NOT_DEALT) {
+            return Err("Flop cards not initialized".to_string());
+        }
+
+        if flop.iter().any(|&c| 52 <= c) {
+            return Err(format!("Flop cards must be in [0, 52): flop = {flop:?}"));
+        }
+
+        if flop[0] == flop[1] || flop[0] == flop[2] || flop[1] == flop[2] {
+            return Err(format!("Flop cards must be unique: flop = {flop:?}"));
+        }
+
+        let dead_cards = &config.dead_cards;
+
+        if dead_cards.iter().any(|&c| 52 <= c) {
+            return Err(format!(
+                "Dead cards must be in [0, 52): dead_cards = {dead_cards:?}"
+            ));
+        }
+
+        if (1..dead_cards.len()).any(|i| dead_cards[..i].contains(&dead_cards[i])) {
+            return Err(format!(
+                "Dead cards must be unique: dead_cards = {dead_cards:?}"
+            ));
+        }
+
+        let turn_group_size = config.turn_abstraction.map(|b| b.rank_group_size);
+        let river_group_size = config.river_abstraction.map(|b| b.rank_group_size);
+        for rank_group_size in turn_group_size.into_iter().chain(river_group_size) {
+            if !(1..=13).contains(&rank_group_size) {
+                return Err(format!(
+                    "Rank group size must be in [1, 13]: rank_group_size = {rank_group_size}"
+                ));
+            }
+        }
+
+        let removed_mask = config.removed_mask();
+
+        if flop.iter().any(|&c| (1 << c) & removed_mask != 0) {
+            return Err(format!(
+                "Flop cards must not be removed from the deck: flop = {flop:?}"
+            ));
+        }
+
+        if turn != NOT_DEALT {
+            if 52 <= turn {
+                return Err(format!("Turn card must be in [0, 52): turn = {turn}"));
+            }
+
+            if flop.contains(&turn) {
+                return Err(format!(
+                    "Turn card must be different from flop cards: turn = {turn}"
+                ));
+            }
+
+            if (1 << turn) & removed_mask != 0 {
+                return Err(format!(
+                    "Turn card must not be removed from the deck: turn = {turn}"
+                ));
+            }
+        }
+
+        if river != NOT_DEALT {
+            if 52 <= river {
+                return Err(format!("River card must be in [0, 52): river = {river}"));
+            }
+
+            if flop.contains(&river) {
+                return Err(format!(
+                    "River card must be different from flop cards: river = {river}"
+                ));
+            }
+
+            if turn == river {
+                return Err(format!(
+                    "River card must be different from turn card: river = {river}"
+                ));
+            }
+
+            if (1 << river) & removed_mask != 0 {
+                return Err(format!(
+                    "River card must not be removed from the deck: river = {river}"
+                ));
+            }
+
+            if turn == NOT_DEALT {
+                return Err(format!(
+                    "River card specified without turn card: river = {river}"
+                ));
+            }
+        }
+
+        let expected_state = match (turn != NOT_DEALT, river != NOT_DEALT) {
+            (false, _) => BoardState::Flop,
+            (true, false) => BoardState::Turn,
+            (true, true) => BoardState::River,
+        };
+
+        if self.tree_config.initial_state != expected_state {
+            return Err(format!(
+                "Invalid initial state of `tree_config`: expected = {:?}, actual = {:?}",
+                expected_state, self.tree_config.initial_state
+            ));
+        }
+
+        if range[0].is_empty() {
+            return Err("OOP range is empty".to_string());
+        }
+
+        if range[1].is_empty() {
+            return Err("IP range is empty".to_string());
+        }
+
+        if !range[0].is_valid() {
+            return Err("OOP range is invalid (loaded broken data?)".to_string());
+        }
+
+        if !range[1].is_valid() {
+            return Err("IP range is invalid (loaded broken data?)".to_string());
+        }
+
+        self.init_hands();
+
+        // distinguish the ranges blocked by the board from the other invalid assignments
+        let blockers = match self.card_config.removed_mask() {
+            0 => "the board",
+            _ => "the board and the removed cards",
+        };
+        for (player, name) in ["OOP", "IP"].iter().enumerate() {
+            if self.private_cards[player].is_empty() {
+                return Err(format!(
+                    "{name}'s range has no combos that avoid {blockers}"
+                ));
+            }
+        }
+
+        self.init_num_combinations()
+    }
+
+    /// Initializes the field `num_combinations` from the initial weights.
+    pub(super) fn init_num_combinations(&mut self) -> Result<(), String> {
         self.num_combinations = 0.0;
 
         for (&(c1, c2), &w1) in self.private_cards[0]
@@ -461,8 +2101,11 @@ impl PostFlopGame {
             board_mask |= 1 << river;
         }
 
-        for player in 0..2 {
-            let (hands, weights) = range[player].get_hands_weights(board_mask);
+        // exclude the hands containing the removed cards
+        let dead_mask = board_mask | config.removed_mask();
+
+        for (player, range) in range.iter().enumerate() {
+            let (hands, weights) = range.get_hands_weights(dead_mask);
             self.initial_weights[player] = weights;
             self.private_cards[player] = hands;
         }
@@ -486,21 +2129,86 @@ impl PostFlopGame {
         }
 
         (
-            self.valid_indices_flop,
-            self.valid_indices_turn,
-            self.valid_indices_river,
-        ) = self.card_config.valid_indices(&self.private_cards);
+            self.valid_indices_flop,
+            self.valid_indices_turn,
+            self.valid_indices_river,
+        ) = self.card_config.valid_indices(&self.private_cards);
+
+        // the hand strengths are computed on demand if not precomputed
+        if !self.card_config.precompute_strength {
+            self.hand_strength = Vec::new();
+        } else {
+            #[cfg(feature = "bincode")]
+            let hand_strength = match &self.hand_strength_cache_dir {
+                Some(dir) => hand_strength_with_cache(&self.card_config, &self.private_cards, dir),
+                None => self.card_config.hand_strength(&self.private_cards),
+            };
+
+            #[cfg(not(feature = "bincode"))]
+            let hand_strength = self.card_config.hand_strength(&self.private_cards);
+
+            self.hand_strength = hand_strength;
+        }
+
+        (
+            self.isomorphism_ref_turn,
+            self.isomorphism_card_turn,
+            self.isomorphism_swap_turn,
+            self.isomorphism_ref_river,
+            self.isomorphism_card_river,
+            self.isomorphism_swap_river,
+        ) = self.card_config.isomorphism(&self.private_cards);
+
+        // the turn abstraction is stored in the same format as the turn isomorphism
+        if self.card_config.turn_abstraction.is_some() {
+            (
+                self.isomorphism_ref_turn,
+                self.isomorphism_card_turn,
+                self.turn_bucket_swap,
+            ) = self.card_config.turn_bucketing(&self.private_cards);
+        } else {
+            self.turn_bucket_swap.clear();
+        }
+
+        // the river abstraction is stored per turn card in place of the river isomorphism
+        if self.card_config.river_abstraction.is_some() {
+            (
+                self.isomorphism_ref_river,
+                self.river_bucket_card,
+                self.river_bucket_swap,
+            ) = self.card_config.river_bucketing(&self.private_cards);
+        } else {
+            self.river_bucket_card.clear();
+            self.river_bucket_swap.clear();
+        }
+    }
+
+    /// Rebuilds the game tree with the given tree configuration, reusing the fields related to
+    /// cards and the added and removed lines.
+    fn rebuild_tree(&mut self, tree_config: TreeConfig) -> Result<(), String> {
+        let action_tree =
+            ActionTree::with_lines(tree_config, &self.added_lines, &self.removed_lines)?;
+        if !action_tree.invalid_terminals().is_empty() {
+            return Err("Invalid terminal is found in action tree".to_string());
+        }
+
+        self.state = State::ConfigError;
+        (
+            self.tree_config,
+            self.added_lines,
+            self.removed_lines,
+            self.action_root,
+        ) = action_tree.eject();
 
-        self.hand_strength = self.card_config.hand_strength(&self.private_cards);
+        self.init_root()?;
+        self.init_payoffs();
 
-        (
-            self.isomorphism_ref_turn,
-            self.isomorphism_card_turn,
-            self.isomorphism_swap_turn,
-            self.isomorphism_ref_river,
-            self.isomorphism_card_river,
-            self.isomorphism_swap_river,
-        ) = self.card_config.isomorphism(&self.private_cards);
+        self.state = State::TreeBuilt;
+
+        self.init_interpreter();
+        self.back_to_root();
+
+        Ok(())
     }
 
     /// Initializes the root node of game tree.
@@ -518,6 +2226,7 @@ impl PostFlopGame {
         self.node_arena = (0..total_num_nodes)
             .map(|_| MutexLike::new(PostFlopNode::default()))
             .collect::<Vec<_>>();
+        self.locking_strategy.clear();
         self.clear_storage();
 
         let mut info = BuildTreeInfo {
@@ -571,31 +2280,65 @@ impl PostFlopGame {
     /// Counts the number of nodes in the game tree.
     #[inline]
     fn count_num_nodes(&self) -> [u64; 3] {
+        let coef = self.street_coefficients();
+        let num_action_nodes = count_num_action_nodes(&self.action_root.lock());
+        [
+            num_action_nodes[0] * coef[0],
+            num_action_nodes[1] * coef[1],
+            num_action_nodes[2] * coef[2],
+        ]
+    }
+
+    /// Returns the hand strengths of the private hands on the board completed by the given turn and
+    /// river cards, computing them on demand if the tables are not precomputed.
+    #[inline]
+    pub(super) fn hand_strength_at(&self, turn: u8, river: u8) -> Cow<'_, [Vec<StrengthItem>; 2]> {
+        if self.card_config.precompute_strength {
+            Cow::Borrowed(&self.hand_strength[card_pair_to_index(turn, river)])
+        } else {
+            Cow::Owned(
+                self.card_config
+                    .board_strength(turn, river, &self.private_cards),
+            )
+        }
+    }
+
+    /// Returns the river cards merged into other river cards after the given turn card, either by
+    /// the river isomorphism or by the river abstraction.
+    #[inline]
+    pub(super) fn river_isomorphism_cards(&self, turn: u8) -> &[u8] {
+        if !self.river_bucket_card.is_empty() {
+            &self.river_bucket_card[turn as usize]
+        } else {
+            &self.isomorphism_card_river[turn as usize & 3]
+        }
+    }
+
+    /// Returns the number of copies of each action node of [flop, turn, river] in the game tree.
+    #[inline]
+    fn street_coefficients(&self) -> [u64; 3] {
+        let deck_size = self.card_config.deck_size();
         let (turn_coef, river_coef) = match (self.card_config.turn, self.card_config.river) {
             (NOT_DEALT, _) => {
                 let mut river_coef = 0;
                 let flop = self.card_config.flop;
                 let skip_cards = &self.isomorphism_card_turn;
                 let flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+                let removed_mask = self.card_config.removed_mask();
                 let skip_mask: u64 = skip_cards.iter().map(|&card| 1 << card).sum();
                 for turn in 0..52 {
-                    if (1 << turn) & (flop_mask | skip_mask) == 0 {
-                        river_coef += 48 - self.isomorphism_card_river[turn & 3].len();
+                    if (1 << turn) & (flop_mask | removed_mask | skip_mask) == 0 {
+                        river_coef +=
+                            deck_size - 4 - self.river_isomorphism_cards(turn as u8).len();
                     }
                 }
-                (49 - self.isomorphism_card_turn.len(), river_coef)
+                (deck_size - 3 - self.isomorphism_card_turn.len(), river_coef)
             }
-            (turn, NOT_DEALT) => (1, 48 - self.isomorphism_card_river[turn as usize & 3].len()),
+            (turn, NOT_DEALT) => (1, deck_size - 4 - self.river_isomorphism_cards(turn).len()),
             _ => (0, 1),
         };
 
-        let num_action_nodes = count_num_action_nodes(&self.action_root.lock());
-
-        [
-            num_action_nodes[0],
-            num_action_nodes[1] * turn_coef as u64,
-            num_action_nodes[2] * river_coef as u64,
-        ]
+        [1, turn_coef as u64, river_coef as u64]
     }
 
     /// Computes the memory usage of this struct.
@@ -629,6 +2372,17 @@ impl PostFlopGame {
             memory_usage += vec_memory_usage(cards);
         }
 
+        memory_usage += vec_memory_usage(&self.river_bucket_card);
+        memory_usage += vec_memory_usage(&self.river_bucket_swap);
+
+        for cards in &self.river_bucket_card {
+            memory_usage += vec_memory_usage(cards);
+        }
+
+        for swaps in &self.river_bucket_swap {
+            memory_usage += vec_memory_usage(swaps);
+        }
+
         for player in 0..2 {
             memory_usage += vec_memory_usage(&self.initial_weights[player]);
             memory_usage += vec_memory_usage(&self.private_cards[player]);
@@ -646,6 +2400,12 @@ impl PostFlopGame {
             for swap in &self.isomorphism_swap_turn {
                 memory_usage += vec_memory_usage(&swap[player]);
             }
+            for swap in &self.turn_bucket_swap {
+                memory_usage += vec_memory_usage(&swap[player]);
+            }
+            for swap in self.river_bucket_swap.iter().flatten() {
+                memory_usage += vec_memory_usage(&swap[player]);
+            }
             for swap_list in &self.isomorphism_swap_river {
                 for swap in swap_list {
                     memory_usage += vec_memory_usage(&swap[player]);
@@ -696,7 +2456,8 @@ impl PostFlopGame {
     fn push_chances(&self, node_index: usize, info: &mut BuildTreeInfo) {
         let mut node = self.node_arena[node_index].lock();
         let flop = self.card_config.flop;
-        let flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        let flop_mask: u64 =
+            (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]) | self.card_config.removed_mask();
 
         // deal turn
         if node.turn == NOT_DEALT {
@@ -718,7 +2479,7 @@ impl PostFlopGame {
         // deal river
         else {
             let turn_mask = flop_mask | (1 << node.turn);
-            let skip_cards = &self.isomorphism_card_river[node.turn as usize & 3];
+            let skip_cards = self.river_isomorphism_cards(node.turn);
             let skip_mask: u64 = skip_cards.iter().map(|&card| 1 << card).sum();
 
             node.children_offset = (info.river_index - node_index) as u32;
@@ -777,7 +2538,7 @@ impl PostFlopGame {
         let num_private_hands = self.num_private_hands(node.player as usize);
         node.num_elements = (node.num_actions() * num_private_hands) as u32;
         node.num_elements_ip = match node.prev_action {
-            Action::None | Action::Chance(_) => self.num_private_hands(PLAYER_IP as usize) as u16,
+            Action::None | Action::Chance(_) => self.num_private_hands(node.player() ^ 1) as u16,
             _ => 0,
         };
 
@@ -1310,6 +3071,546 @@ impl PostFlopGame {
         ret
     }
 
+    /// Recursive function to visit all nodes.
+    fn for_each_node_recursive<F: FnMut(&PostFlopNode, &[Action])>(
+        node: &PostFlopNode,
+        line: &mut Vec<Action>,
+        f: &mut F,
+    ) {
+        f(node, line);
+        for child in node.children() {
+            let child = child.lock();
+            line.push(child.prev_action);
+            Self::for_each_node_recursive(&child, line, f);
+            line.pop();
+        }
+    }
+
+    /// Returns the list of player nodes (i.e., neither terminal nor chance) in the node order.
+    fn player_nodes(&self) -> Vec<MutexGuardLike<'_, PostFlopNode>> {
+        self.node_arena
+            .iter()
+            .map(|node| node.lock())
+            .filter(|node| !node.is_terminal() && !node.is_chance())
+            .collect()
+    }
+
+    /// Recursive function to normalize the strategy of the subtree reached by `line`.
+    fn normalize_subtree_recursive(&self, node: &mut PostFlopNode, line: &[Action]) {
+        if let Some((&action, rest)) = line.split_first() {
+            let child = node
+                .children()
+                .iter()
+                .find(|child| child.lock().prev_action == action)
+                .unwrap();
+            self.normalize_subtree_recursive(&mut child.lock(), rest);
+            return;
+        }
+
+        if node.is_terminal() {
+            return;
+        }
+
+        if !node.is_chance() {
+            if self.is_compression_enabled {
+                let strategy =
+                    normalized_strategy_compressed(node.strategy_compressed(), node.num_actions());
+                let scale = encode_unsigned_slice(node.strategy_compressed_mut(), &strategy);
+                node.set_strategy_scale(scale);
+            } else {
+                let strategy = normalized_strategy(node.strategy(), node.num_actions());
+                node.strategy_mut().copy_from_slice(&strategy);
+            }
+        }
+
+        for child in node.children() {
+            self.normalize_subtree_recursive(&mut child.lock(), &[]);
+        }
+    }
+
+    /// Recursive function to copy the strategy of the corresponding nodes of `other`.
+    fn warm_start_recursive<F: Fn(&[Action]) -> Option<Vec<Action>>>(
+        node: &mut PostFlopNode,
+        line: &mut Vec<Action>,
+        other: &PostFlopGame,
+        map: &F,
+        hand_map: &[Vec<Option<usize>>; 2],
+        copy_regrets: bool,
+    ) {
+        if node.is_terminal() {
+            return;
+        }
+
+        if !node.is_chance() && !node.is_locked {
+            let other_node = map(line).and_then(|other_line| other.node_by_line(&other_line));
+            if let Some(other_node) = other_node {
+                let actions = |node: &PostFlopNode| {
+                    node.children()
+                        .iter()
+                        .map(|child| child.lock().prev_action)
+                        .collect::<Vec<_>>()
+                };
+
+                if node.player == other_node.player && actions(node) == actions(other_node) {
+                    let player = node.player();
+                    let copy = |dst: &mut [f32], src: &[f32]| {
+                        let num_hands = hand_map[player].len();
+                        let num_other_hands = other.num_private_hands(player);
+                        dst.chunks_exact_mut(num_hands)
+                            .zip(src.chunks_exact(num_other_hands))
+                            .for_each(|(dst_row, src_row)| {
+                                dst_row
+                                    .iter_mut()
+                                    .zip(&hand_map[player])
+                                    .for_each(|(d, h)| {
+                                        *d = h.map_or(0.0, |h| src_row[h]);
+                                    });
+                            });
+                    };
+
+                    copy(node.strategy_mut(), other_node.strategy());
+                    if copy_regrets {
+                        copy(node.regrets_mut(), other_node.regrets());
+                    }
+                }
+            }
+        }
+
+        for child in node.children() {
+            let mut child = child.lock();
+            line.push(child.prev_action);
+            Self::warm_start_recursive(&mut child, line, other, map, hand_map, copy_regrets);
+            line.pop();
+        }
+    }
+
+    /// Returns the node reached by the given line (excluding chance actions) from the root and the
+    /// total bet amount of each player at the node.
+    fn node_and_bet_amount(&self, actions: &[Action]) -> Result<(&PostFlopNode, [i32; 2]), String> {
+        let mut node = &*self.root() as *const PostFlopNode;
+        let mut total_bet_amount = [0, 0];
+
+        for &action in actions {
+            unsafe {
+                while (*node).is_chance() {
+                    node = &*(*node).children()[0].lock();
+                }
+
+                if (*node).is_terminal() {
+                    return Err("Unexpected terminal node".to_string());
+                }
+
+                let child = (*node)
+                    .children()
+                    .iter()
+                    .map(|child| &*child.lock() as *const PostFlopNode)
+                    .find(|&child| (*child).prev_action == action)
+                    .ok_or_else(|| format!("Action does not exist: {action:?}"))?;
+
+                total_bet_amount = next_bet_amount(&*node, action, total_bet_amount);
+                node = child;
+            }
+        }
+
+        Ok((unsafe { &*node }, total_bet_amount))
+    }
+
+    /// Returns the [`NodeInfo`] of the node with the given total bet amount of each player.
+    fn node_info_internal(&self, total_bet_amount: [i32; 2]) -> NodeInfo {
+        let stack = self.tree_config.effective_stack;
+        NodeInfo {
+            pot: self.tree_config.starting_pot + total_bet_amount[0] + total_bet_amount[1],
+            oop_stack: stack - total_bet_amount[0],
+            ip_stack: stack - total_bet_amount[1],
+            to_call: (total_bet_amount[0] - total_bet_amount[1]).abs(),
+        }
+    }
+
+    /// Returns the node reached by the given line (including chance actions) from the root.
+    fn node_by_line(&self, line: &[Action]) -> Option<&PostFlopNode> {
+        let mut node = &*self.root() as *const PostFlopNode;
+        for &action in line {
+            unsafe {
+                node = (*node)
+                    .children()
+                    .iter()
+                    .map(|child| &*child.lock() as *const PostFlopNode)
+                    .find(|&child| (*child).prev_action == action)?;
+            }
+        }
+        Some(unsafe { &*node })
+    }
+
+    /// Checks that the analysis methods of the current strategy are supported for the `player`.
+    fn check_analysis_supported(&self, player: usize) -> Result<(), String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if self.storage_mode != BoardState::River {
+            return Err("Storage mode is not compatible".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        if self.card_config.turn_abstraction.is_some() {
+            return Err("Not supported with the turn abstraction".to_string());
+        }
+
+        if self.card_config.river_abstraction.is_some() {
+            return Err("Not supported with the river abstraction".to_string());
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            return Err("Not supported with the bunching effect".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Recursive function to accumulate the values and the probabilities of the boards.
+    ///
+    /// `reach[player]` is the reach probabilities of `player`, and `reach[player ^ 1]` is those of
+    /// the opponent multiplied by the chance probabilities. The contributions are recorded for
+    /// each suit permutation in `suit_maps` to account for the isomorphic runouts.
+    fn ev_by_board_recursive(
+        &self,
+        node: &PostFlopNode,
+        player: usize,
+        reach: [Vec<f32>; 2],
+        suit_maps: &[[u8; 4]],
+        boards: &MutexLike<Vec<[f64; 2]>>,
+    ) {
+        let opponent = player ^ 1;
+
+        // the board is completed
+        if node.river != NOT_DEALT {
+            let cfvalues = ev_vs_recursive(self, self, node, node, player, &reach[opponent]);
+            let value = cfvalues
+                .iter()
+                .zip(&reach[player])
+                .fold(0.0, |acc, (&v, &r)| acc + v as f64 * r as f64);
+            let compatible = self.compatible_reach(player, &reach[opponent], node.turn, node.river);
+            let prob = compatible
+                .iter()
+                .zip(&reach[player])
+                .fold(0.0, |acc, (&c, &r)| acc + c as f64 * r as f64)
+                / self.num_combinations;
+
+            let mut boards = boards.lock();
+            for suit_map in suit_maps {
+                let map = |card: u8| card - (card & 3) + suit_map[card as usize & 3];
+                let index = map(node.turn) as usize * 52 + map(node.river) as usize;
+                boards[index][0] += value;
+                boards[index][1] += prob;
+            }
+
+            return;
+        }
+
+        if node.is_terminal() {
+            return;
+        }
+
+        // chance node
+        if node.is_chance() {
+            let chance_factor = self.chance_factor(node) as f32;
+            let mut reach = reach;
+            reach[opponent].iter_mut().for_each(|r| *r /= chance_factor);
+
+            let (iso_card, iso_ref): (&[u8], &[u8]) = if node.turn == NOT_DEALT {
+                (&self.isomorphism_card_turn, &self.isomorphism_ref_turn)
+            } else {
+                (
+                    self.river_isomorphism_cards(node.turn),
+                    &self.isomorphism_ref_river[node.turn as usize],
+                )
+            };
+
+            for_each_child(node, |action| {
+                let child = node.play(action);
+                let card = match child.prev_action {
+                    Action::Chance(card) => card,
+                    _ => unreachable!(),
+                };
+
+                // the isomorphic cards merged into this card swap the suits of the subtree
+                let mut child_suit_maps = suit_maps.to_vec();
+                for (&iso_card, _) in iso_card
+                    .iter()
+                    .zip(iso_ref)
+                    .filter(|(_, &r)| r == action as u8)
+                {
+                    let (suit1, suit2) = (card & 3, iso_card & 3);
+                    for suit_map in suit_maps {
+                        let mut swapped = *suit_map;
+                        swapped.swap(suit1 as usize, suit2 as usize);
+                        child_suit_maps.push(swapped);
+                    }
+                }
+
+                self.ev_by_board_recursive(&child, player, reach.clone(), &child_suit_maps, boards);
+            });
+
+            return;
+        }
+
+        // player node
+        let acting_player = node.player();
+        let num_hands = self.num_private_hands(acting_player);
+        let strategy = node_strategy(self, node);
+
+        for action in 0..node.num_actions() {
+            let mut reach = reach.clone();
+            mul_slice(&mut reach[acting_player], row(&strategy, action, num_hands));
+            self.ev_by_board_recursive(&node.play(action), player, reach, suit_maps, boards);
+        }
+    }
+
+    /// Recursive function to accumulate the values of the hands of the `player` over the subtree
+    /// under the current strategy.
+    ///
+    /// `visit` is called at every node with the `cfreach` of the node and returns the values
+    /// collected at the node itself (`None` if nothing is collected). The values of the children
+    /// are weighted by the strategy of the `player` and summed over the actions and the chance
+    /// outcomes, including the isomorphic ones.
+    fn accumulate_recursive<const N: usize, F>(
+        &self,
+        node: &PostFlopNode,
+        player: usize,
+        cfreach: &[f32],
+        visit: &F,
+    ) -> [Vec<f32>; N]
+    where
+        F: Fn(&PostFlopNode, &[f32]) -> Option<[Vec<f32>; N]>,
+    {
+        let num_hands = self.num_private_hands(player);
+        let mut ret =
+            visit(node, cfreach).unwrap_or_else(|| std::array::from_fn(|_| vec![0.0; num_hands]));
+
+        // terminal node
+        if node.is_terminal() {
+            return ret;
+        }
+
+        let num_actions = node.num_actions();
+
+        // chance node
+        if node.is_chance() {
+            let mut cfreach_updated = cfreach.to_vec();
+            let chance_factor = self.chance_factor(node) as f32;
+            cfreach_updated.iter_mut().for_each(|r| *r /= chance_factor);
+
+            let mut results = (0..num_actions)
+                .map(|action| {
+                    self.accumulate_recursive(&node.play(action), player, &cfreach_updated, visit)
+                })
+                .collect::<Vec<_>>();
+
+            for result in &results {
+                for (ret, result) in ret.iter_mut().zip(result) {
+                    add_slice(ret, result);
+                }
+            }
+
+            // process isomorphic chances
+            for (i, &isomorphic_index) in self.isomorphic_chances(node).iter().enumerate() {
+                let swap_list = &self.isomorphic_swap(node, i)[player];
+                for (ret, tmp) in ret.iter_mut().zip(&mut results[isomorphic_index as usize]) {
+                    apply_swap(tmp, swap_list);
+                    add_slice(ret, tmp);
+                    apply_swap(tmp, swap_list);
+                }
+            }
+
+            return ret;
+        }
+
+        let acting_player = node.player();
+        let strategy = node_strategy(self, node);
+
+        for action in 0..num_actions {
+            let child = node.play(action);
+            let strategy = row(&strategy, action, self.num_private_hands(acting_player));
+            let result = if acting_player == player {
+                let mut result = self.accumulate_recursive(&child, player, cfreach, visit);
+                result.iter_mut().for_each(|r| mul_slice(r, strategy));
+                result
+            } else {
+                let mut cfreach_updated = cfreach.to_vec();
+                mul_slice(&mut cfreach_updated, strategy);
+                self.accumulate_recursive(&child, player, &cfreach_updated, visit)
+            };
+            for (ret, result) in ret.iter_mut().zip(&result) {
+                add_slice(ret, result);
+            }
+        }
+
+        ret
+    }
+
+    /// Returns the sum of the `cfreach` of the opponent hands compatible with each hand of the
+    /// `player` and the board with the given turn and river cards (`NOT_DEALT` if not dealt).
+    fn compatible_reach(&self, player: usize, cfreach: &[f32], turn: u8, river: u8) -> Vec<f32> {
+        let opponent = player ^ 1;
+        let board_mask = [turn, river]
+            .iter()
+            .filter(|&&card| card != NOT_DEALT)
+            .fold(0u64, |mask, &card| mask | (1 << card));
+        let overlaps = |(c1, c2): (u8, u8)| ((1u64 << c1) | (1u64 << c2)) & board_mask != 0;
+
+        let mut sum = 0.0;
+        let mut sum_card = [0.0; 52];
+        for (&hand, &r) in self.private_cards[opponent].iter().zip(cfreach) {
+            if !overlaps(hand) {
+                sum += r as f64;
+                sum_card[hand.0 as usize] += r as f64;
+                sum_card[hand.1 as usize] += r as f64;
+            }
+        }
+
+        self.private_cards[player]
+            .iter()
+            .enumerate()
+            .map(|(i, &hand)| {
+                if overlaps(hand) {
+                    return 0.0;
+                }
+                let same_hand = match self.same_hand_index[player][i] {
+                    u16::MAX => 0.0,
+                    j => cfreach[j as usize] as f64,
+                };
+                (sum - sum_card[hand.0 as usize] - sum_card[hand.1 as usize] + same_hand) as f32
+            })
+            .collect()
+    }
+
+    /// Verifies that the given chance node covers all cards remaining in the deck.
+    fn verify_chance_node(&self, node: &PostFlopNode) -> Result<(), String> {
+        let flop = self.card_config.flop;
+        let mut board_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        let iso_card = if node.turn == NOT_DEALT {
+            &self.isomorphism_card_turn
+        } else {
+            board_mask |= 1 << node.turn;
+            self.river_isomorphism_cards(node.turn)
+        };
+
+        let expected_mask = ((1 << 52) - 1) & !(board_mask | self.card_config.removed_mask());
+
+        let mut dealt_mask: u64 = 0;
+        let dealt_cards = node.children().iter().map(|child| child.lock().prev_action);
+        let cards = dealt_cards.chain(iso_card.iter().map(|&card| Action::Chance(card)));
+
+        for action in cards {
+            let card = match action {
+                Action::Chance(card) => card,
+                _ => return Err(format!("Unexpected action: {action:?}")),
+            };
+
+            if (1 << card) & expected_mask == 0 {
+                return Err(format!("Card cannot be dealt: {card}"));
+            }
+
+            if (1 << card) & dealt_mask != 0 {
+                return Err(format!("Card is dealt more than once: {card}"));
+            }
+
+            dealt_mask |= 1 << card;
+        }
+
+        if dealt_mask != expected_mask {
+            return Err(format!(
+                "Cards are missing: expected {} cards, found {} cards",
+                expected_mask.count_ones(),
+                dealt_mask.count_ones()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Recursive function to enumerate the terminal nodes of the subtree.
+    fn terminal_nodes_recursive(
+        &self,
+        node: &PostFlopNode,
+        line: &mut Vec<Action>,
+        total_bet_amount: [i32; 2],
+        ret: &mut Vec<(Vec<Action>, TerminalKind)>,
+    ) {
+        if node.is_terminal() {
+            let pot = self.tree_config.starting_pot + total_bet_amount[0] + total_bet_amount[1];
+            let kind = if node.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG {
+                let folder = (node.player & PLAYER_MASK) as usize;
+                TerminalKind::Fold { folder, pot }
+            } else {
+                TerminalKind::Showdown { pot }
+            };
+            ret.push((line.clone(), kind));
+            return;
+        }
+
+        for child in node.children() {
+            let child = child.lock();
+            let bet_amount = next_bet_amount(node, child.prev_action, total_bet_amount);
+            line.push(child.prev_action);
+            self.terminal_nodes_recursive(&child, line, bet_amount, ret);
+            line.pop();
+        }
+    }
+
+    /// Writes the DOT representation of the subtree and returns the ID of the `node`.
+    fn to_dot_recursive(
+        &self,
+        out: &mut String,
+        node: &PostFlopNode,
+        next_id: &mut usize,
+        total_bet_amount: [i32; 2],
+        max_depth: usize,
+    ) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+
+        let name = if node.is_terminal() {
+            if node.prev_action == Action::Fold {
+                "fold"
+            } else {
+                "showdown"
+            }
+        } else if node.is_chance() {
+            "chance"
+        } else if node.player() == PLAYER_OOP as usize {
+            "OOP"
+        } else {
+            "IP"
+        };
+
+        let pot = self.tree_config.starting_pot + total_bet_amount[0] + total_bet_amount[1];
+        writeln!(out, "    {id} [label=\"{name}\\npot = {pot}\"];").unwrap();
+
+        if max_depth == 0 {
+            return id;
+        }
+
+        for child in node.children() {
+            let child = child.lock();
+
+            let bet_amount = next_bet_amount(node, child.prev_action, total_bet_amount);
+
+            let label = match child.prev_action {
+                Action::Chance(card) => card_to_string(card).unwrap(),
+                action => format!("{action:?}"),
+            };
+
+            let child_id = self.to_dot_recursive(out, &child, next_id, bet_amount, max_depth - 1);
+            writeln!(out, "    {id} -> {child_id} [label=\"{label}\"];").unwrap();
+        }
+
+        id
+    }
+
     /// Calculates the number of storage elements that will be removed.
     fn calculate_removed_line_info_recursive(node: &mut PostFlopNode, info: &mut BuildTreeInfo) {
         if node.is_terminal() {
@@ -1432,3 +3733,27 @@ impl PostFlopGame {
         }
     }
 }
+
+/// Returns the total bet amount of each player after `action` is taken at `node`.
+///
+/// This is the same computation as the `play` method of the interpreter.
+#[inline]
+fn next_bet_amount(node: &PostFlopNode, action: Action, total_bet_amount: [i32; 2]) -> [i32; 2] {
+    let mut ret = total_bet_amount;
+    if !node.is_chance() {
+        let player = node.player();
+        match action {
+            Action::Call => ret[player] = ret[player ^ 1],
+            Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => {
+                let prev_bet_amount = match node.prev_action {
+                    Action::Bet(a) | Action::Raise(a) | Action::AllIn(a) => a,
+                    _ => 0,
+                };
+                let to_call = ret[player ^ 1] - ret[player];
+                ret[player] += amount - prev_bet_amount + to_call;
+            }
+            _ => {}
+        }
+    }
+    ret
+}
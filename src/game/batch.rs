@@ -0,0 +1,89 @@
+use super::*;
+use crate::solver::*;
+use crate::utility::*;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Builds and solves the games of the given flops with the shared configurations.
+///
+/// For each flop, the `flop` field of `card_config` is replaced, and the game is built in the same
+/// way as [`PostFlopGame::with_config`] and solved by [`solve`] with the given number of
+/// iterations and target exploitability. The flops are processed in parallel when the `rayon`
+/// feature is enabled.
+///
+/// If `max_memory_mb` is not `0`, the memory usage of each game is limited to this value in
+/// megabytes: the compression is enabled if the uncompressed game does not fit, and an error is
+/// returned if the compressed game does not fit either.
+///
+/// The `tree_config` must start from the flop, and `card_config` must not specify the turn and
+/// river cards. The return value has the same order as `flops`, and an error of one flop (e.g.,
+/// an invalid flop or an exceeded memory limit) does not abort the other flops. To skip the
+/// isomorphic flops, deduplicate `flops` in advance with [`canonical_flop`], or solve only the
+/// canonical flops of [`canonical_flops`].
+///
+/// [`canonical_flop`]: crate::canonical_flop
+/// [`canonical_flops`]: crate::canonical_flops
+pub fn solve_flops(
+    card_config: &CardConfig,
+    tree_config: &TreeConfig,
+    flops: &[[u8; 3]],
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    max_memory_mb: u64,
+) -> Vec<Result<PostFlopGame, String>> {
+    into_par_iter(0..flops.len())
+        .map(|i| {
+            let card_config = CardConfig {
+                flop: flops[i],
+                ..card_config.clone()
+            };
+            solve_flop(
+                card_config,
+                tree_config,
+                max_num_iterations,
+                target_exploitability,
+                max_memory_mb,
+            )
+        })
+        .collect()
+}
+
+/// Builds and solves the game of a single flop for [`solve_flops`].
+fn solve_flop(
+    card_config: CardConfig,
+    tree_config: &TreeConfig,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    max_memory_mb: u64,
+) -> Result<PostFlopGame, String> {
+    if tree_config.initial_state != BoardState::Flop {
+        return Err("Initial state must be the flop".to_string());
+    }
+
+    if card_config.turn != NOT_DEALT || card_config.river != NOT_DEALT {
+        return Err("Turn and river cards must not be specified".to_string());
+    }
+
+    let action_tree = ActionTree::new(tree_config.clone())?;
+    let mut game = PostFlopGame::with_config(card_config, action_tree)?;
+
+    let (uncompressed, compressed) = game.memory_usage();
+    let max_memory = max_memory_mb.saturating_mul(1 << 20);
+    let enable_compression = match max_memory_mb {
+        0 => false,
+        _ if uncompressed <= max_memory => false,
+        _ if compressed <= max_memory => true,
+        _ => {
+            return Err(format!(
+                "Memory usage exceeds the limit: {:.2}MB (limit: {max_memory_mb}MB)",
+                compressed as f64 / (1 << 20) as f64
+            ));
+        }
+    };
+
+    game.allocate_memory(enable_compression);
+    solve(&mut game, max_num_iterations, target_exploitability, false);
+
+    Ok(game)
+}
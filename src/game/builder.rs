@@ -0,0 +1,268 @@
+use super::*;
+use crate::bet_size::*;
+use crate::range::*;
+
+/// A builder for [`PostFlopGame`] that accepts string representations of the cards, ranges, and
+/// bet sizes.
+///
+/// The errors (e.g., a typo in the bet sizes) are deferred and returned by the [`build`] method.
+/// The initial state of the game tree is determined by the given board cards.
+///
+/// [`build`]: #method.build
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let game = GameBuilder::new()
+///     .ranges("AA,KK,QQ", "JJ,TT,99")
+///     .flop("Td9d6h")
+///     .pot(80)
+///     .stack(960)
+///     .flop_bets("50%", "50%")
+///     .max_num_bets(5)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(game.tree_config().initial_state, BoardState::Flop);
+/// assert!(GameBuilder::new().ranges("AA", "KK").flop("Td9d").build().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GameBuilder {
+    card_config: CardConfig,
+    tree_config: TreeConfig,
+    error: Option<String>,
+}
+
+impl GameBuilder {
+    /// Creates a new builder with the default configurations.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the initial ranges of OOP and IP.
+    #[inline]
+    pub fn ranges(mut self, oop: &str, ip: &str) -> Self {
+        match (oop.parse::<Range>(), ip.parse::<Range>()) {
+            (Ok(oop), Ok(ip)) => self.card_config.range = [oop, ip],
+            (Err(e), _) | (_, Err(e)) => self.set_error(e),
+        }
+        self
+    }
+
+    /// Sets the flop cards (e.g., `"Td9d6h"`).
+    #[inline]
+    pub fn flop(mut self, flop: &str) -> Self {
+        match flop_from_str(flop) {
+            Ok(flop) => self.card_config.flop = flop,
+            Err(e) => self.set_error(e),
+        }
+        self
+    }
+
+    /// Sets the turn card (e.g., `"Qc"`).
+    #[inline]
+    pub fn turn(mut self, turn: &str) -> Self {
+        match card_from_str(turn) {
+            Ok(turn) => self.card_config.turn = turn,
+            Err(e) => self.set_error(e),
+        }
+        self
+    }
+
+    /// Sets the river card (e.g., `"7s"`).
+    #[inline]
+    pub fn river(mut self, river: &str) -> Self {
+        match card_from_str(river) {
+            Ok(river) => self.card_config.river = river,
+            Err(e) => self.set_error(e),
+        }
+        self
+    }
+
+    /// Sets the game type.
+    #[inline]
+    pub fn game_type(mut self, game_type: GameType) -> Self {
+        self.card_config.game_type = game_type;
+        self
+    }
+
+    /// Sets the starting pot size.
+    #[inline]
+    pub fn pot(mut self, starting_pot: i32) -> Self {
+        self.tree_config.starting_pot = starting_pot;
+        self
+    }
+
+    /// Sets whether IP acts first on each street.
+    #[inline]
+    pub fn ip_first(mut self, ip_first: bool) -> Self {
+        self.tree_config.ip_first = ip_first;
+        self
+    }
+
+    /// Sets the amount of the starting pot invested by each player.
+    #[inline]
+    pub fn initial_invested(mut self, oop: i32, ip: i32) -> Self {
+        self.tree_config.initial_invested = [oop, ip];
+        self
+    }
+
+    /// Sets the initial effective stack.
+    #[inline]
+    pub fn stack(mut self, effective_stack: i32) -> Self {
+        self.tree_config.effective_stack = effective_stack;
+        self
+    }
+
+    /// Sets the initial effective stack from the stack-to-pot ratio (SPR) of the starting pot.
+    ///
+    /// The effective stack is `spr * starting_pot` rounded to the nearest integer, so the
+    /// starting pot must be set by the [`pot`] method beforehand. The error (e.g., a non-positive
+    /// SPR) is returned by the [`build`] method.
+    ///
+    /// [`pot`]: #method.pot
+    /// [`build`]: #method.build
+    #[inline]
+    pub fn spr(mut self, spr: f64) -> Self {
+        let starting_pot = self.tree_config.starting_pot;
+        if starting_pot <= 0 {
+            self.set_error("Starting pot must be set before the SPR".to_string());
+        } else if spr.is_nan() || spr <= 0.0 {
+            self.set_error(format!("SPR must be positive: {spr}"));
+        } else {
+            let effective_stack = (spr * starting_pot as f64).round();
+            if effective_stack < 1.0 || effective_stack > i32::MAX as f64 {
+                self.set_error(format!("Invalid SPR: {spr}"));
+            } else {
+                self.tree_config.effective_stack = effective_stack as i32;
+            }
+        }
+        self
+    }
+
+    /// Sets the big blind size, which enables bet sizes in big blinds (e.g., `"2.5bb"`).
+    #[inline]
+    pub fn big_blind(mut self, big_blind: i32) -> Self {
+        self.tree_config.big_blind = big_blind;
+        self
+    }
+
+    /// Sets the rake rate and the rake cap.
+    #[inline]
+    pub fn rake(mut self, rake_rate: f64, rake_cap: f64) -> Self {
+        self.tree_config.rake_rate = rake_rate;
+        self.tree_config.rake_cap = rake_cap;
+        self
+    }
+
+    /// Sets the bet sizes and the raise sizes of the flop for both players.
+    ///
+    /// See [`BetSizeCandidates`] for the format.
+    #[inline]
+    pub fn flop_bets(mut self, bet: &str, raise: &str) -> Self {
+        if let Some(sizes) = self.parse_bet_sizes(bet, raise) {
+            self.tree_config.flop_bet_sizes = [sizes.clone(), sizes];
+        }
+        self
+    }
+
+    /// Sets the bet sizes and the raise sizes of the turn for both players.
+    ///
+    /// See [`BetSizeCandidates`] for the format.
+    #[inline]
+    pub fn turn_bets(mut self, bet: &str, raise: &str) -> Self {
+        if let Some(sizes) = self.parse_bet_sizes(bet, raise) {
+            self.tree_config.turn_bet_sizes = [sizes.clone(), sizes];
+        }
+        self
+    }
+
+    /// Sets the bet sizes and the raise sizes of the river for both players.
+    ///
+    /// See [`BetSizeCandidates`] for the format.
+    #[inline]
+    pub fn river_bets(mut self, bet: &str, raise: &str) -> Self {
+        if let Some(sizes) = self.parse_bet_sizes(bet, raise) {
+            self.tree_config.river_bet_sizes = [sizes.clone(), sizes];
+        }
+        self
+    }
+
+    /// Sets the maximum number of bets of every street (set `0` for no limit).
+    #[inline]
+    pub fn max_num_bets(mut self, max_num_bets: i32) -> Self {
+        self.tree_config.max_num_bets = [max_num_bets; 3];
+        self
+    }
+
+    /// Sets the maximum number of raises of every street (set `0` for no limit).
+    #[inline]
+    pub fn max_num_raises(mut self, max_num_raises: i32) -> Self {
+        self.tree_config.max_num_raises = [max_num_raises; 3];
+        self
+    }
+
+    /// Sets the minimum amount of the first bet of each street as a ratio to the pot (set `0.0` to
+    /// disable).
+    #[inline]
+    pub fn min_bet_ratio(mut self, min_bet_ratio: f64) -> Self {
+        self.tree_config.min_bet_ratio = min_bet_ratio;
+        self
+    }
+
+    /// Sets the maximum number of bet and raise sizes at each node (set `0` to disable).
+    #[inline]
+    pub fn max_num_bet_sizes(mut self, max_num_bet_sizes: usize) -> Self {
+        self.tree_config.max_num_bet_sizes = max_num_bet_sizes;
+        self
+    }
+
+    /// Sets the thresholds of adding all-in, forcing all-in, and merging bet actions.
+    ///
+    /// See [`TreeConfig`] for details.
+    #[inline]
+    pub fn thresholds(mut self, add_allin: f64, force_allin: f64, merging: f64) -> Self {
+        self.tree_config.add_allin_threshold = add_allin;
+        self.tree_config.force_allin_threshold = force_allin;
+        self.tree_config.merging_threshold = merging;
+        self
+    }
+
+    /// Builds the game tree.
+    ///
+    /// Returns the first error found in the preceding method calls, if any. Otherwise, the
+    /// configurations are validated in the same way as [`ActionTree::new`] and
+    /// [`PostFlopGame::with_config`].
+    pub fn build(mut self) -> Result<PostFlopGame, String> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        self.tree_config.initial_state = match (self.card_config.turn, self.card_config.river) {
+            (NOT_DEALT, _) => BoardState::Flop,
+            (_, NOT_DEALT) => BoardState::Turn,
+            _ => BoardState::River,
+        };
+
+        let action_tree = ActionTree::new(self.tree_config)?;
+        PostFlopGame::with_config(self.card_config, action_tree)
+    }
+
+    #[inline]
+    fn set_error(&mut self, error: String) {
+        self.error.get_or_insert(error);
+    }
+
+    #[inline]
+    fn parse_bet_sizes(&mut self, bet: &str, raise: &str) -> Option<BetSizeCandidates> {
+        match BetSizeCandidates::try_from((bet, raise)) {
+            Ok(sizes) => Some(sizes),
+            Err(e) => {
+                self.set_error(e);
+                None
+            }
+        }
+    }
+}
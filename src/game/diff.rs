@@ -0,0 +1,455 @@
+use super::*;
+use crate::interface::*;
+use crate::sliceop::*;
+use crate::utility::*;
+
+/// Compares the strategies of two games with the identical tree and returns the disagreement at
+/// each player node.
+///
+/// Each element is a tuple of `(line, diff)`, where `line` is the line leading to the node from
+/// the root (chance actions are included as `Action::Chance(card)`), and `diff` is the L1 distance
+/// between the strategies of the two games weighted by the reach probabilities of the player to
+/// act. The reach probabilities are averaged over the two games and normalized by the initial
+/// weights, so `diff` is between `0.0` and `2.0`, and the nodes rarely reached contribute little.
+/// Isomorphic runouts are merged into their representative runouts.
+///
+/// The elements are sorted in descending order of `diff`.
+///
+/// Returns an error if the memory of either game is not allocated, or if the two games are not
+/// built from the same configurations and lines.
+pub fn strategy_diff(
+    a: &PostFlopGame,
+    b: &PostFlopGame,
+) -> Result<Vec<(Vec<Action>, f32)>, String> {
+    check_compatibility(a, b)?;
+
+    let reach = a.initial_weights.clone();
+    let mut ret = Vec::new();
+
+    strategy_diff_recursive(
+        a,
+        b,
+        &a.root(),
+        &b.root(),
+        &mut Vec::new(),
+        [reach.clone(), reach.clone()],
+        1.0,
+        &mut ret,
+    );
+
+    ret.sort_by(|(_, l), (_, r)| r.partial_cmp(l).unwrap());
+    Ok(ret)
+}
+
+/// Computes the expected value of `player` when the player follows the strategy of `game` and the
+/// opponent follows the strategy of `opponent`.
+///
+/// Unlike the best response, both strategies are fixed; the two games must be built from the
+/// same configurations and lines. As with [`compute_current_ev`], the amount of the starting pot
+/// invested by `player` is already subtracted, so the return value equals
+/// `compute_current_ev(game)[player]` when `opponent` is `game` itself.
+///
+/// Returns an error if the memory of either game is not allocated, if the two games are not
+/// built from the same configurations and lines, or if the `player` is invalid.
+///
+/// [`compute_current_ev`]: crate::compute_current_ev
+pub fn compute_ev_vs(
+    game: &PostFlopGame,
+    player: usize,
+    opponent: &PostFlopGame,
+) -> Result<f32, String> {
+    check_compatibility(game, opponent)?;
+
+    if player >= 2 {
+        return Err(format!("Invalid player: {player}"));
+    }
+
+    let cfvalues = ev_vs_recursive(
+        game,
+        opponent,
+        &game.root(),
+        &opponent.root(),
+        player,
+        &game.initial_weights[player ^ 1],
+    );
+
+    let ev = cfvalues
+        .iter()
+        .zip(&game.initial_weights[player])
+        .fold(0.0, |acc, (&v, &w)| acc + v as f64 * w as f64);
+
+    Ok(ev as f32)
+}
+
+/// Finds the first node where the strategies of the given hand differ between two games.
+///
+/// Both trees are walked in lockstep in depth-first order. At the nodes of `player`, only the
+/// action taken most frequently by the `hand` in `a` is followed, while all actions of the
+/// opponent and all cards not overlapping with the `hand` are explored. The return value is the
+/// line (chance actions are included as `Action::Chance(card)`) to the first node of `player`
+/// where the action probabilities of the `hand` differ by more than `tolerance`, or `None` if no
+/// such node is found. Isomorphic runouts are skipped because their strategies are those of their
+/// representative runouts with the suits swapped.
+///
+/// Returns an error if the memory of either game is not allocated, if the two games are not
+/// built from the same configurations and lines, if the `player` is invalid, or if the `player`
+/// does not have the `hand` in the range.
+pub fn first_divergence(
+    a: &PostFlopGame,
+    b: &PostFlopGame,
+    player: usize,
+    hand: (u8, u8),
+    tolerance: f32,
+) -> Result<Option<Vec<Action>>, String> {
+    check_compatibility(a, b)?;
+
+    if player >= 2 {
+        return Err(format!("Invalid player: {player}"));
+    }
+
+    let hand_index = a
+        .hand_index(player, hand.0, hand.1)
+        .ok_or_else(|| format!("Hand is not in the range: {hand:?}"))?;
+
+    let mut line = Vec::new();
+    let found = first_divergence_recursive(
+        a,
+        b,
+        &a.root(),
+        &b.root(),
+        player,
+        hand,
+        hand_index,
+        tolerance,
+        &mut line,
+    );
+
+    Ok(found.then_some(line))
+}
+
+/// Checks that the two games have allocated memory and the identical trees.
+fn check_compatibility(a: &PostFlopGame, b: &PostFlopGame) -> Result<(), String> {
+    for game in [a, b] {
+        if game.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if game.storage_mode != BoardState::River {
+            return Err("Storage mode is not compatible".to_string());
+        }
+    }
+
+    if a.card_config != b.card_config
+        || a.tree_config != b.tree_config
+        || a.added_lines != b.added_lines
+        || a.removed_lines != b.removed_lines
+        || a.node_arena.len() != b.node_arena.len()
+    {
+        return Err("Game trees are not identical".to_string());
+    }
+
+    Ok(())
+}
+
+/// Returns the normalized strategy of the given node, including the locked strategy.
+pub(super) fn node_strategy(game: &PostFlopGame, node: &PostFlopNode) -> Vec<f32> {
+    let mut strategy = if game.is_compression_enabled {
+        normalized_strategy_compressed(node.strategy_compressed(), node.num_actions())
+    } else {
+        normalized_strategy(node.strategy(), node.num_actions())
+    };
+
+    apply_locking_strategy(&mut strategy, game.locking_strategy(node));
+    strategy
+}
+
+/// Recursive function to compute the strategy differences of the subtree.
+///
+/// `reach` is the reach probabilities of both players in each game, and `chance_weight` is the
+/// probability of the runout leading to the node.
+#[allow(clippy::too_many_arguments)]
+fn strategy_diff_recursive(
+    a: &PostFlopGame,
+    b: &PostFlopGame,
+    node_a: &PostFlopNode,
+    node_b: &PostFlopNode,
+    line: &mut Vec<Action>,
+    reach: [[Vec<f32>; 2]; 2],
+    chance_weight: f32,
+    ret: &mut Vec<(Vec<Action>, f32)>,
+) {
+    if node_a.is_terminal() {
+        return;
+    }
+
+    let num_actions = node_a.num_actions();
+
+    // chance node
+    if node_a.is_chance() {
+        let isomorphic_chances = a.isomorphic_chances(node_a);
+        let chance_factor = a.chance_factor(node_a) as f32;
+
+        for action in 0..num_actions {
+            let child_a = node_a.play(action);
+            let card = match child_a.prev_action {
+                Action::Chance(card) => card,
+                _ => unreachable!(),
+            };
+
+            // hands overlapping with the dealt card cannot reach the child
+            let mut reach = reach.clone();
+            for (player, private_cards) in a.private_cards.iter().enumerate() {
+                for (i, &(c1, c2)) in private_cards.iter().enumerate() {
+                    if c1 == card || c2 == card {
+                        reach[0][player][i] = 0.0;
+                        reach[1][player][i] = 0.0;
+                    }
+                }
+            }
+
+            let num_isomorphic = isomorphic_chances
+                .iter()
+                .filter(|&&index| index as usize == action)
+                .count();
+
+            line.push(child_a.prev_action);
+            strategy_diff_recursive(
+                a,
+                b,
+                &child_a,
+                &node_b.play(action),
+                line,
+                reach,
+                chance_weight * (1 + num_isomorphic) as f32 / chance_factor,
+                ret,
+            );
+            line.pop();
+        }
+
+        return;
+    }
+
+    // player node
+    let player = node_a.player();
+    let num_hands = a.num_private_hands(player);
+    let strategy_a = node_strategy(a, node_a);
+    let strategy_b = node_strategy(b, node_b);
+
+    let mut diff = 0.0;
+    let reach_pairs = reach[0][player].iter().zip(&reach[1][player]);
+    for (hand, (&reach_a, &reach_b)) in reach_pairs.enumerate() {
+        let weight = (reach_a + reach_b) as f64 * 0.5;
+        if weight > 0.0 {
+            let l1 = (0..num_actions)
+                .map(|action| {
+                    let index = action * num_hands + hand;
+                    (strategy_a[index] - strategy_b[index]).abs() as f64
+                })
+                .sum::<f64>();
+            diff += weight * l1;
+        }
+    }
+
+    let initial_weight = a.initial_weights[player]
+        .iter()
+        .fold(0.0, |acc, &w| acc + w as f64);
+    ret.push((
+        line.clone(),
+        (diff * chance_weight as f64 / initial_weight) as f32,
+    ));
+
+    for action in 0..num_actions {
+        let mut reach = reach.clone();
+        mul_slice(&mut reach[0][player], row(&strategy_a, action, num_hands));
+        mul_slice(&mut reach[1][player], row(&strategy_b, action, num_hands));
+
+        let child_a = node_a.play(action);
+        line.push(child_a.prev_action);
+        strategy_diff_recursive(
+            a,
+            b,
+            &child_a,
+            &node_b.play(action),
+            line,
+            reach,
+            chance_weight,
+            ret,
+        );
+        line.pop();
+    }
+}
+
+/// Recursive function to compute the counterfactual values of `player` when the player follows
+/// the strategy of `a` and the opponent follows the strategy of `b`.
+pub(super) fn ev_vs_recursive(
+    a: &PostFlopGame,
+    b: &PostFlopGame,
+    node_a: &PostFlopNode,
+    node_b: &PostFlopNode,
+    player: usize,
+    cfreach: &[f32],
+) -> Vec<f32> {
+    let num_hands = a.num_private_hands(player);
+
+    // terminal node
+    if node_a.is_terminal() {
+        let mut result = Vec::with_capacity(num_hands);
+        a.evaluate(result.spare_capacity_mut(), node_a, player, cfreach);
+        unsafe { result.set_len(num_hands) };
+        return result;
+    }
+
+    let num_actions = node_a.num_actions();
+
+    // chance node
+    if node_a.is_chance() {
+        let mut cfreach_updated = cfreach.to_vec();
+        let chance_factor = a.chance_factor(node_a) as f32;
+        cfreach_updated.iter_mut().for_each(|r| *r /= chance_factor);
+
+        let mut cfv_actions = (0..num_actions)
+            .map(|action| {
+                let child_a = node_a.play(action);
+                let child_b = node_b.play(action);
+                ev_vs_recursive(a, b, &child_a, &child_b, player, &cfreach_updated)
+            })
+            .collect::<Vec<_>>();
+
+        let mut result = vec![0.0; num_hands];
+        for cfv in &cfv_actions {
+            result
+                .iter_mut()
+                .zip(cfv)
+                .for_each(|(r, &v)| *r += v as f64);
+        }
+
+        // process isomorphic chances
+        for (i, &isomorphic_index) in a.isomorphic_chances(node_a).iter().enumerate() {
+            let swap_list = &a.isomorphic_swap(node_a, i)[player];
+            let tmp = &mut cfv_actions[isomorphic_index as usize];
+            apply_swap(tmp, swap_list);
+            result
+                .iter_mut()
+                .zip(&*tmp)
+                .for_each(|(r, &v)| *r += v as f64);
+            apply_swap(tmp, swap_list);
+        }
+
+        return result.into_iter().map(|v| v as f32).collect();
+    }
+
+    // player node
+    if node_a.player() == player {
+        let strategy = node_strategy(a, node_a);
+        let mut result = vec![0.0; num_hands];
+
+        for action in 0..num_actions {
+            let child_a = node_a.play(action);
+            let child_b = node_b.play(action);
+            let cfv = ev_vs_recursive(a, b, &child_a, &child_b, player, cfreach);
+            let strategy = row(&strategy, action, num_hands);
+            result
+                .iter_mut()
+                .zip(&cfv)
+                .zip(strategy)
+                .for_each(|((r, &v), &s)| {
+                    *r += v * s;
+                });
+        }
+
+        return result;
+    }
+
+    // opponent node
+    let opponent_hands = cfreach.len();
+    let strategy = node_strategy(b, node_b);
+    let mut result = vec![0.0; num_hands];
+
+    for action in 0..num_actions {
+        let mut cfreach_updated = cfreach.to_vec();
+        mul_slice(&mut cfreach_updated, row(&strategy, action, opponent_hands));
+
+        let child_a = node_a.play(action);
+        let child_b = node_b.play(action);
+        let cfv = ev_vs_recursive(a, b, &child_a, &child_b, player, &cfreach_updated);
+        result.iter_mut().zip(&cfv).for_each(|(r, &v)| *r += v);
+    }
+
+    result
+}
+
+/// Recursive function to find the first divergence of the strategies of the given hand.
+///
+/// Returns `true` if found, in which case `line` holds the line to the node.
+#[allow(clippy::too_many_arguments)]
+fn first_divergence_recursive(
+    a: &PostFlopGame,
+    b: &PostFlopGame,
+    node_a: &PostFlopNode,
+    node_b: &PostFlopNode,
+    player: usize,
+    hand: (u8, u8),
+    hand_index: usize,
+    tolerance: f32,
+    line: &mut Vec<Action>,
+) -> bool {
+    if node_a.is_terminal() {
+        return false;
+    }
+
+    let num_actions = node_a.num_actions();
+
+    let visit = |action: usize, line: &mut Vec<Action>| {
+        let child_a = node_a.play(action);
+        line.push(child_a.prev_action);
+        let found = first_divergence_recursive(
+            a,
+            b,
+            &child_a,
+            &node_b.play(action),
+            player,
+            hand,
+            hand_index,
+            tolerance,
+            line,
+        );
+        if !found {
+            line.pop();
+        }
+        found
+    };
+
+    // chance node
+    if node_a.is_chance() {
+        return (0..num_actions).any(|action| {
+            let card = match node_a.play(action).prev_action {
+                Action::Chance(card) => card,
+                _ => unreachable!(),
+            };
+            card != hand.0 && card != hand.1 && visit(action, line)
+        });
+    }
+
+    // opponent node
+    if node_a.player() != player {
+        return (0..num_actions).any(|action| visit(action, line));
+    }
+
+    // player node
+    let num_hands = a.num_private_hands(player);
+    let strategy_a = node_strategy(a, node_a);
+    let strategy_b = node_strategy(b, node_b);
+
+    let prob_a = |action: usize| strategy_a[action * num_hands + hand_index];
+    let prob_b = |action: usize| strategy_b[action * num_hands + hand_index];
+
+    if (0..num_actions).any(|action| (prob_a(action) - prob_b(action)).abs() > tolerance) {
+        return true;
+    }
+
+    let best_action = (0..num_actions)
+        .max_by(|&x, &y| prob_a(x).partial_cmp(&prob_a(y)).unwrap().then(y.cmp(&x)))
+        .unwrap();
+
+    visit(best_action, line)
+}
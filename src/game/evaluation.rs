@@ -11,7 +11,143 @@ fn min(x: f64, y: f64) -> f64 {
     }
 }
 
+/// Computes the tournament equity of each player by the Malmuth-Harville method.
+fn icm_equity(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    let num_players = stacks.len();
+    let mut equity = vec![0.0; num_players];
+
+    // prob[mask]: probability that the players in `mask` take the top places
+    let mut prob = vec![0.0; 1 << num_players];
+    prob[0] = 1.0;
+
+    for mask in 0..prob.len() {
+        let place = mask.count_ones() as usize;
+        if prob[mask] == 0.0 || place >= payouts.len() {
+            continue;
+        }
+
+        let rest = (0..num_players).filter(|&i| mask & (1 << i) == 0);
+        let rest_sum = rest.clone().map(|i| stacks[i]).sum::<f64>();
+
+        if rest_sum > 0.0 {
+            for i in rest {
+                let p = prob[mask] * stacks[i] / rest_sum;
+                prob[mask | (1 << i)] += p;
+                equity[i] += p * payouts[place];
+            }
+        } else {
+            // the busted players share the remaining places
+            let num_rest = num_players - place;
+            let share = payouts[place..].iter().take(num_rest).sum::<f64>() / num_rest as f64;
+            rest.for_each(|i| equity[i] += prob[mask] * share);
+        }
+    }
+
+    equity
+}
+
 impl PostFlopGame {
+    /// Returns the amount of the starting pot invested by each player.
+    #[inline]
+    pub(super) fn initial_invested(&self) -> [f64; 2] {
+        match self.tree_config.initial_invested {
+            [0, 0] => [0.5 * self.tree_config.starting_pot as f64; 2],
+            invested => invested.map(|x| x as f64),
+        }
+    }
+
+    /// Computes the rake of the pot at the terminal node.
+    #[inline]
+    fn rake(&self, amount: i32, pot: f64, fold: bool) -> f64 {
+        if (amount == 0 && self.tree_config.no_rake_check_down)
+            || (fold && self.tree_config.no_rake_fold)
+        {
+            0.0
+        } else {
+            min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap)
+        }
+    }
+
+    /// Computes the payoffs of `player` when winning, losing, and tying at the terminal node.
+    ///
+    /// The payoffs are relative to the stacks before the starting pot is invested. `fold`
+    /// specifies whether the terminal node is reached by a fold.
+    #[inline]
+    fn payoffs(&self, amount: i32, player: usize, fold: bool) -> [f64; 3] {
+        if let Some(payoffs) = self.icm_payoffs.get(&(amount, fold)) {
+            return payoffs[player];
+        }
+
+        let pot = (self.tree_config.starting_pot + 2 * amount) as f64;
+        let invested = self.initial_invested()[player] + amount as f64;
+        let rake = self.rake(amount, pot, fold);
+        [
+            pot - invested - rake,
+            -invested,
+            0.5 * (pot - rake) - invested,
+        ]
+    }
+
+    /// Precomputes the ICM payoffs of each terminal bet amount.
+    pub(super) fn init_payoffs(&mut self) {
+        self.icm_payoffs.clear();
+
+        let PayoffModel::Icm { stacks, payouts } = &self.tree_config.payoff_model else {
+            return;
+        };
+
+        let initial_invested = self.initial_invested();
+        let mut base_stacks = stacks.iter().map(|&s| s as f64).collect::<Vec<_>>();
+        base_stacks[0] += initial_invested[0];
+        base_stacks[1] += initial_invested[1];
+
+        // convert the equity to the chip equivalent
+        let total_chips = base_stacks.iter().sum::<f64>();
+        let total_payouts = payouts.iter().take(stacks.len()).sum::<f64>();
+        let chips = |stacks: &[f64]| {
+            let equity = icm_equity(stacks, payouts);
+            [equity[0], equity[1]].map(|e| e * total_chips / total_payouts)
+        };
+        let base_chips = chips(&base_stacks);
+
+        let mut amounts = self
+            .node_arena
+            .iter()
+            .map(|node| node.lock().amount)
+            .collect::<Vec<_>>();
+        amounts.sort_unstable();
+        amounts.dedup();
+
+        for (amount, fold) in amounts.into_iter().flat_map(|a| [(a, false), (a, true)]) {
+            let pot = (self.tree_config.starting_pot + 2 * amount) as f64;
+            let rake = self.rake(amount, pot, fold);
+            let mut payoffs = [[0.0; 3]; 2];
+
+            let invested = initial_invested.map(|x| x + amount as f64);
+
+            for (player, payoffs) in payoffs.iter_mut().enumerate() {
+                let opponent = player ^ 1;
+                let mut win = base_stacks.clone();
+                win[player] += pot - invested[player] - rake;
+                win[opponent] -= invested[opponent];
+                let mut lose = base_stacks.clone();
+                lose[player] -= invested[player];
+                lose[opponent] += pot - invested[opponent] - rake;
+                let mut tie = base_stacks.clone();
+                tie[0] += 0.5 * (pot - rake) - invested[0];
+                tie[1] += 0.5 * (pot - rake) - invested[1];
+
+                *payoffs = [
+                    chips(&win)[player] - base_chips[player],
+                    chips(&lose)[player] - base_chips[player],
+                    chips(&tie)[player] - base_chips[player],
+                ];
+            }
+
+            self.icm_payoffs.insert((amount, fold), payoffs);
+        }
+    }
+
     pub(super) fn evaluate_internal(
         &self,
         result: &mut [MaybeUninit<f32>],
@@ -19,11 +155,10 @@ impl PostFlopGame {
         player: usize,
         cfreach: &[f32],
     ) {
-        let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
-        let half_pot = 0.5 * pot;
-        let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
-        let amount_win = (half_pot - rake) / self.num_combinations;
-        let amount_lose = -half_pot / self.num_combinations;
+        let fold = node.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG;
+        let [amount_win, amount_lose, amount_tie] = self
+            .payoffs(node.amount, player, fold)
+            .map(|v| v / self.num_combinations);
 
         let player_cards = &self.private_cards[player];
         let opponent_cards = &self.private_cards[player ^ 1];
@@ -38,7 +173,7 @@ impl PostFlopGame {
         let result = unsafe { &mut *(result as *mut _ as *mut [f32]) };
 
         // someone folded
-        if node.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG {
+        if fold {
             let folded_player = node.player & PLAYER_MASK;
             let payoff = if folded_player as usize != player {
                 amount_win
@@ -91,10 +226,9 @@ impl PostFlopGame {
                 }
             }
         }
-        // showdown (optimized for no rake; 2-pass)
-        else if rake == 0.0 {
-            let pair_index = card_pair_to_index(node.turn, node.river);
-            let hand_strength = &self.hand_strength[pair_index];
+        // showdown (optimized for no payoff on tie; 2-pass)
+        else if amount_tie == 0.0 {
+            let hand_strength = self.hand_strength_at(node.turn, node.river);
             let player_strength = &hand_strength[player];
             let opponent_strength = &hand_strength[player ^ 1];
 
@@ -151,11 +285,9 @@ impl PostFlopGame {
         }
         // showdown (raked; 3-pass)
         else {
-            let amount_tie = -0.5 * rake / self.num_combinations;
             let same_hand_index = &self.same_hand_index[player];
 
-            let pair_index = card_pair_to_index(node.turn, node.river);
-            let hand_strength = &self.hand_strength[pair_index];
+            let hand_strength = self.hand_strength_at(node.turn, node.river);
             let player_strength = &hand_strength[player];
             let opponent_strength = &hand_strength[player ^ 1];
 
@@ -259,16 +391,14 @@ impl PostFlopGame {
         player: usize,
         cfreach: &[f32],
     ) {
-        let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
-        let half_pot = 0.5 * pot;
-        let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
-        let amount_win = ((half_pot - rake) / self.bunching_num_combinations) as f32;
-        let amount_lose = (-half_pot / self.bunching_num_combinations) as f32;
-        let amount_tie = (-0.5 * rake / self.bunching_num_combinations) as f32;
+        let fold = node.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG;
+        let [amount_win, amount_lose, amount_tie] = self
+            .payoffs(node.amount, player, fold)
+            .map(|v| (v / self.bunching_num_combinations) as f32);
         let opponent_len = self.private_cards[player ^ 1].len();
 
         // someone folded
-        if node.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG {
+        if fold {
             let folded_player = node.player & PLAYER_MASK;
             let payoff = if folded_player as usize != player {
                 amount_win
@@ -1,7 +1,11 @@
 use super::*;
 use crate::interface::*;
+use crate::range::*;
 use crate::sliceop::*;
+use crate::solver::*;
 use crate::utility::*;
+use std::fmt::Write;
+use std::mem::MaybeUninit;
 
 /// Decodes the encoded `i16` slice to the `f32` slice.
 #[inline]
@@ -134,7 +138,8 @@ impl PostFlopGame {
         }
 
         let flop = self.card_config.flop;
-        let mut board_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        let mut board_mask: u64 =
+            (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]) | self.card_config.removed_mask();
         let mut dead_mask: u64 = 0;
 
         // no bunching
@@ -264,7 +269,8 @@ impl PostFlopGame {
     /// - `action`
     ///   - If the current node is a chance node, the `action` corresponds to the card ID of the
     ///     dealt card. The `action` can be `usize::MAX`, in which case the actual card is chosen
-    ///     from possible cards.
+    ///     from possible cards. If the turn abstraction is enabled and the dealt turn card is
+    ///     merged into a bucket, the representative card of the bucket is dealt instead.
     ///   - If the current node is not a chance node, plays the `action`-th action of
     ///     [`available_actions`].
     ///
@@ -291,24 +297,27 @@ impl PostFlopGame {
                 panic!("Storage mode is not compatible");
             }
 
-            let actual_card = if action == usize::MAX {
+            let mut actual_card = if action == usize::MAX {
                 self.possible_cards().trailing_zeros() as u8
             } else {
                 action as u8
             };
 
             // swap the suit if swapping was performed in turn
-            let action_card = if let Some((suit1, suit2)) = self.turn_swapped_suit {
-                if actual_card & 3 == suit1 {
-                    actual_card - suit1 + suit2
-                } else if actual_card & 3 == suit2 {
-                    actual_card + suit1 - suit2
+            let swap_suit = |card: u8| {
+                if let Some((suit1, suit2)) = self.turn_swapped_suit {
+                    if card & 3 == suit1 {
+                        card - suit1 + suit2
+                    } else if card & 3 == suit2 {
+                        card + suit1 - suit2
+                    } else {
+                        card
+                    }
                 } else {
-                    actual_card
+                    card
                 }
-            } else {
-                actual_card
             };
+            let action_card = swap_suit(actual_card);
 
             let actions = self.available_actions();
             let mut action_index = usize::MAX;
@@ -328,12 +337,22 @@ impl PostFlopGame {
                 let isomorphic_cards = if node.turn == NOT_DEALT {
                     &self.isomorphism_card_turn
                 } else {
-                    &self.isomorphism_card_river[node.turn as usize & 3]
+                    self.river_isomorphism_cards(node.turn)
                 };
                 for (i, &repr_index) in isomorphism.iter().enumerate() {
                     if action_card == isomorphic_cards[i] {
                         action_index = repr_index as usize;
-                        if is_turn {
+                        if is_turn && !self.turn_bucket_swap.is_empty() {
+                            // the merged turn card is replaced with the representative card
+                            if let Action::Chance(repr_card) = actions[repr_index as usize] {
+                                actual_card = repr_card;
+                            }
+                        } else if !is_turn && !self.river_bucket_swap.is_empty() {
+                            // the merged river card is replaced with the representative card
+                            if let Action::Chance(repr_card) = actions[repr_index as usize] {
+                                actual_card = swap_suit(repr_card);
+                            }
+                        } else if is_turn {
                             if let Action::Chance(repr_card) = actions[repr_index as usize] {
                                 self.turn_swapped_suit = Some((action_card & 3, repr_card & 3));
                             }
@@ -567,6 +586,27 @@ impl PostFlopGame {
         &self.weights[player]
     }
 
+    /// Returns the weights of each private hand of the given player after the given card is dealt.
+    ///
+    /// The return value is the same as [`weights`] except that the hands containing `card` are
+    /// set to 0.0, as the evaluation does with the board cards. This is useful for visualizing
+    /// how a turn or river card prunes the current ranges. Panics if `card` is not in [0, 52).
+    ///
+    /// **Time complexity:** *O*(#(private hands)).
+    ///
+    /// [`weights`]: #method.weights
+    pub fn range_after_card(&self, player: usize, card: u8) -> Vec<f32> {
+        if 52 <= card {
+            panic!("Invalid card: {card}");
+        }
+
+        self.private_cards(player)
+            .iter()
+            .zip(self.weights(player))
+            .map(|(&(c1, c2), &w)| if c1 == card || c2 == card { 0.0 } else { w })
+            .collect()
+    }
+
     /// Returns the normalized weights of each private hand of the given player.
     ///
     /// The "normalized weights" represent the actual number of combinations that the player is
@@ -610,25 +650,9 @@ impl PostFlopGame {
             panic!("Normalized weights are not cached");
         }
 
-        let num_hands = self.num_private_hands(player);
-
         let tmp = if self.bunching_num_dead_cards == 0 {
-            let mut tmp = vec![0.0; num_hands];
-            if self.river != NOT_DEALT {
-                self.equity_internal(&mut tmp, player, self.turn, self.river, 0.5);
-            } else if self.turn != NOT_DEALT {
-                for river in 0..52 {
-                    if self.turn != river {
-                        self.equity_internal(&mut tmp, player, self.turn, river, 0.5 / 44.0);
-                    }
-                }
-            } else {
-                for turn in 0..52 {
-                    for river in turn + 1..52 {
-                        self.equity_internal(&mut tmp, player, turn, river, 1.0 / (45.0 * 44.0));
-                    }
-                }
-            }
+            let opponent_weights = &self.weights[player ^ 1];
+            let tmp = self.equity_runouts(player, opponent_weights, self.turn, self.river);
             tmp.into_iter().map(|v| v as f32).collect()
         } else {
             let mut tmp = self.equity_internal_bunching(player);
@@ -649,6 +673,106 @@ impl PostFlopGame {
             .collect()
     }
 
+    /// Returns the equity of the whole range of each player [OOP, IP] at the root node, i.e.,
+    /// before the remaining board cards are dealt.
+    ///
+    /// The equity is averaged over all possible runouts and the hands of both players weighted by
+    /// their initial weights, so the two values sum up to 1. Unlike the [`equity`] method, the
+    /// return value does not depend on the current node.
+    ///
+    /// Panics if the game is not successfully initialized or the bunching effect is enabled.
+    ///
+    /// [`equity`]: #method.equity
+    pub fn flop_equity(&self) -> [f32; 2] {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            panic!("Bunching effect is not supported");
+        }
+
+        let (turn, river) = (self.card_config.turn, self.card_config.river);
+        [0, 1].map(|player| {
+            let opponent_weights = &self.initial_weights[player ^ 1];
+            let tmp = self.equity_runouts(player, opponent_weights, turn, river);
+            let sum = tmp
+                .iter()
+                .zip(&self.initial_weights[player])
+                .fold(0.0, |acc, (&v, &w)| acc + v * w as f64);
+            (sum / self.num_combinations + 0.5) as f32
+        })
+    }
+
+    /// Returns the range advantage and the nut advantage of OOP over IP at the root node, i.e.,
+    /// before the remaining board cards are dealt.
+    ///
+    /// The equity advantage is the difference of the [`flop_equity`] of the two players. The nut
+    /// advantage is the difference of the fractions of the ranges made of the hands whose equity
+    /// against the opponent's range, averaged over all possible runouts, is at least
+    /// `nut_threshold` (e.g., `0.8`). The fractions are weighted in the same way as
+    /// [`flop_equity`], i.e., by the initial weights of the hands and the weights of the
+    /// compatible opponent hands. Positive values favor OOP.
+    ///
+    /// Panics if the game is not successfully initialized or the bunching effect is enabled.
+    ///
+    /// [`flop_equity`]: #method.flop_equity
+    pub fn flop_advantage(&self, nut_threshold: f32) -> FlopAdvantage {
+        let equity = self.flop_equity();
+
+        let nut_fraction = [0, 1].map(|player| {
+            let hands = self.root_hand_equities(player);
+            let sum = hands.iter().fold(0.0, |acc, &(w, _)| acc + w);
+            let nut_sum = hands
+                .iter()
+                .filter(|&&(_, equity)| equity >= nut_threshold as f64)
+                .fold(0.0, |acc, &(w, _)| acc + w);
+            if sum > 0.0 {
+                (nut_sum / sum) as f32
+            } else {
+                0.0
+            }
+        });
+
+        FlopAdvantage {
+            equity_advantage: equity[0] - equity[1],
+            nut_advantage: nut_fraction[0] - nut_fraction[1],
+        }
+    }
+
+    /// Returns the distribution of the equities of the hands of the given player at the root node,
+    /// i.e., before the remaining board cards are dealt.
+    ///
+    /// The equity of each hand against the opponent's range is averaged over all possible runouts,
+    /// and the interval `[0, 1]` is divided into `buckets` intervals of equal width (the last one
+    /// includes `1`). The return value is a vector of the length of `buckets`, whose `i`-th element
+    /// is the fraction of the range whose equity falls into the `i`-th interval. The hands are
+    /// weighted in the same way as [`flop_equity`], so the elements sum up to 1 unless the range
+    /// has no live hands.
+    ///
+    /// Panics if the game is not successfully initialized, the bunching effect is enabled, or
+    /// `buckets` is zero.
+    ///
+    /// [`flop_equity`]: #method.flop_equity
+    pub fn equity_histogram(&self, player: usize, buckets: usize) -> Vec<f32> {
+        if buckets == 0 {
+            panic!("Number of buckets must be positive");
+        }
+
+        let hands = self.root_hand_equities(player);
+        let sum = hands.iter().fold(0.0, |acc, &(w, _)| acc + w);
+
+        let mut ret = vec![0.0; buckets];
+        for (w, equity) in hands {
+            let bucket = ((equity * buckets as f64) as usize).min(buckets - 1);
+            ret[bucket] += w;
+        }
+
+        ret.iter()
+            .map(|&mass| if sum > 0.0 { (mass / sum) as f32 } else { 0.0 })
+            .collect()
+    }
+
     /// Returns the expected values of each private hand of the given player.
     ///
     /// Panics if the game is not solved.
@@ -692,11 +816,113 @@ impl PostFlopGame {
         ret
     }
 
+    /// Returns the expected values of each private hand of the given player in big blinds.
+    ///
+    /// This method is the same as the [`expected_values`] method, except that the values are
+    /// divided by `big_blind`. Returns an error if `big_blind` is not positive. To use the big
+    /// blind of the tree configuration, pass `self.tree_config().big_blind`.
+    ///
+    /// [`expected_values`]: #method.expected_values
+    pub fn expected_values_bb(&self, player: usize, big_blind: i32) -> Result<Vec<f32>, String> {
+        if big_blind <= 0 {
+            return Err(format!("Big blind must be positive: {big_blind}"));
+        }
+
+        let mut ret = self.expected_values(player);
+        ret.iter_mut().for_each(|v| *v /= big_blind as f32);
+        Ok(ret)
+    }
+
+    /// Returns the expected values of each private hand of the given player as a fraction of the
+    /// starting pot.
+    ///
+    /// This method is the same as the [`expected_values`] method, except that the values are
+    /// divided by the `starting_pot` of the tree configuration.
+    ///
+    /// [`expected_values`]: #method.expected_values
+    pub fn expected_values_pot_fraction(&self, player: usize) -> Vec<f32> {
+        let starting_pot = self.tree_config.starting_pot as f32;
+        let mut ret = self.expected_values(player);
+        ret.iter_mut().for_each(|v| *v /= starting_pot);
+        ret
+    }
+
+    /// Returns the equity realization of each private hand of the given player.
+    ///
+    /// The equity realization of a hand is its expected value divided by its share of the current
+    /// pot by the raw equity, i.e., `ev / (equity * pot)`. A value above `1.0` means the hand
+    /// realizes more than its equity, and a value below `1.0` means it realizes less. The pot is
+    /// the starting pot plus the total bet amount of both players. The value is `0.0` for the
+    /// hands with zero equity.
+    ///
+    /// Panics if the game is not solved.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn equity_realization(&self, player: usize) -> Vec<f32> {
+        let expected_values = self.expected_values(player);
+        let equity = self.equity(player);
+        let total_bet_amount = self.total_bet_amount();
+        let pot =
+            (self.tree_config.starting_pot + total_bet_amount[0] + total_bet_amount[1]) as f32;
+
+        expected_values
+            .iter()
+            .zip(equity.iter())
+            .map(|(&ev, &eq)| if eq > 0.0 { ev / (eq * pot) } else { 0.0 })
+            .collect()
+    }
+
+    /// Returns the summary of the expected values of the range of the given player.
+    ///
+    /// The average is computed with the [`normalized_weights`], so `avg_ev` is the same as
+    /// `compute_average(&game.expected_values(player), game.normalized_weights(player))`. A hand
+    /// is counted in `num_combos` and `reach_weighted_combos` if it does not overlap with the
+    /// board and can be held against the opponent's range.
+    ///
+    /// Panics if the game is not solved.
+    ///
+    /// After mutating the current node, you must call the [`cache_normalized_weights`] method
+    /// before calling this method.
+    ///
+    /// **Time complexity:** see [`expected_values_detail`].
+    ///
+    /// [`normalized_weights`]: #method.normalized_weights
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`expected_values_detail`]: #method.expected_values_detail
+    pub fn range_ev_summary(&self, player: usize) -> RangeEvSummary {
+        let expected_values = self.expected_values(player);
+        let normalized_weights = &self.normalized_weights[player];
+
+        let mut num_combos = 0;
+        let mut reach_weighted_combos = 0.0;
+        for (&w, &w_normalized) in self.weights[player].iter().zip(normalized_weights) {
+            if w > 0.0 && w_normalized > 0.0 {
+                num_combos += 1;
+                reach_weighted_combos += w as f64;
+            }
+        }
+
+        let avg_ev = compute_average(&expected_values, normalized_weights);
+        let reach_weighted_combos = reach_weighted_combos as f32;
+
+        RangeEvSummary {
+            avg_ev,
+            total_ev: avg_ev * reach_weighted_combos,
+            num_combos,
+            reach_weighted_combos,
+        }
+    }
+
     /// Returns the expected values of each action of each private hand of the given player.
     ///
     /// If the given player is the current player, the return value is a vector of the length of
     /// `#(actions) * #(private hands)`. The expected value of the `i`-th action with the `j`-th
     /// private hand is stored in the `i * #(private hands) + j`-th element.
+    /// The expected value of an action is the value of taking the action with probability 1,
+    /// assuming that the rest of the strategy is fixed.
     ///
     /// Otherwise, this method is the same as the [`expected_values`] method, so the return vector
     /// is the length of `#(private hands)`.
@@ -710,7 +936,7 @@ impl PostFlopGame {
     /// - (with bunching and the current node is terminal) *O*(#(OOP private hands) * #(IP private hands)).
     /// - (otherwise) *O*(#(actions) * #(private hands)).
     ///
-    /// [`expected_values`]: #method.expected_value
+    /// [`expected_values`]: #method.expected_values
     /// [`cache_normalized_weights`]: #method.cache_normalized_weights
     pub fn expected_values_detail(&self, player: usize) -> Vec<f32> {
         if self.state != State::Solved {
@@ -724,12 +950,13 @@ impl PostFlopGame {
         let node = self.node();
         let num_hands = self.num_private_hands(player);
 
+        let deck_size = self.card_config.deck_size();
         let mut chance_factor = 1;
         if self.card_config.turn == NOT_DEALT && self.turn != NOT_DEALT {
-            chance_factor *= 45 - self.bunching_num_dead_cards;
+            chance_factor *= deck_size - 7 - self.bunching_num_dead_cards;
         }
         if self.card_config.river == NOT_DEALT && self.river != NOT_DEALT {
-            chance_factor *= 44 - self.bunching_num_dead_cards;
+            chance_factor *= deck_size - 8 - self.bunching_num_dead_cards;
         }
 
         let num_combinations = match self.bunching_num_dead_cards {
@@ -756,7 +983,7 @@ impl PostFlopGame {
             } else {
                 node.cfvalues_chance().to_vec()
             }
-        } else if node.has_cfvalues_ip() && player == PLAYER_IP as usize {
+        } else if node.has_cfvalues_ip() && player != self.current_player() {
             if self.is_compression_enabled {
                 let slice = node.cfvalues_ip_compressed();
                 let scale = node.cfvalue_ip_scale();
@@ -777,7 +1004,7 @@ impl PostFlopGame {
             self.cfvalues_cache[player].to_vec()
         };
 
-        let starting_pot = self.tree_config.starting_pot;
+        let initial_invested = self.initial_invested()[player] as f32;
         let total_bet_amount = self.total_bet_amount();
         let bias = (total_bet_amount[player] - total_bet_amount[player ^ 1]).max(0);
 
@@ -794,7 +1021,7 @@ impl PostFlopGame {
                             *v = 0.0;
                         } else {
                             *v *= normalizer * (w_raw / w_normalized);
-                            *v += starting_pot as f32 * 0.5 + (self.node().amount + bias) as f32;
+                            *v += initial_invested + (self.node().amount + bias) as f32;
                         }
                     });
             });
@@ -802,175 +1029,1131 @@ impl PostFlopGame {
         ret
     }
 
-    /// Returns the strategy of the current player.
-    ///
-    /// The return value is a vector of the length of `#(actions) * #(private hands)`.
-    /// The probability of the `i`-th action with the `j`-th private hand is stored in the
-    /// `i * #(private hands) + j`-th element.
+    /// Returns the expected value of each available action of the given hand at the node reached
+    /// by the given line from the root, where the acting player faces a bet.
     ///
-    /// If a hand overlaps with the board, an undefined value is returned.
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The `hand_index` is
+    /// the index of the hand in [`private_cards`] of the acting player. Each element of the return
+    /// value is a tuple of `(action, ev)`. Unlike [`expected_values_detail`], `ev` is the expected
+    /// profit from the start of the game; that is, the amount the player has put into the pot so
+    /// far (including the initial investment) is subtracted. Therefore, the expected value of the
+    /// fold action equals the negative of the invested amount.
     ///
-    /// Panics if the current node is a terminal node or a chance node. Also, panics if the memory
-    /// is not yet allocated.
+    /// Returns an error if the game is not solved, the line is invalid, the acting player does not
+    /// face a bet, or the hand cannot reach the node. The current node and the cached normalized
+    /// weights are restored before returning.
     ///
-    /// **Time complexity:** *O*(#(actions) * #(private hands)).
-    pub fn strategy(&self) -> Vec<f32> {
-        if self.state < State::MemoryAllocated {
-            panic!("Memory is not allocated");
+    /// [`private_cards`]: #method.private_cards
+    /// [`expected_values_detail`]: #method.expected_values_detail
+    pub fn facing_bet_evs(
+        &mut self,
+        actions: &[Action],
+        hand_index: usize,
+    ) -> Result<Vec<(Action, f32)>, String> {
+        if self.state != State::Solved {
+            return Err("Game is not solved".to_string());
         }
 
-        if self.is_terminal_node() {
-            panic!("Terminal node is not allowed");
-        }
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
 
-        if self.is_chance_node() {
-            panic!("Chance node is not allowed");
+        let result = self.facing_bet_evs_internal(actions, hand_index);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
         }
 
-        let node = self.node();
-        let player = self.current_player();
-        let num_actions = node.num_actions();
-        let num_hands = self.num_private_hands(player);
+        result
+    }
 
-        let mut ret = if self.is_compression_enabled {
-            normalized_strategy_compressed(node.strategy_compressed(), num_actions)
-        } else {
-            normalized_strategy(node.strategy(), num_actions)
-        };
+    /// Returns the expected values of calling and folding of the given hand at the node reached by
+    /// the given line from the root, where the `player` faces a bet.
+    ///
+    /// The return value is a tuple of `(call_ev, fold_ev)`, computed in the same way as
+    /// [`facing_bet_evs`]. At an equilibrium, a hand that mixes calling and folding (i.e., a
+    /// bluff-catcher) is indifferent between the two actions, so the two values should be
+    /// approximately equal for such a hand.
+    ///
+    /// Returns `None` if the game is not solved, the line is invalid, the `player` is not the
+    /// acting player or does not face a bet, or the hand cannot reach the node. The current node
+    /// and the cached normalized weights are restored before returning.
+    ///
+    /// [`facing_bet_evs`]: #method.facing_bet_evs
+    pub fn indifference_check(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+        hand_index: usize,
+    ) -> Option<(f32, f32)> {
+        if self.state != State::Solved {
+            return None;
+        }
 
-        let locking = self.locking_strategy(&node);
-        apply_locking_strategy(&mut ret, locking);
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
 
-        ret.chunks_exact_mut(num_hands).for_each(|chunk| {
-            self.apply_swap(chunk, player, false);
-        });
+        let result = self.indifference_check_internal(actions, player, hand_index);
 
-        ret
-    }
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
 
-    /// Returns the total bet amount of each player (OOP, IP).
-    #[inline]
-    pub fn total_bet_amount(&self) -> [i32; 2] {
-        self.total_bet_amount
+        result
     }
 
-    /// Locks the strategy of the current node.
+    /// Exports the data of the given player's hands at the node reached by the given line from the
+    /// root in the CSV format.
     ///
-    /// The `strategy` argument must be a slice of the length of `#(actions) * #(private hands)`.
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The output has a header
+    /// row and one row per private hand of the `player` that does not overlap with the board. The
+    /// columns are the hand (e.g., `AsKh`), the reach probability (same as [`weights`]), the
+    /// expected value (same as [`expected_values`]), and the equity (same as [`equity`]). If the
+    /// `player` is to act at the node, the frequencies of the available actions follow, with the
+    /// action labels (e.g., `Bet 50`) as the headers. Numbers are written with six decimal places.
     ///
-    /// - A negative value is treated as a zero.
-    /// - If the `i * #(private hands) + j`-th element of the `strategy` is positive for some `i`,
-    ///   the `j`-th private hand will be locked. The probability for each action will be normalized
-    ///   so that their sum is 1.0.
-    /// - If the `i * #(private hands) + j`-th element of the `strategy` is not positive for all
-    ///   `i`, the `j`-th private hand will not be locked. That is, the solver can adjust the
-    ///   strategy of the `j`-th private hand.
-    pub fn lock_current_strategy(&mut self, strategy: &[f32]) {
-        if self.state < State::MemoryAllocated {
-            panic!("Memory is not allocated");
+    /// Returns an error if the game is not solved, the line is invalid, or the `player` is invalid.
+    /// The current node and the cached normalized weights are restored before returning.
+    ///
+    /// [`weights`]: #method.weights
+    /// [`expected_values`]: #method.expected_values
+    /// [`equity`]: #method.equity
+    pub fn node_to_csv(&mut self, actions: &[Action], player: usize) -> Result<String, String> {
+        if self.state != State::Solved {
+            return Err("Game is not solved".to_string());
         }
 
-        if self.is_terminal_node() {
-            panic!("Terminal node is not allowed");
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
         }
 
-        if self.is_chance_node() {
-            panic!("Chance node is not allowed");
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.node_to_csv_internal(actions, player);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
         }
 
-        let mut node = self.node();
-        let player = self.current_player();
-        let num_actions = node.num_actions();
-        let num_hands = self.num_private_hands(player);
+        result
+    }
 
-        if strategy.len() != num_actions * num_hands {
-            panic!("Invalid strategy length");
+    /// Returns the reach probability of each private hand of the given player at the node reached
+    /// by the given line from the root.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The reach probability
+    /// is the initial weight of the hand multiplied by the probabilities of the actions of the
+    /// `player` along the line under the current strategy, i.e., the same values as [`weights`]
+    /// after playing the line. The probabilities of the opponent's actions and the chance deals
+    /// are not included, except that the hands overlapping with the dealt cards have the reach
+    /// probability of 0.0.
+    ///
+    /// Returns an error if the memory is not yet allocated or the line is invalid. The current node
+    /// and the cached normalized weights are restored before returning.
+    ///
+    /// [`weights`]: #method.weights
+    pub fn reach_at(&mut self, actions: &[Action], player: usize) -> Result<Vec<f32>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
         }
 
-        let mut locking = vec![-1.0; num_actions * num_hands];
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
 
-        for hand in 0..num_hands {
-            let mut sum = 0.0;
-            let mut lock = false;
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
 
-            for action in 0..num_actions {
-                let freq = strategy[action * num_hands + hand];
-                if freq > 0.0 {
-                    sum += freq as f64;
-                    lock = true;
-                }
-            }
+        let result = self
+            .play_actions(actions)
+            .map(|_| self.weights[player].clone());
 
-            if lock {
-                for action in 0..num_actions {
-                    let freq = strategy[action * num_hands + hand].max(0.0) as f64;
-                    locking[action * num_hands + hand] = (freq / sum) as f32;
-                }
-            }
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
         }
 
-        locking.chunks_exact_mut(num_hands).for_each(|chunk| {
-            self.apply_swap(chunk, player, true);
-        });
-
-        node.is_locked = true;
-        let index = self.node_index(&node);
-        self.locking_strategy.insert(index, locking);
+        result
     }
 
-    /// Unlocks the strategy of the current node.
-    #[inline]
-    pub fn unlock_current_strategy(&mut self) {
-        if self.state < State::MemoryAllocated {
-            panic!("Memory is not allocated");
+    /// Returns the expected value of the given player at the node reached by the given line from
+    /// the root.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The expected value is
+    /// averaged over the hands of the `player` weighted by the [`normalized_weights`] at the node,
+    /// i.e., given the reach probabilities into the node. Like [`facing_bet_evs`], the value is
+    /// the expected profit from the start of the game, so the value at the root equals that of
+    /// [`compute_current_ev`], and the value at a node equals the average of the values at its
+    /// children weighted by the probabilities of reaching them.
+    ///
+    /// Returns an error if the game is not solved, the `player` is invalid, the line is invalid, or
+    /// the node cannot be reached. The current node and the cached normalized weights are restored
+    /// before returning.
+    ///
+    /// [`normalized_weights`]: #method.normalized_weights
+    /// [`facing_bet_evs`]: #method.facing_bet_evs
+    /// [`compute_current_ev`]: crate::compute_current_ev
+    pub fn compute_ev_at(&mut self, actions: &[Action], player: usize) -> Result<f32, String> {
+        if self.state != State::Solved {
+            return Err("Game is not solved".to_string());
         }
 
-        if self.is_terminal_node() {
-            panic!("Terminal node is not allowed");
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
         }
 
-        if self.is_chance_node() {
-            panic!("Chance node is not allowed");
-        }
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
 
-        let mut node = self.node();
-        if !node.is_locked {
-            return;
+        let result = self.compute_ev_at_internal(actions, player);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
         }
 
-        node.is_locked = false;
-        let index = self.node_index(&node);
-        self.locking_strategy.remove(&index);
+        result
     }
 
-    /// Returns the locking strategy of the current node.
+    /// Returns the minimum defense frequency (MDF) of the given player facing a bet at the node
+    /// reached by the given line from the root, together with the actual defense frequency.
     ///
-    /// If the current node is not locked, `None` is returned.
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The required defense
+    /// is `pot / (pot + bet)`, where `bet` is the amount to call and `pot` is the pot size before
+    /// the bet (the rake is not considered). The actual defense is one minus the frequency of the
+    /// fold action, averaged over the hands of the `player` weighted by the
+    /// [`normalized_weights`] at the node.
     ///
-    /// Otherwise, returns a reference to the vector of the length of
-    /// `#(actions) * #(private hands)`.
-    /// The probability of the `i`-th action with the `j`-th private hand is stored in the
-    /// `i * #(private hands) + j`-th element.
-    /// If the `j`-th private hand is not locked, returns `-1.0` for all `i`.
-    #[inline]
-    pub fn current_locking_strategy(&self) -> Option<Vec<f32>> {
+    /// Returns an error if the memory is not yet allocated, the line is invalid, the `player` does
+    /// not face a bet at the node, or the node cannot be reached. The current node and the cached
+    /// normalized weights are restored before returning.
+    ///
+    /// [`normalized_weights`]: #method.normalized_weights
+    pub fn mdf_report(&mut self, actions: &[Action], player: usize) -> Result<MdfReport, String> {
         if self.state < State::MemoryAllocated {
-            panic!("Memory is not allocated");
+            return Err("Memory is not allocated".to_string());
         }
 
-        if self.is_terminal_node() {
-            panic!("Terminal node is not allowed");
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
         }
 
-        if self.is_chance_node() {
-            panic!("Chance node is not allowed");
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.mdf_report_internal(actions, player);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
         }
 
-        let index = self.node_index(&self.node());
-        self.locking_strategy.get(&index).map(|s| {
-            let mut ret = s.clone();
-            let player = self.current_player();
-            let num_hands = self.num_private_hands(player);
+        result
+    }
+
+    /// Returns the frequency with which the given player continues (calls or raises) facing a bet
+    /// at the node reached by the given line from the root.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The return value is
+    /// the same as the actual defense of [`mdf_report`], i.e., one minus the fold frequency
+    /// weighted by the [`normalized_weights`] at the node.
+    ///
+    /// Returns `None` if the memory is not yet allocated, the line is invalid, the `player` does not
+    /// face a bet at the node, or the node cannot be reached. The current node and the cached
+    /// normalized weights are restored before returning.
+    ///
+    /// [`mdf_report`]: #method.mdf_report
+    /// [`normalized_weights`]: #method.normalized_weights
+    #[inline]
+    pub fn continue_frequency(&mut self, actions: &[Action], player: usize) -> Option<f32> {
+        self.mdf_report(actions, player)
+            .ok()
+            .map(|report| report.actual_defense)
+    }
+
+    /// Returns the average bet size of the given player at the node reached by the given line from
+    /// the root, as a fraction of the pot.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The sizes of the bet
+    /// actions (including the all-in) are averaged with the frequencies of the actions over the
+    /// hands of the `player` weighted by the [`normalized_weights`] at the node. For example, if
+    /// the player mixes a 50% pot bet and a 100% pot bet equally, the return value is `0.75`.
+    ///
+    /// Returns `Ok(None)` if the player never bets at the node. Returns an error if the memory is
+    /// not yet allocated, the line is invalid, the `player` does not act at the node, the `player`
+    /// faces a bet, or the node cannot be reached. The current node and the cached normalized
+    /// weights are restored before returning.
+    ///
+    /// [`normalized_weights`]: #method.normalized_weights
+    pub fn avg_bet_size(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<Option<f32>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.avg_bet_size_internal(actions, player);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result
+    }
+
+    /// Returns the strategy of the given player at the node reached by the given line from the
+    /// root.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The return value is in
+    /// the same format as [`strategy`] and is normalized in the same way: the probabilities of
+    /// each private hand sum up to 1, and a hand whose accumulated strategy is all zero (e.g.,
+    /// before solving) is assigned the uniform strategy. The locked strategies are reflected.
+    ///
+    /// Returns an error if the memory is not yet allocated, the line is invalid, or the `player`
+    /// does not act at the node. The current node and the cached normalized weights are restored
+    /// before returning.
+    ///
+    /// [`strategy`]: #method.strategy
+    pub fn normalized_strategy_at(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<Vec<f32>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.normalized_strategy_at_internal(actions, player);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result
+    }
+
+    /// Returns the strategy of the given player at the node reached by the given line from the
+    /// root, expanded to all 1326 combinations of two cards.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The return value is a
+    /// vector of the length of `#(actions) * 1326`: the probability of the `i`-th action with the
+    /// combination of the `j`-th index is stored in the `i * 1326 + j`-th element, where the
+    /// combinations are indexed as `"2d2c"` => `0`, `"2h2c"` => `1`, ..., `"AsAh"` => `1325`
+    /// regardless of the range. The probabilities of the private hands are those of
+    /// [`normalized_strategy_at`], and the combinations not in the range or overlapping with the
+    /// board have zero probabilities.
+    ///
+    /// Returns an error if the memory is not yet allocated, the line is invalid, or the `player`
+    /// does not act at the node. The current node and the cached normalized weights are restored
+    /// before returning.
+    ///
+    /// [`normalized_strategy_at`]: #method.normalized_strategy_at
+    pub fn full_strategy_at(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<Vec<f32>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.full_strategy_at_internal(actions, player);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result
+    }
+
+    /// Returns the pure strategy of the given player at the node reached by the given line from the
+    /// root, i.e., the most frequent action of each private hand.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The `i`-th element of
+    /// the return value is the action with the highest probability in the strategy of the `i`-th
+    /// private hand. Ties are broken toward the most passive action, that is, the action that
+    /// appears first in the [`available_actions`] (fold, check or call, then the bets in ascending
+    /// order of size). If a hand overlaps with the board, an undefined action is returned.
+    ///
+    /// Returns an error if the memory is not yet allocated, the line is invalid, or the `player`
+    /// does not act at the node. The current node and the cached normalized weights are restored
+    /// before returning.
+    ///
+    /// [`available_actions`]: #method.available_actions
+    pub fn pure_strategy_at(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<Vec<Action>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.pure_strategy_at_internal(actions, player);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result
+    }
+
+    /// Returns the indices of the private hands of the given player that take the given `action`
+    /// with a frequency of at least `min_freq` at the node reached by the given line from the
+    /// root.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The frequencies are
+    /// those of [`normalized_strategy_at`], and the returned indices point into
+    /// [`private_cards`] in ascending order. Hands overlapping with the board are never
+    /// included.
+    ///
+    /// Returns an error if the memory is not yet allocated, the line is invalid, the `player` does
+    /// not act at the node, or the `action` is not available at the node. The current node and
+    /// the cached normalized weights are restored before returning.
+    ///
+    /// [`normalized_strategy_at`]: #method.normalized_strategy_at
+    /// [`private_cards`]: #method.private_cards
+    pub fn combos_taking(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+        action: Action,
+        min_freq: f32,
+    ) -> Result<Vec<usize>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.combos_taking_internal(actions, player, action, min_freq);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result
+    }
+
+    /// Returns the results of the given player at the node reached by the given line from the root,
+    /// bundled into a [`NodeView`].
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The returned view
+    /// contains the same values as the [`available_actions`], [`strategy`], [`expected_values`],
+    /// [`equity`], and [`normalized_weights`] methods at the node, together with the labels of the
+    /// hands and the actions. Hands overlapping with the board have undefined values, as in these
+    /// methods.
+    ///
+    /// Returns an error if the game is not solved, the line is invalid, or the `player` does not
+    /// act at the node. The current node and the cached normalized weights are restored before
+    /// returning.
+    ///
+    /// [`available_actions`]: #method.available_actions
+    /// [`strategy`]: #method.strategy
+    /// [`expected_values`]: #method.expected_values
+    /// [`equity`]: #method.equity
+    /// [`normalized_weights`]: #method.normalized_weights
+    pub fn node_view(&mut self, actions: &[Action], player: usize) -> Result<NodeView, String> {
+        if self.state != State::Solved {
+            return Err("Game is not solved".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.node_view_internal(actions, player);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result
+    }
+
+    /// Re-solves the subtree rooted at the node reached by the given line from the root, with the
+    /// strategies outside the subtree held fixed.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The reach
+    /// probabilities of both players at the subtree root are computed from the current strategies
+    /// above the node (i.e., the same values as [`reach_at`]) and used as fixed inputs, and then
+    /// `num_iterations` iterations of Discounted CFR are performed only within the subtree. The
+    /// cumulative regrets and strategies of the subtree are cleared before the first iteration,
+    /// so the result does not depend on the previous solve, and the nodes outside the subtree are
+    /// left untouched. The locked strategies in the subtree are
+    /// respected.
+    ///
+    /// **Warning:** the subtree is re-solved without a gadget game, i.e., the opponent cannot
+    /// deviate to the actions leading away from the subtree. The resolved strategy is the best
+    /// one only against the fixed reach probabilities, so the opponent can exploit it by changing
+    /// the range entering the subtree, and the resulting strategy can be more exploitable than
+    /// the original blueprint strategy.
+    ///
+    /// This method must be called before finalizing the game: call [`finalize`] after resolving
+    /// to obtain the expected values.
+    ///
+    /// Returns an error if the memory is not yet allocated, the game is already solved, the line
+    /// is invalid or leads to a terminal node, or the turn abstraction or the bunching effect is
+    /// enabled. The current node and the cached normalized weights are restored before returning.
+    ///
+    /// [`reach_at`]: #method.reach_at
+    /// [`finalize`]: crate::finalize
+    pub fn resolve_subtree(
+        &mut self,
+        actions: &[Action],
+        num_iterations: u32,
+    ) -> Result<(), String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if self.state == State::Solved {
+            return Err("Game is already solved".to_string());
+        }
+
+        if self.storage_mode != BoardState::River {
+            return Err("Storage mode is not compatible".to_string());
+        }
+
+        if self.card_config.turn_abstraction.is_some() {
+            return Err("Not supported with the turn abstraction".to_string());
+        }
+
+        if self.card_config.river_abstraction.is_some() {
+            return Err("Not supported with the river abstraction".to_string());
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            return Err("Not supported with the bunching effect".to_string());
+        }
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.resolve_subtree_internal(actions, num_iterations);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result
+    }
+
+    /// Returns the strategy of the current player.
+    ///
+    /// The return value is a vector of the length of `#(actions) * #(private hands)`.
+    /// The probability of the `i`-th action with the `j`-th private hand is stored in the
+    /// `i * #(private hands) + j`-th element.
+    ///
+    /// If a hand overlaps with the board, an undefined value is returned.
+    ///
+    /// Panics if the current node is a terminal node or a chance node. Also, panics if the memory
+    /// is not yet allocated.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    pub fn strategy(&self) -> Vec<f32> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let node = self.node();
+        let player = self.current_player();
+        let num_actions = node.num_actions();
+        let num_hands = self.num_private_hands(player);
+
+        let mut ret = if self.is_compression_enabled {
+            normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+        } else {
+            normalized_strategy(node.strategy(), num_actions)
+        };
+
+        let locking = self.locking_strategy(&node);
+        apply_locking_strategy(&mut ret, locking);
+
+        ret.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, false);
+        });
+
+        ret
+    }
+
+    /// Returns the weights of each private hand of the current player after playing the
+    /// `action`-th action of [`available_actions`].
+    ///
+    /// The return value is the same as [`weights`] of the current player after calling
+    /// [`play`] with the `action`, without mutating the current node. This is useful for
+    /// obtaining the range of a player for resolving a subsequent street.
+    ///
+    /// Panics if the current node is a terminal node or a chance node. Also, panics if the memory
+    /// is not yet allocated or the `action` is out of range.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`available_actions`]: #method.available_actions
+    /// [`weights`]: #method.weights
+    /// [`play`]: #method.play
+    pub fn updated_weights(&self, action: usize) -> Vec<f32> {
+        let strategy = self.strategy();
+        let player = self.current_player();
+        let num_hands = self.num_private_hands(player);
+
+        if action >= self.node().num_actions() {
+            panic!("Invalid action");
+        }
+
+        let mut ret = self.weights[player].clone();
+        mul_slice(&mut ret, row(&strategy, action, num_hands));
+        ret
+    }
+
+    /// Samples an action of the current player holding the `hand`-th private hand from the current
+    /// strategy.
+    ///
+    /// `random` must be a uniform random number in [`0.0`, `1.0`), which is supplied by the caller
+    /// so that any random number generator can be used. The return value is the index of the
+    /// sampled action, which can be passed to the [`play`] method.
+    ///
+    /// Returns an error if `hand` is out of range or the hand overlaps with the current board.
+    /// Panics if the current node is a terminal node or a chance node. Also, panics if the memory
+    /// is not yet allocated.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`play`]: #method.play
+    pub fn sample_action(&self, hand: usize, random: f64) -> Result<usize, String> {
+        let strategy = self.strategy();
+        let player = self.current_player();
+        let num_hands = self.num_private_hands(player);
+
+        if hand >= num_hands {
+            return Err(format!("Invalid hand index: {hand}"));
+        }
+
+        let (c1, c2) = self.private_cards[player][hand];
+        let hand_mask: u64 = (1 << c1) | (1 << c2);
+        let board_mask = self
+            .current_board()
+            .iter()
+            .fold(0u64, |acc, &card| acc | (1 << card));
+        if hand_mask & board_mask != 0 {
+            return Err(format!("Hand overlaps with the board: {hand}"));
+        }
+
+        let row = strategy.iter().skip(hand).step_by(num_hands);
+        let total = row.clone().fold(0.0, |acc, &x| acc + x as f64);
+        let threshold = random * total;
+
+        let mut cumulative = 0.0;
+        let mut last_positive = 0;
+        for (action, &x) in row.enumerate() {
+            if x > 0.0 {
+                cumulative += x as f64;
+                last_positive = action;
+                if threshold < cumulative {
+                    return Ok(action);
+                }
+            }
+        }
+
+        // rounding error
+        Ok(last_positive)
+    }
+
+    /// Returns the strategy of the current player aggregated into the 13x13 hand grid.
+    ///
+    /// The return value is a vector of the length of `#(actions) * 169`. The frequency of the
+    /// `i`-th action in the grid cell at the `j`-th row and the `k`-th column is stored in the
+    /// `i * 169 + j * 13 + k`-th element. Both rows and columns are ordered from ace to deuce;
+    /// pairs are on the diagonal, suited hands are in the upper triangle (`j < k`), and offsuit
+    /// hands are in the lower triangle (`j > k`).
+    ///
+    /// The frequencies of each cell are averaged over the combinations weighted by the current
+    /// [`weights`], so the hands that overlap with the board are ignored. If a cell contains no
+    /// combination with a positive weight, the frequencies of the cell are all `0.0`.
+    ///
+    /// Panics if the current node is a terminal node or a chance node. Also, panics if the memory
+    /// is not yet allocated.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`weights`]: #method.weights
+    pub fn strategy_grid(&self) -> Vec<f32> {
+        let strategy = self.strategy();
+        let player = self.current_player();
+        let num_actions = self.node().num_actions();
+        let num_hands = self.num_private_hands(player);
+
+        let mut weight_sum = [0.0; 169];
+        let mut ret = vec![0.0; num_actions * 169];
+
+        for (hand, (&(c1, c2), &w)) in self.private_cards[player]
+            .iter()
+            .zip(self.weights[player].iter())
+            .enumerate()
+        {
+            let (row, col) = hand_grid_cell(c1, c2);
+            let index = row * 13 + col;
+
+            let w = w as f64;
+            weight_sum[index] += w;
+            for action in 0..num_actions {
+                ret[action * 169 + index] += w * strategy[action * num_hands + hand] as f64;
+            }
+        }
+
+        ret.chunks_exact(169)
+            .flat_map(|row| {
+                row.iter().zip(weight_sum.iter()).map(
+                    |(&v, &w)| {
+                        if w > 0.0 {
+                            (v / w) as f32
+                        } else {
+                            0.0
+                        }
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the action frequencies of the given hand class at the node reached by the given
+    /// line from the root.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`. The `class` is a hand
+    /// class such as `"AKo"`, `"AKs"`, `"AK"`, or `"QQ"`. Each element of the return value is a
+    /// tuple of `(action, frequency)`, where the frequencies of the combinations of the class held
+    /// by the acting player are averaged with the weights of the reach probabilities (i.e.,
+    /// [`weights`] at the node), so the combinations that overlap with the board are ignored.
+    ///
+    /// Returns an error if the memory is not yet allocated, the line is invalid, the node is not a
+    /// player node, or the class has no combination with a positive reach probability. The
+    /// current node and the cached normalized weights are restored before returning.
+    ///
+    /// [`weights`]: #method.weights
+    pub fn class_strategy(
+        &mut self,
+        actions: &[Action],
+        class: &str,
+    ) -> Result<Vec<(Action, f32)>, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        let indices = hand_class_indices(class)?;
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let result = self.class_strategy_internal(actions, class, &indices);
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result
+    }
+
+    /// Aggregates the strategy of `player` at the node reached by the given line over all runouts.
+    ///
+    /// The `line` follows the format of [`ActionTree::add_line`], i.e., chance actions must be
+    /// omitted: every possible card is dealt at each chance node along the line, and the node
+    /// reached after each runout is examined. If the line ends at a chance node, the player nodes
+    /// right after the chance node are examined. For each runout, the action frequencies are
+    /// averaged over the private hands weighted by the normalized weights (i.e., the reach
+    /// probabilities of both players are taken into account). The average over the runouts is
+    /// weighted in the same way, so that it represents the overall frequencies of the actions
+    /// when the line is played.
+    ///
+    /// Returns an error if the memory is not yet allocated, the `player` is invalid, the line is
+    /// invalid, or `player` does not act at the reached nodes. The current node and the cached
+    /// normalized weights are restored before returning.
+    ///
+    /// **Time complexity:** *O*(#(runouts) * #(nodes in the line) * #(private hands)).
+    ///
+    /// [`ActionTree::add_line`]: crate::ActionTree::add_line
+    pub fn aggregate_strategy(
+        &mut self,
+        line: &[Action],
+        player: usize,
+    ) -> Result<AggregatedReport, String> {
+        if self.state < State::MemoryAllocated {
+            return Err("Memory is not allocated".to_string());
+        }
+
+        if player >= 2 {
+            return Err(format!("Invalid player: {player}"));
+        }
+
+        let history = self.action_history.clone();
+        let is_normalized_weight_cached = self.is_normalized_weight_cached;
+
+        let mut report = AggregatedReport {
+            player,
+            ..Default::default()
+        };
+        let mut runout_weights = Vec::new();
+        let mut frequencies = Vec::new();
+
+        self.back_to_root();
+        let result = self.aggregate_strategy_recursive(
+            line,
+            &mut Vec::new(),
+            &mut report,
+            &mut runout_weights,
+            &mut frequencies,
+        );
+
+        self.apply_history(&history);
+        if is_normalized_weight_cached {
+            self.cache_normalized_weights();
+        }
+
+        result?;
+
+        let total_weight = runout_weights.iter().sum::<f64>();
+        let num_actions = report.actions.len();
+        let mut average = vec![0.0; num_actions];
+
+        for (&runout_weight, runout_frequencies) in runout_weights.iter().zip(&frequencies) {
+            for (avg, &freq) in average.iter_mut().zip(runout_frequencies) {
+                *avg += freq;
+            }
+            report
+                .frequencies
+                .extend(runout_frequencies.iter().map(|&freq| {
+                    if runout_weight > 0.0 {
+                        (freq / runout_weight) as f32
+                    } else {
+                        0.0
+                    }
+                }));
+        }
+
+        if total_weight > 0.0 {
+            report.runout_weights = runout_weights
+                .iter()
+                .map(|&w| (w / total_weight) as f32)
+                .collect();
+            report.average = average.iter().map(|&v| (v / total_weight) as f32).collect();
+        } else {
+            report.runout_weights = vec![0.0; runout_weights.len()];
+            report.average = vec![0.0; num_actions];
+        }
+
+        Ok(report)
+    }
+
+    /// Returns the best response of the current player against the current strategy of the
+    /// opponent.
+    ///
+    /// The return value is a vector of the length of `#(actions) * #(private hands)`.
+    /// The `i * #(private hands) + j`-th element is `1.0` if the `i`-th action maximizes the
+    /// expected value of the `j`-th private hand (assuming that the current player also plays the
+    /// best response in the subsequent nodes), and `0.0` otherwise.
+    ///
+    /// If a hand overlaps with the board, an undefined value is returned.
+    ///
+    /// Panics if the current node is a terminal node or a chance node. Also, panics if the memory
+    /// is not yet allocated.
+    ///
+    /// **Time complexity:** same as computing the exploitability of the current subtree.
+    pub fn best_response(&self) -> Vec<f32> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.storage_mode != BoardState::River {
+            panic!("Storage mode is not compatible");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let node = self.node();
+        let player = self.current_player();
+        let num_actions = node.num_actions();
+        let num_hands = self.num_private_hands(player);
+
+        let mut cfreach = self.weights[player ^ 1].clone();
+        self.apply_swap(&mut cfreach, player ^ 1, true);
+
+        let mut cfv_actions = Vec::with_capacity(num_actions * num_hands);
+        for action in 0..num_actions {
+            compute_best_cfv_recursive(
+                row_mut(cfv_actions.spare_capacity_mut(), action, num_hands),
+                self,
+                &node.play(action),
+                player,
+                &cfreach,
+            );
+        }
+        unsafe { cfv_actions.set_len(num_actions * num_hands) };
+
+        cfv_actions.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, false);
+        });
+
+        let mut ret = vec![0.0; num_actions * num_hands];
+        for hand in 0..num_hands {
+            let best_action = (1..num_actions).fold(0, |best, action| {
+                let value = cfv_actions[action * num_hands + hand];
+                if value > cfv_actions[best * num_hands + hand] {
+                    action
+                } else {
+                    best
+                }
+            });
+            ret[best_action * num_hands + hand] = 1.0;
+        }
+
+        ret
+    }
+
+    /// Returns the local exploitability of each player node.
+    ///
+    /// Each element is a tuple of `(line, gap)`, where `line` is the line leading to the node from
+    /// the root (chance actions are included as `Action::Chance(card)`), and `gap` is the amount
+    /// of chips that the player to act could gain by switching to the best response from the node
+    /// onward while the opponent's strategy is fixed. The gap is weighted by the reach
+    /// probabilities, so it directly represents the contribution of the node to the expected value
+    /// of the player. Isomorphic runouts are merged into their representative runouts.
+    ///
+    /// The elements are sorted in the node order of the tree; sort them by `gap` to find the spots
+    /// that leak the most.
+    ///
+    /// Panics if the memory is not yet allocated.
+    ///
+    /// **Time complexity:** same as computing the exploitability.
+    pub fn local_exploitability(&self) -> Vec<(Vec<Action>, f32)> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.storage_mode != BoardState::River {
+            panic!("Storage mode is not compatible");
+        }
+
+        let mut ret = Vec::new();
+
+        for player in 0..2 {
+            let num_hands = self.num_private_hands(player);
+            let mut cfv = Vec::with_capacity(num_hands);
+            let mut best_cfv = Vec::with_capacity(num_hands);
+            self.local_exploitability_recursive(
+                cfv.spare_capacity_mut(),
+                best_cfv.spare_capacity_mut(),
+                &self.root(),
+                &mut Vec::new(),
+                player,
+                &self.initial_weights[player],
+                &self.initial_weights[player ^ 1],
+                &mut ret,
+            );
+        }
+
+        ret.sort_unstable_by_key(|&(index, _, _)| index);
+        ret.into_iter().map(|(_, line, gap)| (line, gap)).collect()
+    }
+
+    /// Returns the total bet amount of each player (OOP, IP).
+    #[inline]
+    pub fn total_bet_amount(&self) -> [i32; 2] {
+        self.total_bet_amount
+    }
+
+    /// Locks the strategy of the current node.
+    ///
+    /// The `strategy` argument must be a slice of the length of `#(actions) * #(private hands)`.
+    ///
+    /// - A negative value is treated as a zero.
+    /// - If the `i * #(private hands) + j`-th element of the `strategy` is positive for some `i`,
+    ///   the `j`-th private hand will be locked. The probability for each action will be normalized
+    ///   so that their sum is 1.0.
+    /// - If the `i * #(private hands) + j`-th element of the `strategy` is not positive for all
+    ///   `i`, the `j`-th private hand will not be locked. That is, the solver can adjust the
+    ///   strategy of the `j`-th private hand.
+    pub fn lock_current_strategy(&mut self, strategy: &[f32]) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let mut node = self.node();
+        let player = self.current_player();
+        let num_actions = node.num_actions();
+        let num_hands = self.num_private_hands(player);
+
+        if strategy.len() != num_actions * num_hands {
+            panic!("Invalid strategy length");
+        }
+
+        let mut locking = vec![-1.0; num_actions * num_hands];
+
+        for hand in 0..num_hands {
+            let mut sum = 0.0;
+            let mut lock = false;
+
+            for action in 0..num_actions {
+                let freq = strategy[action * num_hands + hand];
+                if freq > 0.0 {
+                    sum += freq as f64;
+                    lock = true;
+                }
+            }
+
+            if lock {
+                for action in 0..num_actions {
+                    let freq = strategy[action * num_hands + hand].max(0.0) as f64;
+                    locking[action * num_hands + hand] = (freq / sum) as f32;
+                }
+            }
+        }
+
+        locking.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, true);
+        });
+
+        node.is_locked = true;
+        let index = self.node_index(&node);
+        self.locking_strategy.insert(index, locking);
+    }
+
+    /// Unlocks the strategy of the current node.
+    #[inline]
+    pub fn unlock_current_strategy(&mut self) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let mut node = self.node();
+        if !node.is_locked {
+            return;
+        }
+
+        node.is_locked = false;
+        let index = self.node_index(&node);
+        self.locking_strategy.remove(&index);
+    }
+
+    /// Unlocks the strategies of all nodes.
+    #[inline]
+    pub fn unlock_all_strategies(&mut self) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        for &index in self.locking_strategy.keys() {
+            self.node_arena[index].lock().is_locked = false;
+        }
+
+        self.locking_strategy.clear();
+    }
+
+    /// Returns the locking strategy of the current node.
+    ///
+    /// If the current node is not locked, `None` is returned.
+    ///
+    /// Otherwise, returns a reference to the vector of the length of
+    /// `#(actions) * #(private hands)`.
+    /// The probability of the `i`-th action with the `j`-th private hand is stored in the
+    /// `i * #(private hands) + j`-th element.
+    /// If the `j`-th private hand is not locked, returns `-1.0` for all `i`.
+    #[inline]
+    pub fn current_locking_strategy(&self) -> Option<Vec<f32>> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let index = self.node_index(&self.node());
+        self.locking_strategy.get(&index).map(|s| {
+            let mut ret = s.clone();
+            let player = self.current_player();
+            let num_hands = self.num_private_hands(player);
             ret.chunks_exact_mut(num_hands).for_each(|chunk| {
                 self.apply_swap(chunk, player, false);
             });
@@ -980,7 +2163,7 @@ impl PostFlopGame {
 
     /// Returns the reference to the current node.
     #[inline]
-    fn node(&self) -> MutexGuardLike<PostFlopNode> {
+    fn node(&self) -> MutexGuardLike<'_, PostFlopNode> {
         self.node_arena[self.node_history.last().cloned().unwrap_or(0)].lock()
     }
 
@@ -1048,22 +2231,169 @@ impl PostFlopGame {
                     &mut weights_buf
                 };
 
-                for (w, &index) in weights.iter_mut().zip(indices.iter()) {
-                    if index == 0 {
-                        *w = 0.0;
-                    } else {
-                        let slice = &self.bunching_arena[index..index + opponent_len];
-                        if slice.iter().all(|&n| n == 0.0) {
-                            *w = 0.0;
-                        }
-                    }
-                }
+                for (w, &index) in weights.iter_mut().zip(indices.iter()) {
+                    if index == 0 {
+                        *w = 0.0;
+                    } else {
+                        let slice = &self.bunching_arena[index..index + opponent_len];
+                        if slice.iter().all(|&n| n == 0.0) {
+                            *w = 0.0;
+                        }
+                    }
+                }
+
+                if self.turn_swap.is_some() || self.river_swap.is_some() {
+                    self.apply_swap(&mut weights_buf, player, false);
+                    self.weights[player].copy_from_slice(&weights_buf);
+                }
+            }
+        }
+    }
+
+    /// Recursive function to compute the counterfactual values of the current strategy and the
+    /// best response at the same time, recording the gap of each node of the `player`.
+    #[allow(clippy::too_many_arguments)]
+    fn local_exploitability_recursive(
+        &self,
+        cfv: &mut [MaybeUninit<f32>],
+        best_cfv: &mut [MaybeUninit<f32>],
+        node: &PostFlopNode,
+        line: &mut Vec<Action>,
+        player: usize,
+        reach: &[f32],
+        cfreach: &[f32],
+        ret: &mut Vec<(usize, Vec<Action>, f32)>,
+    ) {
+        let num_hands = cfv.len();
+
+        // terminal node
+        if node.is_terminal() {
+            self.evaluate(cfv, node, player, cfreach);
+            let cfv = unsafe { &*(cfv as *const _ as *const [f32]) };
+            best_cfv.iter_mut().zip(cfv).for_each(|(b, &v)| {
+                b.write(v);
+            });
+            return;
+        }
+
+        let num_actions = node.num_actions();
+        let mut cfv_actions = Vec::with_capacity(num_actions * num_hands);
+        let mut best_cfv_actions = Vec::with_capacity(num_actions * num_hands);
+
+        let mut recurse = |action: usize, reach: &[f32], cfreach: &[f32]| {
+            let child = node.play(action);
+            line.push(child.prev_action);
+            self.local_exploitability_recursive(
+                row_mut(cfv_actions.spare_capacity_mut(), action, num_hands),
+                row_mut(best_cfv_actions.spare_capacity_mut(), action, num_hands),
+                &child,
+                line,
+                player,
+                reach,
+                cfreach,
+                ret,
+            );
+            line.pop();
+        };
+
+        // chance node
+        if node.is_chance() {
+            let cfreach_updated = cfreach
+                .iter()
+                .map(|&v| v / self.chance_factor(node) as f32)
+                .collect::<Vec<_>>();
+
+            for action in 0..num_actions {
+                recurse(action, reach, &cfreach_updated);
+            }
+
+            unsafe {
+                cfv_actions.set_len(num_actions * num_hands);
+                best_cfv_actions.set_len(num_actions * num_hands);
+            }
+
+            // sum up the counterfactual values, including the isomorphic chances
+            let isomorphic_chances = self.isomorphic_chances(node);
+            let sum_up = |result: &mut [MaybeUninit<f32>], values: &mut [f32]| {
+                let mut result_f64 = Vec::with_capacity(num_hands);
+                sum_slices_f64_uninit(result_f64.spare_capacity_mut(), values);
+                unsafe { result_f64.set_len(num_hands) };
+
+                for (i, &isomorphic_index) in isomorphic_chances.iter().enumerate() {
+                    let swap_list = &self.isomorphic_swap(node, i)[player];
+                    let tmp = row_mut(values, isomorphic_index as usize, num_hands);
+                    apply_swap(tmp, swap_list);
+                    result_f64.iter_mut().zip(&*tmp).for_each(|(r, &v)| {
+                        *r += v as f64;
+                    });
+                    apply_swap(tmp, swap_list);
+                }
+
+                result.iter_mut().zip(&result_f64).for_each(|(r, &v)| {
+                    r.write(v as f32);
+                });
+            };
+
+            sum_up(cfv, &mut cfv_actions);
+            sum_up(best_cfv, &mut best_cfv_actions);
+            return;
+        }
+
+        let mut strategy = if self.is_compression_enabled {
+            normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+        } else {
+            normalized_strategy(node.strategy(), num_actions)
+        };
+
+        let locking = self.locking_strategy(node);
+        apply_locking_strategy(&mut strategy, locking);
+
+        // player node
+        if node.player() == player {
+            for action in 0..num_actions {
+                let mut reach_updated = row(&strategy, action, num_hands).to_vec();
+                mul_slice(&mut reach_updated, reach);
+                recurse(action, &reach_updated, cfreach);
+            }
+
+            unsafe {
+                cfv_actions.set_len(num_actions * num_hands);
+                best_cfv_actions.set_len(num_actions * num_hands);
+            }
+
+            let cfv = fma_slices_uninit(cfv, &strategy, &cfv_actions);
+            let best_cfv = if locking.is_empty() {
+                max_slices_uninit(best_cfv, &best_cfv_actions)
+            } else {
+                max_fma_slices_uninit(best_cfv, &best_cfv_actions, locking)
+            };
+
+            let gap = reach
+                .iter()
+                .zip(best_cfv.iter().zip(cfv.iter()))
+                .map(|(&r, (&b, &v))| r as f64 * (b - v) as f64)
+                .sum::<f64>();
+
+            ret.push((self.node_index(node), line.clone(), gap as f32));
+        }
+        // opponent node
+        else {
+            let row_size = cfreach.len();
+            strategy.chunks_exact_mut(row_size).for_each(|row| {
+                mul_slice(row, cfreach);
+            });
+
+            for action in 0..num_actions {
+                recurse(action, reach, row(&strategy, action, row_size));
+            }
 
-                if self.turn_swap.is_some() || self.river_swap.is_some() {
-                    self.apply_swap(&mut weights_buf, player, false);
-                    self.weights[player].copy_from_slice(&weights_buf);
-                }
+            unsafe {
+                cfv_actions.set_len(num_actions * num_hands);
+                best_cfv_actions.set_len(num_actions * num_hands);
             }
+
+            sum_slices_uninit(cfv, &cfv_actions);
+            sum_slices_uninit(best_cfv, &best_cfv_actions);
         }
     }
 
@@ -1091,10 +2421,694 @@ impl PostFlopGame {
         }
     }
 
+    /// Moves to the node reached by the given line from the root.
+    ///
+    /// The `actions` must include chance actions as `Action::Chance(card)`.
+    fn play_actions(&mut self, actions: &[Action]) -> Result<(), String> {
+        self.back_to_root();
+
+        for &action in actions {
+            if self.is_terminal_node() {
+                return Err(format!("Invalid line: {actions:?}"));
+            }
+
+            if self.is_chance_node() {
+                match action {
+                    Action::Chance(card)
+                        if card < 52 && self.possible_cards() & (1 << card) != 0 =>
+                    {
+                        self.play(card as usize);
+                    }
+                    _ => return Err(format!("Invalid action: {action:?}")),
+                }
+            } else {
+                let index = self
+                    .available_actions()
+                    .iter()
+                    .position(|&a| a == action)
+                    .ok_or_else(|| format!("Invalid action: {action:?}"))?;
+                self.play(index);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recursive function for [`aggregate_strategy`](#method.aggregate_strategy).
+    ///
+    /// Deals every possible card at the chance nodes and plays the remaining `line` from the
+    /// current node, recording the unnormalized action frequencies and the weight of each runout.
+    fn aggregate_strategy_recursive(
+        &mut self,
+        line: &[Action],
+        runout: &mut Vec<u8>,
+        report: &mut AggregatedReport,
+        runout_weights: &mut Vec<f64>,
+        frequencies: &mut Vec<Vec<f64>>,
+    ) -> Result<(), String> {
+        if self.is_terminal_node() {
+            return Err("Terminal node is not allowed".to_string());
+        }
+
+        if self.is_chance_node() {
+            let history = self.action_history.clone();
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) == 0 {
+                    continue;
+                }
+                self.play(card as usize);
+                runout.push(card);
+                self.aggregate_strategy_recursive(
+                    line,
+                    runout,
+                    report,
+                    runout_weights,
+                    frequencies,
+                )?;
+                runout.pop();
+                self.apply_history(&history);
+            }
+            return Ok(());
+        }
+
+        if let Some((&action, rest)) = line.split_first() {
+            let index = self
+                .available_actions()
+                .iter()
+                .position(|&a| a == action)
+                .ok_or_else(|| format!("Invalid action: {action:?}"))?;
+            self.play(index);
+            return self.aggregate_strategy_recursive(
+                rest,
+                runout,
+                report,
+                runout_weights,
+                frequencies,
+            );
+        }
+
+        let player = report.player;
+        if self.current_player() != player {
+            return Err(format!("Player {player} does not act at the node"));
+        }
+
+        if report.runouts.is_empty() {
+            report.actions = self.available_actions();
+        }
+
+        self.cache_normalized_weights();
+        let strategy = self.strategy();
+        let weights = self.normalized_weights(player);
+        let num_hands = weights.len();
+
+        let runout_weight = weights.iter().fold(0.0, |acc, &w| acc + w as f64);
+        let runout_frequencies = strategy
+            .chunks_exact(num_hands)
+            .map(|row| {
+                row.iter()
+                    .zip(weights)
+                    .fold(0.0, |acc, (&s, &w)| acc + s as f64 * w as f64)
+            })
+            .collect();
+
+        report.runouts.push(runout.clone());
+        runout_weights.push(runout_weight);
+        frequencies.push(runout_frequencies);
+
+        Ok(())
+    }
+
+    /// Internal method for [`facing_bet_evs`](#method.facing_bet_evs).
+    fn facing_bet_evs_internal(
+        &mut self,
+        actions: &[Action],
+        hand_index: usize,
+    ) -> Result<Vec<(Action, f32)>, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node()
+            || self.is_chance_node()
+            || !self.available_actions().contains(&Action::Fold)
+        {
+            return Err("Not facing a bet".to_string());
+        }
+
+        let player = self.current_player();
+        let num_hands = self.num_private_hands(player);
+        if hand_index >= num_hands {
+            return Err(format!("Invalid hand index: {hand_index}"));
+        }
+
+        self.cache_normalized_weights();
+        if self.normalized_weights[player][hand_index] == 0.0 {
+            return Err(format!("Hand cannot reach the node: {hand_index}"));
+        }
+
+        let expected_values = self.expected_values_detail(player);
+        let invested =
+            self.initial_invested()[player] as f32 + self.total_bet_amount[player] as f32;
+
+        let ret = self
+            .available_actions()
+            .into_iter()
+            .enumerate()
+            .map(|(i, action)| {
+                (
+                    action,
+                    expected_values[i * num_hands + hand_index] - invested,
+                )
+            })
+            .collect();
+
+        Ok(ret)
+    }
+
+    /// Internal method for [`indifference_check`](#method.indifference_check).
+    fn indifference_check_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+        hand_index: usize,
+    ) -> Option<(f32, f32)> {
+        let evs = self.facing_bet_evs_internal(actions, hand_index).ok()?;
+        if self.current_player() != player {
+            return None;
+        }
+
+        let ev_of = |target: Action| {
+            evs.iter()
+                .find(|&&(action, _)| action == target)
+                .map(|&(_, ev)| ev)
+        };
+
+        Some((ev_of(Action::Call)?, ev_of(Action::Fold)?))
+    }
+
+    /// Internal method for [`node_to_csv`](#method.node_to_csv).
+    fn node_to_csv_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<String, String> {
+        self.play_actions(actions)?;
+        self.cache_normalized_weights();
+
+        let is_acting =
+            !self.is_terminal_node() && !self.is_chance_node() && self.current_player() == player;
+        let (available_actions, strategy) = if is_acting {
+            (self.available_actions(), self.strategy())
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        let expected_values = self.expected_values(player);
+        let equity = self.equity(player);
+        let board_mask = self
+            .current_board()
+            .iter()
+            .fold(0u64, |mask, &card| mask | (1 << card));
+
+        let mut out = String::from("hand,weight,ev,equity");
+        for action in &available_actions {
+            write!(out, ",{action}").unwrap();
+        }
+        out.push('\n');
+
+        let num_hands = self.num_private_hands(player);
+        for (hand, &(c1, c2)) in self.private_cards[player].iter().enumerate() {
+            if ((1u64 << c1) | (1u64 << c2)) & board_mask != 0 {
+                continue;
+            }
+
+            let hand_str = hole_to_string((c1, c2))?;
+            let weight = self.weights[player][hand];
+            write!(
+                out,
+                "{hand_str},{weight:.6},{:.6},{:.6}",
+                expected_values[hand], equity[hand]
+            )
+            .unwrap();
+            for action in 0..available_actions.len() {
+                write!(out, ",{:.6}", strategy[action * num_hands + hand]).unwrap();
+            }
+            out.push('\n');
+        }
+
+        Ok(out)
+    }
+
+    /// Internal method for [`compute_ev_at`](#method.compute_ev_at).
+    fn compute_ev_at_internal(&mut self, actions: &[Action], player: usize) -> Result<f32, String> {
+        self.play_actions(actions)?;
+        self.cache_normalized_weights();
+
+        let normalized_weights = &self.normalized_weights[player];
+        if normalized_weights.iter().all(|&w| w == 0.0) {
+            return Err("Node cannot be reached".to_string());
+        }
+
+        let expected_value = compute_average(&self.expected_values(player), normalized_weights);
+        let invested =
+            self.initial_invested()[player] as f32 + self.total_bet_amount[player] as f32;
+
+        Ok(expected_value - invested)
+    }
+
+    /// Internal method for [`mdf_report`](#method.mdf_report).
+    fn mdf_report_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<MdfReport, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() || self.is_chance_node() || self.current_player() != player {
+            return Err(format!("Player {player} does not act at the node"));
+        }
+
+        let fold_index = self
+            .available_actions()
+            .iter()
+            .position(|&action| action == Action::Fold)
+            .ok_or_else(|| "Not facing a bet".to_string())?;
+
+        self.cache_normalized_weights();
+        let normalized_weights = &self.normalized_weights[player];
+        if normalized_weights.iter().all(|&w| w == 0.0) {
+            return Err("Node cannot be reached".to_string());
+        }
+
+        let [bet0, bet1] = self.total_bet_amount;
+        let pot = (self.tree_config.starting_pot + bet0 + bet1) as f32;
+        let to_call = (bet0 - bet1).abs() as f32;
+        let required_defense = (pot - to_call) / pot;
+
+        let num_hands = self.num_private_hands(player);
+        let strategy = self.strategy();
+        let fold_strategy = &strategy[fold_index * num_hands..(fold_index + 1) * num_hands];
+        let fold_frequency = compute_average(fold_strategy, normalized_weights);
+
+        Ok(MdfReport {
+            required_defense,
+            actual_defense: 1.0 - fold_frequency,
+        })
+    }
+
+    /// Internal method for [`avg_bet_size`](#method.avg_bet_size).
+    fn avg_bet_size_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<Option<f32>, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() || self.is_chance_node() || self.current_player() != player {
+            return Err(format!("Player {player} does not act at the node"));
+        }
+
+        let available_actions = self.available_actions();
+        if available_actions.contains(&Action::Fold) {
+            return Err("Facing a bet".to_string());
+        }
+
+        self.cache_normalized_weights();
+        let normalized_weights = &self.normalized_weights[player];
+        if normalized_weights.iter().all(|&w| w == 0.0) {
+            return Err("Node cannot be reached".to_string());
+        }
+
+        let [bet0, bet1] = self.total_bet_amount;
+        let pot = (self.tree_config.starting_pot + bet0 + bet1) as f64;
+
+        let num_hands = self.num_private_hands(player);
+        let strategy = self.strategy();
+
+        let mut frequency_sum = 0.0;
+        let mut size_sum = 0.0;
+
+        for (action, row) in available_actions
+            .iter()
+            .zip(strategy.chunks_exact(num_hands))
+        {
+            let amount = match *action {
+                Action::Bet(amount) | Action::AllIn(amount) => amount,
+                _ => continue,
+            };
+
+            let frequency = compute_average(row, normalized_weights) as f64;
+            frequency_sum += frequency;
+            size_sum += frequency * amount as f64 / pot;
+        }
+
+        if frequency_sum == 0.0 {
+            Ok(None)
+        } else {
+            Ok(Some((size_sum / frequency_sum) as f32))
+        }
+    }
+
+    /// Internal method for [`resolve_subtree`](#method.resolve_subtree).
+    fn resolve_subtree_internal(
+        &mut self,
+        actions: &[Action],
+        num_iterations: u32,
+    ) -> Result<(), String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() {
+            return Err("Terminal node is not allowed".to_string());
+        }
+
+        let mut node = self.node();
+
+        // chance probabilities of the cards dealt along the line
+        let deck_size = self.card_config.deck_size();
+        let mut chance_probability = 1.0;
+        if self.card_config.turn == NOT_DEALT && node.turn != NOT_DEALT {
+            chance_probability /= (deck_size - 7) as f32;
+        }
+        if self.card_config.river == NOT_DEALT && node.river != NOT_DEALT {
+            chance_probability /= (deck_size - 8) as f32;
+        }
+
+        // the weights are oriented to the actual cards, while the node may be isomorphic
+        let reach = [0, 1].map(|player| {
+            let mut reach = self.weights[player].clone();
+            self.apply_swap(&mut reach, player, true);
+            reach.iter_mut().for_each(|r| *r *= chance_probability);
+            reach
+        });
+
+        solve_subtree(self, &mut node, &reach, num_iterations);
+
+        Ok(())
+    }
+
+    /// Internal method for [`normalized_strategy_at`](#method.normalized_strategy_at).
+    fn normalized_strategy_at_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<Vec<f32>, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() || self.is_chance_node() || self.current_player() != player {
+            return Err(format!("Player {player} does not act at the node"));
+        }
+
+        Ok(self.strategy())
+    }
+
+    /// Internal method for [`full_strategy_at`](#method.full_strategy_at).
+    fn full_strategy_at_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<Vec<f32>, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() || self.is_chance_node() || self.current_player() != player {
+            return Err(format!("Player {player} does not act at the node"));
+        }
+
+        let board_mask = self
+            .current_board()
+            .iter()
+            .fold(0u64, |mask, &card| mask | (1 << card));
+
+        let num_hands = self.num_private_hands(player);
+        let strategy = self.strategy();
+        let mut ret = vec![0.0; strategy.len() / num_hands * 1326];
+
+        for (hand, &(c1, c2)) in self.private_cards(player).iter().enumerate() {
+            let hand_mask: u64 = (1 << c1) | (1 << c2);
+            if hand_mask & board_mask != 0 {
+                continue;
+            }
+            let index = card_pair_to_index(c1, c2);
+            for (row, chunk) in ret
+                .chunks_exact_mut(1326)
+                .zip(strategy.chunks_exact(num_hands))
+            {
+                row[index] = chunk[hand];
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// Internal method for [`pure_strategy_at`](#method.pure_strategy_at).
+    fn pure_strategy_at_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<Vec<Action>, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() || self.is_chance_node() || self.current_player() != player {
+            return Err(format!("Player {player} does not act at the node"));
+        }
+
+        let available_actions = self.available_actions();
+        let num_hands = self.num_private_hands(player);
+        let strategy = self.strategy();
+
+        let ret = (0..num_hands)
+            .map(|hand| {
+                let mut best_index = 0;
+                for index in 1..available_actions.len() {
+                    // strict comparison breaks ties toward the earlier (more passive) action
+                    if strategy[index * num_hands + hand] > strategy[best_index * num_hands + hand]
+                    {
+                        best_index = index;
+                    }
+                }
+                available_actions[best_index]
+            })
+            .collect();
+
+        Ok(ret)
+    }
+
+    /// Internal method for [`combos_taking`](#method.combos_taking).
+    fn combos_taking_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+        action: Action,
+        min_freq: f32,
+    ) -> Result<Vec<usize>, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() || self.is_chance_node() || self.current_player() != player {
+            return Err(format!("Player {player} does not act at the node"));
+        }
+
+        let action_index = self
+            .available_actions()
+            .iter()
+            .position(|&a| a == action)
+            .ok_or_else(|| format!("Action `{action:?}` is not available"))?;
+
+        let board_mask = self
+            .current_board()
+            .iter()
+            .fold(0u64, |mask, &card| mask | (1 << card));
+
+        let num_hands = self.num_private_hands(player);
+        let strategy = self.strategy();
+        let frequencies = &strategy[action_index * num_hands..(action_index + 1) * num_hands];
+
+        let ret = self
+            .private_cards(player)
+            .iter()
+            .zip(frequencies)
+            .enumerate()
+            .filter(|&(_, (&(c1, c2), &freq))| {
+                let hand_mask: u64 = (1 << c1) | (1 << c2);
+                hand_mask & board_mask == 0 && freq >= min_freq
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        Ok(ret)
+    }
+
+    /// Internal method for [`node_view`](#method.node_view).
+    fn node_view_internal(
+        &mut self,
+        actions: &[Action],
+        player: usize,
+    ) -> Result<NodeView, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() || self.is_chance_node() || self.current_player() != player {
+            return Err(format!("Player {player} does not act at the node"));
+        }
+
+        self.cache_normalized_weights();
+
+        let actions = self.available_actions();
+        Ok(NodeView {
+            hands: holes_to_strings(self.private_cards(player))?,
+            action_labels: actions.iter().map(Action::to_string).collect(),
+            actions,
+            strategy: self.strategy(),
+            ev_per_hand: self.expected_values(player),
+            equity: self.equity(player),
+            normalized_weights: self.normalized_weights(player).to_vec(),
+        })
+    }
+
+    /// Internal method for [`class_strategy`](#method.class_strategy).
+    fn class_strategy_internal(
+        &mut self,
+        actions: &[Action],
+        class: &str,
+        indices: &[usize],
+    ) -> Result<Vec<(Action, f32)>, String> {
+        self.play_actions(actions)?;
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err("Not a player node".to_string());
+        }
+
+        let strategy = self.strategy();
+        let player = self.current_player();
+        let num_actions = self.node().num_actions();
+        let num_hands = self.num_private_hands(player);
+
+        let mut weight_sum = 0.0;
+        let mut ret = vec![0.0; num_actions];
+
+        for (hand, (&(c1, c2), &w)) in self.private_cards[player]
+            .iter()
+            .zip(self.weights[player].iter())
+            .enumerate()
+        {
+            if w > 0.0 && indices.contains(&card_pair_to_index(c1, c2)) {
+                let w = w as f64;
+                weight_sum += w;
+                for (action, r) in ret.iter_mut().enumerate() {
+                    *r += w * strategy[action * num_hands + hand] as f64;
+                }
+            }
+        }
+
+        if weight_sum == 0.0 {
+            return Err(format!("No live combinations of the hand class: {class}"));
+        }
+
+        Ok(self
+            .available_actions()
+            .into_iter()
+            .zip(ret)
+            .map(|(action, r)| (action, (r / weight_sum) as f32))
+            .collect())
+    }
+
+    /// Returns the weight and the equity of each live hand of the given player at the root node,
+    /// averaged over all possible runouts.
+    ///
+    /// Each hand is weighted by its initial weight and the initial weights of the compatible
+    /// opponent hands, as in [`flop_equity`]. Hands with zero weight are omitted.
+    ///
+    /// [`flop_equity`]: #method.flop_equity
+    fn root_hand_equities(&self, player: usize) -> Vec<(f64, f64)> {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            panic!("Bunching effect is not supported");
+        }
+
+        let (turn, river) = (self.card_config.turn, self.card_config.river);
+        let board_mask = [turn, river]
+            .iter()
+            .filter(|&&card| card != NOT_DEALT)
+            .fold(0u64, |mask, &card| mask | (1 << card));
+
+        let player_weights = &self.initial_weights[player];
+        let opponent_cards = &self.private_cards[player ^ 1];
+        let opponent_weights = &self.initial_weights[player ^ 1];
+        let tmp = self.equity_runouts(player, opponent_weights, turn, river);
+
+        let mut ret = Vec::new();
+
+        for (i, &(c1, c2)) in self.private_cards[player].iter().enumerate() {
+            let hand_mask: u64 = (1 << c1) | (1 << c2);
+            if player_weights[i] == 0.0 || hand_mask & board_mask != 0 {
+                continue;
+            }
+
+            let compatible_weight = opponent_cards
+                .iter()
+                .zip(opponent_weights)
+                .filter(|&(&(c3, c4), _)| {
+                    let opponent_mask: u64 = (1 << c3) | (1 << c4);
+                    opponent_mask & (hand_mask | board_mask) == 0
+                })
+                .fold(0.0, |acc, (_, &w)| acc + w as f64);
+
+            if compatible_weight == 0.0 {
+                continue;
+            }
+
+            let weight = player_weights[i] as f64 * compatible_weight;
+            ret.push((weight, tmp[i] / compatible_weight + 0.5));
+        }
+
+        ret
+    }
+
+    /// Sums up the results of [`equity_internal`] over the runouts of the given board.
+    ///
+    /// The return value of each hand is `0.5 * (win - lose)` weighted by `opponent_weights`, where
+    /// `win` and `lose` are averaged over the runouts.
+    ///
+    /// [`equity_internal`]: #method.equity_internal
+    fn equity_runouts(
+        &self,
+        player: usize,
+        opponent_weights: &[f32],
+        turn: u8,
+        river: u8,
+    ) -> Vec<f64> {
+        let mut ret = vec![0.0; self.num_private_hands(player)];
+        let deck_size = self.card_config.deck_size() as f64;
+        if river != NOT_DEALT {
+            self.equity_internal(&mut ret, player, opponent_weights, turn, river, 0.5);
+        } else if turn != NOT_DEALT {
+            let amount = 0.5 / (deck_size - 8.0);
+            for river in 0..52 {
+                if turn != river {
+                    self.equity_internal(&mut ret, player, opponent_weights, turn, river, amount);
+                }
+            }
+        } else {
+            let amount = 1.0 / ((deck_size - 7.0) * (deck_size - 8.0));
+            for turn in 0..52 {
+                for river in turn + 1..52 {
+                    self.equity_internal(&mut ret, player, opponent_weights, turn, river, amount);
+                }
+            }
+        }
+        ret
+    }
+
     /// Internal method for calculating the equity.
-    fn equity_internal(&self, result: &mut [f64], player: usize, turn: u8, river: u8, amount: f64) {
-        let pair_index = card_pair_to_index(turn, river);
-        let hand_strength = &self.hand_strength[pair_index];
+    fn equity_internal(
+        &self,
+        result: &mut [f64],
+        player: usize,
+        opponent_weights: &[f32],
+        turn: u8,
+        river: u8,
+        amount: f64,
+    ) {
+        let hand_strength = self.hand_strength_at(turn, river);
         let player_strength = &hand_strength[player];
         let opponent_strength = &hand_strength[player ^ 1];
 
@@ -1108,7 +3122,6 @@ impl PostFlopGame {
         let player_cards = &self.private_cards[player];
         let opponent_cards = &self.private_cards[player ^ 1];
 
-        let opponent_weights = &self.weights[player ^ 1];
         let mut weight_sum = 0.0;
         let mut weight_minus = [0.0; 52];
 
@@ -1,4 +1,7 @@
 mod base;
+mod batch;
+mod builder;
+mod diff;
 mod evaluation;
 mod interpreter;
 mod node;
@@ -6,14 +9,27 @@ mod node;
 #[cfg(feature = "bincode")]
 mod serialization;
 
+#[cfg(feature = "bincode")]
+mod solution;
+
 #[cfg(test)]
 mod tests;
 
+pub use batch::*;
+pub use builder::*;
+pub use diff::*;
+
+#[cfg(feature = "bincode")]
+pub use solution::*;
+
 use crate::action_tree::*;
 use crate::card::*;
 use crate::mutex_like::*;
 use std::collections::BTreeMap;
 
+#[cfg(feature = "bincode")]
+use std::path::PathBuf;
+
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
@@ -57,6 +73,12 @@ pub struct PostFlopGame {
     isomorphism_ref_river: Vec<Vec<u8>>,
     isomorphism_card_river: [Vec<u8>; 4],
     isomorphism_swap_river: [[SwapList; 4]; 4],
+    turn_bucket_swap: Vec<SwapList>,
+    river_bucket_card: Vec<Vec<u8>>,
+    river_bucket_swap: Vec<Vec<SwapList>>,
+    icm_payoffs: BTreeMap<(i32, bool), [[f64; 3]; 2]>,
+    #[cfg(feature = "bincode")]
+    hand_strength_cache_dir: Option<PathBuf>,
 
     // bunching effect
     bunching_num_dead_cards: usize,
@@ -104,7 +126,8 @@ pub struct PostFlopGame {
 
 /// A struct representing a node in a postflop game tree.
 ///
-/// The nodes must be stored as `Vec<MutexLike<PostFlopNode>>`.
+/// The nodes must be stored as `Vec<MutexLike<PostFlopNode>>`. The storage pointers point into
+/// the contiguous buffers of [`PostFlopGame`] (e.g., `storage1`).
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct PostFlopNode {
@@ -123,7 +146,187 @@ pub struct PostFlopNode {
     scale3: f32,
     storage1: *mut u8, // strategy
     storage2: *mut u8, // regrets or cfvalues
-    storage3: *mut u8, // IP cfvalues
+    storage3: *mut u8, // cfvalues of the player not acting first (usually IP)
+}
+
+/// Role of a node in a postflop game tree, returned by [`PostFlopNode::role`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeRole {
+    /// OOP is to act.
+    Oop,
+
+    /// IP is to act.
+    Ip,
+
+    /// A turn or river card is dealt.
+    Chance,
+
+    /// The hand ends with a showdown or with an all-in call on the river.
+    Terminal,
+
+    /// The hand ends because `folder` (0 = OOP, 1 = IP) folded.
+    Fold { folder: usize },
+}
+
+/// Statistics of a postflop game tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    /// Total number of nodes.
+    pub num_nodes: u64,
+
+    /// Number of terminal nodes.
+    pub num_terminal: u64,
+
+    /// Number of chance nodes.
+    pub num_chance: u64,
+
+    /// Number of player nodes.
+    pub num_player: u64,
+
+    /// Size of the (uncompressed) strategy arrays in bytes.
+    pub strategy_bytes: u64,
+}
+
+/// Pot size and stacks at a node of a postflop game tree.
+///
+/// See [`PostFlopGame::node_info`] for details.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// Pot size, including the starting pot and the bets of the both players.
+    pub pot: i32,
+
+    /// Remaining stack of OOP.
+    pub oop_stack: i32,
+
+    /// Remaining stack of IP.
+    pub ip_stack: i32,
+
+    /// Amount that the player facing a bet needs to call (`0` if there is no pending bet).
+    pub to_call: i32,
+}
+
+/// Outcome type of a terminal node of a postflop game tree.
+///
+/// See [`PostFlopGame::terminal_nodes`] for details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalKind {
+    /// The `folder` (`0` for OOP, `1` for IP) folded. `pot` includes the uncalled bet.
+    Fold { folder: usize, pot: i32 },
+
+    /// The hand went to a showdown with the pot size of `pot`.
+    Showdown { pot: i32 },
+}
+
+/// Summary of the expected values of a player's range at the current node.
+///
+/// See [`PostFlopGame::range_ev_summary`] for details.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RangeEvSummary {
+    /// Average expected value of the range, weighted by the normalized weights.
+    pub avg_ev: f32,
+
+    /// Total expected value of the range (`avg_ev * reach_weighted_combos`).
+    pub total_ev: f32,
+
+    /// Number of private hands that can be held at the current node.
+    pub num_combos: usize,
+
+    /// Number of combinations weighted by the initial weights and the reach probabilities.
+    pub reach_weighted_combos: f32,
+}
+
+/// Expected value of a private hand split by the type of the terminal node.
+///
+/// See [`PostFlopGame::ev_breakdown`] for details.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EvBreakdown {
+    /// Part of the expected value collected at the terminal nodes where someone folded.
+    pub fold_ev: f32,
+
+    /// Part of the expected value collected at the showdowns.
+    pub showdown_ev: f32,
+
+    /// Expected value of the hand (`fold_ev + showdown_ev`).
+    pub total: f32,
+}
+
+/// Minimum defense frequency of a player facing a bet and the actual defense frequency.
+///
+/// See [`PostFlopGame::mdf_report`] for details.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MdfReport {
+    /// Minimum defense frequency computed from the pot odds, i.e., `pot / (pot + bet)`.
+    pub required_defense: f32,
+
+    /// Frequency of continuing (calling or raising) in the solved strategy.
+    pub actual_defense: f32,
+}
+
+/// Range advantage and nut advantage of OOP over IP at the root node.
+///
+/// See [`PostFlopGame::flop_advantage`] for details.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlopAdvantage {
+    /// Difference of the range equities (OOP minus IP).
+    pub equity_advantage: f32,
+
+    /// Difference of the fractions of the ranges whose equity is at least the threshold (OOP
+    /// minus IP).
+    pub nut_advantage: f32,
+}
+
+/// Results of a player at a node, bundled for rendering.
+///
+/// All per-hand vectors are aligned to the [`PostFlopGame::private_cards`] of the player. See
+/// [`PostFlopGame::node_view`] for details.
+#[derive(Debug, Clone, Default)]
+pub struct NodeView {
+    /// Labels of the private hands (e.g., `"AsKs"`).
+    pub hands: Vec<String>,
+
+    /// Available actions at the node.
+    pub actions: Vec<Action>,
+
+    /// Labels of the available actions (e.g., `"Bet 50"`).
+    pub action_labels: Vec<String>,
+
+    /// Strategy of the player. The probability of the `i`-th action with the `j`-th private hand
+    /// is stored in the `i * #(private hands) + j`-th element.
+    pub strategy: Vec<f32>,
+
+    /// Expected value of each private hand.
+    pub ev_per_hand: Vec<f32>,
+
+    /// Equity of each private hand.
+    pub equity: Vec<f32>,
+
+    /// Normalized weight of each private hand at the node.
+    pub normalized_weights: Vec<f32>,
+}
+
+/// Strategy of a player at the node reached by a line, aggregated over all runouts.
+///
+/// See [`PostFlopGame::aggregate_strategy`] for details.
+#[derive(Debug, Clone, Default)]
+pub struct AggregatedReport {
+    /// Player of the player nodes.
+    pub player: usize,
+
+    /// Available actions of the player nodes.
+    pub actions: Vec<Action>,
+
+    /// Cards dealt along the line for each runout.
+    pub runouts: Vec<Vec<u8>>,
+
+    /// Probability of reaching the player node with each runout (sums to 1).
+    pub runout_weights: Vec<f32>,
+
+    /// Action frequencies for each runout. The frequency of the `j`-th action after the `i`-th
+    /// runout is stored in the `i * #(actions) + j`-th element.
+    pub frequencies: Vec<f32>,
+
+    /// Action frequencies averaged over the runouts.
+    pub average: Vec<f32>,
 }
 
 unsafe impl Send for PostFlopNode {}
@@ -35,7 +35,7 @@ impl GameNode for PostFlopNode {
     }
 
     #[inline]
-    fn play(&self, action: usize) -> MutexGuardLike<Self> {
+    fn play(&self, action: usize) -> MutexGuardLike<'_, Self> {
         self.children()[action].lock()
     }
 
@@ -229,6 +229,27 @@ impl Default for PostFlopNode {
 }
 
 impl PostFlopNode {
+    /// Returns the role of the node.
+    ///
+    /// Note that an all-in call before the river leads to a chance node, not a terminal node, as
+    /// the remaining cards are dealt in the tree.
+    #[inline]
+    pub fn role(&self) -> NodeRole {
+        if self.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG {
+            NodeRole::Fold {
+                folder: (self.player & PLAYER_MASK) as usize,
+            }
+        } else if self.is_terminal() {
+            NodeRole::Terminal
+        } else if self.is_chance() {
+            NodeRole::Chance
+        } else if self.player == PLAYER_OOP {
+            NodeRole::Oop
+        } else {
+            NodeRole::Ip
+        }
+    }
+
     #[inline]
     pub(super) fn children(&self) -> &[MutexLike<Self>] {
         // This is safe because `MutexLike<T>` is a `repr(transparent)` wrapper around `T`.
@@ -3,6 +3,7 @@ use super::*;
 use crate::interface::*;
 use crate::utility::*;
 use std::cell::Cell;
+use std::mem;
 use std::ptr;
 
 use bincode::{
@@ -73,7 +74,7 @@ impl PostFlopGame {
 
         let mut num_storage = [0; 4];
 
-        while num_storage.iter().any(|&x| x == 0) {
+        while num_storage.contains(&0) {
             node_index -= 1;
             let node = self.node_arena[node_index].lock();
             if num_storage[0] == 0 && !node.is_terminal() && !node.is_chance() {
@@ -96,13 +97,13 @@ impl PostFlopGame {
     }
 }
 
-static VERSION_STR: &str = "2023-03-19";
+static VERSION_STR: &str = "2026-10-16";
 
 thread_local! {
-    static PTR_BASE: Cell<[*const u8; 2]> = Cell::new([ptr::null(); 2]);
-    static CHANCE_BASE: Cell<*const u8> = Cell::new(ptr::null());
-    static PTR_BASE_MUT: Cell<[*mut u8; 3]> = Cell::new([ptr::null_mut(); 3]);
-    static CHANCE_BASE_MUT: Cell<*mut u8> = Cell::new(ptr::null_mut());
+    static PTR_BASE: Cell<[*const u8; 2]> = const { Cell::new([ptr::null(); 2]) };
+    static CHANCE_BASE: Cell<*const u8> = const { Cell::new(ptr::null()) };
+    static PTR_BASE_MUT: Cell<[*mut u8; 3]> = const { Cell::new([ptr::null_mut(); 3]) };
+    static CHANCE_BASE_MUT: Cell<*mut u8> = const { Cell::new(ptr::null_mut()) };
 }
 
 impl Encode for PostFlopGame {
@@ -146,6 +147,9 @@ impl Encode for PostFlopGame {
         locking_strategy.retain(|&i, _| i < num_nodes);
         locking_strategy.encode(encoder)?;
 
+        // initial weights (may be overridden by `set_initial_weights`)
+        self.initial_weights.encode(encoder)?;
+
         // store base pointers
         PTR_BASE.with(|c| {
             if self.state >= State::MemoryAllocated {
@@ -200,6 +204,7 @@ impl Decode for PostFlopGame {
             storage_ip: Decode::decode(decoder)?,
             storage_chance: Decode::decode(decoder)?,
             locking_strategy: Decode::decode(decoder)?,
+            initial_weights: Decode::decode(decoder)?,
             ..Default::default()
         };
 
@@ -236,8 +241,18 @@ impl Decode for PostFlopGame {
         game.node_arena = Decode::decode(decoder)?;
 
         // initialization
+        let initial_weights = mem::take(&mut game.initial_weights);
         game.check_card_config().map_err(DecodeError::OtherString)?;
+        for (player, weights) in initial_weights.iter().enumerate() {
+            if weights.len() != game.num_private_hands(player) {
+                return Err(DecodeError::Other("Invalid length of initial weights"));
+            }
+        }
+        game.initial_weights = initial_weights;
+        game.init_num_combinations()
+            .map_err(DecodeError::OtherString)?;
         game.init_card_fields();
+        game.init_payoffs();
         game.init_interpreter();
         game.back_to_root();
 
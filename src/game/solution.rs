@@ -0,0 +1,403 @@
+// [Solution format]
+// The solution format is a stable, versioned representation of a game and its solution that can
+// be parsed without depending on the internal layout of `PostFlopGame`. It consists of a header
+// and a body. The header is as follows:
+//  - Magic bytes (5 bytes): "PFSOL"
+//  - Version number (4 bytes, little endian): 1
+//
+// The body is encoded with the standard configuration of bincode (`VarIntEncoding`) and contains
+// the following fields in this order:
+//  - Card configuration (`CardConfig`)
+//  - Tree configuration (`TreeConfig`)
+//  - Added lines and removed lines of the action tree (`Vec<Vec<Action>>` each)
+//  - Initial weights of OOP and IP (`[Vec<f32>; 2]`)
+//  - Whether the compression is enabled (`bool`)
+//  - Whether the game is solved, i.e., finalized (`bool`)
+//  - Locked strategies keyed by the node index in tree order (`BTreeMap<usize, Vec<f32>>`; `usize`
+//    is encoded as `u64`)
+//  - Cumulative strategies of the player nodes in tree order (`Vec<Vec<f32>>`)
+//  - Cumulative regrets of the player nodes in tree order (`Vec<Vec<f32>>`); empty if the game is
+//    solved, since the counterfactual values are recomputed on loading
+//
+// The tree order is the depth-first order in which the game tree is built from the action tree,
+// where the children of a chance node are visited in the order of the dealt cards. The buffer of
+// each player node is of the length `#(actions) * #(private hands of the player)`, and the value
+// of the `i`-th action with the `j`-th private hand is stored in the
+// `i * #(private hands) + j`-th element.
+//
+// The card and tree configurations are encoded with their derived bincode layouts, i.e., their
+// fields in the declaration order. Therefore, `SOLUTION_VERSION` must be incremented whenever a
+// field of `CardConfig` or `TreeConfig` (or of the types they contain) is added, removed, or
+// reordered. The `solution_config_layout` test fails in that case as a reminder.
+//
+// `VarIntEncoding`: https://github.com/bincode-org/bincode/blob/trunk/docs/spec.md#varintencoding
+
+use super::*;
+use crate::interface::*;
+use crate::utility::*;
+use std::io::{Read, Write};
+
+/// Magic bytes at the beginning of the solution format.
+pub const SOLUTION_MAGIC: [u8; 5] = *b"PFSOL";
+
+/// Version number of the solution format written by [`write_solution`].
+pub const SOLUTION_VERSION: u32 = 1;
+
+/// Body of the solution format.
+#[derive(Decode, Encode)]
+struct PostFlopSolution {
+    card_config: CardConfig,
+    tree_config: TreeConfig,
+    added_lines: Vec<Vec<Action>>,
+    removed_lines: Vec<Vec<Action>>,
+    initial_weights: [Vec<f32>; 2],
+    is_compression_enabled: bool,
+    is_solved: bool,
+    locking_strategy: BTreeMap<usize, Vec<f32>>,
+    strategy: Vec<Vec<f32>>,
+    regrets: Vec<Vec<f32>>,
+}
+
+/// Writes the game and its solution into the writer in the versioned solution format.
+///
+/// Unlike [`save_data_into_std_write`], the format does not depend on the internal layout of
+/// [`PostFlopGame`], so that external tools can parse it. The strategies are always written as
+/// `f32` values, even if the compression is enabled.
+///
+/// Returns an error if the memory is not allocated, the storage mode is not
+/// [`BoardState::River`] (e.g., the game was loaded with a lower target storage mode), the bunching
+/// effect is enabled (the bunching data is not stored), the tree configuration has a bet size
+/// function, or writing fails. Nothing is written to the writer if the solution cannot be
+/// encoded.
+///
+/// [`save_data_into_std_write`]: crate::save_data_into_std_write
+pub fn write_solution<W: Write>(game: &PostFlopGame, writer: &mut W) -> Result<(), String> {
+    if game.state < State::MemoryAllocated {
+        return Err("Memory is not allocated".to_string());
+    }
+
+    if game.storage_mode != BoardState::River {
+        return Err("Storage mode is not compatible".to_string());
+    }
+
+    if game.bunching_num_dead_cards != 0 {
+        return Err("Bunching effect is not supported".to_string());
+    }
+
+    let is_solved = game.state == State::Solved;
+    let mut strategy = Vec::new();
+    let mut regrets = Vec::new();
+
+    for node in &game.node_arena {
+        let node = node.lock();
+        if node.is_terminal() || node.is_chance() {
+            continue;
+        }
+
+        if game.is_compression_enabled {
+            let decoder = node.strategy_scale() / u16::MAX as f32;
+            let slice = node.strategy_compressed();
+            strategy.push(slice.iter().map(|&x| x as f32 * decoder).collect());
+            if !is_solved {
+                let decoder = node.regret_scale() / i16::MAX as f32;
+                let slice = node.regrets_compressed();
+                regrets.push(slice.iter().map(|&x| x as f32 * decoder).collect());
+            }
+        } else {
+            strategy.push(node.strategy().to_vec());
+            if !is_solved {
+                regrets.push(node.regrets().to_vec());
+            }
+        }
+    }
+
+    // write only the locks of the nodes that are currently locked
+    let mut locking_strategy = game.locking_strategy.clone();
+    locking_strategy.retain(|&i, _| game.node_arena.get(i).is_some_and(|n| n.lock().is_locked));
+
+    let solution = PostFlopSolution {
+        card_config: game.card_config.clone(),
+        tree_config: game.tree_config.clone(),
+        added_lines: game.added_lines.clone(),
+        removed_lines: game.removed_lines.clone(),
+        initial_weights: game.initial_weights.clone(),
+        is_compression_enabled: game.is_compression_enabled,
+        is_solved,
+        locking_strategy,
+        strategy,
+        regrets,
+    };
+
+    // encode the body first so that nothing is written if the encoding fails
+    let body = bincode::encode_to_vec(solution, bincode::config::standard())
+        .map_err(|e| format!("Failed to encode solution: {e}"))?;
+
+    writer
+        .write_all(&SOLUTION_MAGIC)
+        .map_err(|e| format!("Failed to write magic bytes: {e}"))?;
+    writer
+        .write_all(&SOLUTION_VERSION.to_le_bytes())
+        .map_err(|e| format!("Failed to write version number: {e}"))?;
+    writer
+        .write_all(&body)
+        .map_err(|e| format!("Failed to write solution: {e}"))?;
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to flush writer: {e}"))
+}
+
+/// Reads a game and its solution in the versioned solution format from the reader.
+///
+/// The game is rebuilt from the configurations, the memory is allocated with the stored
+/// compression setting, and the stored buffers are restored. If the stored game is solved, the
+/// returned game is finalized.
+///
+/// Returns an error if the magic bytes are invalid, the version is unknown, or the body is
+/// corrupted or does not match the game tree built from the stored configurations.
+pub fn read_solution<R: Read>(reader: &mut R) -> Result<PostFlopGame, String> {
+    let mut magic = [0; 5];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|e| format!("Failed to read magic bytes: {e}"))?;
+    if magic != SOLUTION_MAGIC {
+        return Err("Magic bytes are invalid".to_string());
+    }
+
+    let mut version = [0; 4];
+    reader
+        .read_exact(&mut version)
+        .map_err(|e| format!("Failed to read version number: {e}"))?;
+    let version = u32::from_le_bytes(version);
+    if version != SOLUTION_VERSION {
+        return Err(format!(
+            "Unknown solution version: {version} (supported: {SOLUTION_VERSION})"
+        ));
+    }
+
+    let solution: PostFlopSolution =
+        bincode::decode_from_std_read(reader, bincode::config::standard())
+            .map_err(|e| format!("Failed to read solution: {e}"))?;
+
+    let action_tree = ActionTree::with_lines(
+        solution.tree_config,
+        &solution.added_lines,
+        &solution.removed_lines,
+    )?;
+    let mut game = PostFlopGame::with_config(solution.card_config, action_tree)?;
+    for player in 0..2 {
+        game.set_initial_weights(player, &solution.initial_weights[player])?;
+    }
+    game.allocate_memory(solution.is_compression_enabled);
+
+    let mismatch = || "Solution does not match the game tree".to_string();
+
+    let player_nodes = game
+        .node_arena
+        .iter()
+        .filter(|node| {
+            let node = node.lock();
+            !node.is_terminal() && !node.is_chance()
+        })
+        .collect::<Vec<_>>();
+
+    let num_regrets = if solution.is_solved {
+        0
+    } else {
+        player_nodes.len()
+    };
+
+    if solution.strategy.len() != player_nodes.len() || solution.regrets.len() != num_regrets {
+        return Err(mismatch());
+    }
+
+    for (i, node) in player_nodes.into_iter().enumerate() {
+        let mut node = node.lock();
+        let num_elements = node.num_elements as usize;
+        let strategy = &solution.strategy[i];
+        let regrets = solution.regrets.get(i);
+        if strategy.len() != num_elements || regrets.is_some_and(|r| r.len() != num_elements) {
+            return Err(mismatch());
+        }
+
+        if game.is_compression_enabled {
+            let scale = encode_unsigned_slice(node.strategy_compressed_mut(), strategy);
+            node.set_strategy_scale(scale);
+            if let Some(regrets) = regrets {
+                let scale = encode_signed_slice(node.regrets_compressed_mut(), regrets);
+                node.set_regret_scale(scale);
+            }
+        } else {
+            node.strategy_mut().copy_from_slice(strategy);
+            if let Some(regrets) = regrets {
+                node.regrets_mut().copy_from_slice(regrets);
+            }
+        }
+    }
+
+    for (index, locking) in solution.locking_strategy {
+        let mut node = match game.node_arena.get(index) {
+            Some(node) => node.lock(),
+            None => return Err(mismatch()),
+        };
+        if node.is_terminal() || node.is_chance() || locking.len() != node.num_elements as usize {
+            return Err(mismatch());
+        }
+        node.is_locked = true;
+        game.locking_strategy.insert(index, locking);
+    }
+
+    if solution.is_solved {
+        finalize(&mut game);
+    }
+
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bet_size::*;
+    use crate::solver::*;
+    use std::sync::Arc;
+
+    fn solved_game(enable_compression: bool) -> PostFlopGame {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ,AK,AQs", "JJ-88,KQ,QJ,T9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "60%")
+            .river_bets("50%, 100%", "60%")
+            .build()
+            .unwrap();
+        game.allocate_memory(enable_compression);
+        solve(&mut game, 50, 0.0, false);
+        game
+    }
+
+    #[test]
+    fn write_and_read_solution() {
+        for enable_compression in [false, true] {
+            let mut game = solved_game(enable_compression);
+
+            let mut buf = Vec::new();
+            write_solution(&game, &mut buf).unwrap();
+            assert_eq!(&buf[0..5], b"PFSOL");
+            assert_eq!(&buf[5..9], &1u32.to_le_bytes());
+
+            let mut loaded = read_solution(&mut buf.as_slice()).unwrap();
+            assert!(loaded.is_solved());
+            assert_eq!(loaded.is_compression_enabled(), enable_compression);
+
+            for game in [&mut game, &mut loaded] {
+                game.play(1);
+                game.cache_normalized_weights();
+            }
+            assert_eq!(loaded.strategy(), game.strategy());
+            assert_eq!(loaded.expected_values(1), game.expected_values(1));
+        }
+    }
+
+    #[test]
+    fn read_solution_invalid_header() {
+        let game = solved_game(false);
+        let mut buf = Vec::new();
+        write_solution(&game, &mut buf).unwrap();
+
+        // corrupted magic bytes
+        let mut corrupted = buf.clone();
+        corrupted[0] = b'X';
+        let err = read_solution(&mut corrupted.as_slice()).err().unwrap();
+        assert_eq!(err, "Magic bytes are invalid");
+
+        // unknown version
+        let mut corrupted = buf.clone();
+        corrupted[5..9].copy_from_slice(&2u32.to_le_bytes());
+        let err = read_solution(&mut corrupted.as_slice()).err().unwrap();
+        assert!(err.starts_with("Unknown solution version: 2"));
+
+        // truncated body
+        let err = read_solution(&mut &buf[..buf.len() / 2]).err().unwrap();
+        assert!(err.starts_with("Failed to read solution"));
+    }
+
+    #[test]
+    fn write_solution_locking() {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ,AK", "JJ-88,KQ,QJ")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .river("2s")
+            .pot(60)
+            .stack(970)
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+
+        let num_hands = game.num_private_hands(0);
+        let mut locking = vec![0.0; 2 * num_hands];
+        locking[..num_hands].fill(1.0);
+        game.lock_current_strategy(&locking);
+
+        // an entry whose node is not locked must not be written
+        game.locking_strategy.insert(1, vec![1.0; 2 * num_hands]);
+
+        let mut buf = Vec::new();
+        write_solution(&game, &mut buf).unwrap();
+        let loaded = read_solution(&mut buf.as_slice()).unwrap();
+        assert_eq!(loaded.locking_strategy.len(), 1);
+        assert_eq!(
+            loaded.current_locking_strategy(),
+            game.current_locking_strategy()
+        );
+    }
+
+    #[test]
+    fn write_solution_unsupported() {
+        // the bet size function cannot be encoded; nothing is written
+        let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK", "QQ,JJ")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .river("2s")
+            .pot(60)
+            .stack(970)
+            .build()
+            .unwrap();
+        let tree_config = TreeConfig {
+            bet_sizes_fn: Some(BetSizesFn(Arc::new(move |_| bet_sizes.clone()))),
+            ..game.tree_config().clone()
+        };
+        let card_config = game.card_config().clone();
+        game.update_config(card_config, ActionTree::new(tree_config).unwrap())
+            .unwrap();
+        game.allocate_memory(false);
+
+        let mut buf = Vec::new();
+        let err = write_solution(&game, &mut buf).err().unwrap();
+        assert!(err.starts_with("Failed to encode solution"));
+        assert!(buf.is_empty());
+
+        // the bunching data is not stored
+        let mut game = solved_game(false);
+        game.bunching_num_dead_cards = 4;
+        let err = write_solution(&game, &mut buf).err().unwrap();
+        assert_eq!(err, "Bunching effect is not supported");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn solution_config_layout() {
+        // if this test fails, a field of `CardConfig` or `TreeConfig` has been changed: increment
+        // `SOLUTION_VERSION` and update the expected lengths
+        let config = bincode::config::standard();
+        let card_config = bincode::encode_to_vec(CardConfig::default(), config).unwrap();
+        let tree_config = bincode::encode_to_vec(TreeConfig::default(), config).unwrap();
+        assert_eq!(SOLUTION_VERSION, 1);
+        assert_eq!(card_config.len(), 10619);
+        assert_eq!(tree_config.len(), 94);
+    }
+}
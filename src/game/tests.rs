@@ -1,4 +1,6 @@
 use super::*;
+use crate::bet_size::*;
+use crate::interface::*;
 use crate::range::*;
 use crate::solver::*;
 use crate::utility::*;
@@ -201,6 +203,104 @@ fn one_raise_all_range() {
     assert!((ev_ip - 60.0).abs() < 1e-4);
 }
 
+#[test]
+fn aggression_frequency() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK".parse().unwrap(),
+            "JJ-88,KQ,QJ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    // no betting is possible
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    assert!(game.aggression_frequency(0).is_err());
+    game.allocate_memory(false);
+    finalize(&mut game);
+    assert_eq!(game.aggression_frequency(0), Ok(0.0));
+    assert_eq!(game.aggression_frequency(1), Ok(0.0));
+    assert!(game.aggression_frequency(2).is_err());
+
+    // OOP bets half of the time and IP can only fold or call
+    let card_config = CardConfig {
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..card_config
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..tree_config
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    let num_hands = game.num_private_hands(0);
+    game.lock_current_strategy(&vec![0.5; 2 * num_hands]);
+    finalize(&mut game);
+
+    assert!((game.aggression_frequency(0).unwrap() - 0.5).abs() < 1e-5);
+    assert_eq!(game.aggression_frequency(1), Ok(0.0));
+}
+
+#[test]
+fn verify_zero_sum() {
+    let build = |rake_rate, payoff_model| {
+        let card_config = CardConfig {
+            range: [Range::ones(); 2],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 970,
+            rake_rate,
+            rake_cap: 30.0,
+            payoff_model,
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        PostFlopGame::with_config(card_config, action_tree).unwrap()
+    };
+
+    let mut game = build(0.0, PayoffModel::ChipEv);
+    assert!(game.verify_zero_sum(1e-3).is_err());
+    game.allocate_memory(false);
+    assert!(game.verify_zero_sum(1e-3).is_ok());
+    solve(&mut game, 10, 0.0, false);
+    assert!(game.verify_zero_sum(1e-3).is_ok());
+
+    // finalized without solving
+    let mut game = build(0.0, PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    finalize(&mut game);
+    assert!(game.verify_zero_sum(1e-3).is_ok());
+
+    let mut game = build(0.05, PayoffModel::ChipEv);
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+    assert!(game.verify_zero_sum(1e-3).is_ok());
+
+    let icm = PayoffModel::Icm {
+        stacks: vec![1000, 1000, 1000],
+        payouts: vec![70.0, 30.0],
+    };
+    let mut game = build(0.0, icm);
+    game.allocate_memory(false);
+    assert!(game.verify_zero_sum(1e-3).is_err());
+}
+
 #[test]
 fn one_raise_all_range_compressed() {
     let card_config = CardConfig {
@@ -344,16 +444,16 @@ fn one_raise_all_range_with_turn() {
 }
 
 #[test]
-fn one_raise_all_range_with_river() {
+fn one_raise_all_range_check_only_flop() {
     let card_config = CardConfig {
-        range: [Range::ones(); 2],
         flop: flop_from_str("Td9d6h").unwrap(),
-        turn: card_from_str("Qc").unwrap(),
-        river: card_from_str("7s").unwrap(),
+        range: [Range::ones(); 2],
+        ..Default::default()
     };
 
+    // empty bet sizes make the flop and the turn check-only
     let tree_config = TreeConfig {
-        initial_state: BoardState::River,
+        initial_state: BoardState::Flop,
         starting_pot: 60,
         effective_stack: 970,
         river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
@@ -366,238 +466,704 @@ fn one_raise_all_range_with_river() {
     game.allocate_memory(false);
     finalize(&mut game);
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 0.5).abs() < 1e-5);
-    assert!((equity_ip - 0.5).abs() < 1e-5);
-    assert!((ev_oop - 37.5).abs() < 1e-4);
-    assert!((ev_ip - 22.5).abs() < 1e-4);
-
+    assert_eq!(game.available_actions(), [Action::Check]);
     game.play(0);
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 0.5).abs() < 1e-5);
-    assert!((equity_ip - 0.5).abs() < 1e-5);
-    assert!((ev_oop - 30.0).abs() < 1e-4);
-    assert!((ev_ip - 30.0).abs() < 1e-4);
-
+    assert_eq!(game.available_actions(), [Action::Check]);
     game.play(0);
-    assert!(game.is_terminal_node());
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 0.5).abs() < 1e-5);
-    assert!((equity_ip - 0.5).abs() < 1e-5);
-    assert!((ev_oop - 30.0).abs() < 1e-4);
-    assert!((ev_ip - 30.0).abs() < 1e-4);
-
+    assert!(game.is_chance_node());
     game.back_to_root();
-    game.play(1);
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 0.5).abs() < 1e-5);
-    assert!((equity_ip - 0.5).abs() < 1e-5);
-    assert!((ev_oop - 75.0).abs() < 1e-4);
-    assert!((ev_ip - 15.0).abs() < 1e-4);
 
-    game.play(0);
-    assert!(game.is_terminal_node());
+    // same as starting from the turn
     game.cache_normalized_weights();
     let weights_oop = game.normalized_weights(0);
     let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!(game.is_terminal_node());
-    assert!((equity_oop - 0.5).abs() < 1e-5);
-    assert!((equity_ip - 0.5).abs() < 1e-5);
-    assert!((ev_oop - 90.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
+    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
+    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_equity_oop - 0.5).abs() < 1e-5);
+    assert!((root_equity_ip - 0.5).abs() < 1e-5);
+    assert!((root_ev_oop - 37.5).abs() < 1e-4);
+    assert!((root_ev_ip - 22.5).abs() < 1e-4);
+
+    let strategy = game.strategy();
+    assert!(strategy.iter().all(|&s| s == 1.0));
 }
 
 #[test]
-fn always_win() {
-    // be careful for straight flushes
-    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+fn solve_with_discount_params() {
     let card_config = CardConfig {
-        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
-        flop: flop_from_str("AcAdKh").unwrap(),
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
         ..Default::default()
     };
 
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "60%")).unwrap();
     let tree_config = TreeConfig {
+        initial_state: BoardState::River,
         starting_pot: 60,
         effective_stack: 970,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let params = DiscountParams {
+        alpha: 1.5,
+        beta: 0.0,
+        gamma: 2.0,
+    };
 
-    game.allocate_memory(false);
-    finalize(&mut game);
+    let mut evs = Vec::new();
+    for params in [DiscountParams::default(), params] {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 1.0).abs() < 1e-5);
-    assert!((equity_ip - 0.0).abs() < 1e-5);
-    assert!((ev_oop - 60.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
+        let exploitability = solve_with_params(&mut game, 1000, 0.06, false, &params);
+        assert!(exploitability <= 0.06);
 
-    game.play(0);
-    game.play(0);
-    assert!(game.is_chance_node());
-    game.play(usize::MAX);
-    game.play(0);
-    game.play(0);
-    assert!(game.is_chance_node());
-    game.play(usize::MAX);
-    game.play(0);
-    game.play(0);
-    assert!(game.is_terminal_node());
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0);
+        evs.push(compute_average(&game.expected_values(0), weights));
+    }
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 1.0).abs() < 1e-5);
-    assert!((equity_ip - 0.0).abs() < 1e-5);
-    assert!((ev_oop - 60.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
+    assert!((evs[0] - evs[1]).abs() < 0.12);
 }
 
 #[test]
-fn always_win_raked() {
-    // be careful for straight flushes
-    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+fn discount_params_affect_convergence() {
     let card_config = CardConfig {
-        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
-        flop: flop_from_str("AcAdKh").unwrap(),
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
         ..Default::default()
     };
 
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "60%")).unwrap();
     let tree_config = TreeConfig {
+        initial_state: BoardState::River,
         starting_pot: 60,
         effective_stack: 970,
-        rake_rate: 0.05,
-        rake_cap: 10.0,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-
-    game.allocate_memory(false);
-    finalize(&mut game);
-
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((ev_oop - 57.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
-
-    game.play(0);
-    game.play(0);
-    assert!(game.is_chance_node());
-    game.play(usize::MAX);
-    game.play(0);
-    game.play(0);
-    assert!(game.is_chance_node());
-    game.play(usize::MAX);
-    game.play(0);
-    game.play(0);
-    assert!(game.is_terminal_node());
+    let solve_params = |alpha, beta, gamma| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        let params = DiscountParams { alpha, beta, gamma };
+        solve_with_params(&mut game, 100, 0.0, false, &params)
+    };
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((ev_oop - 57.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
+    // with the same number of iterations, the default parameters converge faster than
+    // α = β = γ = 1, which in turn converges faster than α = β = γ = 0 (i.e., halving all the
+    // cumulative values in every iteration)
+    let exploitability_default = solve_params(1.5, 0.0, 3.0);
+    let exploitability_ones = solve_params(1.0, 1.0, 1.0);
+    let exploitability_zeros = solve_params(0.0, 0.0, 0.0);
+    assert!(exploitability_default < 0.5 * exploitability_ones);
+    assert!(exploitability_ones < 0.5 * exploitability_zeros);
 }
 
 #[test]
-fn always_lose() {
-    // be careful for straight flushes
-    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+fn solve_with_linear_cfr() {
     let card_config = CardConfig {
-        range: [lose_range_str.parse().unwrap(), "AA".parse().unwrap()],
-        flop: flop_from_str("AcAdKh").unwrap(),
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
         ..Default::default()
     };
 
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "60%")).unwrap();
     let tree_config = TreeConfig {
+        initial_state: BoardState::River,
         starting_pot: 60,
         effective_stack: 970,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let solve_variant = |variant, max_num_iterations| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
 
-    game.allocate_memory(false);
-    finalize(&mut game);
+        let exploitability = solve_with_variant(&mut game, max_num_iterations, 0.0, false, variant);
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
-    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0);
+        let ev = compute_average(&game.expected_values(0), weights);
+        (exploitability, ev)
+    };
 
-    assert!((root_equity_oop - 0.0).abs() < 1e-5);
-    assert!((root_equity_ip - 1.0).abs() < 1e-5);
-    assert!((root_ev_oop - 0.0).abs() < 1e-4);
-    assert!((root_ev_ip - 60.0).abs() < 1e-4);
+    // linear CFR reaches a lower exploitability than vanilla CFR with 5x fewer iterations
+    let (exploitability_linear, ev_linear) = solve_variant(&CfrVariant::Linear, 200);
+    let (exploitability_vanilla, ev_vanilla) = solve_variant(&CfrVariant::Vanilla, 1000);
+    assert!(exploitability_linear < exploitability_vanilla);
+    assert!((ev_linear - ev_vanilla).abs() < 0.12);
 }
 
 #[test]
-fn always_lose_raked() {
-    // be careful for straight flushes
-    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+fn solve_step_matches_solve() {
     let card_config = CardConfig {
-        range: [lose_range_str.parse().unwrap(), "AA".parse().unwrap()],
-        flop: flop_from_str("AcAdKh").unwrap(),
+        range: [
+            "AA,KK,QQ,AK".parse().unwrap(),
+            "JJ-88,KQ,QJ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
         ..Default::default()
     };
 
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "60%")).unwrap();
     let tree_config = TreeConfig {
+        initial_state: BoardState::River,
         starting_pot: 60,
         effective_stack: 970,
-        rake_rate: 0.05,
-        rake_cap: 10.0,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut stepped = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    stepped.allocate_memory(false);
+
+    solve(&mut game, 1000, 0.0, false);
+
+    for t in 0..1000 {
+        solve_step(&stepped, t);
+    }
+    finalize(&mut stepped);
+
+    assert_eq!(game.export_strategy(), stepped.export_strategy());
+
+    game.cache_normalized_weights();
+    stepped.cache_normalized_weights();
+    assert_eq!(game.expected_values(0), stepped.expected_values(0));
+    assert_eq!(game.expected_values(1), stepped.expected_values(1));
+}
+
+#[test]
+fn solve_canceled() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ,AK", "JJ-88,KQ,QJ")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "60%")
+            .river_bets("50%", "60%")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let check = |game: &mut PostFlopGame| {
+        game.back_to_root();
+        game.cache_normalized_weights();
+        let strategy = game.strategy();
+        let num_hands = game.private_cards(0).len();
+        for hand in 0..num_hands {
+            let sum = strategy.iter().skip(hand).step_by(num_hands).sum::<f32>();
+            assert!((sum - 1.0).abs() < 1e-5);
+        }
+        assert!(game.expected_values(0).iter().all(|v| v.is_finite()));
+        assert!(game.expected_values(1).iter().all(|v| v.is_finite()));
+    };
+
+    // canceled before the first iteration
+    let mut game = build();
+    let cancel = AtomicBool::new(true);
+    let (num_iterations, exploitability) = solve_with_cancel(&mut game, 1000, 0.0, false, &cancel);
+    assert_eq!(num_iterations, 0);
+    assert!(exploitability.is_finite());
+    check(&mut game);
+
+    // passed through `SolveParams`: the solving runs to the end while the flag is not set
+    let mut game = build();
+    let cancel = AtomicBool::new(false);
+    let params = SolveParams {
+        max_iterations: 20,
+        cancel: Some(&cancel),
+        ..Default::default()
+    };
+    let (num_iterations, exploitability) = solve_with_config(&mut game, &params);
+    assert_eq!(num_iterations, 20);
+    assert!(exploitability.is_finite());
+    check(&mut game);
+
+    // set before the call through `SolveParams`
+    let mut game = build();
+    cancel.store(true, Ordering::Relaxed);
+    let (num_iterations, _) = solve_with_config(&mut game, &params);
+    assert_eq!(num_iterations, 0);
+    check(&mut game);
+}
+
+#[test]
+fn solve_with_config_eval_interval() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ,AK", "JJ-88,KQ,QJ")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "60%")
+            .river_bets("50%", "60%")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let target = 60.0 * 0.05;
+    let params = SolveParams {
+        max_iterations: 1000,
+        target_exploitability: target,
+        eval_interval: 50,
+        ..Default::default()
+    };
+
+    let mut game = build();
+    let (num_iterations, exploitability) = solve_with_config(&mut game, &params);
+    assert!(num_iterations > 0 && num_iterations < 1000);
+    assert_eq!(num_iterations % 50, 0);
+    assert!(exploitability <= target);
+    assert!(game.is_solved());
+
+    let params = SolveParams {
+        convergence_metric: ConvergenceMetric::RegretBound,
+        ..params
+    };
+
+    let mut game = build();
+    let (num_iterations, exploitability) = solve_with_config(&mut game, &params);
+    assert!(num_iterations > 0 && num_iterations < 1000);
+    assert_eq!(num_iterations % 50, 0);
+    assert!(exploitability.is_finite());
+    assert!((exploitability - compute_exploitability(&game)).abs() < 1e-3);
+}
+
+#[test]
+fn regret_bound() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK,QQ,AK,AQs", "JJ-88,KQ,QJ,T9s")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .turn_bets("50%", "60%")
+        .river_bets("50%, 100%", "60%")
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+
+    let mut bounds = Vec::new();
+    for t in 0..200 {
+        solve_step(&game, t);
+        if (t + 1) % 50 == 0 {
+            bounds.push(compute_regret_bound(&game, t + 1));
+        }
+    }
+
+    // the bound is only an estimate because the regrets are discounted, so only the overall trend
+    // is checked
+    assert!(bounds.iter().all(|&bound| bound.is_finite() && bound > 0.0));
+    assert!(bounds[3] < 0.5 * bounds[0]);
+}
+
+#[test]
+fn solve_with_config_per_player() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ,AK,AQs", "JJ-88,KQ,QJ,T9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "60%")
+            .river_bets("50%, 100%", "60%")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let target = 60.0 * 0.005;
+    let params = SolveParams {
+        max_iterations: 1000,
+        target_exploitability: target,
+        eval_interval: 5,
+        ..Default::default()
+    };
+
+    // the average of the two players hides the worse player
+    let mut game = build();
+    let (num_iterations_avg, _) = solve_with_config(&mut game, &params);
+    let exploitability = compute_exploitability_per_player(&game);
+    assert!(exploitability[0].max(exploitability[1]) > target);
+    assert!(
+        ((exploitability[0] + exploitability[1]) * 0.5 - compute_exploitability(&game)).abs()
+            < 1e-4
+    );
+
+    let params = SolveParams {
+        convergence_metric: ConvergenceMetric::PerPlayerExploitability,
+        ..params
+    };
+
+    let mut game = build();
+    let (num_iterations, _) = solve_with_config(&mut game, &params);
+    let exploitability = compute_exploitability_per_player(&game);
+    assert!(num_iterations > num_iterations_avg);
+    assert!(exploitability[0] <= target && exploitability[1] <= target);
+}
+
+#[test]
+fn solve_with_config_averaging() {
+    let solve = |averaging| {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ,AK,AQs", "JJ-88,KQ,QJ,T9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "60%")
+            .river_bets("50%, 100%", "60%")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+
+        let params = SolveParams {
+            max_iterations: 100,
+            eval_interval: 100,
+            averaging: Some(averaging),
+            ..Default::default()
+        };
+        solve_with_config(&mut game, &params)
+    };
+
+    let (num_iterations, uniform) = solve(AveragingScheme::Uniform);
+    let (_, linear) = solve(AveragingScheme::Linear);
+    let (_, quadratic) = solve(AveragingScheme::Quadratic);
+
+    // weighting the later iterations more heavily converges faster
+    assert_eq!(num_iterations, 100);
+    assert!(linear < uniform);
+    assert!(quadratic < linear);
+}
+
+#[test]
+fn solve_with_config_variant() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ,AK,AQs", "JJ-88,KQ,QJ,T9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .river("2s")
+            .pot(60)
+            .stack(970)
+            .river_bets("50%, 100%", "60%")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game = build();
+    let exploitability = solve_with_variant(&mut game, 50, 0.0, false, &CfrVariant::Linear);
+
+    let params = SolveParams {
+        max_iterations: 50,
+        variant: CfrVariant::Linear,
+        ..Default::default()
+    };
+    let mut game_config = build();
+    let (num_iterations, exploitability_config) = solve_with_config(&mut game_config, &params);
+    assert_eq!(num_iterations, 50);
+    assert_eq!(exploitability, exploitability_config);
+    assert_eq!(game.export_strategy(), game_config.export_strategy());
+}
+
+#[test]
+fn solve_with_config_min_iterations() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK,QQ,AK,AQs", "JJ-88,KQ,QJ,T9s")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .river("2s")
+        .pot(60)
+        .stack(970)
+        .river_bets("50%, 100%", "60%")
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+
+    // the target is met before the first iteration
+    let params = SolveParams {
+        max_iterations: 1000,
+        target_exploitability: f32::INFINITY,
+        min_iterations: 25,
+        eval_interval: 10,
+        ..Default::default()
+    };
+    let (num_iterations, exploitability) = solve_with_config(&mut game, &params);
+    assert_eq!(num_iterations, 25);
+    assert!(exploitability.is_finite());
+    assert!(game.is_solved());
+}
+
+#[test]
+fn compress_storage() {
+    let card_config = CardConfig {
+        range: [
+            "AA-99,AK,AQ".parse().unwrap(),
+            "TT-66,KQ,QJ,JT".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let ev = compute_current_ev(&game);
+    game.cache_normalized_weights();
+    let strategy = game.strategy();
+    let expected_values = game.expected_values(1);
+
+    game.play(0);
+    game.play(0);
+    game.play(usize::MAX);
+    game.cache_normalized_weights();
+    let expected_values_river = game.expected_values(0);
+    game.back_to_root();
+
+    game.compress_storage();
+    assert_eq!(game.is_memory_allocated(), Some(true));
+
+    // the quantization error is bounded by the step of the fixed-point representation
+    let ev_compressed = compute_current_ev(&game);
+    assert!((ev[0] - ev_compressed[0]).abs() < 1e-2);
+    assert!((ev[1] - ev_compressed[1]).abs() < 1e-2);
+
+    game.cache_normalized_weights();
+    let max_diff = |a: &[f32], b: &[f32]| {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0f32, f32::max)
+    };
+    assert!(max_diff(&strategy, &game.strategy()) < 1e-4);
+    assert!(max_diff(&expected_values, &game.expected_values(1)) < 0.1);
+
+    game.play(0);
+    game.play(0);
+    game.play(usize::MAX);
+    game.cache_normalized_weights();
+    assert!(max_diff(&expected_values_river, &game.expected_values(0)) < 0.1);
+}
+
+#[test]
+fn solve_with_pruning_converges() {
+    let card_config = CardConfig {
+        range: [
+            "AA-99,AK,AQ".parse().unwrap(),
+            "TT-66,KQ,QJ,JT".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "60%")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let mut evs = Vec::new();
+    for prune_threshold in [0.0, 1e-3] {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+
+        let exploitability = solve_with_pruning(&mut game, 1000, 0.15, false, prune_threshold);
+        assert!(exploitability <= 0.15);
+
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0);
+        evs.push(compute_average(&game.expected_values(0), weights));
+    }
+
+    assert!((evs[0] - evs[1]).abs() < 0.1);
+}
+
+#[test]
+fn solve_with_pruning_exploitability() {
+    let card_config = CardConfig {
+        range: [
+            "AA-99,AK,AQ".parse().unwrap(),
+            "TT-66,KQ,QJ,JT".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "60%")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let solve_pruning = |prune_threshold| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        solve_with_pruning(&mut game, 300, 0.0, false, prune_threshold)
+    };
+
+    // a small threshold does not affect the convergence at a fixed number of iterations
+    let exploitability = solve_pruning(0.0);
+    let exploitability_pruned = solve_pruning(1e-3);
+    assert!(exploitability < 60.0 * 0.005);
+    assert!((exploitability - exploitability_pruned).abs() < 60.0 * 0.001);
+}
+
+#[test]
+fn solve_with_history_decreasing() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "60%")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    let history = solve_with_history(&mut game, 95, 0.0, false);
+    let iterations = history.iter().map(|h| h.0).collect::<Vec<_>>();
+    assert_eq!(iterations, [0, 10, 20, 30, 40, 50, 60, 70, 80, 90, 95]);
+
+    // roughly decreasing (the cumulative strategy is reset at iteration 64)
+    let mut min_exploitability = f32::MAX;
+    for &(_, exploitability) in &history {
+        assert!(exploitability < 2.0 * min_exploitability);
+        min_exploitability = min_exploitability.min(exploitability);
+    }
+    assert!(history.last().unwrap().1 < 0.01 * history[0].1);
+    assert_eq!(history.last().unwrap().1, compute_exploitability(&game));
+}
+
+#[test]
+fn solve_mccfr_converges() {
+    let card_config = CardConfig {
+        range: [
+            "AA-99,AK,AQ".parse().unwrap(),
+            "TT-66,KQ,QJ,JT".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let mut evs = Vec::new();
+    for mccfr in [false, true] {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+
+        // MCCFR converges much more slowly, so we use a looser tolerance
+        if mccfr {
+            let exploitability = solve_mccfr(&mut game, 10000, 0.3, false, 42);
+            assert!(exploitability <= 60.0 * 0.05);
+        } else {
+            let exploitability = solve(&mut game, 1000, 0.3, false);
+            assert!(exploitability <= 0.3);
+        }
+
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0);
+        evs.push(compute_average(&game.expected_values(0), weights));
+    }
+
+    assert!((evs[0] - evs[1]).abs() < 60.0 * 0.01);
+}
+
+#[test]
+fn one_raise_all_range_with_river() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
         ..Default::default()
     };
 
@@ -610,18 +1176,4306 @@ fn always_lose_raked() {
     game.cache_normalized_weights();
     let weights_oop = game.normalized_weights(0);
     let weights_ip = game.normalized_weights(1);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 0.5).abs() < 1e-5);
+    assert!((equity_ip - 0.5).abs() < 1e-5);
+    assert!((ev_oop - 37.5).abs() < 1e-4);
+    assert!((ev_ip - 22.5).abs() < 1e-4);
+
+    game.play(0);
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 0.5).abs() < 1e-5);
+    assert!((equity_ip - 0.5).abs() < 1e-5);
+    assert!((ev_oop - 30.0).abs() < 1e-4);
+    assert!((ev_ip - 30.0).abs() < 1e-4);
+
+    game.play(0);
+    assert!(game.is_terminal_node());
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 0.5).abs() < 1e-5);
+    assert!((equity_ip - 0.5).abs() < 1e-5);
+    assert!((ev_oop - 30.0).abs() < 1e-4);
+    assert!((ev_ip - 30.0).abs() < 1e-4);
+
+    game.back_to_root();
+    game.play(1);
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 0.5).abs() < 1e-5);
+    assert!((equity_ip - 0.5).abs() < 1e-5);
+    assert!((ev_oop - 75.0).abs() < 1e-4);
+    assert!((ev_ip - 15.0).abs() < 1e-4);
+
+    game.play(0);
+    assert!(game.is_terminal_node());
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!(game.is_terminal_node());
+    assert!((equity_oop - 0.5).abs() < 1e-5);
+    assert!((equity_ip - 0.5).abs() < 1e-5);
+    assert!((ev_oop - 90.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+}
+
+#[test]
+fn always_win() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 1.0).abs() < 1e-5);
+    assert!((equity_ip - 0.0).abs() < 1e-5);
+    assert!((ev_oop - 60.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    game.play(usize::MAX);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    game.play(usize::MAX);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_terminal_node());
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 1.0).abs() < 1e-5);
+    assert!((equity_ip - 0.0).abs() < 1e-5);
+    assert!((ev_oop - 60.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+}
+
+#[test]
+fn always_win_raked() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        rake_rate: 0.05,
+        rake_cap: 10.0,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((ev_oop - 57.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    game.play(usize::MAX);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    game.play(usize::MAX);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_terminal_node());
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((ev_oop - 57.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+}
+
+#[test]
+fn always_win_no_rake_check_down() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        turn: card_from_str("3d").unwrap(),
+        river: card_from_str("3h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        rake_rate: 0.05,
+        rake_cap: 10.0,
+        no_rake_check_down: true,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    // checked-down pot is not raked
+    game.play(0);
+    game.play(0);
+    assert!(game.is_terminal_node());
+    game.cache_normalized_weights();
+    let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+    assert!((ev_oop - 60.0).abs() < 1e-4);
+
+    // bet pot is raked
+    game.back_to_root();
+    game.play(1);
+    game.play(1);
+    assert!(game.is_terminal_node());
+    game.cache_normalized_weights();
+    let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+    assert!((ev_oop - 114.0).abs() < 1e-4);
+}
+
+#[test]
+fn always_win_no_rake_fold() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        turn: card_from_str("3d").unwrap(),
+        river: card_from_str("3h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        rake_rate: 0.05,
+        rake_cap: 10.0,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    for (no_rake_fold, expected_fold_ev) in [(false, 87.0), (true, 90.0)] {
+        let action_tree = ActionTree::new(TreeConfig {
+            no_rake_fold,
+            ..tree_config.clone()
+        })
+        .unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+
+        game.allocate_memory(false);
+        finalize(&mut game);
+
+        // bet-fold pot
+        game.play(1);
+        game.play(0);
+        assert!(game.is_terminal_node());
+        game.cache_normalized_weights();
+        let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+        assert!((ev_oop - expected_fold_ev).abs() < 1e-4);
+
+        // showdown pot is raked regardless of the flag
+        game.back_to_root();
+        game.play(1);
+        game.play(1);
+        assert!(game.is_terminal_node());
+        game.cache_normalized_weights();
+        let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+        assert!((ev_oop - 114.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn initial_invested() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        turn: card_from_str("3d").unwrap(),
+        river: card_from_str("3h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 80,
+        initial_invested: [60, 20],
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // OOP always bets, and IP always folds
+    let num_hands_oop = game.num_private_hands(0);
+    let num_hands_ip = game.num_private_hands(1);
+    let mut strategy_oop = vec![0.0; 2 * num_hands_oop];
+    strategy_oop[num_hands_oop..].fill(1.0);
+    game.lock_current_strategy(&strategy_oop);
+    game.play(1);
+    let mut strategy_ip = vec![0.0; 2 * num_hands_ip];
+    strategy_ip[..num_hands_ip].fill(1.0);
+    game.lock_current_strategy(&strategy_ip);
+    game.back_to_root();
+
+    finalize(&mut game);
+
+    // IP forfeits the invested 20 chips
+    let ev = compute_current_ev(&game);
+    assert!((ev[0] - 20.0).abs() < 1e-4);
+    assert!((ev[1] + 20.0).abs() < 1e-4);
+
+    // OOP still collects the whole pot
+    game.cache_normalized_weights();
+    let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+    assert!((ev_oop - 80.0).abs() < 1e-4);
+
+    let invalid_config = TreeConfig {
+        initial_invested: [60, 30],
+        ..tree_config
+    };
+    assert!(ActionTree::new(invalid_config).is_err());
+}
+
+#[test]
+fn ip_first() {
+    let build = |ip_first| {
+        let range = "AA,KK,QQ,JJ,TT,AK,AQs,KQs,JTs";
+        let mut game = GameBuilder::new()
+            .ranges(range, range)
+            .flop("9d6h2c")
+            .turn("3s")
+            .pot(60)
+            .stack(970)
+            .ip_first(ip_first)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game = build(false);
+    let mut game_ip_first = build(true);
+    assert_eq!(game.current_player(), 0);
+    assert_eq!(game_ip_first.current_player(), 1);
+
+    // IP checks and OOP acts next, and then the river starts with IP
+    game_ip_first.play(0);
+    assert_eq!(game_ip_first.current_player(), 0);
+    game_ip_first.play(0);
+    assert!(game_ip_first.is_chance_node());
+    game_ip_first.play(card_from_str("4c").unwrap() as usize);
+    assert_eq!(game_ip_first.current_player(), 1);
+    game_ip_first.back_to_root();
+
+    solve(&mut game, 1000, 0.05, false);
+    solve(&mut game_ip_first, 1000, 0.05, false);
+
+    // the ranges are symmetric, so the EVs are swapped
+    let ev = compute_current_ev(&game);
+    let ev_ip_first = compute_current_ev(&game_ip_first);
+    assert!(ev[0] < ev[1]);
+    assert!((ev[0] - ev_ip_first[1]).abs() < 0.2);
+    assert!((ev[1] - ev_ip_first[0]).abs() < 0.2);
+
+    // the stored expected values are consistent with the current EVs
+    for (player, &ev_ip_first) in ev_ip_first.iter().enumerate() {
+        game_ip_first.cache_normalized_weights();
+        let weights = game_ip_first.normalized_weights(player);
+        let ev_stored = compute_average(&game_ip_first.expected_values(player), weights);
+        assert!((ev_stored - 30.0 - ev_ip_first).abs() < 1e-2);
+    }
+}
+
+#[test]
+fn icm_heads_up_parity() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AKs".parse().unwrap(),
+            "QQ-TT,AQs+".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 100,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let solve_ev = |payoff_model| {
+        let tree_config = TreeConfig {
+            payoff_model,
+            ..tree_config.clone()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.0, false);
+        game.cache_normalized_weights();
+        [0, 1].map(|player| {
+            compute_average(
+                &game.expected_values(player),
+                game.normalized_weights(player),
+            )
+        })
+    };
+
+    // heads-up for the win: ICM equity is proportional to the chips
+    let ev_chip = solve_ev(PayoffModel::ChipEv);
+    let ev_icm = solve_ev(PayoffModel::Icm {
+        stacks: vec![100, 300],
+        payouts: vec![1.0],
+    });
+
+    for player in 0..2 {
+        assert!((ev_chip[player] - ev_icm[player]).abs() < 1e-2);
+    }
+
+    let invalid_config = TreeConfig {
+        payoff_model: PayoffModel::Icm {
+            stacks: vec![50, 300],
+            payouts: vec![1.0],
+        },
+        ..tree_config
+    };
+    assert!(ActionTree::new(invalid_config).is_err());
+}
+
+#[test]
+fn expected_values_detail_per_action() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        turn: card_from_str("3d").unwrap(),
+        river: card_from_str("3h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // IP always calls the bet
+    game.play(1);
+    let num_hands = game.num_private_hands(1);
+    let mut locking = vec![0.0; 2 * num_hands];
+    locking[num_hands..].fill(1.0);
+    game.lock_current_strategy(&locking);
+    game.back_to_root();
+
+    finalize(&mut game);
+    game.cache_normalized_weights();
+
+    // OOP: [Check, Bet(30)]
+    let num_hands = game.num_private_hands(0);
+    let ev_detail = game.expected_values_detail(0);
+    assert_eq!(ev_detail.len(), 2 * num_hands);
+
+    let weights = game.normalized_weights(0);
+    let ev_check = compute_average(&ev_detail[..num_hands], weights);
+    let ev_bet = compute_average(&ev_detail[num_hands..], weights);
+    assert!((ev_check - 60.0).abs() < 1e-4);
+    assert!((ev_bet - 90.0).abs() < 1e-4);
+}
+
+#[test]
+fn facing_bet_evs() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        turn: card_from_str("3d").unwrap(),
+        river: card_from_str("3h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.facing_bet_evs(&[Action::Bet(30)], 0).is_err());
+
+    // IP always calls the bet
+    game.play(1);
+    let num_hands = game.num_private_hands(1);
+    let mut locking = vec![0.0; 2 * num_hands];
+    locking[num_hands..].fill(1.0);
+    game.lock_current_strategy(&locking);
+    game.back_to_root();
+
+    finalize(&mut game);
+    game.play(0);
+
+    // IP has invested 30 chips and always loses: folding loses 30 chips, calling loses 60 chips
+    let hand = game
+        .hand_index(
+            1,
+            card_from_str("2c").unwrap(),
+            card_from_str("2s").unwrap(),
+        )
+        .unwrap();
+    let evs = game.facing_bet_evs(&[Action::Bet(30)], hand).unwrap();
+    assert_eq!(evs.len(), 2);
+    assert_eq!(evs[0].0, Action::Fold);
+    assert_eq!(evs[1].0, Action::Call);
+    assert!((evs[0].1 + 30.0).abs() < 1e-4);
+    assert!((evs[1].1 + 60.0).abs() < 1e-4);
+
+    // the current node is restored
+    assert_eq!(game.history(), &[0]);
+
+    // not facing a bet
+    assert!(game.facing_bet_evs(&[], 0).is_err());
+    assert!(game.facing_bet_evs(&[Action::Check], hand).is_err());
+    assert!(game.facing_bet_evs(&[Action::Bet(40)], hand).is_err());
+    assert!(game.facing_bet_evs(&[Action::Bet(30)], num_hands).is_err());
+}
+
+#[test]
+fn indifference_check() {
+    let mut game = GameBuilder::new()
+        .ranges("QQ+,AK,87,J8", "TT-66,AT,KT,QT")
+        .flop("Td9d6h")
+        .turn("3c")
+        .river("2s")
+        .pot(100)
+        .stack(100)
+        .river_bets("100%", "")
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+
+    let line = [Action::AllIn(100)];
+    assert_eq!(game.indifference_check(&line, 1, 0), None);
+
+    solve(&mut game, 1000, 100.0 * 0.0001, false);
+    game.play(1);
+    let strategy = game.strategy();
+    game.back_to_root();
+
+    // the bluff-catchers mixing calls and folds are indifferent between the two actions
+    let num_hands = game.num_private_hands(1);
+    let mut num_mixed = 0;
+    for hand in 0..num_hands {
+        let call_frequency = strategy[num_hands + hand];
+        if 0.01 < call_frequency && call_frequency < 0.99 {
+            let (call_ev, fold_ev) = game.indifference_check(&line, 1, hand).unwrap();
+            assert!((call_ev - fold_ev).abs() < 0.5);
+            assert!((fold_ev + 50.0).abs() < 1e-4);
+            num_mixed += 1;
+        }
+    }
+    assert!(num_mixed > 0);
+    assert!(game.history().is_empty());
+
+    // OOP does not face a bet
+    assert_eq!(game.indifference_check(&line, 0, 0), None);
+    assert_eq!(game.indifference_check(&[], 0, 0), None);
+    assert_eq!(game.indifference_check(&line, 1, num_hands), None);
+}
+
+#[test]
+fn node_to_csv() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK,QQ,AK", "JJ-88,KQ,QJ")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .turn_bets("50%", "")
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+    assert!(game.node_to_csv(&[], 0).is_err());
+
+    solve(&mut game, 100, 0.0, false);
+
+    // OOP is to act at the root: [Check, Bet 30]
+    let csv = game.node_to_csv(&[], 0).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("hand,weight,ev,equity,Check,Bet 30"));
+    assert_eq!(lines.clone().count(), game.num_private_hands(0));
+    assert!(lines.all(|line| line.split(',').count() == 6));
+
+    // the hands overlapping with the river are skipped
+    let river = card_from_str("Kd").unwrap();
+    let line = [Action::Check, Action::Check, Action::Chance(river)];
+    let csv = game.node_to_csv(&line, 1).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("hand,weight,ev,equity"));
+    assert_eq!(lines.clone().count(), game.live_combos(1, &[river]));
+    assert!(lines.clone().count() < game.num_private_hands(1));
+    assert!(lines.all(|line| !line.split(',').next().unwrap().contains("Kd")));
+
+    // the current node is restored
+    assert!(game.history().is_empty());
+    assert!(game.node_to_csv(&line, 2).is_err());
+    assert!(game.node_to_csv(&[Action::Bet(40)], 0).is_err());
+}
+
+#[test]
+fn mdf_report() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("100%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.1, false);
+
+    // KK is indifferent and must call exactly at the MDF against a polarized range
+    let report = game.mdf_report(&[Action::Bet(100)], 1).unwrap();
+    assert!((report.required_defense - 0.5).abs() < 1e-6);
+    assert!((report.actual_defense - 0.5).abs() < 0.05);
+
+    assert!(game.mdf_report(&[Action::Bet(100)], 0).is_err());
+    assert!(game.mdf_report(&[Action::Check], 1).is_err());
+    assert!(game.mdf_report(&[Action::Bet(50)], 1).is_err());
+}
+
+#[test]
+fn continue_frequency() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK,QQ,44", "KK-JJ,AQs")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("100%", "a")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.1, false);
+
+    let line = [Action::Bet(100)];
+    let frequency = game.continue_frequency(&line, 1).unwrap();
+    assert!(0.0 < frequency && frequency < 1.0);
+
+    let report = game.mdf_report(&line, 1).unwrap();
+    assert!((frequency - report.actual_defense).abs() < 1e-6);
+    assert!(game.history().is_empty());
+
+    assert!(game.continue_frequency(&line, 0).is_none());
+    assert!(game.continue_frequency(&[Action::Check], 1).is_none());
+}
+
+#[test]
+fn avg_bet_size() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("50%, 100%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+
+    // OOP mixes the 50% and 100% bets equally
+    let num_hands = game.num_private_hands(0);
+    let strategy = [
+        vec![0.5; num_hands],
+        vec![0.25; num_hands],
+        vec![0.25; num_hands],
+    ];
+    game.lock_current_strategy(&strategy.concat());
+
+    // IP never bets after the check
+    game.play(0);
+    let num_hands = game.num_private_hands(1);
+    let strategy = [
+        vec![1.0; num_hands],
+        vec![0.0; num_hands],
+        vec![0.0; num_hands],
+    ];
+    game.lock_current_strategy(&strategy.concat());
+    game.back_to_root();
+
+    solve(&mut game, 100, 0.0, false);
+
+    let avg = game.avg_bet_size(&[], 0).unwrap().unwrap();
+    assert!((avg - 0.75).abs() < 1e-5);
+    assert_eq!(game.avg_bet_size(&[Action::Check], 1), Ok(None));
+
+    assert!(game.avg_bet_size(&[], 1).is_err());
+    assert!(game.avg_bet_size(&[Action::Bet(50)], 1).is_err());
+}
+
+#[test]
+fn resolve_subtree() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,JTs")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(100)
+        .stack(1000)
+        .turn_bets("50%", "")
+        .river_bets("50%, 100%", "3x")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    for t in 0..3 {
+        solve_step(&game, t);
+    }
+
+    let subtree = [Action::Check];
+    let subtree_gap = |game: &PostFlopGame| {
+        game.local_exploitability()
+            .iter()
+            .filter(|(line, _)| line.starts_with(&subtree))
+            .map(|&(_, gap)| gap)
+            .sum::<f32>()
+    };
+
+    let root_strategy = game.normalized_strategy_at(&[], 0).unwrap();
+    let gap_before = subtree_gap(&game);
+
+    game.resolve_subtree(&subtree, 300).unwrap();
+
+    assert_eq!(game.normalized_strategy_at(&[], 0).unwrap(), root_strategy);
+    assert!(subtree_gap(&game) < gap_before * 0.5);
+
+    // the result does not depend on the previous solve of the subtree
+    let resolved_strategy = game.normalized_strategy_at(&subtree, 1).unwrap();
+    let resolved_gap = subtree_gap(&game);
+    game.resolve_subtree(&subtree, 300).unwrap();
+    assert_eq!(
+        game.normalized_strategy_at(&subtree, 1).unwrap(),
+        resolved_strategy
+    );
+    assert_eq!(subtree_gap(&game), resolved_gap);
+
+    finalize(&mut game);
+    assert!(game.resolve_subtree(&subtree, 10).is_err());
+}
+
+#[test]
+fn normalized_strategy_at() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ,T9s", "KK,JJ,AKs")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("50%, 100%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+
+    // uniform before solving
+    let num_hands = game.num_private_hands(0);
+    let strategy = game.normalized_strategy_at(&[], 0).unwrap();
+    assert_eq!(strategy.len(), 3 * num_hands);
+    assert!(strategy.iter().all(|&x| (x - 1.0 / 3.0).abs() < 1e-6));
+
+    solve(&mut game, 100, 0.0, false);
+
+    for (line, player) in [(vec![], 0), (vec![Action::Bet(50)], 1)] {
+        let strategy = game.normalized_strategy_at(&line, player).unwrap();
+        let num_hands = game.num_private_hands(player);
+        for hand in 0..num_hands {
+            let sum = strategy.iter().skip(hand).step_by(num_hands).sum::<f32>();
+            assert!((sum - 1.0).abs() < 1e-5);
+        }
+    }
+
+    // the current node is not changed
+    assert_eq!(game.current_player(), 0);
+    assert!(game.normalized_strategy_at(&[], 1).is_err());
+    assert!(game.normalized_strategy_at(&[Action::Call], 0).is_err());
+}
+
+#[test]
+fn pure_strategy_at() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .build()
+        .unwrap();
+
+    // check-only tree
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+    let num_hands = game.num_private_hands(0);
+    let pure = game.pure_strategy_at(&[], 0).unwrap();
+    assert_eq!(pure, vec![Action::Check; num_hands]);
+
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+
+    // AA bets more often than it checks, QQ mixes equally
+    let is_aces = game
+        .private_cards(0)
+        .iter()
+        .map(|&(c1, _)| c1 / 4 == 12)
+        .collect::<Vec<_>>();
+    let bet = is_aces
+        .iter()
+        .map(|&aces| if aces { 0.75 } else { 0.5 })
+        .collect::<Vec<_>>();
+    let check = bet.iter().map(|&b| 1.0 - b).collect::<Vec<_>>();
+    game.lock_current_strategy(&[check, bet].concat());
+    solve(&mut game, 10, 0.0, false);
+
+    let pure = game.pure_strategy_at(&[], 0).unwrap();
+    for (&aces, &action) in is_aces.iter().zip(pure.iter()) {
+        let expected = if aces { Action::Bet(50) } else { Action::Check };
+        assert_eq!(action, expected);
+    }
+
+    assert!(game.pure_strategy_at(&[], 1).is_err());
+    assert!(game
+        .pure_strategy_at(&[Action::Check, Action::Check], 0)
+        .is_err());
+}
+
+#[test]
+fn full_strategy_at() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK,2c2d")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("50%", "50%")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    let line = [Action::Check];
+    let strategy = game.normalized_strategy_at(&line, 1).unwrap();
+    let full_strategy = game.full_strategy_at(&line, 1).unwrap();
+    let num_hands = game.num_private_hands(1);
+    let num_actions = strategy.len() / num_hands;
+    assert_eq!(full_strategy.len(), num_actions * 1326);
+
+    let mut in_range = vec![false; 1326];
+    for (hand, &(c1, c2)) in game.private_cards(1).iter().enumerate() {
+        let index = card_pair_to_index(c1, c2);
+        let overlaps = [c1, c2].contains(&card_from_str("2c").unwrap());
+        in_range[index] = !overlaps;
+        for action in 0..num_actions {
+            let expected = if overlaps {
+                0.0
+            } else {
+                strategy[action * num_hands + hand]
+            };
+            assert_eq!(full_strategy[action * 1326 + index], expected);
+        }
+    }
+
+    // combinations out of the range (or overlapping with the board) are zero
+    for action in 0..num_actions {
+        for index in (0..1326).filter(|&index| !in_range[index]) {
+            assert_eq!(full_strategy[action * 1326 + index], 0.0);
+        }
+    }
+
+    assert!(game.full_strategy_at(&line, 0).is_err());
+}
+
+#[test]
+fn combos_taking() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK,2c2d")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+
+    // check-only tree: every live combo checks
+    let turn = card_from_str("2c").unwrap();
+    let live = (0..game.num_private_hands(1))
+        .filter(|&i| {
+            let (c1, c2) = game.private_cards(1)[i];
+            c1 != turn && c2 != turn
+        })
+        .collect::<Vec<_>>();
+    let combos = game.combos_taking(&[], 0, Action::Check, 0.99).unwrap();
+    assert_eq!(combos, (0..game.num_private_hands(0)).collect::<Vec<_>>());
+    let line = [Action::Check];
+    let combos = game.combos_taking(&line, 1, Action::Check, 0.99).unwrap();
+    assert_eq!(combos, live);
+    assert_eq!(live.len(), 6);
+    assert!(game.history().is_empty());
+
+    assert!(game.combos_taking(&[], 0, Action::Bet(50), 0.0).is_err());
+    assert!(game.combos_taking(&[], 1, Action::Check, 0.0).is_err());
+}
+
+#[test]
+fn node_view() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ,T9s", "KK,JJ,AKs")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    assert!(game.node_view(&[], 0).is_err());
+
+    solve(&mut game, 100, 0.0, false);
+
+    for (line, player) in [
+        (vec![], 0),
+        (vec![Action::Check], 1),
+        (vec![Action::Bet(50)], 1),
+    ] {
+        let view = game.node_view(&line, player).unwrap();
+        let num_hands = game.num_private_hands(player);
+        assert_eq!(view.hands.len(), num_hands);
+        assert_eq!(view.action_labels.len(), view.actions.len());
+        assert_eq!(view.strategy.len(), view.actions.len() * num_hands);
+        assert_eq!(view.ev_per_hand.len(), num_hands);
+        assert_eq!(view.equity.len(), num_hands);
+        assert_eq!(view.normalized_weights.len(), num_hands);
+
+        // the values are the same as the individual methods at the node
+        for action in &line {
+            let actions = game.available_actions();
+            game.play(actions.iter().position(|a| a == action).unwrap());
+        }
+        game.cache_normalized_weights();
+        assert_eq!(view.actions, game.available_actions());
+        assert_eq!(view.strategy, game.strategy());
+        assert_eq!(view.ev_per_hand, game.expected_values(player));
+        game.back_to_root();
+    }
+
+    let view = game.node_view(&[Action::Bet(50)], 1).unwrap();
+    assert_eq!(view.action_labels, ["Fold", "Call"]);
+    assert!(game.node_view(&[], 1).is_err());
+}
+
+#[test]
+fn reach_at() {
+    let card_config = CardConfig {
+        range: [
+            "AA,QQ,T9s,87s".parse().unwrap(),
+            "KK,JJ,AKs,KQo".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.reach_at(&[], 0).is_err());
+
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+    game.play(0);
+
+    // the reach probabilities at the root are the initial weights
+    let sum = |v: &[f32]| v.iter().sum::<f32>();
+    let root = [
+        game.reach_at(&[], 0).unwrap(),
+        game.reach_at(&[], 1).unwrap(),
+    ];
+    assert_eq!(root[0], game.initial_weights(0));
+    assert_eq!(root[1], game.initial_weights(1));
+
+    // only the acting player's reach probabilities change
+    let check = [
+        game.reach_at(&[Action::Check], 0).unwrap(),
+        game.reach_at(&[Action::Check], 1).unwrap(),
+    ];
+    assert!(sum(&check[0]) < sum(&root[0]));
+    assert_eq!(check[1], root[1]);
+
+    let line = [Action::Check, Action::Bet(30)];
+    let bet = [
+        game.reach_at(&line, 0).unwrap(),
+        game.reach_at(&line, 1).unwrap(),
+    ];
+    assert_eq!(bet[0], check[0]);
+    assert!(sum(&bet[1]) < sum(&check[1]));
+
+    // the hands overlapping with the river card cannot reach
+    let river = card_from_str("As").unwrap();
+    let line = [Action::Check, Action::Check, Action::Chance(river)];
+    let reach = game.reach_at(&line, 0).unwrap();
+    for (&(c1, c2), &r) in game.private_cards(0).iter().zip(&reach) {
+        if c1 == river || c2 == river {
+            assert_eq!(r, 0.0);
+        }
+    }
+
+    // the current node is restored
+    assert_eq!(game.history(), &[0]);
+    assert!(game.reach_at(&[Action::Bet(1)], 0).is_err());
+}
+
+#[test]
+fn compute_ev_at() {
+    let card_config = CardConfig {
+        range: [
+            "AA,QQ,T9s,87s".parse().unwrap(),
+            "KK,JJ,AKs,KQo".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.compute_ev_at(&[], 0).is_err());
+
+    solve(&mut game, 50, 0.0, false);
+
+    // the value at the root is the same as `compute_current_ev`
+    let current_ev = compute_current_ev(&game);
+    for (player, &current_ev) in current_ev.iter().enumerate() {
+        let ev = game.compute_ev_at(&[], player).unwrap();
+        assert!((ev - current_ev).abs() < 1e-3);
+    }
+
+    // the value at a node is the reach-weighted average of the values at its children
+    let root_actions = game.available_actions();
+    let reached_action = (0..root_actions.len())
+        .find(|&i| {
+            game.apply_history(&[i]);
+            game.cache_normalized_weights();
+            game.normalized_weights(0).iter().any(|&w| w > 0.0)
+        })
+        .unwrap();
+
+    for line in [vec![], vec![root_actions[reached_action]]] {
+        game.apply_history(&[]);
+        for &action in &line {
+            let index = game.available_actions().iter().position(|&a| a == action);
+            game.play(index.unwrap());
+        }
+
+        let history = game.history().to_vec();
+        game.cache_normalized_weights();
+        let node_weight = game.normalized_weights(0).iter().sum::<f32>();
+
+        for player in 0..2 {
+            let mut sum = 0.0;
+            for (i, action) in game.available_actions().into_iter().enumerate() {
+                game.play(i);
+                game.cache_normalized_weights();
+                let child_weight = game.normalized_weights(0).iter().sum::<f32>();
+                let child_line = [line.clone(), vec![action]].concat();
+                if child_weight > 0.0 {
+                    let ev = game.compute_ev_at(&child_line, player).unwrap();
+                    sum += ev * child_weight / node_weight;
+                } else {
+                    assert!(game.compute_ev_at(&child_line, player).is_err());
+                }
+                game.apply_history(&history);
+            }
+
+            let ev = game.compute_ev_at(&line, player).unwrap();
+            assert!((ev - sum).abs() < 1e-3);
+        }
+    }
+
+    assert!(game.compute_ev_at(&[], 2).is_err());
+    assert!(game.compute_ev_at(&[Action::Bet(1)], 0).is_err());
+}
+
+#[test]
+fn short_stack_allin() {
+    // the effective stack is 200 chips, so OOP can win at most the pot plus 200 chips from IP
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        turn: card_from_str("3d").unwrap(),
+        river: card_from_str("3h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 200,
+        river_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert_eq!(
+        game.available_actions(),
+        [Action::Check, Action::AllIn(200)]
+    );
+
+    // IP always calls the all-in
+    game.play(1);
+    let num_hands = game.num_private_hands(1);
+    let mut locking = vec![0.0; 2 * num_hands];
+    locking[num_hands..].fill(1.0);
+    game.lock_current_strategy(&locking);
+    game.back_to_root();
+
+    finalize(&mut game);
+    game.cache_normalized_weights();
+
+    // IP loses only the matched 200 chips
+    let num_hands = game.num_private_hands(0);
+    let ev_detail = game.expected_values_detail(0);
+    let ev_allin = compute_average(&ev_detail[num_hands..], game.normalized_weights(0));
+    assert!((ev_allin - 260.0).abs() < 1e-4);
+
+    game.play(1);
+    game.play(1);
+    assert_eq!(game.total_bet_amount(), [200, 200]);
+}
+
+#[test]
+fn always_lose() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: [lose_range_str.parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
+    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_equity_oop - 0.0).abs() < 1e-5);
+    assert!((root_equity_ip - 1.0).abs() < 1e-5);
+    assert!((root_ev_oop - 0.0).abs() < 1e-4);
+    assert!((root_ev_ip - 60.0).abs() < 1e-4);
+}
+
+#[test]
+fn always_lose_raked() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: [lose_range_str.parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        rake_rate: 0.05,
+        rake_cap: 10.0,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_ev_oop - 0.0).abs() < 1e-4);
+    assert!((root_ev_ip - 57.0).abs() < 1e-4);
+}
+
+#[test]
+fn always_tie() {
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
+    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_equity_oop - 0.5).abs() < 1e-5);
+    assert!((root_equity_ip - 0.5).abs() < 1e-5);
+    assert!((root_ev_oop - 30.0).abs() < 1e-4);
+    assert!((root_ev_ip - 30.0).abs() < 1e-4);
+}
+
+#[test]
+fn always_tie_raked() {
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        rake_rate: 0.05,
+        rake_cap: 10.0,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_ev_oop - 28.5).abs() < 1e-4);
+    assert!((root_ev_ip - 28.5).abs() < 1e-4);
+}
+
+#[test]
+fn no_assignment() {
+    let card_config = CardConfig {
+        range: ["TT".parse().unwrap(), "TT".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree);
+    assert!(game.is_err());
+}
+
+#[test]
+fn remove_lines() {
+    use crate::bet_size::BetSizeCandidates;
+    let card_config = CardConfig {
+        range: ["TT+,AKo,AQs+".parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        ..Default::default()
+    };
+
+    // Simple tree: force checks on flop, and only use 1/2 pot bets on turn and river
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [
+            BetSizeCandidates::try_from(("50%", "")).unwrap(),
+            Default::default(),
+        ],
+        river_bet_sizes: [
+            BetSizeCandidates::try_from(("50%", "")).unwrap(),
+            Default::default(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let lines = vec![
+        vec![
+            Action::Check,
+            Action::Check,
+            Action::Chance(2),
+            Action::Check,
+        ],
+        vec![
+            Action::Check,
+            Action::Check,
+            Action::Chance(2),
+            Action::Bet(30),
+            Action::Call,
+            Action::Chance(3),
+            Action::Bet(60),
+        ],
+    ];
+
+    let res = game.remove_lines(&lines);
+    assert!(res.is_ok());
+
+    game.allocate_memory(false);
+
+    // Check that the turn line is removed
+    game.apply_history(&[0, 0, 2]);
+    assert_eq!(game.available_actions(), vec![Action::Bet(30)]);
+
+    // Check that other turn lines are correct
+    game.apply_history(&[0, 0, 3]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(30)]
+    );
+
+    // Check that the river line is removed
+    game.apply_history(&[0, 0, 2, 0, 1, 3]);
+    assert_eq!(game.available_actions(), vec![Action::Check]);
+
+    // Check that other river lines are correct
+    game.apply_history(&[0, 0, 2, 0, 1, 4]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(60)]
+    );
+
+    game.apply_history(&[0, 0, 3, 1, 1, 4]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(60)]
+    );
+
+    solve(&mut game, 10, 0.05, false);
+}
+
+#[test]
+fn update_bet_sizes() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK".parse().unwrap(),
+            "JJ-88,KQ,QJ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let sizes = |s: &str| {
+        let bet_sizes = BetSizeCandidates::try_from((s, "")).unwrap();
+        [bet_sizes.clone(), bet_sizes]
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: sizes("50%"),
+        river_bet_sizes: sizes("50%"),
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    // same as building the game from scratch with the new sizes
+    game.update_bet_sizes(sizes(""), sizes("75%, a"), sizes("33%, a"), [0, 2, 2])
+        .unwrap();
+    assert!(!game.is_solved());
+
+    let tree_config = TreeConfig {
+        turn_bet_sizes: sizes("75%, a"),
+        river_bet_sizes: sizes("33%, a"),
+        max_num_bets: [0, 2, 2],
+        ..tree_config
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game2 = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert_eq!(game.tree_config(), game2.tree_config());
+    assert_eq!(game.available_actions(), game2.available_actions());
+
+    game.allocate_memory(false);
+    game2.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+    solve(&mut game2, 50, 0.0, false);
+
+    game.cache_normalized_weights();
+    game2.cache_normalized_weights();
+    for player in 0..2 {
+        assert_eq!(game.expected_values(player), game2.expected_values(player));
+    }
+}
+
+#[test]
+fn update_bet_sizes_clears_locks() {
+    let build = |sizes: &str| {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ,AK", "JJ-88,KQ,QJ")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .river("2s")
+            .pot(60)
+            .stack(970)
+            .river_bets(sizes, "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    // lock OOP to always check at the root
+    let mut game = build("50%");
+    let num_hands = game.num_private_hands(0);
+    let mut locking = vec![0.0; 2 * num_hands];
+    locking[..num_hands].fill(1.0);
+    game.lock_current_strategy(&locking);
+
+    game.update_bet_sizes(
+        Default::default(),
+        Default::default(),
+        [
+            BetSizeCandidates::try_from(("75%, a", "")).unwrap(),
+            BetSizeCandidates::try_from(("75%, a", "")).unwrap(),
+        ],
+        [0, 0, 0],
+    )
+    .unwrap();
+    game.allocate_memory(false);
+    assert!(game.current_locking_strategy().is_none());
+    game.unlock_all_strategies();
+
+    // the lock of the previous tree does not affect the solving
+    let mut game2 = build("75%, a");
+    assert_eq!(game.tree_config(), game2.tree_config());
+    solve(&mut game, 50, 0.0, false);
+    solve(&mut game2, 50, 0.0, false);
+    game.cache_normalized_weights();
+    game2.cache_normalized_weights();
+    assert_eq!(game.strategy(), game2.strategy());
+    assert_eq!(game.expected_values(0), game2.expected_values(0));
+}
+
+#[test]
+fn update_effective_stack() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK,QQ,AK", "JJ-88,KQ,QJ")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(100)
+        .stack(1000)
+        .turn_bets("100%", "")
+        .river_bets("100%", "")
+        .build()
+        .unwrap();
+
+    // bet - call - (2c dealt)
+    let history = [1, 1, card_from_str("2c").unwrap() as usize];
+
+    // the second pot-sized bet is not all-in with the full stack
+    game.allocate_memory(false);
+    game.apply_history(&history);
+    assert_eq!(game.available_actions(), [Action::Check, Action::Bet(300)]);
+
+    // the second pot-sized bet is all-in with the shorter stack
+    game.update_effective_stack(400).unwrap();
+    assert_eq!(game.tree_config().effective_stack, 400);
+    game.allocate_memory(false);
+    game.apply_history(&history);
+    assert_eq!(
+        game.available_actions(),
+        [Action::Check, Action::AllIn(300)]
+    );
+
+    assert!(game.update_effective_stack(0).is_err());
+}
+
+#[test]
+fn update_effective_stack_lines_and_locks() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK".parse().unwrap(),
+            "JJ-88,KQ,QJ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("100%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 1000,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let mut action_tree = ActionTree::new(tree_config).unwrap();
+    action_tree.add_line(&[Action::Bet(50)]).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // lock OOP to always check at the root
+    let num_hands = game.num_private_hands(0);
+    let mut locking = vec![0.0; 3 * num_hands];
+    locking[..num_hands].fill(1.0);
+    game.lock_current_strategy(&locking);
+
+    // the added line is kept, while the lock is cleared
+    game.update_effective_stack(500).unwrap();
+    game.allocate_memory(false);
+    assert_eq!(game.added_lines(), [vec![Action::Bet(50)]]);
+    assert_eq!(
+        game.available_actions(),
+        [Action::Check, Action::Bet(50), Action::Bet(100)]
+    );
+    assert!(game.current_locking_strategy().is_none());
+
+    // the added line is not valid with the new effective stack
+    assert!(game.update_effective_stack(40).is_err());
+    assert_eq!(game.tree_config().effective_stack, 500);
+    assert_eq!(game.added_lines(), [vec![Action::Bet(50)]]);
+    solve(&mut game, 10, 0.0, false);
+}
+
+#[test]
+fn restrict_actions() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(200)
+        .river_bets("50%, 100%", "")
+        .build()
+        .unwrap();
+
+    let root_actions = [Action::Check, Action::Bet(50)];
+    assert!(game.restrict_actions(&[], &[]).is_err());
+    assert!(game.restrict_actions(&[], &[Action::Bet(70)]).is_err());
+    assert!(game
+        .restrict_actions(&[Action::Fold], &root_actions)
+        .is_err());
+    game.restrict_actions(&[], &root_actions).unwrap();
+
+    // IP can only check back
+    game.restrict_actions(&[Action::Check], &[Action::Check])
+        .unwrap();
+
+    game.allocate_memory(false);
+    assert!(game.restrict_actions(&[], &[Action::Check]).is_err());
+
+    solve(&mut game, 1000, 0.01, false);
+    assert_eq!(game.available_actions(), root_actions);
+
+    game.play(0);
+    game.cache_normalized_weights();
+    assert_eq!(game.available_actions(), [Action::Check]);
+    assert!(game.strategy().iter().all(|&v| v == 1.0));
+
+    // the nodes after the allowed actions are not affected
+    game.back_to_root();
+    game.play(1);
+    assert_eq!(game.available_actions(), [Action::Fold, Action::Call]);
+}
+
+#[test]
+fn isomorphism_monotone() {
+    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
+    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QhJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 100,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let mut check = |history: &[usize],
+                     expected_turn_swap: Option<u8>,
+                     expected_river_swap: Option<(u8, u8)>| {
+        game.apply_history(history);
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0);
+        let ev = game.expected_values(0);
+        weights.iter().zip(ev.iter()).for_each(|(&w, &v)| {
+            assert!(!(w > 0.0 && v == 50.0));
+        });
+        assert_eq!(game.turn_swap, expected_turn_swap);
+        assert_eq!(game.river_swap, expected_river_swap);
+    };
+
+    check(&[0, 0, 4], None, None);
+    check(&[0, 0, 5], Some(1), None);
+    check(&[0, 0, 6], None, None);
+    check(&[0, 0, 7], Some(3), None);
+
+    check(&[0, 0, 4, 0, 0, 8], None, None);
+    check(&[0, 0, 4, 0, 0, 9], None, None);
+    check(&[0, 0, 4, 0, 0, 10], None, None);
+    check(&[0, 0, 4, 0, 0, 11], None, Some((0, 3)));
+
+    check(&[0, 0, 5, 0, 0, 8], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 9], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 10], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 11], Some(1), Some((1, 3)));
+
+    check(&[0, 0, 6, 0, 0, 8], None, None);
+    check(&[0, 0, 6, 0, 0, 9], None, Some((2, 1)));
+    check(&[0, 0, 6, 0, 0, 10], None, None);
+    check(&[0, 0, 6, 0, 0, 11], None, Some((2, 3)));
+
+    check(&[0, 0, 7, 0, 0, 8], Some(3), Some((3, 1)));
+    check(&[0, 0, 7, 0, 0, 9], Some(3), None);
+    check(&[0, 0, 7, 0, 0, 10], Some(3), None);
+    check(&[0, 0, 7, 0, 0, 11], Some(3), None);
+}
+
+#[test]
+fn isomorphism_two_tone_flush() {
+    // two-tone flop: diamonds and spades are isomorphic, but hearts and clubs are not
+    let card_config = CardConfig {
+        range: [
+            "AA,QQ,QJs,T9s,76s".parse().unwrap(),
+            "KK,77,AQs,J9s,AKo".parse().unwrap(),
+        ],
+        flop: flop_from_str("AhKh7c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    let card_config_no_iso = CardConfig {
+        use_isomorphism: false,
+        ..card_config
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game_no_iso = PostFlopGame::with_config(card_config_no_iso, action_tree).unwrap();
+
+    let card = |s| card_from_str(s).unwrap();
+    let representative = |actions: &[Action], c| {
+        let chances = game.chance_actions(actions).unwrap();
+        chances.iter().find(|&&(x, _)| x == c).unwrap().1
+    };
+
+    // dealing the third heart (flush draw) is not isomorphic to the other suits
+    let line = [Action::Check, Action::Check];
+    assert_eq!(representative(&line, card("2h")), None);
+    assert_eq!(representative(&line, card("2c")), None);
+    assert_eq!(representative(&line, card("2s")), Some(card("2d")));
+
+    // the flush-completing river is not isomorphic either
+    let line = [
+        Action::Check,
+        Action::Check,
+        Action::Chance(card("Qh")),
+        Action::Check,
+        Action::Check,
+    ];
+    assert_eq!(representative(&line, card("2h")), None);
+    assert_eq!(representative(&line, card("2c")), None);
+    assert_eq!(representative(&line, card("2s")), Some(card("2d")));
+
+    // the turn card breaks the symmetry of diamonds and spades
+    let line = [
+        Action::Check,
+        Action::Check,
+        Action::Chance(card("Qd")),
+        Action::Check,
+        Action::Check,
+    ];
+    assert_eq!(representative(&line, card("2d")), None);
+    assert_eq!(representative(&line, card("2s")), None);
+
+    // the results must be identical to those without isomorphism
+    game.allocate_memory(false);
+    game_no_iso.allocate_memory(false);
+    finalize(&mut game);
+    finalize(&mut game_no_iso);
+
+    let histories = [
+        vec![],
+        vec![0, 0, card("Qh") as usize, 0, 0, card("2h") as usize],
+        vec![0, 0, card("Qh") as usize, 0, 0, card("2s") as usize],
+        vec![0, 0, card("Qs") as usize, 0, 0, card("2h") as usize],
+        vec![0, 0, card("Qs") as usize, 0, 0, card("2d") as usize],
+        vec![0, 0, card("Qs") as usize, 0, 0, card("2s") as usize],
+    ];
+
+    for history in &histories {
+        game.apply_history(history);
+        game_no_iso.apply_history(history);
+        game.cache_normalized_weights();
+        game_no_iso.cache_normalized_weights();
+        for player in 0..2 {
+            let ev = game.expected_values(player);
+            let ev_no_iso = game_no_iso.expected_values(player);
+            ev.iter().zip(&ev_no_iso).for_each(|(&v, &w)| {
+                assert!((v - w).abs() < 1e-3);
+            });
+        }
+    }
+}
+
+#[test]
+fn for_each_node() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("QhJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 100,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let mut num_nodes = 0;
+    let mut num_turn_cards = 0;
+    game.for_each_node(|node, line| {
+        num_nodes += 1;
+        if line.len() == 3 {
+            assert!(matches!(line[2], Action::Chance(_)));
+            assert!(!node.is_chance() && !node.is_terminal());
+            num_turn_cards += 1;
+        }
+    });
+
+    assert_eq!(num_nodes, game.node_arena.len());
+    assert_eq!(num_turn_cards, 49 - game.isomorphism_card_turn.len());
+}
+
+#[test]
+fn collapsed_sizes() {
+    let build = |stack| {
+        GameBuilder::new()
+            .ranges("AA,KK", "QQ,JJ")
+            .flop("Td9d6h")
+            .turn("2c")
+            .river("3s")
+            .pot(100)
+            .stack(stack)
+            .river_bets("75%, 125%, a", "")
+            .build()
+            .unwrap()
+    };
+
+    // all three sizes exceed the stack
+    let game = build(50);
+    let sizes = vec![
+        BetSize::PotRelative(0.75),
+        BetSize::PotRelative(1.25),
+        BetSize::AllIn,
+    ];
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::AllIn(50)]
+    );
+    assert_eq!(
+        game.collapsed_sizes(),
+        vec![(vec![], sizes.clone()), (vec![Action::Check], sizes)]
+    );
+
+    // only the 125% bet and the all-in collapse
+    let game = build(120);
+    let sizes = vec![BetSize::PotRelative(1.25), BetSize::AllIn];
+    assert_eq!(
+        game.collapsed_sizes(),
+        vec![(vec![], sizes.clone()), (vec![Action::Check], sizes)]
+    );
+
+    assert!(build(1000).collapsed_sizes().is_empty());
+}
+
+#[test]
+fn tree_stats() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let stats = game.tree_stats();
+    let num_river_cards =
+        48 - game.isomorphism_card_river[card_from_str("Qc").unwrap() as usize & 3].len() as u64;
+
+    // turn: player x 3 (OOP, IP after check, IP after bet), chance x 2, terminal x 1 (fold)
+    // river (per card): player x 4, terminal x 2 (check-check lines only)
+    assert_eq!(stats.num_player, 3 + 4 * num_river_cards);
+    assert_eq!(stats.num_chance, 2);
+    assert_eq!(stats.num_terminal, 1 + 2 * num_river_cards);
+    assert_eq!(
+        stats.num_nodes,
+        stats.num_player + stats.num_chance + stats.num_terminal
+    );
+    assert_eq!(stats.num_nodes, game.node_arena.len() as u64);
+
+    game.allocate_memory(false);
+    assert_eq!(stats.strategy_bytes, game.storage1.len() as u64);
+}
+
+#[test]
+fn two_phase_allocation() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK,QQ,AK", "JJ-88,KQ,QJ")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .turn_bets("50%", "")
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+
+    // the tree can be examined before allocating the memory
+    let stats = game.tree_stats();
+    assert!(stats.num_player > 0);
+    assert_eq!(game.is_memory_allocated(), None);
+    assert!(game.storage1.is_empty() && game.storage2.is_empty());
+    assert!(!game.is_ready());
+
+    game.allocate_memory(false);
+    assert_eq!(game.is_memory_allocated(), Some(false));
+    assert_eq!(game.storage1.len() as u64, stats.strategy_bytes);
+    assert!(game.is_ready());
+
+    let exploitability = solve(&mut game, 100, 60.0 * 0.01, false);
+    assert!(exploitability <= 60.0 * 0.01);
+}
+
+#[test]
+#[should_panic(expected = "the game is not ready")]
+fn solve_without_allocation() {
+    let mut game = GameBuilder::new()
+        .ranges("AA", "KK")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .river("2s")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    solve(&mut game, 10, 0.0, false);
+}
+
+#[test]
+fn reach_validation() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,KK,QQ", "JJ,TT,AK")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .river("2s")
+            .pot(60)
+            .stack(970)
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let game = build();
+    solve_step(&game, 0);
+    assert!(validate_reach(&game).is_ok());
+
+    // inject a corrupted locking strategy
+    let mut game = build();
+    let num_hands = game.num_private_hands(0);
+    game.lock_current_strategy(&vec![0.5; 2 * num_hands]);
+    game.locking_strategy.get_mut(&0).unwrap()[..num_hands].fill(f32::NAN);
+
+    let error = validate_reach(&game).unwrap_err();
+    assert!(error.contains("NaN"));
+    assert!(error.contains("path = [0]"));
+}
+
+#[test]
+#[should_panic(expected = "Invalid reach probability")]
+fn reach_validation_in_solve() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK,QQ", "JJ,TT,AK")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .river("2s")
+        .pot(60)
+        .stack(970)
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+
+    let num_hands = game.num_private_hands(0);
+    game.lock_current_strategy(&vec![0.5; 2 * num_hands]);
+    game.locking_strategy.get_mut(&0).unwrap()[..num_hands].fill(f32::NAN);
+
+    let params = SolveParams {
+        max_iterations: 10,
+        validate_reach: true,
+        ..Default::default()
+    };
+    solve_with_config(&mut game, &params);
+}
+
+#[test]
+fn showdown_frequency() {
+    // check-only tree: every hand that can be dealt reaches the showdown
+    let card_config = CardConfig {
+        range: ["AA,QQ,T9s".parse().unwrap(), "KK,JJ,AKs".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 1000,
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    for player in 0..2 {
+        let frequency = game.showdown_frequency(player).unwrap();
+        assert_eq!(frequency.len(), game.num_private_hands(player));
+        assert!(frequency.iter().all(|&f| (f - 1.0).abs() < 1e-5));
+    }
+
+    // OOP always bets all-in and IP always folds: no hand reaches the showdown
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ,T9s", "KK,JJ,AKs")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("a", "")
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+
+    let num_hands = game.num_private_hands(0);
+    game.lock_current_strategy(&[vec![0.0; num_hands], vec![1.0; num_hands]].concat());
+    game.play(1);
+    let num_hands = game.num_private_hands(1);
+    game.lock_current_strategy(&[vec![1.0; num_hands], vec![0.0; num_hands]].concat());
+    game.back_to_root();
+    solve(&mut game, 10, 0.0, false);
+
+    let frequency = game.showdown_frequency(1).unwrap();
+    assert!(frequency.iter().all(|&f| f.abs() < 1e-5));
+    assert!(game.showdown_frequency(2).is_err());
+}
+
+#[test]
+fn verify_chance_completeness() {
+    // rainbow, two-tone, monotone, and paired flops
+    for flop in ["Td9c6h", "Td9d6h", "Td9d6d", "TdTc6h"] {
+        let game = GameBuilder::new()
+            .ranges("AA,KK,AK", "QQ,JJ,AQs")
+            .flop(flop)
+            .pot(60)
+            .stack(970)
+            .build()
+            .unwrap();
+
+        assert_eq!(game.verify_chance_completeness(), Ok(()));
+    }
+
+    // short deck and dead cards
+    let game = GameBuilder::new()
+        .ranges("AA,KK,AK", "QQ,JJ,AQs")
+        .game_type(GameType::ShortDeck)
+        .flop("Td9d6h")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+    assert_eq!(game.verify_chance_completeness(), Ok(()));
+
+    let card_config = CardConfig {
+        range: ["AA,KK,AK".parse().unwrap(), "QQ,JJ,AQs".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        dead_cards: vec![card_from_str("2c").unwrap(), card_from_str("3s").unwrap()],
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert_eq!(game.verify_chance_completeness(), Ok(()));
+
+    // a card both dealt and merged into another card is detected
+    let card = card_from_str("As").unwrap();
+    game.isomorphism_card_turn.push(card);
+    assert!(game.verify_chance_completeness().is_err());
+}
+
+#[test]
+fn terminal_nodes() {
+    // same configuration as `one_raise_all_range`
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let terminals = game.terminal_nodes();
+    assert_eq!(terminals.len() as u64, game.tree_stats().num_terminal);
+
+    // river (per runout): check-check (showdown), bet-fold (IP folds), bet-call (showdown)
+    let num_fold = terminals
+        .iter()
+        .filter(|(_, kind)| matches!(kind, TerminalKind::Fold { .. }))
+        .count();
+    assert!(num_fold > 0);
+    assert_eq!(terminals.len(), 3 * num_fold);
+
+    for (line, kind) in &terminals {
+        assert_eq!(line.len(), 8);
+        match kind {
+            TerminalKind::Fold { folder, pot } => {
+                assert_eq!(line.last(), Some(&Action::Fold));
+                assert_eq!((*folder, *pot), (1, 90));
+            }
+            TerminalKind::Showdown { pot } => match line.last() {
+                Some(Action::Check) => assert_eq!(*pot, 60),
+                Some(Action::Call) => assert_eq!(*pot, 120),
+                _ => unreachable!(),
+            },
+        }
+    }
+}
+
+#[test]
+fn estimate_memory() {
+    let oop_range = "66+,A8s+,A5s-A4s,AJo+,K9s+,KQo,QTs+,JTs,96s+,85s+,75s+,65s,54s";
+    let ip_range = "QQ-22,AQs-A2s,ATo+,K5s+,KJo+,Q8s+,J8s+,T7s+,96s+,86s+,75s+,64s+,53s+";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QhJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%, a", "3x")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 400,
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let estimated = PostFlopGame::estimate_memory(&card_config, &action_tree).unwrap();
+
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let actual = game.memory_usage();
+
+    assert_eq!(estimated.0 - estimated.1, actual.0 - actual.1);
+    assert!(estimated.0 <= actual.0);
+}
+
+#[test]
+fn best_response() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        turn: card_from_str("3s").unwrap(),
+        river: card_from_str("8c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    // OOP always wins, so OOP should bet against the uniform strategy of IP
+    let num_oop_hands = game.private_cards(0).len();
+    let best_response = game.best_response();
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(30)]
+    );
+    assert!(best_response[..num_oop_hands].iter().all(|&x| x == 0.0));
+    assert!(best_response[num_oop_hands..].iter().all(|&x| x == 1.0));
+
+    // IP always loses, so IP should fold against a bet
+    game.play(1);
+    let num_ip_hands = game.private_cards(1).len();
+    let best_response = game.best_response();
+    assert_eq!(game.available_actions(), vec![Action::Fold, Action::Call]);
+    assert!(best_response[..num_ip_hands].iter().all(|&x| x == 1.0));
+    assert!(best_response[num_ip_hands..].iter().all(|&x| x == 0.0));
+}
+
+#[test]
+fn to_dot() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let dot = game.to_dot(usize::MAX);
+    assert!(dot.starts_with("digraph {"));
+    assert_eq!(
+        dot.matches(" -> ").count() as u64,
+        game.tree_stats().num_nodes - 1
+    );
+    assert_eq!(dot.matches("fold").count(), 1);
+    assert_eq!(dot.matches("showdown").count(), 2);
+    assert!(dot.contains("[label=\"Bet(30)\"]"));
+    assert!(dot.contains("[label=\"showdown\\npot = 120\"]"));
+
+    let dot = game.to_dot(1);
+    assert_eq!(dot.matches(" -> ").count(), 2);
+    assert!(dot.contains("0 [label=\"OOP\\npot = 60\"]"));
+}
+
+#[test]
+fn node_info() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "2x")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 900,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let info = |actions: &[Action]| game.node_info(actions).unwrap();
+    let node_info = |pot, oop_stack, ip_stack, to_call| NodeInfo {
+        pot,
+        oop_stack,
+        ip_stack,
+        to_call,
+    };
+
+    assert_eq!(info(&[]), node_info(100, 900, 900, 0));
+    assert_eq!(info(&[Action::Bet(50)]), node_info(150, 850, 900, 50));
+    assert_eq!(
+        info(&[Action::Bet(50), Action::Raise(100)]),
+        node_info(250, 850, 800, 50)
+    );
+
+    // the river card is omitted from the line
+    let line = [
+        Action::Bet(50),
+        Action::Call,
+        Action::Check,
+        Action::Bet(100),
+    ];
+    assert_eq!(info(&line), node_info(300, 850, 750, 100));
+    let line = [
+        Action::Bet(50),
+        Action::Call,
+        Action::Bet(100),
+        Action::Call,
+    ];
+    assert_eq!(info(&line), node_info(400, 750, 750, 0));
+
+    assert!(game.node_info(&[Action::Bet(60)]).is_err());
+    let line = [Action::Check, Action::Bet(50), Action::Fold, Action::Check];
+    assert!(game.node_info(&line).is_err());
+}
+
+#[test]
+fn child_info() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "2x")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 900,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    // the children of the root match the available actions
+    let actions = game.available_actions();
+    for (i, &action) in actions.iter().enumerate() {
+        let (child_action, info, role) = game.child_info(&[], i).unwrap();
+        assert_eq!(child_action, action);
+        assert_eq!(info, game.node_info(&[action]).unwrap());
+        assert_eq!(role, NodeRole::Ip);
+    }
+    assert!(game.child_info(&[], actions.len()).is_err());
+
+    // the role of the child tells who acts next
+    let roles = (0..3)
+        .map(|i| game.child_info(&[Action::Bet(50)], i).unwrap().2)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roles,
+        [
+            NodeRole::Fold { folder: 1 },
+            NodeRole::Chance,
+            NodeRole::Oop
+        ]
+    );
+
+    // dealing a card does not change the pot
+    let line = [Action::Check, Action::Check];
+    let (action, info, role) = game.child_info(&line, 0).unwrap();
+    assert!(matches!(action, Action::Chance(_)));
+    assert_eq!(info, game.node_info(&line).unwrap());
+    assert_eq!(role, NodeRole::Oop);
+
+    assert!(game
+        .child_info(&[Action::Bet(50), Action::Fold], 0)
+        .is_err());
+}
+
+#[test]
+fn spr_at() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "2x")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 900,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    assert_eq!(game.spr_at(&[]).unwrap(), 9.0);
+    assert_eq!(game.spr_at(&[Action::Check]).unwrap(), 9.0);
+
+    // 850 / 150 (the bettor has the smaller stack)
+    let spr = game.spr_at(&[Action::Bet(50)]).unwrap();
+    assert!((spr - 850.0 / 150.0).abs() < 1e-6);
+
+    // 850 / 200 on the river
+    let spr = game.spr_at(&[Action::Bet(50), Action::Call]).unwrap();
+    assert!((spr - 4.25).abs() < 1e-6);
+
+    assert!(game.spr_at(&[Action::Bet(60)]).is_err());
+}
+
+#[test]
+fn hand_index() {
+    let card_config = CardConfig {
+        range: ["AA,KK,AKs".parse().unwrap(), Range::ones()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    for player in 0..2 {
+        for (i, &(c1, c2)) in game.private_cards(player).iter().enumerate() {
+            assert_eq!(game.hand_index(player, c1, c2), Some(i));
+            assert_eq!(game.hand_index(player, c2, c1), Some(i));
+        }
+    }
+
+    let ace_clubs = card_from_str("Ac").unwrap();
+    let queen_clubs = card_from_str("Qc").unwrap();
+    let ten_diamonds = card_from_str("Td").unwrap();
+    assert_eq!(game.hand_index(0, ace_clubs, queen_clubs), None);
+    assert_eq!(game.hand_index(1, ace_clubs, ten_diamonds), None);
+}
+
+#[test]
+fn live_combos() {
+    let game = GameBuilder::new()
+        .ranges("AA,KK", "QQ")
+        .flop("AcAdKh")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    // pocket aces are reduced to AhAs and pocket kings to three combinations by the flop
+    let flop = flop_from_str("AcAdKh").unwrap();
+    assert_eq!(game.live_combos(0, &flop), 4);
+    assert_eq!(game.live_combos(0, &[]), 4);
+    assert_eq!(game.live_combos(1, &flop), 6);
+
+    // the turn and river cards remove more combinations
+    let ace_of_spades = card_from_str("As").unwrap();
+    let queen_of_clubs = card_from_str("Qc").unwrap();
+    assert_eq!(game.live_combos(0, &[ace_of_spades]), 3);
+    assert_eq!(game.live_combos(1, &[ace_of_spades, queen_of_clubs]), 3);
+}
+
+#[test]
+fn node_role() {
+    let game = GameBuilder::new()
+        .ranges("AA,KK", "QQ")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .turn_bets("50%", "")
+        .build()
+        .unwrap();
+
+    // OOP: [Check, Bet(30)]
+    let root = game.root();
+    assert_eq!(root.role(), NodeRole::Oop);
+
+    // IP: [Check]
+    let ip_node = root.play(0);
+    assert_eq!(ip_node.role(), NodeRole::Ip);
+    assert_eq!(ip_node.play(0).role(), NodeRole::Chance);
+
+    // IP facing a bet: [Fold, Call]
+    let bet_node = root.play(1);
+    assert_eq!(bet_node.role(), NodeRole::Ip);
+    assert_eq!(bet_node.play(0).role(), NodeRole::Fold { folder: 1 });
+    let chance_node = bet_node.play(1);
+    assert_eq!(chance_node.role(), NodeRole::Chance);
+
+    // river: check-check ends in a showdown
+    let river_root = chance_node.play(0);
+    assert_eq!(river_root.role(), NodeRole::Oop);
+    assert_eq!(river_root.play(0).play(0).role(), NodeRole::Terminal);
+}
+
+#[test]
+fn range_after_card() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK,AK:0.5", "QQ")
+        .flop("Td9d6h")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+
+    let ace_of_spades = card_from_str("As").unwrap();
+    let weights = game.range_after_card(0, ace_of_spades);
+    assert_eq!(weights.len(), game.num_private_hands(0));
+
+    // the hands containing the card are removed and the others are unchanged
+    let mut num_removed = 0;
+    for (i, &(c1, c2)) in game.private_cards(0).iter().enumerate() {
+        if c1 == ace_of_spades || c2 == ace_of_spades {
+            assert_eq!(weights[i], 0.0);
+            num_removed += 1;
+        } else {
+            assert_eq!(weights[i], game.weights(0)[i]);
+        }
+    }
+    assert_eq!(num_removed, 3 + 4);
+
+    let weights = game.range_after_card(1, ace_of_spades);
+    assert_eq!(weights, game.weights(1));
+}
+
+#[test]
+fn combos_by_class() {
+    let game = GameBuilder::new()
+        .ranges("AA,AKs,AKo", "AA,KQo")
+        .flop("Ac7d2h")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    // the ace on the flop blocks three combinations of pocket aces
+    let grid = game.combos_by_class(0);
+    assert_eq!(grid[0][0].len(), 3);
+    assert_eq!(grid[0][1].len(), 3);
+    assert_eq!(grid[1][0].len(), 9);
+    assert_eq!(grid.iter().flatten().map(Vec::len).sum::<usize>(), 15);
+
+    for &index in &grid[0][1] {
+        let (c1, c2) = game.private_cards(0)[index];
+        assert_eq!(c1 & 3, c2 & 3);
+    }
+
+    // without an ace on the flop, all six combinations are live
+    let game = GameBuilder::new()
+        .ranges("AA", "KQo")
+        .flop("Td9d6h")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    let grid = game.combos_by_class(0);
+    assert_eq!(grid[0][0], (0..6).collect::<Vec<_>>());
+    assert_eq!(game.combos_by_class(1)[2][1].len(), 12);
+}
+
+#[test]
+fn game_builder() {
+    let game = GameBuilder::new()
+        .ranges("AA,KK", "QQ")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .turn_bets("50%", "2x")
+        .river_bets("a", "")
+        .build()
+        .unwrap();
+
+    let tree_config = game.tree_config();
+    assert_eq!(tree_config.initial_state, BoardState::Turn);
+    assert_eq!(tree_config.starting_pot, 60);
+    assert_eq!(tree_config.effective_stack, 970);
+    assert_eq!(
+        tree_config.turn_bet_sizes[1].raise,
+        vec![BetSize::PrevBetRelative(2.0)]
+    );
+    assert_eq!(tree_config.river_bet_sizes[0].bet, vec![BetSize::AllIn]);
+
+    // the first error is reported
+    let err = GameBuilder::new()
+        .ranges("AA", "KK")
+        .flop("Td9d6h")
+        .turn_bets("50", "x")
+        .river_bets("y", "")
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err, BetSizeCandidates::try_from(("50", "x")).unwrap_err());
+}
+
+#[test]
+fn game_builder_spr() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK", "QQ,AK")
+        .flop("Td9d6h")
+        .pot(80)
+        .spr(2.0)
+        .flop_bets("100%", "")
+        .turn_bets("100%", "")
+        .build()
+        .unwrap();
+    assert_eq!(game.tree_config().effective_stack, 160);
+
+    // a pot-sized bet on the flop leaves less than a pot-sized bet behind on the turn
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+    game.play(1);
+    game.play(1);
+    game.play(usize::MAX);
+    assert_eq!(game.available_actions(), [Action::Check, Action::AllIn(80)]);
+
+    let build = |builder: GameBuilder| builder.ranges("AA", "KK").flop("Td9d6h").build();
+    assert!(build(GameBuilder::new().spr(2.0)).is_err());
+    assert!(build(GameBuilder::new().pot(80).spr(0.0)).is_err());
+    assert!(build(GameBuilder::new().pot(80).spr(f64::NAN)).is_err());
+    assert!(build(GameBuilder::new().pot(80).spr(1e9)).is_err());
+    assert_eq!(
+        build(GameBuilder::new().pot(60).spr(3.3))
+            .unwrap()
+            .tree_config()
+            .effective_stack,
+        198
+    );
+}
+
+#[test]
+fn short_deck() {
+    // flush vs full house
+    let mut card_config = CardConfig {
+        range: ["Th6h".parse().unwrap(), "AsAd".parse().unwrap()],
+        flop: flop_from_str("AhKhKs").unwrap(),
+        turn: card_from_str("9h").unwrap(),
+        river: card_from_str("Qd").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    for (game_type, expected) in [(GameType::Holdem, 0.0), (GameType::ShortDeck, 1.0)] {
+        card_config.game_type = game_type;
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.cache_normalized_weights();
+        assert_eq!(game.equity(0), vec![expected]);
+    }
+
+    // removed cards
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        game_type: GameType::ShortDeck,
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    let private_cards = game.private_cards(0);
+    assert_eq!(private_cards.len(), 32 * 31 / 2);
+    assert!(private_cards
+        .iter()
+        .all(|&(c1, c2)| c1 >> 2 >= 4 && c2 >> 2 >= 4));
+
+    game.allocate_memory(false);
+    solve(&mut game, 100, 60.0 * 0.005, false);
+
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    assert_eq!(game.possible_cards().count_ones(), 32);
+    assert_eq!(game.possible_cards() & 0xffff, 0);
+
+    game.back_to_root();
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0);
+    let equity = compute_average(&game.equity(0), weights);
+    let ev = compute_average(&game.expected_values(0), weights);
+    assert!((equity - 0.5).abs() < 1e-5);
+    assert!((ev - 30.0).abs() < 1e-4);
+
+    let mut card_config = card_config;
+    card_config.turn = card_from_str("5c").unwrap();
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    assert!(PostFlopGame::with_config(card_config, action_tree).is_err());
+}
+
+#[test]
+fn range_blocked_by_board() {
+    let build = |oop: &str, ip: &str| {
+        GameBuilder::new()
+            .ranges(oop, ip)
+            .flop("Td9d6h")
+            .pot(100)
+            .stack(1000)
+            .build()
+            .err()
+    };
+
+    let err = build("AA", "Td9d,Td6h,9d6h");
+    assert_eq!(
+        err.unwrap(),
+        "IP's range has no combos that avoid the board"
+    );
+
+    let err = build("TdTh", "AA");
+    assert_eq!(
+        err.unwrap(),
+        "OOP's range has no combos that avoid the board"
+    );
+
+    // both ranges have live combos, but they always conflict with each other
+    let err = build("AsAh", "AsAh");
+    assert_eq!(err.unwrap(), "Valid card assignment does not exist");
+}
+
+#[test]
+fn dead_cards() {
+    let dead_cards = vec![card_from_str("As").unwrap(), card_from_str("Ks").unwrap()];
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        dead_cards: dead_cards.clone(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    assert_eq!(game.private_cards(0).len(), 46 * 45 / 2);
+
+    game.for_each_node(|_, line| {
+        assert!(!line
+            .iter()
+            .any(|&a| matches!(a, Action::Chance(c) if dead_cards.contains(&c))));
+    });
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.play(0);
+    game.play(0);
+    assert_eq!(game.possible_cards().count_ones(), 46);
+
+    game.back_to_root();
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0);
+    let ev = compute_average(&game.expected_values(0), weights);
+    assert!((ev - 30.0).abs() < 1e-4);
+
+    // the dead card breaks the suit isomorphism of hearts and clubs
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AKs,T9s".parse().unwrap(),
+            "JJ+,AQs+,87s".parse().unwrap(),
+        ],
+        turn: card_from_str("6c").unwrap(),
+        dead_cards: vec![card_from_str("Ah").unwrap()],
+        ..card_config
+    };
+
+    let compute_ev = |use_isomorphism| {
+        let card_config = CardConfig {
+            use_isomorphism,
+            ..card_config.clone()
+        };
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        game.cache_normalized_weights();
+        compute_average(&game.expected_values(0), game.normalized_weights(0))
+    };
+
+    assert!((compute_ev(true) - compute_ev(false)).abs() < 1e-4);
+
+    let card_config = CardConfig {
+        dead_cards: vec![card_from_str("6c").unwrap()],
+        ..card_config
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    assert!(PostFlopGame::with_config(card_config, action_tree).is_err());
+}
+
+#[test]
+fn strategy_grid() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // pairs bet except AcAd and AhAs, other hands check
+    let num_hands = game.private_cards(0).len();
+    let mut locking = vec![0.0; 2 * num_hands];
+    for (hand, &(c1, c2)) in game.private_cards(0).iter().enumerate() {
+        let is_half_aces = (c1, c2) == (48, 49) || (c1, c2) == (50, 51);
+        let action = (c1 >> 2 == c2 >> 2 && !is_half_aces) as usize;
+        locking[action * num_hands + hand] = 1.0;
+    }
+    game.lock_current_strategy(&locking);
+
+    let grid = game.strategy_grid();
+    assert_eq!(grid.len(), 2 * 169);
+
+    let bet = &grid[169..];
+    assert!((bet[0] - 2.0 / 3.0).abs() < 1e-6); // AA: 2 of 6 combos check
+    assert_eq!(bet[2 * 13 + 2], 1.0); // QQ: 3 combos, all bet
+    assert_eq!(bet[12 * 13 + 12], 1.0); // 22
+    assert_eq!(bet[1], 0.0); // AKs
+    assert_eq!(bet[13], 0.0); // AKo
+    assert_eq!(grid[13], 1.0); // AKo checks
+
+    // TdQc is blocked, but not the other combos of QTs
+    let qts = 2 * 13 + 4;
+    assert_eq!(grid[qts], 1.0);
+    assert_eq!(bet[qts], 0.0);
+}
+
+#[test]
+fn class_strategy() {
+    let card_config = CardConfig {
+        range: ["AA,KK,AK".parse().unwrap(), "QQ-JJ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("Ks").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // AKo always checks, AKs and KK always bet, AA bets with AcAd only
+    let num_hands = game.private_cards(0).len();
+    let mut locking = vec![0.0; 2 * num_hands];
+    for (hand, &(c1, c2)) in game.private_cards(0).iter().enumerate() {
+        let is_offsuit_ak = c1 >> 2 != c2 >> 2 && c1 & 3 != c2 & 3;
+        let is_checking_aa = c1 >> 2 == 12 && (c1, c2) != (48, 49);
+        let action = (!is_offsuit_ak && !is_checking_aa) as usize;
+        locking[action * num_hands + hand] = 1.0;
+    }
+    game.lock_current_strategy(&locking);
+
+    let ako = game.class_strategy(&[], "AKo").unwrap();
+    assert_eq!(ako, [(Action::Check, 1.0), (Action::Bet(30), 0.0)]);
+
+    // KsKx are blocked by the board
+    let kk = game.class_strategy(&[], "KK").unwrap();
+    assert_eq!(kk, [(Action::Check, 0.0), (Action::Bet(30), 1.0)]);
+
+    let aa = game.class_strategy(&[], "AA").unwrap();
+    assert!((aa[1].1 - 1.0 / 6.0).abs() < 1e-6);
+
+    assert!(game.class_strategy(&[], "QQ").is_err()); // not in the range
+    assert!(game.class_strategy(&[], "KAo").is_err()); // invalid class
+    assert!(game.class_strategy(&[Action::Check], "QQ").is_ok()); // IP's node
+    assert!(game
+        .class_strategy(&[Action::Check, Action::Check], "QQ")
+        .is_err());
+}
+
+#[test]
+fn aggregate_strategy() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s", "KK,JJ,AKs,KQo")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+
+    let line = [Action::Check, Action::Check];
+    assert!(game.aggregate_strategy(&line, 0).is_err()); // memory is not allocated
+
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    game.play(0);
+    let report = game.aggregate_strategy(&line, 0).unwrap();
+
+    // history is restored
+    assert_eq!(game.history(), &[0]);
+
+    assert_eq!(report.player, 0);
+    assert_eq!(report.runouts.len(), 48);
+    assert_eq!(report.frequencies.len(), 48 * report.actions.len());
+
+    let weight_sum = report.runout_weights.iter().sum::<f32>();
+    assert!((weight_sum - 1.0).abs() < 1e-5);
+
+    // blocker effect: a card that blocks both ranges is less likely
+    let ac = report.runouts.iter().position(|r| r == &[48]).unwrap();
+    let c2 = report.runouts.iter().position(|r| r == &[0]).unwrap();
+    assert!(report.runout_weights[ac] < report.runout_weights[c2]);
+
+    // the average is consistent with the per-runout frequencies
+    for action in 0..report.actions.len() {
+        let expected = report
+            .runout_weights
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| w * report.frequencies[i * report.actions.len() + action])
+            .sum::<f32>();
+        assert!((report.average[action] - expected).abs() < 1e-5);
+    }
+
+    // the per-runout frequencies agree with the river node
+    game.apply_history(&[0, 0, 0]);
+    game.cache_normalized_weights();
+    assert_eq!(report.actions, game.available_actions());
+
+    let num_actions = report.actions.len();
+    let strategy = game.strategy();
+    let weights = game.normalized_weights(0);
+    let num_hands = weights.len();
+    for action in 0..num_actions {
+        let row = &strategy[action * num_hands..(action + 1) * num_hands];
+        let freq = compute_average(row, weights);
+        assert!((report.frequencies[c2 * num_actions + action] - freq).abs() < 1e-5);
+    }
+
+    // the line may continue after the chance node
+    let report = game
+        .aggregate_strategy(&[Action::Check, Action::Check, Action::Bet(30)], 1)
+        .unwrap();
+    assert_eq!(report.runouts.len(), 48);
+    assert_eq!(report.actions, [Action::Fold, Action::Call]);
+
+    assert!(game.aggregate_strategy(&line, 1).is_err()); // OOP acts first
+    assert!(game.aggregate_strategy(&line, 2).is_err());
+    assert!(game.aggregate_strategy(&[Action::Fold], 0).is_err());
+    assert!(game.aggregate_strategy(&[Action::Chance(0)], 0).is_err());
+}
+
+#[test]
+fn aggregate_strategy_all_runouts() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,KK", "QQ,JJ")
+        .flop("Td9d6h")
+        .pot(60)
+        .stack(970)
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 20, 0.0, false);
+
+    // the turn and river cards are both dealt along the line
+    let line = [Action::Check; 4];
+    let report = game.aggregate_strategy(&line, 0).unwrap();
+    assert_eq!(report.runouts.len(), 49 * 48);
+    assert!(report.runouts.iter().all(|runout| runout.len() == 2));
+
+    let weight_sum = report.runout_weights.iter().sum::<f32>();
+    assert!((weight_sum - 1.0).abs() < 1e-4);
+
+    // the per-runout frequencies agree with the river node
+    let index = report.runouts.iter().position(|r| r == &[0, 4]).unwrap();
+    assert!(report.runout_weights[index] > 0.0);
+
+    game.apply_history(&[0, 0, 0, 0, 0, 4]);
+    game.cache_normalized_weights();
+    assert_eq!(report.actions, game.available_actions());
+
+    let num_actions = report.actions.len();
+    let strategy = game.strategy();
+    let weights = game.normalized_weights(0);
+    let num_hands = weights.len();
+    for action in 0..num_actions {
+        let row = &strategy[action * num_hands..(action + 1) * num_hands];
+        let freq = compute_average(row, weights);
+        assert!((report.frequencies[index * num_actions + action] - freq).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn clear_strategy() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s", "KK,JJ,AKs,KQo")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .river("7s")
+        .pot(60)
+        .stack(970)
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+    game.cache_normalized_weights();
+    let ev = compute_average(&game.expected_values(0), game.normalized_weights(0));
+
+    game.clear_strategy();
+    assert!(!game.is_solved());
+    assert!(game.strategy().iter().all(|&x| x == 0.5));
+
+    solve(&mut game, 100, 0.0, false);
+    game.cache_normalized_weights();
+    let ev_resolved = compute_average(&game.expected_values(0), game.normalized_weights(0));
+    assert!((ev - ev_resolved).abs() < 1e-3);
+}
+
+#[test]
+fn warm_start_from() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game = build();
+    for t in 0..100 {
+        solve_step(&game, t);
+    }
+
+    let mut warm = build();
+    warm.warm_start_from(&game, |line| Some(line.to_vec()))
+        .unwrap();
+    let exploitability = compute_exploitability(&game);
+    assert!((compute_exploitability(&warm) - exploitability).abs() < 1e-3);
+
+    // continue from the copied values
+    let cold = build();
+    for t in 0..10 {
+        solve_step(&warm, 100 + t);
+        solve_step(&cold, t);
+    }
+    assert!(compute_exploitability(&warm) < compute_exploitability(&cold));
+
+    game.allocate_memory(true);
+    assert!(warm.warm_start_from(&game, |_| None).is_err());
+}
+
+#[test]
+fn local_exploitability() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .turn_bets("50%", "")
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+
+    let check = |game: &PostFlopGame| {
+        let report = game.local_exploitability();
+        assert!(report.iter().all(|&(_, gap)| gap > -1e-3));
+
+        // the root node is the only top-level node of OOP
+        let mes_ev = compute_mes_ev(game);
+        let current_ev = compute_current_ev(game);
+        assert!(report[0].0.is_empty());
+        assert!((report[0].1 - (mes_ev[0] - current_ev[0])).abs() < 1e-2);
+
+        // the top-level nodes of IP are the children of the root
+        let gap_ip = report
+            .iter()
+            .filter(|(line, _)| line.len() == 1)
+            .map(|&(_, gap)| gap)
+            .sum::<f32>();
+        assert!((gap_ip - (mes_ev[1] - current_ev[1])).abs() < 1e-2);
+
+        report.iter().map(|&(_, gap)| gap).fold(0.0, f32::max)
+    };
+
+    let max_gap = check(&game);
+    solve(&mut game, 100, 0.0, false);
+    assert!(check(&game) < max_gap);
+}
+
+#[test]
+fn strategy_diff() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game_a = build();
+    let mut game_b = build();
+    solve(&mut game_a, 100, 0.0, false);
+    solve(&mut game_b, 10, 0.0, false);
+
+    // a game against itself
+    let report = super::strategy_diff(&game_a, &game_a).unwrap();
+    assert_eq!(report.len(), game_a.tree_stats().num_player as usize);
+    assert!(report.iter().all(|&(_, diff)| diff == 0.0));
+
+    let report = super::strategy_diff(&game_a, &game_b).unwrap();
+    assert!(report.iter().any(|&(_, diff)| diff > 0.0));
+    assert!(report.iter().all(|&(_, diff)| (0.0..=2.0).contains(&diff)));
+    assert!(report.windows(2).all(|w| w[0].1 >= w[1].1));
+
+    // the root node is always reached, so its value is the average L1 distance over the range
+    game_a.back_to_root();
+    game_b.back_to_root();
+    let strategy_a = game_a.strategy();
+    let strategy_b = game_b.strategy();
+    let weights = game_a.initial_weights(0);
+    let num_hands = weights.len();
+    let expected = (0..num_hands)
+        .map(|hand| {
+            let l1 = (0..strategy_a.len() / num_hands)
+                .map(|action| {
+                    let index = action * num_hands + hand;
+                    (strategy_a[index] - strategy_b[index]).abs()
+                })
+                .sum::<f32>();
+            weights[hand] * l1
+        })
+        .sum::<f32>()
+        / weights.iter().sum::<f32>();
+    let root_diff = report.iter().find(|(line, _)| line.is_empty()).unwrap().1;
+    assert!((root_diff - expected).abs() < 1e-5);
+
+    // games with different trees
+    let game_c = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .turn_bets("75%", "")
+        .build()
+        .unwrap();
+    assert!(super::strategy_diff(&game_a, &game_c).is_err());
+}
+
+#[test]
+fn compute_ev_vs() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game_a = build();
+    let mut game_b = build();
+    solve(&mut game_a, 100, 0.0, false);
+    solve(&mut game_b, 10, 0.0, false);
+
+    // a game against itself
+    let current_ev = compute_current_ev(&game_a);
+    for (player, &current_ev) in current_ev.iter().enumerate() {
+        let ev = super::compute_ev_vs(&game_a, player, &game_a).unwrap();
+        assert!((ev - current_ev).abs() < 1e-3);
+    }
+
+    // the same strategy profile seen from both sides is zero-sum
+    let ev_oop = super::compute_ev_vs(&game_a, 0, &game_b).unwrap();
+    let ev_ip = super::compute_ev_vs(&game_b, 1, &game_a).unwrap();
+    assert!((ev_oop + ev_ip).abs() < 1e-3);
+
+    // the best response cannot be exceeded
+    let mes_ev = compute_mes_ev(&game_b);
+    assert!(super::compute_ev_vs(&game_a, 0, &game_b).unwrap() <= mes_ev[0] + 1e-3);
+    assert!(super::compute_ev_vs(&game_a, 1, &game_b).unwrap() <= mes_ev[1] + 1e-3);
+
+    assert!(super::compute_ev_vs(&game_a, 2, &game_b).is_err());
+}
+
+#[test]
+fn first_divergence() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game_a = build();
+    let mut game_b = build();
+    solve(&mut game_a, 100, 0.0, false);
+    solve(&mut game_b, 3, 0.0, false);
+
+    let hand = (card_from_str("As").unwrap(), card_from_str("Ah").unwrap());
+
+    // a game against itself
+    let divergence = super::first_divergence(&game_a, &game_a, 0, hand, 0.0).unwrap();
+    assert_eq!(divergence, None);
+
+    // the line leads to a node of the player where the strategies of the hand differ
+    let line = super::first_divergence(&game_a, &game_b, 0, hand, 0.01)
+        .unwrap()
+        .unwrap();
+    let index = game_a.hand_index(0, hand.0, hand.1).unwrap();
+    let strategy_at = |game: &mut PostFlopGame| {
+        game.back_to_root();
+        for &action in &line {
+            let actions = game.available_actions();
+            game.play(match action {
+                Action::Chance(card) => card as usize,
+                _ => actions.iter().position(|&a| a == action).unwrap(),
+            });
+        }
+        assert_eq!(game.current_player(), 0);
+        let num_hands = game.num_private_hands(0);
+        game.strategy()
+            .into_iter()
+            .skip(index)
+            .step_by(num_hands)
+            .collect::<Vec<_>>()
+    };
+    let strategy_a = strategy_at(&mut game_a);
+    let strategy_b = strategy_at(&mut game_b);
+    assert!(strategy_a
+        .iter()
+        .zip(&strategy_b)
+        .any(|(a, b)| (a - b).abs() > 0.01));
+
+    // the hand is not in the range
+    let hand = (card_from_str("Ks").unwrap(), card_from_str("Kh").unwrap());
+    assert!(super::first_divergence(&game_a, &game_b, 0, hand, 0.01).is_err());
+}
+
+#[test]
+fn ev_by_board() {
+    let card_config = CardConfig {
+        range: [
+            "AA,QQ,T9s,AK".parse().unwrap(),
+            "KK,JJ,AQ,Q9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    // only checks are allowed before the river, so every board is reached
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 20, 0.0, false);
+
+    // the clubs and spades are isomorphic on the turn, but all boards are listed
+    assert!(!game.isomorphism_card_turn.is_empty());
+
+    let current_ev = compute_current_ev(&game);
+    for (player, &current_ev) in current_ev.iter().enumerate() {
+        let boards = game.ev_by_board(player).unwrap();
+        assert_eq!(boards.len(), 49 * 48);
+
+        let mut total = 0.0;
+        for &(turn, river, ev) in &boards {
+            let overlaps = |(c1, c2): (u8, u8)| [c1, c2].iter().any(|&c| c == turn || c == river);
+            let mut num_pairs = 0.0;
+            for (&h0, &w0) in game.private_cards(0).iter().zip(game.initial_weights(0)) {
+                for (&h1, &w1) in game.private_cards(1).iter().zip(game.initial_weights(1)) {
+                    let disjoint = h0.0 != h1.0 && h0.0 != h1.1 && h0.1 != h1.0 && h0.1 != h1.1;
+                    if disjoint && !overlaps(h0) && !overlaps(h1) {
+                        num_pairs += w0 as f64 * w1 as f64;
+                    }
+                }
+            }
+            let prob = num_pairs / game.num_combinations / (45.0 * 44.0);
+            total += prob * ev as f64;
+        }
+
+        assert!((total as f32 - current_ev).abs() < 1e-3);
+    }
+
+    assert!(game.ev_by_board(2).is_err());
+}
+
+#[test]
+fn export_and_import_strategy() {
+    let build = |enable_compression| {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(enable_compression);
+        game
+    };
+
+    let mut game = build(false);
+    solve(&mut game, 100, 0.0, false);
+    let bytes = game.export_strategy();
+
+    for enable_compression in [false, true] {
+        let mut imported = build(enable_compression);
+        imported.import_strategy(&bytes).unwrap();
+        finalize(&mut imported);
+
+        game.play(1);
+        imported.play(1);
+        let diff = game
+            .strategy()
+            .iter()
+            .zip(&imported.strategy())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max);
+        assert!(diff < 1e-3);
+
+        game.back_to_root();
+        imported.back_to_root();
+        game.cache_normalized_weights();
+        imported.cache_normalized_weights();
+        let ev = compute_average(&game.expected_values(0), game.normalized_weights(0));
+        let ev_imported =
+            compute_average(&imported.expected_values(0), imported.normalized_weights(0));
+        assert!((ev - ev_imported).abs() < 1e-2);
+    }
+
+    // the tree shape does not match
+    let mut other = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .turn_bets("75%", "")
+        .build()
+        .unwrap();
+    other.allocate_memory(false);
+    assert!(other.import_strategy(&bytes).is_err());
+
+    // the data is truncated
+    let mut truncated = build(false);
+    assert!(truncated
+        .import_strategy(&bytes[..bytes.len() - 4])
+        .is_err());
+}
+
+#[test]
+fn normalize_subtree() {
+    for enable_compression in [false, true] {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ,T9s,87s,A5s", "KK,JJ,AKs,KQo,Q9s")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(enable_compression);
+        solve(&mut game, 20, 0.0, false);
+
+        let river = card_from_str("2c").unwrap() as usize;
+        let lines = [vec![0], vec![0, 0, river], vec![1]];
+        let collect = |game: &mut PostFlopGame| {
+            lines
+                .iter()
+                .map(|line| {
+                    game.apply_history(line);
+                    game.strategy()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let strategies = collect(&mut game);
+        let exploitability = compute_exploitability(&game);
+        let root_raw = if enable_compression {
+            let root = game.root();
+            root.strategy_compressed()
+                .iter()
+                .map(|&v| v as f32)
+                .collect()
+        } else {
+            game.root().strategy().to_vec()
+        };
+
+        assert!(game.normalize_subtree(&[Action::Fold]).is_err());
+        game.normalize_subtree(&[Action::Check]).unwrap();
+
+        // the computed results are unchanged
+        for (a, b) in strategies.iter().zip(&collect(&mut game)) {
+            let diff = a
+                .iter()
+                .zip(b)
+                .map(|(x, y)| (x - y).abs())
+                .fold(0.0, f32::max);
+            assert!(diff < 1e-3);
+        }
+        assert!((exploitability - compute_exploitability(&game)).abs() < 1e-3);
+
+        // the nodes outside the subtree are not modified
+        let root = game.root();
+        if enable_compression {
+            let root_compressed = root.strategy_compressed().iter().map(|&v| v as f32);
+            assert!(root_compressed.eq(root_raw));
+        } else {
+            assert_eq!(root.strategy(), &root_raw[..]);
+        }
+
+        // the stored strategy of the subtree is normalized
+        if !enable_compression {
+            let node = root.children()[0].lock();
+            assert_eq!(node.prev_action, Action::Check);
+            let num_hands = game.num_private_hands(node.player());
+            let strategy = node.strategy();
+            for hand in 0..num_hands {
+                let sum = (0..node.num_actions())
+                    .map(|action| strategy[action * num_hands + hand])
+                    .sum::<f32>();
+                assert!((sum - 1.0).abs() < 1e-5);
+            }
+        }
+    }
+}
+
+#[test]
+fn expected_values_bb_and_pot_fraction() {
+    let mut game = GameBuilder::new()
+        .ranges("QQ-22,AQ-A2,KQ-K2", "QQ-22,AQ-A2,KQ-K2")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .river("7s")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+    game.cache_normalized_weights();
+
+    // check-only tree with the same ranges: each player wins a half of the pot
+    for player in 0..2 {
+        let weights = game.normalized_weights(player);
+        let ev = compute_average(&game.expected_values(player), weights);
+        let ev_bb = compute_average(&game.expected_values_bb(player, 20).unwrap(), weights);
+        let ev_pot = compute_average(&game.expected_values_pot_fraction(player), weights);
+        assert!((ev - 30.0).abs() < 1e-4);
+        assert!((ev_bb - 1.5).abs() < 1e-5);
+        assert!((ev_pot - 0.5).abs() < 1e-5);
+    }
+
+    assert!(game.expected_values_bb(0, 0).is_err());
+}
+
+#[test]
+fn expected_values_bb_with_big_blind() {
+    let mut game = GameBuilder::new()
+        .ranges("QQ-22,AQ-A2,KQ-K2", "QQ-22,AQ-A2,KQ-K2")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .river("7s")
+        .pot(60)
+        .stack(970)
+        .big_blind(20)
+        .river_bets("2.5bb", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(50)]
+    );
+
+    // lock both players to check: each player wins a half of the pot
+    let lock = vec![1.0; game.num_private_hands(0)]
+        .into_iter()
+        .chain(vec![0.0; game.num_private_hands(0)])
+        .collect::<Vec<_>>();
+    game.lock_current_strategy(&lock);
+    game.play(0);
+    let lock = vec![1.0; game.num_private_hands(1)]
+        .into_iter()
+        .chain(vec![0.0; game.num_private_hands(1)])
+        .collect::<Vec<_>>();
+    game.lock_current_strategy(&lock);
+    game.back_to_root();
+
+    solve(&mut game, 10, 0.0, false);
+    game.cache_normalized_weights();
+
+    for player in 0..2 {
+        let weights = game.normalized_weights(player);
+        let big_blind = game.tree_config().big_blind;
+        let ev_bb = compute_average(
+            &game.expected_values_bb(player, big_blind).unwrap(),
+            weights,
+        );
+        assert!((ev_bb - 1.5).abs() < 1e-4);
+    }
+
+    let mut game = GameBuilder::new()
+        .ranges("QQ-22", "QQ-22")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .river("7s")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+    // the big blind is not set
+    let big_blind = game.tree_config().big_blind;
+    assert!(game.expected_values_bb(0, big_blind).is_err());
+}
+
+#[test]
+fn flop_equity() {
+    // AA makes quads: OOP always wins
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let equity = game.flop_equity();
+    assert!((equity[0] - 1.0).abs() < 1e-5);
+    assert!(equity[1].abs() < 1e-5);
+
+    // the same as the average of the per-hand equities at the root
+    let card_config = CardConfig {
+        range: [
+            "QQ+,AKs,T9s".parse().unwrap(),
+            "JJ-88,AQo,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    game.cache_normalized_weights();
+
+    let equity = game.flop_equity();
+    assert!((equity[0] + equity[1] - 1.0).abs() < 1e-5);
+    for (player, &equity) in equity.iter().enumerate() {
+        let weights = game.normalized_weights(player);
+        let expected = compute_average(&game.equity(player), weights);
+        assert!((equity - expected).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn flop_advantage() {
+    // AA makes quads: OOP always wins
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let advantage = game.flop_advantage(0.8);
+    assert!((advantage.equity_advantage - 1.0).abs() < 1e-5);
+    assert!((advantage.nut_advantage - 1.0).abs() < 1e-5);
+
+    // no advantage with the same ranges
+    let card_config = CardConfig {
+        range: ["QQ+,AKs,T9s".parse().unwrap(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(TreeConfig {
+        initial_state: BoardState::Turn,
+        ..tree_config
+    })
+    .unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let advantage = game.flop_advantage(0.8);
+    assert!(advantage.equity_advantage.abs() < 1e-5);
+    assert!(advantage.nut_advantage.abs() < 1e-5);
+    assert_eq!(game.flop_advantage(0.0).nut_advantage, 0.0);
+
+    // sets and overpairs against underpairs
+    let card_config = CardConfig {
+        range: ["QQ+,99".parse().unwrap(), "JJ,88-77".parse().unwrap()],
+        ..game.card_config().clone()
+    };
+    let action_tree = ActionTree::new(game.tree_config().clone()).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let advantage = game.flop_advantage(0.8);
+    assert!(advantage.equity_advantage > 0.5);
+    assert!(advantage.nut_advantage > 0.5);
+}
+
+#[test]
+fn equity_histogram() {
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    // OOP always wins, so the whole mass is in the top bucket
+    let histogram_oop = game.equity_histogram(0, 10);
+    assert_eq!(histogram_oop.len(), 10);
+    assert!((histogram_oop[9] - 1.0).abs() < 1e-5);
+    assert!(histogram_oop[..9].iter().all(|&mass| mass == 0.0));
+
+    let histogram_ip = game.equity_histogram(1, 4);
+    assert!((histogram_ip[0] - 1.0).abs() < 1e-5);
+    assert!(histogram_ip[1..].iter().all(|&mass| mass == 0.0));
+}
+
+#[test]
+fn equity_realization() {
+    // check-only tree: the nut hands realize exactly their equity
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+    game.cache_normalized_weights();
+
+    let realization_oop = game.equity_realization(0);
+    let realization_ip = game.equity_realization(1);
+    for (&r, &w) in realization_oop.iter().zip(game.normalized_weights(0)) {
+        assert!(w == 0.0 || (r - 1.0).abs() < 1e-5);
+    }
+    assert!(realization_ip.iter().all(|&r| r == 0.0));
+
+    // river bluff-catcher against a polarized range
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(100)
+        .river_bets("100%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.01, false);
+    game.cache_normalized_weights();
+
+    // AA wins the pot and a called bet half of the time: 150 / (1.0 * 100) = 1.5
+    // KK is indifferent between calling and folding: 25 / (0.5 * 100) = 0.5
+    // QQ has no equity: 0.0
+    let realization_oop = game.equity_realization(0);
+    let realization_ip = game.equity_realization(1);
+    for (&(c1, _), &r) in game.private_cards(0).iter().zip(realization_oop.iter()) {
+        let expected = if c1 >> 2 == 12 { 1.5 } else { 0.0 };
+        assert!((r - expected).abs() < 1e-3);
+    }
+    assert!(realization_ip.iter().all(|&r| (r - 0.5).abs() < 1e-3));
+}
+
+#[test]
+fn range_ev_summary() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 80,
+        effective_stack: 960,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+    game.cache_normalized_weights();
+
+    // all-check tree: each player wins a half of the pot with C(47, 2) = 1081 combinations
+    for player in 0..2 {
+        let summary = game.range_ev_summary(player);
+        assert!((summary.avg_ev - 40.0).abs() < 1e-4);
+        assert!((summary.total_ev - 40.0 * 1081.0).abs() < 1e-1);
+        assert_eq!(summary.num_combos, 1081);
+        assert!((summary.reach_weighted_combos - 1081.0).abs() < 1e-3);
+    }
+
+    let mut game = GameBuilder::new()
+        .ranges("AA:0.5,KK", "JJ")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .river("7s")
+        .pot(80)
+        .stack(960)
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+    game.cache_normalized_weights();
+
+    let summary = game.range_ev_summary(0);
+    assert!((summary.avg_ev - 80.0).abs() < 1e-4);
+    assert!((summary.total_ev - 80.0 * 9.0).abs() < 1e-3);
+    assert_eq!(summary.num_combos, 12);
+    assert!((summary.reach_weighted_combos - 9.0).abs() < 1e-5);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn solve_with_num_threads() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ,T9s,87s", "KK,JJ,AKs,KQo")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game = build();
+    solve(&mut game, 50, 0.0, false);
+    let ev = compute_current_ev(&game);
+
+    let (ev_pool, num_threads) = with_num_threads(2, || {
+        let mut game = build();
+        solve(&mut game, 50, 0.0, false);
+        (compute_current_ev(&game), rayon::current_num_threads())
+    })
+    .unwrap();
+
+    assert_eq!(num_threads, 2);
+    assert!((ev[0] - ev_pool[0]).abs() < 1e-3);
+    assert!((ev[1] - ev_pool[1]).abs() < 1e-3);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn solve_in_thread_pool() {
+    let build = || {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ,T9s,87s", "KK,JJ,AKs,KQo")
+            .flop("Td9d6h")
+            .turn("Qc")
+            .pot(60)
+            .stack(970)
+            .turn_bets("50%", "")
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game = build();
+    solve(&mut game, 50, 0.0, false);
+    let ev = compute_current_ev(&game);
+
+    // build with 4 threads and solve with 1 thread
+    let mut game_pool = with_num_threads(4, build).unwrap();
+    solve_in_pool(&mut game_pool, 1, 50, 0.0, false).unwrap();
+    let ev_pool = compute_current_ev(&game_pool);
+
+    assert!((ev[0] - ev_pool[0]).abs() < 1e-3);
+    assert!((ev[1] - ev_pool[1]).abs() < 1e-3);
+}
+
+#[test]
+fn solve_flops() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AKs,T9s".parse().unwrap(),
+            "JJ,TT,AQs,KQs,87s".parse().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let flops = [
+        flop_from_str("Td9d6h").unwrap(),
+        flop_from_str("As7c2h").unwrap(),
+        flop_from_str("Ks8s3s").unwrap(),
+        [0, 0, 1], // invalid
+    ];
+
+    let games = super::solve_flops(&card_config, &tree_config, &flops, 100, 0.3, 0);
+    assert_eq!(games.len(), 4);
+    assert!(games[3].is_err());
+
+    for (game, flop) in games[..3].iter().zip(&flops) {
+        let game = game.as_ref().unwrap();
+        assert!(game.is_solved());
+        assert_eq!(game.card_config().flop, *flop);
+        assert!(compute_exploitability(game) <= 0.3);
+
+        // zero-sum without rake
+        let ev = compute_current_ev(game);
+        assert!((ev[0] + ev[1]).abs() < 1e-3);
+    }
+
+    // the river card must not be specified
+    let card_config = CardConfig {
+        river: card_from_str("2c").unwrap(),
+        ..card_config
+    };
+    let games = super::solve_flops(&card_config, &tree_config, &flops[..1], 100, 0.3, 0);
+    assert!(games[0].is_err());
+}
+
+#[test]
+fn sample_action() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s", "KK,JJ,AKs,KQo")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+    game.allocate_memory(false);
+
+    // deal As on the river
+    game.play(0);
+    game.play(0);
+    game.play(51);
+
+    // bet with the first hand, check with the second hand, and mix with the others
+    let num_hands = game.num_private_hands(0);
+    let mut locking = vec![0.5; 2 * num_hands];
+    locking[0] = 0.0;
+    locking[num_hands] = 1.0;
+    locking[1] = 1.0;
+    locking[num_hands + 1] = 0.0;
+    game.lock_current_strategy(&locking);
+
+    assert_eq!(game.sample_action(0, 0.0), Ok(1));
+    assert_eq!(game.sample_action(0, 0.999), Ok(1));
+    assert_eq!(game.sample_action(1, 0.999), Ok(0));
+    assert_eq!(game.sample_action(2, 0.25), Ok(0));
+    assert_eq!(game.sample_action(2, 0.75), Ok(1));
+    assert!(game.sample_action(num_hands, 0.5).is_err());
+
+    // AsAx is blocked by the river card
+    let blocked = game
+        .private_cards(0)
+        .iter()
+        .position(|&(_, c2)| c2 == 51)
+        .unwrap();
+    assert!(game.sample_action(blocked, 0.5).is_err());
+}
+
+#[test]
+fn runouts() {
+    // clubs and spades are isomorphic
+    let game = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s", "KK,JJ,AKs,KQo")
+        .flop("Td9d6h")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    let runouts = game.runouts();
+    let weight_sum = runouts.iter().map(|r| r.2).sum::<f32>();
+    assert_eq!(weight_sum, 49.0 * 48.0);
+    assert!(runouts.len() < 49 * 48);
+
+    // Ac is the representative of As
+    let ac_2c = runouts.iter().find(|r| (r.0, r.1) == (48, 0)).unwrap();
+    assert_eq!(ac_2c.2, 2.0);
+    assert!(runouts.iter().all(|r| r.0 != 51));
+
+    // Ah-Ac and Ah-As are isomorphic, but Ah-Ad is not isomorphic to any other runout
+    let ah_ac = runouts.iter().find(|r| (r.0, r.1) == (50, 48)).unwrap();
+    let ah_ad = runouts.iter().find(|r| (r.0, r.1) == (50, 49)).unwrap();
+    assert_eq!(ah_ac.2, 2.0);
+    assert_eq!(ah_ad.2, 1.0);
+
+    // turn is given
+    let game = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s", "KK,JJ,AKs,KQo")
+        .flop("Td9d6h")
+        .turn("Qc")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    let runouts = game.runouts();
+    assert!(runouts.iter().all(|r| r.0 == 40));
+    assert_eq!(runouts.iter().map(|r| r.2).sum::<f32>(), 48.0);
+    assert_eq!(runouts.len(), 48);
+}
+
+#[test]
+fn chance_actions() {
+    // clubs, hearts, and spades are isomorphic
+    let game = GameBuilder::new()
+        .ranges("AA,QQ,T9s,87s", "KK,JJ,AKs,KQo")
+        .flop("Td9d6d")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    let line = [Action::Check, Action::Check];
+    let turns = game.chance_actions(&line).unwrap();
+    assert_eq!(turns.len(), 49);
+    assert_eq!(turns.iter().filter(|t| t.1.is_none()).count(), 23);
+
+    for &(card, representative) in &turns {
+        if let Some(representative) = representative {
+            assert_eq!(card >> 2, representative >> 2);
+            assert!(turns.contains(&(representative, None)));
+        }
+    }
+
+    // Ac is the representative of Ah and As
+    let ac = card_from_str("Ac").unwrap();
+    let ah = card_from_str("Ah").unwrap();
+    assert!(turns.contains(&(ac, None)));
+    assert!(turns.contains(&(ah, Some(ac))));
+
+    // hearts and spades are isomorphic after the Ac turn
+    let line = [
+        Action::Check,
+        Action::Check,
+        Action::Chance(ac),
+        Action::Check,
+        Action::Check,
+    ];
+    let rivers = game.chance_actions(&line).unwrap();
+    assert_eq!(rivers.len(), 48);
+    assert!(rivers.contains(&(card_from_str("Kc").unwrap(), None)));
+    assert!(rivers.contains(&(card_from_str("Kh").unwrap(), None)));
+    assert!(rivers.contains(&(
+        card_from_str("Ks").unwrap(),
+        Some(card_from_str("Kh").unwrap())
+    )));
+
+    // isomorphic cards cannot be used in the line
+    let line = [Action::Check, Action::Check, Action::Chance(ah)];
+    assert!(game.chance_actions(&line).is_err());
+    assert!(game.chance_actions(&[Action::Check]).is_err());
+}
+
+#[test]
+fn turn_river_order() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "JJ-88,KQ,QJs,A5s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        use_isomorphism: false,
+        ..Default::default()
+    };
+
+    // only checks are allowed before the river, so the river subgames of the same board are
+    // identical regardless of the order of the turn and river cards
+    let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    let (x, y) = (card_from_str("Qc").unwrap(), card_from_str("2s").unwrap());
+    let mut river_root = |turn: u8, river: u8| {
+        game.apply_history(&[0, 0, turn as usize, 0, 0, river as usize]);
+        game.cache_normalized_weights();
+        (
+            game.strategy(),
+            game.expected_values(0),
+            game.expected_values(1),
+            game.normalized_weights(0).to_vec(),
+        )
+    };
+
+    let xy = river_root(x, y);
+    let yx = river_root(y, x);
+    let is_close = |a: &[f32], b: &[f32]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4);
+    assert!(is_close(&xy.0, &yx.0));
+    assert!(is_close(&xy.1, &yx.1));
+    assert!(is_close(&xy.2, &yx.2));
+    assert_eq!(xy.3, yx.3);
+
+    // both orders are dealt as separate runouts with the same probability
+    let prefix = [Action::Check, Action::Check];
+    let turn_cards = game.chance_actions(&prefix).unwrap();
+    assert!(turn_cards.contains(&(x, None)) && turn_cards.contains(&(y, None)));
+    let river_cards = |turn: u8| {
+        let prefix = [prefix.as_slice(), &[Action::Chance(turn)], &prefix].concat();
+        game.chance_actions(&prefix).unwrap()
+    };
+    assert!(river_cards(x).contains(&(y, None)));
+    assert!(river_cards(y).contains(&(x, None)));
+    assert_eq!(river_cards(x).len(), river_cards(y).len());
+}
+
+#[test]
+fn cum_regret_at() {
+    for enable_compression in [false, true] {
+        let mut game = GameBuilder::new()
+            .ranges("AA,QQ", "KK")
+            .flop("Td9d6h")
+            .turn("2c")
+            .river("3s")
+            .pot(60)
+            .stack(970)
+            .river_bets("50%", "")
+            .build()
+            .unwrap();
+
+        assert!(game.cum_regret_at(&[]).is_err());
+        game.allocate_memory(enable_compression);
+
+        let num_elements = 2 * game.num_private_hands(0);
+        let regrets = game.cum_regret_at(&[]).unwrap();
+        assert_eq!(regrets.len(), num_elements);
+        assert!(regrets.iter().all(|&r| r == 0.0));
+
+        for t in 0..10 {
+            solve_step(&game, t);
+        }
+
+        let regrets = game.cum_regret_at(&[]).unwrap();
+        assert!(regrets.iter().all(|r| r.is_finite()));
+        assert!(regrets.iter().any(|&r| r != 0.0));
+        assert!(game.cum_regret_at(&[Action::Check]).is_ok());
+        assert!(game.cum_regret_at(&[Action::Check, Action::Check]).is_err());
+        assert!(game.cum_regret_at(&[Action::Fold]).is_err());
+
+        finalize(&mut game);
+        assert!(game.cum_regret_at(&[]).is_err());
+    }
+}
+
+#[test]
+fn strategy_at_card() {
+    // clubs and diamonds are isomorphic
+    let mut game = GameBuilder::new()
+        .ranges("AQs,KJs,T9s,A5s,99", "AKs,QJs,JTs,65s,QQ")
+        .flop("8c7c8d")
+        .turn("7d")
+        .pot(60)
+        .stack(970)
+        .river_bets("50%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    let prefix = [Action::Check, Action::Check];
+    let (kc, kd) = (card_from_str("Kc").unwrap(), card_from_str("Kd").unwrap());
+    let (iso_card, repr_card) = match game.chance_actions(&prefix).unwrap() {
+        actions if actions.contains(&(kc, Some(kd))) => (kc, kd),
+        actions if actions.contains(&(kd, Some(kc))) => (kd, kc),
+        _ => panic!("Kc and Kd must be isomorphic"),
+    };
 
-    assert!((root_ev_oop - 0.0).abs() < 1e-4);
-    assert!((root_ev_ip - 57.0).abs() < 1e-4);
+    let strategy_iso = game.strategy_at_card(&prefix, iso_card).unwrap();
+    let strategy_repr = game.strategy_at_card(&prefix, repr_card).unwrap();
+
+    // same as the strategy obtained by playing the isomorphic card
+    game.apply_history(&[0, 0, iso_card as usize]);
+    assert_eq!(game.strategy(), strategy_iso);
+
+    // the flush-completing hand in the isomorphic suit takes the representative's strategy
+    let num_hands = game.num_private_hands(0);
+    let suit = |card: u8| card & 3;
+    let (iso_suit, repr_suit) = (suit(iso_card), suit(repr_card));
+    for (rank1, rank2) in [(12, 10), (12, 3)] {
+        let i_iso = game.hand_index(0, 4 * rank1 + iso_suit, 4 * rank2 + iso_suit);
+        let i_repr = game.hand_index(0, 4 * rank1 + repr_suit, 4 * rank2 + repr_suit);
+        let (i_iso, i_repr) = (i_iso.unwrap(), i_repr.unwrap());
+        for action in 0..strategy_iso.len() / num_hands {
+            assert_eq!(
+                strategy_iso[action * num_hands + i_iso],
+                strategy_repr[action * num_hands + i_repr]
+            );
+        }
+    }
+
+    assert!(game
+        .strategy_at_card(&prefix, card_from_str("8c").unwrap())
+        .is_err());
+    assert!(game.strategy_at_card(&[Action::Check], kc).is_err());
 }
 
 #[test]
-fn always_tie() {
+fn outcome_matrix() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
     let card_config = CardConfig {
-        range: ["AA".parse().unwrap(), "AA".parse().unwrap()],
-        flop: flop_from_str("2c6dTh").unwrap(),
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
         ..Default::default()
     };
 
@@ -632,218 +5486,505 @@ fn always_tie() {
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let (turn, river) = (card_from_str("2s").unwrap(), card_from_str("3c").unwrap());
+    let (win, tie, lose) = game.outcome_matrix(turn, river, 0);
+    let win_sum = win.iter().sum::<f32>();
+    assert!(win_sum > 0.0);
+    assert_eq!(tie.iter().sum::<f32>(), 0.0);
+    assert_eq!(lose.iter().sum::<f32>(), 0.0);
+
+    let (win_ip, tie_ip, lose_ip) = game.outcome_matrix(turn, river, 1);
+    assert_eq!(win_ip.iter().sum::<f32>(), 0.0);
+    assert_eq!(tie_ip.iter().sum::<f32>(), 0.0);
+    assert!((lose_ip.iter().sum::<f32>() - win_sum).abs() < 1e-3);
+
+    // hands overlapping with the turn or the river have zero masses
+    let deuces = game
+        .hand_index(
+            1,
+            card_from_str("2s").unwrap(),
+            card_from_str("2h").unwrap(),
+        )
+        .unwrap();
+    assert_eq!(lose_ip[deuces], 0.0);
+
+    // each QQ combo ties exactly one non-overlapping QQ combo
+    let game = GameBuilder::new()
+        .ranges("QQ", "QQ")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    let (win, tie, lose) = game.outcome_matrix(
+        card_from_str("2c").unwrap(),
+        card_from_str("3s").unwrap(),
+        0,
+    );
+    assert!(win.iter().all(|&w| w == 0.0));
+    assert!(tie.iter().all(|&t| t == 1.0));
+    assert!(lose.iter().all(|&l| l == 0.0));
+}
 
-    game.allocate_memory(false);
-    finalize(&mut game);
+#[test]
+fn pot_share() {
+    let game = GameBuilder::new()
+        .ranges("AA,QQ", "KK,JJ")
+        .flop("AcAdKh")
+        .pot(60)
+        .stack(970)
+        .build()
+        .unwrap();
+
+    let (turn, river) = (card_from_str("5s").unwrap(), card_from_str("7d").unwrap());
+    let hand = |player, hand: &str| {
+        let card1 = card_from_str(&hand[0..2]).unwrap();
+        let card2 = card_from_str(&hand[2..4]).unwrap();
+        game.hand_index(player, card1, card2).unwrap()
+    };
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
-    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    // quad aces always win, and queens beat the six combos of JJ but lose to the three of KK
+    let pot_share = game.pot_share(turn, river, 0);
+    assert!((pot_share[hand(0, "AhAs")] - 1.0).abs() < 1e-6);
+    assert!((pot_share[hand(0, "QcQd")] - 6.0 / 9.0).abs() < 1e-6);
 
-    assert!((root_equity_oop - 0.5).abs() < 1e-5);
-    assert!((root_equity_ip - 0.5).abs() < 1e-5);
-    assert!((root_ev_oop - 30.0).abs() < 1e-4);
-    assert!((root_ev_ip - 30.0).abs() < 1e-4);
+    let pot_share_ip = game.pot_share(turn, river, 1);
+    assert!((pot_share_ip[hand(1, "KcKd")] - 6.0 / 7.0).abs() < 1e-6);
+    assert!((pot_share_ip[hand(1, "JcJd")] - 0.0).abs() < 1e-6);
 }
 
 #[test]
-fn always_tie_raked() {
-    let card_config = CardConfig {
-        range: ["AA".parse().unwrap(), "AA".parse().unwrap()],
-        flop: flop_from_str("2c6dTh").unwrap(),
-        ..Default::default()
+fn disable_isomorphism() {
+    let build = |use_isomorphism| {
+        let card_config = CardConfig {
+            range: [
+                "AA,QQ,T9s,87s,AKs".parse().unwrap(),
+                "KK,JJ,AKs,KQo,JTs".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6d").unwrap(),
+            turn: card_from_str("2h").unwrap(),
+            use_isomorphism,
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 970,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.0, false);
+        game
     };
 
-    let tree_config = TreeConfig {
-        starting_pot: 60,
-        effective_stack: 970,
-        rake_rate: 0.05,
-        rake_cap: 10.0,
-        ..Default::default()
+    let mut game = build(true);
+    let mut game_no_iso = build(false);
+
+    // clubs and spades are isomorphic
+    for game in [&mut game, &mut game_no_iso] {
+        game.play(0);
+        game.play(0);
+    }
+    assert_eq!(game.available_actions().len(), 35);
+    assert_eq!(game_no_iso.available_actions().len(), 48);
+
+    let ev = compute_current_ev(&game);
+    let ev_no_iso = compute_current_ev(&game_no_iso);
+    assert!((ev[0] - ev_no_iso[0]).abs() < 1e-3);
+    assert!((ev[1] - ev_no_iso[1]).abs() < 1e-3);
+}
+
+#[test]
+fn turn_abstraction() {
+    let build = |turn_abstraction| {
+        let card_config = CardConfig {
+            range: [
+                "AA,QQ,T9s,87s,AKs".parse().unwrap(),
+                "KK,JJ,AKs,KQo,JTs".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn_abstraction,
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 970,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.0, false);
+        game
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let game = build(None);
+    let game_bucketed = build(Some(TurnBucketing { rank_group_size: 2 }));
 
-    game.allocate_memory(false);
-    finalize(&mut game);
+    let line = [Action::Check, Action::Check];
+    let num_turns = |game: &PostFlopGame| {
+        let turns = game.chance_actions(&line).unwrap();
+        assert_eq!(turns.len(), 49);
+        turns.iter().filter(|t| t.1.is_none()).count()
+    };
+    assert!(num_turns(&game_bucketed) < num_turns(&game));
+    assert!(game_bucketed.memory_usage().0 < game.memory_usage().0);
+
+    // the weights of the runouts are preserved
+    let weight_sum = game_bucketed.runouts().iter().map(|r| r.2).sum::<f32>();
+    assert_eq!(weight_sum, 49.0 * 48.0);
+
+    // the error is within 2% of the pot in this spot
+    let ev = compute_current_ev(&game);
+    let ev_bucketed = compute_current_ev(&game_bucketed);
+    assert!((ev[0] - ev_bucketed[0]).abs() < 60.0 * 0.02);
+    assert!((ev[1] - ev_bucketed[1]).abs() < 60.0 * 0.02);
+
+    // a merged turn card is replaced with the representative card
+    let mut game_bucketed = game_bucketed;
+    let turns = game_bucketed.chance_actions(&line).unwrap();
+    let &(merged, representative) = turns.iter().find(|t| t.1.is_some()).unwrap();
+    game_bucketed.play(0);
+    game_bucketed.play(0);
+    game_bucketed.play(merged as usize);
+    assert_eq!(game_bucketed.current_board()[3], representative.unwrap());
+}
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+#[test]
+fn river_abstraction() {
+    let build = |river_abstraction| {
+        let card_config = CardConfig {
+            range: [
+                "AA,QQ,T9s,87s,AKs".parse().unwrap(),
+                "KK,JJ,AKs,KQo,JTs".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            river_abstraction,
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 970,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 200, 0.0, false);
+        game
+    };
 
-    assert!((root_ev_oop - 28.5).abs() < 1e-4);
-    assert!((root_ev_ip - 28.5).abs() < 1e-4);
+    let game = build(None);
+    let game_bucketed = build(Some(RiverBucketing { rank_group_size: 2 }));
+
+    let line = [Action::Check, Action::Check];
+    let num_rivers = |game: &PostFlopGame| {
+        let rivers = game.chance_actions(&line).unwrap();
+        assert_eq!(rivers.len(), 48);
+        rivers.iter().filter(|r| r.1.is_none()).count()
+    };
+    assert!(num_rivers(&game_bucketed) < num_rivers(&game));
+    assert!(game_bucketed.memory_usage().0 < game.memory_usage().0);
+
+    // the weights of the runouts are preserved
+    let weight_sum = game_bucketed.runouts().iter().map(|r| r.2).sum::<f32>();
+    assert_eq!(weight_sum, 48.0);
+
+    // the error is within 3% of the pot in this spot
+    let ev = compute_current_ev(&game);
+    let ev_bucketed = compute_current_ev(&game_bucketed);
+    assert!((ev[0] - ev_bucketed[0]).abs() < 60.0 * 0.03);
+    assert!((ev[1] - ev_bucketed[1]).abs() < 60.0 * 0.03);
+
+    // a merged river card is replaced with the representative card
+    let mut game_bucketed = game_bucketed;
+    let rivers = game_bucketed.chance_actions(&line).unwrap();
+    let &(merged, representative) = rivers.iter().find(|r| r.1.is_some()).unwrap();
+    game_bucketed.play(0);
+    game_bucketed.play(0);
+    game_bucketed.play(merged as usize);
+    assert_eq!(game_bucketed.current_board()[4], representative.unwrap());
 }
 
 #[test]
-fn no_assignment() {
-    let card_config = CardConfig {
-        range: ["TT".parse().unwrap(), "TT".parse().unwrap()],
-        flop: flop_from_str("Td9d6h").unwrap(),
-        ..Default::default()
+fn precompute_strength() {
+    let build = |precompute_strength| {
+        let card_config = CardConfig {
+            range: [
+                "AA,QQ,T9s,87s,AKs".parse().unwrap(),
+                "KK,JJ,AKs,KQo,JTs".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            precompute_strength,
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 970,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 50, 0.0, false);
+        game
+    };
+
+    let mut game = build(true);
+    let mut game_on_demand = build(false);
+    assert!(game_on_demand.memory_usage().0 < game.memory_usage().0);
+
+    let ev = compute_current_ev(&game);
+    let ev_on_demand = compute_current_ev(&game_on_demand);
+    assert!((ev[0] - ev_on_demand[0]).abs() < 1e-4);
+    assert!((ev[1] - ev_on_demand[1]).abs() < 1e-4);
+
+    let river = card_from_str("2s").unwrap() as usize;
+    for game in [&mut game, &mut game_on_demand] {
+        game.play(0);
+        game.play(0);
+        game.play(river);
+        game.cache_normalized_weights();
+    }
+    assert_eq!(game.equity(0), game_on_demand.equity(0));
+    assert_eq!(game.expected_values(1), game_on_demand.expected_values(1));
+}
+
+#[test]
+fn ev_breakdown() {
+    let build = |river_bet_sizes: &str| {
+        let card_config = CardConfig {
+            range: [
+                "AA,QQ,T9s,87s,AKs".parse().unwrap(),
+                "KK,JJ,AKs,KQo,JTs".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            river: card_from_str("2s").unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 970,
+            river_bet_sizes: [
+                (river_bet_sizes, "").try_into().unwrap(),
+                Default::default(),
+            ],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.0, false);
+        game.cache_normalized_weights();
+        game
+    };
+
+    // without bets, every hand only collects chips at showdown
+    let game = build("");
+    let ev = game.expected_values(0);
+    for (hand, &ev) in ev.iter().enumerate() {
+        let breakdown = game.ev_breakdown(0, hand).unwrap();
+        assert_eq!(breakdown.fold_ev, 0.0);
+        assert!((breakdown.total - ev).abs() < 1e-3);
+    }
+
+    // with bets, the parts sum up to the expected value of the hand
+    let game = build("50%");
+    for player in 0..2 {
+        let ev = game.expected_values(player);
+        for (hand, &ev) in ev.iter().enumerate() {
+            let breakdown = game.ev_breakdown(player, hand).unwrap();
+            assert!((breakdown.fold_ev + breakdown.showdown_ev - breakdown.total).abs() < 1e-4);
+            assert!((breakdown.total - ev).abs() < 1e-3);
+        }
+    }
+
+    // the bets of OOP are sometimes folded to
+    let num_hands = game.private_cards(0).len();
+    assert!((0..num_hands).any(|hand| game.ev_breakdown(0, hand).unwrap().fold_ev > 0.0));
+
+    assert!(game.ev_breakdown(0, game.private_cards(0).len()).is_err());
+    assert!(game.ev_breakdown(2, 0).is_err());
+}
+
+#[test]
+fn max_depth_street() {
+    let build = |max_depth_street| {
+        let card_config = CardConfig {
+            range: [
+                "QQ-55,AJs+,KQs,T9s,87s,A5s".parse().unwrap(),
+                "AA-22,AQs+,KQs,JTs,T9s,98s".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+        let bet_sizes = BetSizeCandidates::try_from(("50%", "")).unwrap();
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 970,
+            flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+            turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            max_depth_street,
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.0, false);
+        game
+    };
+
+    let mut game = build(None);
+    let mut game_capped = build(Some(BoardState::River));
+    assert!(game_capped.memory_usage().0 < game.memory_usage().0);
+
+    // the gross tendencies of the flop strategy are preserved: most hands of IP take the same
+    // main action (call or fold) facing a bet
+    let call_strategy = |game: &mut PostFlopGame| {
+        game.play(1);
+        let num_hands = game.private_cards(1).len();
+        let call = game.strategy()[num_hands..].to_vec();
+        game.back_to_root();
+        call
     };
+    let call = call_strategy(&mut game);
+    let call_capped = call_strategy(&mut game_capped);
+    let num_agree = call
+        .iter()
+        .zip(&call_capped)
+        .filter(|&(&a, &b)| (a > 0.5) == (b > 0.5))
+        .count();
+    assert!(num_agree * 4 >= call.len() * 3);
+
+    // no actions are taken on the river
+    game_capped.play(0);
+    game_capped.play(0);
+    game_capped.play(usize::MAX);
+    game_capped.play(0);
+    game_capped.play(0);
+    assert!(game_capped.is_chance_node());
+    game_capped.play(usize::MAX);
+    assert!(game_capped.is_terminal_node());
 
     let tree_config = TreeConfig {
         starting_pot: 60,
         effective_stack: 970,
+        max_depth_street: Some(BoardState::Flop),
         ..Default::default()
     };
-
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let game = PostFlopGame::with_config(card_config, action_tree);
-    assert!(game.is_err());
+    assert!(ActionTree::new(tree_config).is_err());
 }
 
 #[test]
-fn remove_lines() {
-    use crate::bet_size::BetSizeCandidates;
+fn updated_weights() {
     let card_config = CardConfig {
-        range: ["TT+,AKo,AQs+".parse().unwrap(), "AA".parse().unwrap()],
-        flop: flop_from_str("2c6dTh").unwrap(),
+        range: ["AA,KK".parse().unwrap(), "QQ".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
         ..Default::default()
     };
 
-    // Simple tree: force checks on flop, and only use 1/2 pot bets on turn and river
     let tree_config = TreeConfig {
-        starting_pot: 60,
-        effective_stack: 970,
-        turn_bet_sizes: [
-            BetSizeCandidates::try_from(("50%", "")).unwrap(),
-            Default::default(),
-        ],
-        river_bet_sizes: [
-            BetSizeCandidates::try_from(("50%", "")).unwrap(),
-            Default::default(),
-        ],
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
 
-    let lines = vec![
-        vec![
-            Action::Check,
-            Action::Check,
-            Action::Chance(2),
-            Action::Check,
-        ],
-        vec![
-            Action::Check,
-            Action::Check,
-            Action::Chance(2),
-            Action::Bet(30),
-            Action::Call,
-            Action::Chance(3),
-            Action::Bet(60),
-        ],
-    ];
-
-    let res = game.remove_lines(&lines);
-    assert!(res.is_ok());
-
     game.allocate_memory(false);
 
-    // Check that the turn line is removed
-    game.apply_history(&[0, 0, 2]);
-    assert_eq!(game.available_actions(), vec![Action::Bet(30)]);
-
-    // Check that other turn lines are correct
-    game.apply_history(&[0, 0, 3]);
-    assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(30)]
-    );
-
-    // Check that the river line is removed
-    game.apply_history(&[0, 0, 2, 0, 1, 3]);
-    assert_eq!(game.available_actions(), vec![Action::Check]);
-
-    // Check that other river lines are correct
-    game.apply_history(&[0, 0, 2, 0, 1, 4]);
-    assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(60)]
-    );
-
-    game.apply_history(&[0, 0, 3, 1, 1, 4]);
-    assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(60)]
-    );
+    // AA always checks, KK bets 25%
+    let num_hands = game.private_cards(0).len();
+    let mut locking = vec![0.0; 2 * num_hands];
+    for (hand, &(c1, _)) in game.private_cards(0).iter().enumerate() {
+        if c1 >> 2 == 12 {
+            locking[hand] = 1.0;
+        } else {
+            locking[hand] = 0.75;
+            locking[num_hands + hand] = 0.25;
+        }
+    }
+    game.lock_current_strategy(&locking);
+
+    let check_weights = game.updated_weights(0);
+    let bet_weights = game.updated_weights(1);
+    for hand in 0..num_hands {
+        assert!((check_weights[hand] - locking[hand]).abs() < 1e-6);
+        assert!((bet_weights[hand] - locking[num_hands + hand]).abs() < 1e-6);
+    }
 
-    solve(&mut game, 10, 0.05, false);
+    game.play(1);
+    assert_eq!(game.weights(0), bet_weights);
 }
 
 #[test]
-fn isomorphism_monotone() {
-    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
-    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
-
+fn set_initial_weights() {
     let card_config = CardConfig {
-        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
-        flop: flop_from_str("QhJh2h").unwrap(),
+        range: ["AA,KK".parse().unwrap(), "QQ".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        starting_pot: 100,
-        effective_stack: 100,
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 10,
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
 
-    game.allocate_memory(false);
-    finalize(&mut game);
-
-    let mut check = |history: &[usize],
-                     expected_turn_swap: Option<u8>,
-                     expected_river_swap: Option<(u8, u8)>| {
-        game.apply_history(history);
-        game.cache_normalized_weights();
-        let weights = game.normalized_weights(0);
-        let ev = game.expected_values(0);
-        weights.iter().zip(ev.iter()).for_each(|(&w, &v)| {
-            assert!(!(w > 0.0 && v == 50.0));
-        });
-        assert_eq!(game.turn_swap, expected_turn_swap);
-        assert_eq!(game.river_swap, expected_river_swap);
-    };
-
-    check(&[0, 0, 4], None, None);
-    check(&[0, 0, 5], Some(1), None);
-    check(&[0, 0, 6], None, None);
-    check(&[0, 0, 7], Some(3), None);
-
-    check(&[0, 0, 4, 0, 0, 8], None, None);
-    check(&[0, 0, 4, 0, 0, 9], None, None);
-    check(&[0, 0, 4, 0, 0, 10], None, None);
-    check(&[0, 0, 4, 0, 0, 11], None, Some((0, 3)));
+    assert!(game.set_initial_weights(0, &[1.0; 6]).is_err());
+    assert!(game.set_initial_weights(0, &[1.5; 12]).is_err());
+    assert!(game.set_initial_weights(0, &[0.0; 12]).is_err());
 
-    check(&[0, 0, 5, 0, 0, 8], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 9], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 10], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 11], Some(1), Some((1, 3)));
+    // AA only
+    let weights = game
+        .private_cards(0)
+        .iter()
+        .map(|&(c1, _)| if c1 >> 2 == 12 { 1.0 } else { 0.0 })
+        .collect::<Vec<_>>();
+    game.set_initial_weights(0, &weights).unwrap();
+    assert_eq!(game.weights(0), weights);
 
-    check(&[0, 0, 6, 0, 0, 8], None, None);
-    check(&[0, 0, 6, 0, 0, 9], None, Some((2, 1)));
-    check(&[0, 0, 6, 0, 0, 10], None, None);
-    check(&[0, 0, 6, 0, 0, 11], None, Some((2, 3)));
+    game.allocate_memory(false);
+    finalize(&mut game);
 
-    check(&[0, 0, 7, 0, 0, 8], Some(3), Some((3, 1)));
-    check(&[0, 0, 7, 0, 0, 9], Some(3), None);
-    check(&[0, 0, 7, 0, 0, 10], Some(3), None);
-    check(&[0, 0, 7, 0, 0, 11], Some(3), None);
+    game.cache_normalized_weights();
+    let equity_oop = compute_average(&game.equity(0), game.normalized_weights(0));
+    let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+    assert!((equity_oop - 1.0).abs() < 1e-5);
+    assert!((ev_oop - 20.0).abs() < 1e-4);
 }
 
 #[test]
@@ -853,6 +5994,7 @@ fn node_locking() {
         flop: flop_from_str("2s3h4d").unwrap(),
         turn: card_from_str("6c").unwrap(),
         river: card_from_str("7c").unwrap(),
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
@@ -907,6 +6049,49 @@ fn node_locking() {
     assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
 }
 
+#[test]
+fn node_locking_unlock_all() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.lock_current_strategy(&[1.0, 1.0, 0.0, 0.0]); // always check
+    game.play(1); // all-in
+    game.lock_current_strategy(&[0.25, 0.75]); // 25% fold, 75% call
+    game.back_to_root();
+
+    solve(&mut game, 100, 0.0, false);
+    assert_eq!(game.strategy(), vec![1.0, 1.0, 0.0, 0.0]);
+
+    game.unlock_all_strategies();
+    assert!(game.current_locking_strategy().is_none());
+    game.play(1);
+    assert!(game.current_locking_strategy().is_none());
+    game.back_to_root();
+
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.0, false);
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+}
+
 #[test]
 fn node_locking_partial() {
     let card_config = CardConfig {
@@ -914,6 +6099,7 @@ fn node_locking_partial() {
         flop: flop_from_str("2s3h4d").unwrap(),
         turn: card_from_str("6c").unwrap(),
         river: card_from_str("7c").unwrap(),
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
@@ -1247,3 +6433,49 @@ fn solve_pio_preset_raked() {
     assert!((root_ev_oop - 95.57).abs() < 0.2);
     assert!((root_ev_ip - 66.98).abs() < 0.2);
 }
+
+#[test]
+fn size_addition_value() {
+    let mut game = GameBuilder::new()
+        .ranges("AA,QQ", "KK")
+        .flop("Td9d6h")
+        .turn("2c")
+        .river("3s")
+        .pot(100)
+        .stack(1000)
+        .river_bets("10%", "")
+        .build()
+        .unwrap();
+
+    game.allocate_memory(false);
+    assert!(game
+        .size_addition_value(&[], 0, BetSize::PotRelative(1.0), 10, None)
+        .is_err());
+
+    solve(&mut game, 300, 0.0, false);
+
+    // a polarized range gains by betting larger than 10% of the pot
+    let gain = game
+        .size_addition_value(&[], 0, BetSize::PotRelative(1.0), 300, None)
+        .unwrap();
+    assert!(gain > 5.0);
+
+    // OOP checks only with QQ, which never calls a bet of KK
+    let gain = game
+        .size_addition_value(&[Action::Check], 1, BetSize::PotRelative(1.0), 300, None)
+        .unwrap();
+    assert!(gain.abs() < 1e-3);
+
+    // the size already exists at the node
+    assert!(game
+        .size_addition_value(&[], 0, BetSize::PotRelative(0.1), 10, None)
+        .is_err());
+    assert!(game
+        .size_addition_value(&[Action::Fold], 0, BetSize::PotRelative(1.0), 10, None)
+        .is_err());
+
+    // the game with the size added does not fit in the memory limit
+    assert!(game
+        .size_addition_value(&[], 0, BetSize::PotRelative(1.0), 10, Some(1))
+        .is_err());
+}
@@ -18,12 +18,17 @@ fn keep_n_msb(mut x: i32, n: i32) -> i32 {
 }
 
 #[inline]
-fn find_straight(rankset: i32) -> i32 {
-    const WHEEL: i32 = 0b1_0000_0000_1111;
+fn find_straight(rankset: i32, short_deck: bool) -> i32 {
+    // A-2-3-4-5 (or A-6-7-8-9 in short deck)
+    let wheel: i32 = if short_deck {
+        0b1_0000_1111_0000
+    } else {
+        0b1_0000_0000_1111
+    };
     let is_straight = rankset & (rankset << 1) & (rankset << 2) & (rankset << 3) & (rankset << 4);
     if is_straight != 0 {
         keep_n_msb(is_straight, 1)
-    } else if (rankset & WHEEL) == WHEEL {
+    } else if (rankset & wheel) == wheel {
         1 << 3
     } else {
         0
@@ -51,10 +56,38 @@ impl Hand {
 
     #[inline]
     pub fn evaluate(&self) -> u16 {
-        HAND_TABLE.binary_search(&self.evaluate_internal()).unwrap() as u16
+        HAND_TABLE
+            .binary_search(&self.evaluate_internal(false))
+            .unwrap() as u16
+    }
+
+    /// Evaluates the hand with the short-deck rules: a flush beats a full house, and A-6-7-8-9 is
+    /// the lowest straight.
+    #[inline]
+    pub fn evaluate_short_deck(&self) -> u16 {
+        let flush_begin = HAND_TABLE.partition_point(|&x| x < 5 << 26);
+        let full_house_begin = HAND_TABLE.partition_point(|&x| x < 6 << 26);
+        let full_house_end = HAND_TABLE.partition_point(|&x| x < 7 << 26);
+        let num_flushes = full_house_begin - flush_begin;
+        let num_full_houses = full_house_end - full_house_begin;
+
+        let index = HAND_TABLE
+            .binary_search(&self.evaluate_internal(true))
+            .unwrap();
+
+        // swap the ranges of flushes and full houses
+        let index = if (flush_begin..full_house_begin).contains(&index) {
+            index + num_full_houses
+        } else if (full_house_begin..full_house_end).contains(&index) {
+            index - num_flushes
+        } else {
+            index
+        };
+
+        index as u16
     }
 
-    fn evaluate_internal(&self) -> i32 {
+    fn evaluate_internal(&self, short_deck: bool) -> i32 {
         let mut rankset = 0i32;
         let mut rankset_suit = [0i32; 4];
         let mut rankset_of_count = [0i32; 5];
@@ -79,10 +112,10 @@ impl Hand {
             }
         }
 
-        let is_straight = find_straight(rankset);
+        let is_straight = find_straight(rankset, short_deck);
 
         if flush_suit >= 0 {
-            let is_straight_flush = find_straight(rankset_suit[flush_suit as usize]);
+            let is_straight_flush = find_straight(rankset_suit[flush_suit as usize], short_deck);
             if is_straight_flush != 0 {
                 // straight flush
                 (8 << 26) | is_straight_flush
@@ -129,6 +162,30 @@ impl Hand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::range::*;
+
+    fn hand_from_str(s: &str) -> Hand {
+        (0..7).fold(Hand::new(), |hand, i| {
+            let card = card_from_str(&s[2 * i..2 * i + 2]).unwrap();
+            hand.add_card(card as usize)
+        })
+    }
+
+    #[test]
+    fn test_short_deck() {
+        let flush = hand_from_str("AhKh9h7h6hAsKs");
+        let full_house = hand_from_str("AhAsAdKhKs9c7d");
+        let wheel = hand_from_str("Ah6s7d8c9hKsQs");
+        let straight = hand_from_str("6s7d8c9hTsKsQs");
+        let trips = hand_from_str("AhAsAdKhQs9c7d");
+
+        assert!(flush.evaluate() < full_house.evaluate());
+        assert!(flush.evaluate_short_deck() > full_house.evaluate_short_deck());
+        assert!(wheel.evaluate() < trips.evaluate());
+        assert!(wheel.evaluate_short_deck() > trips.evaluate_short_deck());
+        assert!(wheel.evaluate_short_deck() < straight.evaluate_short_deck());
+        assert_eq!(straight.evaluate(), straight.evaluate_short_deck());
+    }
 
     #[test]
     fn test_all_hands() {
@@ -149,7 +206,7 @@ mod tests {
                                 let hand = hand.add_card(p);
                                 for q in (p + 1)..52 {
                                     let hand = hand.add_card(q);
-                                    let raw_value = hand.evaluate_internal();
+                                    let raw_value = hand.evaluate_internal(false);
                                     let index_result = HAND_TABLE.binary_search(&raw_value);
                                     assert!(index_result.is_ok());
                                     appeared[index_result.unwrap()] = true;
@@ -1,4 +1,4 @@
-pub(crate) const HAND_TABLE: [i32; 4824] = [
+pub(crate) static HAND_TABLE: [i32; 4824] = [
     236, 244, 364, 372, 376, 428, 436, 440, 460, 468, 472, 484, 488, 620, 628, 632, 684, 692, 696,
     716, 724, 728, 740, 744, 752, 812, 820, 824, 844, 852, 856, 868, 872, 880, 908, 916, 920, 932,
     936, 944, 964, 968, 976, 1132, 1140, 1144, 1196, 1204, 1208, 1228, 1236, 1240, 1252, 1256,
@@ -10,7 +10,7 @@ pub trait Game: Send + Sync {
 
     /// Returns the root node of game tree.
     #[doc(hidden)]
-    fn root(&self) -> MutexGuardLike<Self::Node>;
+    fn root(&self) -> MutexGuardLike<'_, Self::Node>;
 
     /// Returns the number of private hands of given player.
     #[doc(hidden)]
@@ -99,7 +99,7 @@ pub trait GameNode: Send + Sync {
 
     /// Returns the node after taking the given action.
     #[doc(hidden)]
-    fn play(&self, action: usize) -> MutexGuardLike<Self>;
+    fn play(&self, action: usize) -> MutexGuardLike<'_, Self>;
 
     /// Returns the strategy.
     #[doc(hidden)]
@@ -0,0 +1,115 @@
+use crate::action_tree::*;
+use crate::card::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize)]
+struct GameConfigRef<'a> {
+    card_config: &'a CardConfig,
+    tree_config: &'a TreeConfig,
+}
+
+#[derive(Deserialize)]
+struct GameConfig {
+    card_config: CardConfig,
+    tree_config: TreeConfig,
+}
+
+/// Serializes the game configurations into a pretty-printed JSON string.
+///
+/// Only the configurations are stored, not the solved data. The ranges are stored as their string
+/// representations so that the output remains human-editable. The bet size function
+/// ([`TreeConfig::bet_sizes_fn`]) cannot be serialized and is silently skipped, so the
+/// configuration read by [`config_from_json`] has no bet size function.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let card_config = CardConfig {
+///     range: ["QQ+,AKs".parse().unwrap(), "TT-88,AQo:0.5".parse().unwrap()],
+///     flop: flop_from_str("Td9d6h").unwrap(),
+///     ..Default::default()
+/// };
+/// let tree_config = TreeConfig {
+///     starting_pot: 200,
+///     effective_stack: 900,
+///     ..Default::default()
+/// };
+///
+/// let json = config_to_json(&card_config, &tree_config).unwrap();
+/// let (card_config2, tree_config2) = config_from_json(&json).unwrap();
+/// assert_eq!(card_config, card_config2);
+/// assert_eq!(tree_config, tree_config2);
+/// ```
+pub fn config_to_json(
+    card_config: &CardConfig,
+    tree_config: &TreeConfig,
+) -> Result<String, String> {
+    let config = GameConfigRef {
+        card_config,
+        tree_config,
+    };
+    serde_json::to_string_pretty(&config).map_err(|e| e.to_string())
+}
+
+/// Deserializes the game configurations from a JSON string created by [`config_to_json`].
+///
+/// The configurations are not validated here; they are validated when the game is built.
+pub fn config_from_json(json: &str) -> Result<(CardConfig, TreeConfig), String> {
+    let config: GameConfig = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    Ok((config.card_config, config.tree_config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bet_size::*;
+    use crate::range::*;
+
+    #[test]
+    fn config_json_round_trip() {
+        let card_config = CardConfig {
+            range: [
+                "QQ+,AKs,AQo:0.75,KhQh:0.3".parse().unwrap(),
+                "TT-66,AJs-ATs,KQo:0.25".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            dead_cards: vec![card_from_str("2s").unwrap()],
+            use_isomorphism: false,
+            ..Default::default()
+        };
+
+        let bet_sizes = BetSizeCandidates::try_from(("33%, 75%, e, a", "2.5x, 60%")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 120,
+            effective_stack: 880,
+            rake_rate: 0.05,
+            rake_cap: 30.0,
+            payoff_model: PayoffModel::Icm {
+                stacks: vec![1000, 1000, 2000],
+                payouts: vec![50.0, 30.0, 20.0],
+            },
+            turn_bet_sizes: [bet_sizes.clone(), Default::default()],
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            river_donk_sizes: Some(DonkSizeCandidates::try_from("50%").unwrap()),
+            max_num_bets: [0, 3, 3],
+            min_bet_ratio: 0.25,
+            add_allin_threshold: 1.5,
+            force_allin_threshold: 0.15,
+            merging_threshold: 0.1,
+            ..Default::default()
+        };
+
+        let json = config_to_json(&card_config, &tree_config).unwrap();
+        let (card_config2, tree_config2) = config_from_json(&json).unwrap();
+        assert_eq!(card_config, card_config2);
+        assert_eq!(tree_config, tree_config2);
+
+        assert!(config_from_json("{}").is_err());
+
+        let oop_range = card_config.range[0].to_string();
+        assert!(config_from_json(&json.replacen(&oop_range, "QQ+++", 1)).is_err());
+    }
+}
@@ -13,6 +13,12 @@
 //!     flop: flop_from_str("Td9d6h").unwrap(),
 //!     turn: card_from_str("Qc").unwrap(),
 //!     river: NOT_DEALT,
+//!     game_type: GameType::Holdem,
+//!     dead_cards: Vec::new(),
+//!     use_isomorphism: true,
+//!     turn_abstraction: None, // no turn card abstraction
+//!     river_abstraction: None, // no river card abstraction
+//!     precompute_strength: true, // precompute the hand strength tables
 //! };
 //!
 //! // bet sizes -> 60% of the pot, geometric size, and all-in
@@ -22,18 +28,31 @@
 //!
 //! let tree_config = TreeConfig {
 //!     initial_state: BoardState::Turn,
+//!     ip_first: false, // OOP acts first
 //!     starting_pot: 200,
+//!     initial_invested: [0, 0], // split the starting pot equally
 //!     effective_stack: 900,
+//!     big_blind: 10, // used to specify and report amounts in big blinds
 //!     rake_rate: 0.0,
 //!     rake_cap: 0.0,
+//!     no_rake_check_down: false,
+//!     no_rake_fold: false,
+//!     payoff_model: PayoffModel::ChipEv, // value chips linearly
 //!     flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()], // [OOP, IP]
 //!     turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
 //!     river_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
 //!     turn_donk_sizes: None, // use default bet sizes
 //!     river_donk_sizes: Some(DonkSizeCandidates::try_from("50%").unwrap()),
+//!     bet_sizes_fn: None, // use the bet sizes of each street
+//!     max_num_bets: [0, 0, 0], // no limit on the number of bets
+//!     max_num_raises: [0, 0, 0], // no limit on the number of raises
+//!     bet_rounding: 0, // do not round bet amounts
+//!     min_bet_ratio: 0.0, // no minimum bet size other than the rules
 //!     add_allin_threshold: 1.5, // add all-in if (maximum bet size) <= 1.5x pot
 //!     force_allin_threshold: 0.15, // force all-in if (SPR after the opponent's call) <= 0.15
 //!     merging_threshold: 0.1,
+//!     max_num_bet_sizes: 0, // no limit on the number of bet sizes
+//!     max_depth_street: None, // build the full tree
 //! };
 //!
 //! // build the game tree
@@ -140,7 +159,8 @@
 //!
 //! # Implementation details
 //! - **Algorithm**: The solver uses the state-of-the-art [Discounted CFR] algorithm.
-//!   Currently, the value of γ is set to 3.0 instead of the 2.0 recommended in the original paper.
+//!   By default, the value of γ is set to 3.0 instead of the 2.0 recommended in the original paper
+//!   (the parameters can be changed with `solve_with_params`).
 //!   Also, the solver resets the cumulative strategy when the number of iterations is a power of 4.
 //! - **Performance**: The solver engine is highly optimized for performance with maintainable code.
 //!   The engine supports multithreading by default, and it takes full advantage of unsafe Rust in hot spots.
@@ -174,13 +194,21 @@
 //!   when solving in a program.
 //!   Disabled by default.
 //! - `rayon`: Uses [rayon] crate for parallelization.
+//!   The number of threads can be limited by running the computation in `with_num_threads`.
+//!   When disabled, the solver runs in a single thread with the same results,
+//!   which is useful for targets without thread support (e.g., WebAssembly).
 //!   Enabled by default.
+//! - `serde`: Uses [serde] and [serde_json] crates to export and import the game configurations
+//!   (`CardConfig` and `TreeConfig`) as JSON.
+//!   Disabled by default.
 //! - `zstd`: Uses [zstd] crate to compress and decompress the game tree.
 //!   This feature is required to save and load the game tree with compression.
 //!   Disabled by default.
 //!
 //! [bincode]: https://github.com/bincode-org/bincode
 //! [rayon]: https://github.com/rayon-rs/rayon
+//! [serde]: https://github.com/serde-rs/serde
+//! [serde_json]: https://github.com/serde-rs/json
 //! [zstd]: https://github.com/gyscos/zstd-rs
 
 #![cfg_attr(feature = "custom-alloc", feature(allocator_api))]
@@ -191,6 +219,9 @@ mod alloc;
 #[cfg(feature = "bincode")]
 mod file;
 
+#[cfg(feature = "serde")]
+mod json;
+
 mod action_tree;
 mod atomic_float;
 mod bet_size;
@@ -209,6 +240,9 @@ mod utility;
 #[cfg(feature = "bincode")]
 pub use file::*;
 
+#[cfg(feature = "serde")]
+pub use json::*;
+
 pub use action_tree::*;
 pub use bet_size::*;
 pub use bunching::*;
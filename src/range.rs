@@ -1,12 +1,15 @@
 use crate::card::*;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::fmt::Write;
+use std::fmt::{self, Write};
 use std::str::FromStr;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 /// A struct representing a player's range.
 ///
 /// # Examples
@@ -53,6 +56,32 @@ static RANGE_REGEX: Lazy<Regex> = Lazy::new(|| {
 
 static TRIM_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s*([-:,])\s*").unwrap());
 
+static CANONICAL_FLOPS: Lazy<Vec<FlopGroup>> = Lazy::new(|| {
+    let mut groups = Vec::<FlopGroup>::new();
+
+    // iterating in ascending order visits each canonical flop before its isomorphic flops
+    for c1 in 0..52 {
+        for c2 in c1 + 1..52 {
+            for c3 in c2 + 1..52 {
+                let flop = [c1, c2, c3];
+                let (canonical, suit_map) = canonical_flop(flop);
+
+                let mut inverse_map = [0; 4];
+                for (suit, &mapped) in suit_map.iter().enumerate() {
+                    inverse_map[mapped as usize] = suit as u8;
+                }
+
+                match groups.binary_search_by(|(c, _)| c.cmp(&canonical)) {
+                    Ok(index) => groups[index].1.push((flop, inverse_map)),
+                    Err(index) => groups.insert(index, (canonical, vec![(flop, inverse_map)])),
+                }
+            }
+        }
+    }
+
+    groups
+});
+
 #[inline]
 fn pair_indices(rank: u8) -> Vec<usize> {
     let mut result = Vec::with_capacity(6);
@@ -119,6 +148,32 @@ fn indices_with_suitedness(rank1: u8, rank2: u8, suitedness: Suitedness) -> Vec<
     }
 }
 
+/// Returns the indices of the combinations of the given hand class (e.g., `"AKo"`, `"AKs"`, `"AK"`,
+/// or `"QQ"`).
+#[inline]
+pub(crate) fn hand_class_indices(class: &str) -> Result<Vec<usize>, String> {
+    if !(2..=3).contains(&class.len()) {
+        return Err(format!("Invalid hand class: {class}"));
+    }
+    let (rank1, rank2, suitedness) = parse_compound_singleton(class)?;
+    Ok(indices_with_suitedness(rank1, rank2, suitedness))
+}
+
+/// Returns the `(row, column)` of the given hand in the 13x13 hand grid.
+///
+/// Rows and columns are ordered from ace to deuce; suited hands are placed in the upper triangle.
+#[inline]
+pub(crate) fn hand_grid_cell(c1: u8, c2: u8) -> (usize, usize) {
+    let (c1, c2) = (c1.min(c2), c1.max(c2));
+    let high = 12 - (c2 >> 2) as usize;
+    let low = 12 - (c1 >> 2) as usize;
+    if c1 & 3 == c2 & 3 {
+        (high, low)
+    } else {
+        (low, high)
+    }
+}
+
 /// Attempts to convert a rank character to a rank index.
 ///
 /// `'A'` => `12`, `'K'` => `11`, ..., `'2'` => `0`.
@@ -324,6 +379,103 @@ pub fn flop_from_str(s: &str) -> Result<[u8; 3], String> {
     Ok(result)
 }
 
+/// Returns the canonical form of the given flop up to suit relabeling, together with the suit
+/// mapping used.
+///
+/// Flops that are identical up to suit relabeling (e.g., `Td9d6h` and `Tc9c6s`) have the same
+/// canonical form. The returned canonical flop is sorted. The `i`-th element of the suit mapping is
+/// the suit that the suit `i` is mapped to; use [`map_card_suit`] to translate other cards (e.g.,
+/// private hands) in the same way. Note that the strategies of two isomorphic flops are equivalent
+/// only if the ranges are also invariant under the suit mapping.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let flop1 = flop_from_str("Td9d6h").unwrap();
+/// let flop2 = flop_from_str("Tc9c6s").unwrap();
+/// let (canonical1, suit_map1) = canonical_flop(flop1);
+/// let (canonical2, suit_map2) = canonical_flop(flop2);
+/// assert_eq!(canonical1, canonical2);
+///
+/// let mut mapped = flop1.map(|card| map_card_suit(card, &suit_map1));
+/// mapped.sort_unstable();
+/// assert_eq!(mapped, canonical1);
+///
+/// let flop3 = flop_from_str("Td9h6d").unwrap();
+/// assert_ne!(canonical_flop(flop3).0, canonical1);
+/// ```
+pub fn canonical_flop(flop: [u8; 3]) -> ([u8; 3], [u8; 4]) {
+    let mut best = ([u8::MAX; 3], [0, 1, 2, 3]);
+
+    for a in 0..4 {
+        for b in 0..4 {
+            for c in 0..4 {
+                if a == b || a == c || b == c {
+                    continue;
+                }
+                let suit_map = [a, b, c, 6 - a - b - c];
+                let mut mapped = flop.map(|card| map_card_suit(card, &suit_map));
+                mapped.sort_unstable();
+                if mapped < best.0 {
+                    best = (mapped, suit_map);
+                }
+            }
+        }
+    }
+
+    best
+}
+
+/// A canonical flop and the flops it represents, each with the suit mapping from the canonical
+/// flop to it. See [`canonical_flops`] for details.
+pub type FlopGroup = ([u8; 3], Vec<([u8; 3], [u8; 4])>);
+
+/// Returns all strategically distinct flops up to suit relabeling, each together with the flops it
+/// represents.
+///
+/// The canonical flops are the ones returned by [`canonical_flop`] and are sorted in ascending
+/// order. Each canonical flop is paired with the list of its isomorphic flops (including itself)
+/// in ascending order, where each flop comes with the suit mapping from the canonical flop to it;
+/// use [`map_card_suit`] to remap the results solved on the canonical flop (e.g., private hands)
+/// to the represented flop. The groups partition all 22,100 flops, and there are 1,755 canonical
+/// flops. The result is computed on the first call and cached afterwards.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let groups = canonical_flops();
+/// assert_eq!(groups.len(), 1755);
+///
+/// for (canonical, flops) in groups {
+///     for (flop, suit_map) in flops {
+///         let mut mapped = canonical.map(|card| map_card_suit(card, suit_map));
+///         mapped.sort_unstable();
+///         assert_eq!(&mapped, flop);
+///     }
+/// }
+/// ```
+#[inline]
+pub fn canonical_flops() -> &'static [FlopGroup] {
+    &CANONICAL_FLOPS
+}
+
+/// Applies the suit mapping returned by [`canonical_flop`] to the given card.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let suit_map = [1, 0, 2, 3]; // club <=> diamond
+/// assert_eq!(map_card_suit(card_from_str("Ac").unwrap(), &suit_map), card_from_str("Ad").unwrap());
+/// assert_eq!(map_card_suit(card_from_str("Ah").unwrap(), &suit_map), card_from_str("Ah").unwrap());
+/// ```
+#[inline]
+pub fn map_card_suit(card: u8, suit_map: &[u8; 4]) -> u8 {
+    card - (card & 3) + suit_map[card as usize & 3]
+}
+
 #[inline]
 fn parse_singleton(combo: &str) -> Result<(u8, u8, Suitedness), String> {
     if combo.len() == 4 {
@@ -452,6 +604,33 @@ impl Range {
         Ok(range)
     }
 
+    /// Attempts to create a range from a list of hands paired with their weights.
+    ///
+    /// Each hand must consist of two different valid cards and appear at most once (in either
+    /// card order). Hands not in the list have the weight of `0.0`.
+    pub fn from_combos(combos: &[((u8, u8), f32)]) -> Result<Self, String> {
+        let mut range = Self::default();
+        let mut seen = [false; 52 * 51 / 2];
+        for &((card1, card2), weight) in combos {
+            check_card(card1)?;
+            check_card(card2)?;
+            check_weight(weight)?;
+            if card1 == card2 {
+                return Err("Hand must consist of two different cards".to_string());
+            }
+            let index = card_pair_to_index(card1, card2);
+            if seen[index] {
+                return Err(format!(
+                    "Duplicate hand: {}",
+                    hole_to_string((card1, card2))?
+                ));
+            }
+            seen[index] = true;
+            range.data[index] = weight;
+        }
+        Ok(range)
+    }
+
     /// Returns a list of all hands in this range and their associated weights.
     ///
     /// If there are no dead cards, pass `0` to `dead_cards_mask`.
@@ -537,6 +716,50 @@ impl Range {
             .for_each(|el| *el = (1.0 - el.to_string().parse::<f64>().unwrap()) as f32);
     }
 
+    /// Updates the range to the union with `other`: the weight of each hand is set to the
+    /// maximum of the two weights.
+    #[inline]
+    pub fn union(&mut self, other: &Self) {
+        self.data
+            .iter_mut()
+            .zip(other.data.iter())
+            .for_each(|(el, &other)| *el = el.max(other));
+    }
+
+    /// Updates the range to the intersection with `other`: the weight of each hand is set to the
+    /// minimum of the two weights.
+    #[inline]
+    pub fn intersect(&mut self, other: &Self) {
+        self.data
+            .iter_mut()
+            .zip(other.data.iter())
+            .for_each(|(el, &other)| *el = el.min(other));
+    }
+
+    /// Subtracts `other` from the range: the weight of each hand is decreased by the weight in
+    /// `other`, clamped to `0.0`.
+    #[inline]
+    pub fn subtract(&mut self, other: &Self) {
+        // we want to obtain 0.4 when computing 0.9 - 0.5, not 0.39999998
+        self.data
+            .iter_mut()
+            .zip(other.data.iter())
+            .for_each(|(el, &other)| {
+                let diff = el.to_string().parse::<f64>().unwrap()
+                    - other.to_string().parse::<f64>().unwrap();
+                *el = diff.max(0.0) as f32;
+            });
+    }
+
+    /// Multiplies the weight of each hand by `factor`, clamped to the range [`0.0`, `1.0`].
+    #[inline]
+    pub fn scale(&mut self, factor: f64) {
+        self.data.iter_mut().for_each(|el| {
+            let scaled = el.to_string().parse::<f64>().unwrap() * factor;
+            *el = scaled.clamp(0.0, 1.0) as f32;
+        });
+    }
+
     /// Obtains the weight of a specified hand.
     ///
     /// Undefined behavior if:
@@ -733,6 +956,9 @@ impl Range {
         let gap2 = rank21 - rank22;
         if suitedness != suitedness2 {
             Err(format!("Suitedness does not match: {range}"))
+        } else if (gap == 0) != (gap2 == 0) {
+            // a span cannot mix pairs and non-pairs (e.g., 88-82)
+            Err(format!("Invalid range: {range}"))
         } else if gap == gap2 {
             // same gap (e.g., 88-55, KQo-JTo)
             if rank11 > rank21 {
@@ -983,14 +1209,32 @@ impl FromStr for Range {
     }
 }
 
-impl ToString for Range {
+impl fmt::Display for Range {
     #[inline]
-    fn to_string(&self) -> String {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut result = Vec::new();
         self.pairs_strings(&mut result);
         self.nonpairs_strings(&mut result);
         self.suit_specified_strings(&mut result);
-        result.join(",")
+        write!(f, "{}", result.join(","))
+    }
+}
+
+/// Serializes the range as its string representation so that it remains human-editable.
+#[cfg(feature = "serde")]
+impl Serialize for Range {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Range {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
     }
 }
 
@@ -998,6 +1242,52 @@ impl ToString for Range {
 mod tests {
     use super::*;
 
+    #[test]
+    fn canonical_flops() {
+        let mut canonical_flops = Vec::new();
+        for c1 in 0..52 {
+            for c2 in c1 + 1..52 {
+                for c3 in c2 + 1..52 {
+                    let (canonical, suit_map) = canonical_flop([c1, c2, c3]);
+                    let mut mapped = [c1, c2, c3].map(|card| map_card_suit(card, &suit_map));
+                    mapped.sort_unstable();
+                    assert_eq!(mapped, canonical);
+                    canonical_flops.push(canonical);
+                }
+            }
+        }
+
+        canonical_flops.sort_unstable();
+        canonical_flops.dedup();
+        assert_eq!(canonical_flops.len(), 1755);
+    }
+
+    #[test]
+    fn canonical_flop_groups() {
+        let groups = super::canonical_flops();
+        assert_eq!(groups.len(), 1755);
+
+        let mut seen = vec![false; 52 * 52 * 52];
+        let mut num_flops = 0;
+        for (canonical, flops) in groups {
+            assert_eq!(flops[0], (*canonical, [0, 1, 2, 3]));
+            for &(flop, suit_map) in flops {
+                assert_eq!(canonical_flop(flop).0, *canonical);
+                let mut mapped = canonical.map(|card| map_card_suit(card, &suit_map));
+                mapped.sort_unstable();
+                assert_eq!(mapped, flop);
+
+                let index = (flop[0] as usize * 52 + flop[1] as usize) * 52 + flop[2] as usize;
+                assert!(!seen[index]);
+                seen[index] = true;
+                num_flops += 1;
+            }
+        }
+
+        // (52 choose 3)
+        assert_eq!(num_flops, 22100);
+    }
+
     #[test]
     fn range_regex() {
         let tests = [
@@ -1135,6 +1425,101 @@ mod tests {
         assert_eq!(data.get_weight_offsuit(6, 3), 0.0);
     }
 
+    #[test]
+    fn range_from_str_spans() {
+        let tests = [
+            // same first rank
+            ("K9s-K2s", "K9s,K8s,K7s,K6s,K5s,K4s,K3s,K2s"),
+            ("K9o-K2o", "K9o,K8o,K7o,K6o,K5o,K4o,K3o,K2o"),
+            ("K9-K2", "K9s-K2s,K9o-K2o"),
+            ("AhKh-AhTh", "AhKh,AhQh,AhJh,AhTh"),
+            // same gap
+            ("T8s-64s", "T8s,97s,86s,75s,64s"),
+            ("T8o-64o", "T8o,97o,86o,75o,64o"),
+            ("T8-64", "T8s-64s,T8o-64o"),
+            ("KJ-T8", "KJ,QT,J9,T8"),
+            // plus (a gapper keeps the first rank)
+            ("T8s+", "T9s,T8s"),
+            ("J9o+", "JTo,J9o"),
+        ];
+
+        for (range, expected) in tests {
+            assert_eq!(range.parse::<Range>(), expected.parse::<Range>(), "{range}");
+        }
+
+        let errors = [
+            "K9s-K2o",   // mismatched suitedness
+            "K9s-K2",    // mismatched suitedness
+            "K9s-Q2s",   // mismatched ranks
+            "T8s-63s",   // mismatched gaps
+            "K2s-K9s",   // ascending order
+            "64s-T8s",   // ascending order
+            "88-82",     // pair and non-pair
+            "98-88",     // non-pair and pair
+            "AhKh-AcTc", // mismatched suits
+            "K9s-",      // missing end
+        ];
+
+        for range in errors {
+            assert!(range.parse::<Range>().is_err(), "{range}");
+        }
+    }
+
+    #[test]
+    fn range_from_str_specific_combos() {
+        let ahkh = card_pair_to_index(card_from_str("Ah").unwrap(), card_from_str("Kh").unwrap());
+        let tc9c = card_pair_to_index(card_from_str("Tc").unwrap(), card_from_str("9c").unwrap());
+
+        let range = "AhKh:0.5,Tc9c".parse::<Range>().unwrap();
+        for (i, &w) in range.raw_data().iter().enumerate() {
+            let expected = match i {
+                _ if i == ahkh => 0.5,
+                _ if i == tc9c => 1.0,
+                _ => 0.0,
+            };
+            assert_eq!(w, expected);
+        }
+
+        // the earlier token takes precedence over the class it belongs to
+        let range = "AhKh:0.25,AKs".parse::<Range>().unwrap();
+        assert_eq!(range.get_weight_by_cards(51, 47), 1.0);
+        assert_eq!(range.raw_data()[ahkh], 0.25);
+
+        let duplicate_error = "AhAh".parse::<Range>();
+        assert!(duplicate_error.is_err());
+
+        let three_cards_error = "AhKhQh".parse::<Range>();
+        assert!(three_cards_error.is_err());
+
+        let order_error = "KhAh".parse::<Range>();
+        assert!(order_error.is_err());
+    }
+
+    #[test]
+    fn range_set_operations() {
+        let mut range = "QQ+,AK".parse::<Range>().unwrap();
+        range.union(&"TT-88:0.5,KQs".parse().unwrap());
+        assert_eq!(range, "QQ+,AK,TT-88:0.5,KQs".parse().unwrap());
+
+        let mut range = "QQ+:0.5,AK".parse::<Range>().unwrap();
+        range.union(&"KK:0.25,AQs".parse().unwrap());
+        assert_eq!(range, "QQ+:0.5,AK,AQs".parse().unwrap());
+
+        let mut range = "QQ+:0.5,AK".parse::<Range>().unwrap();
+        range.intersect(&"KK+,AKs:0.25,KQ".parse().unwrap());
+        assert_eq!(range, "KK+:0.5,AKs:0.25".parse().unwrap());
+
+        let mut range = "QQ+:0.9,AK".parse::<Range>().unwrap();
+        range.subtract(&"KK:0.5,AA,AKs".parse().unwrap());
+        assert_eq!(range, "KK:0.4,QQ:0.9,AKo".parse().unwrap());
+
+        let mut range = "QQ+:0.5,AK".parse::<Range>().unwrap();
+        range.scale(0.6);
+        assert_eq!(range, "QQ+:0.3,AK:0.6".parse().unwrap());
+        range.scale(2.0);
+        assert_eq!(range, "QQ+:0.6,AK".parse().unwrap());
+    }
+
     #[test]
     fn range_to_string() {
         let tests = [
@@ -1156,4 +1541,56 @@ mod tests {
             assert_eq!(range.unwrap().to_string(), expected);
         }
     }
+
+    #[test]
+    fn range_to_string_round_trip() {
+        let mut range = Range::new();
+        range.set_weight_pair(12, 1.0);
+        range.set_weight_pair(11, 1.0);
+        range.set_weight_pair(3, 0.5);
+        range.set_weight_suited(12, 11, 1.0);
+        range.set_weight_offsuit(12, 10, 0.5);
+        range.set_weight_suited(9, 8, 0.25);
+        range.set_weight_suited(8, 7, 0.25);
+        range.set_weight_by_cards(4 * 10 + 3, 4 * 9 + 2, 0.75);
+        range.set_weight_by_cards(4 * 5, 4 * 5 + 1, 0.3);
+
+        let round_trip = range.to_string().parse::<Range>().unwrap();
+        assert_eq!(round_trip.raw_data(), range.raw_data());
+
+        // deterministic pseudo-random weights for all combinations
+        let mut state = 12345u32;
+        let data = (0..52 * 51 / 2)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                [0.0, 0.0, 0.25, 0.5, 0.7, 1.0][(state >> 16) as usize % 6]
+            })
+            .collect::<Vec<_>>();
+        let range = Range::from_raw_data(&data).unwrap();
+
+        let round_trip = range.to_string().parse::<Range>().unwrap();
+        assert_eq!(round_trip.raw_data(), range.raw_data());
+    }
+
+    #[test]
+    fn range_from_combos() {
+        let ah_kh = (card_from_str("Ah").unwrap(), card_from_str("Kh").unwrap());
+        let qs_qd = (card_from_str("Qs").unwrap(), card_from_str("Qd").unwrap());
+        let range = Range::from_combos(&[(ah_kh, 0.5), (qs_qd, 1.0)]).unwrap();
+
+        assert_eq!(range.get_weight_by_cards(ah_kh.0, ah_kh.1), 0.5);
+        assert_eq!(range.get_weight_by_cards(qs_qd.1, qs_qd.0), 1.0);
+        assert_eq!(range.raw_data().iter().filter(|&&w| w > 0.0).count(), 2);
+        assert_eq!(range.to_string(), "QsQd,AhKh:0.5");
+
+        // the range is used in the same way as a parsed range
+        let (hands, weights) = range.get_hands_weights(0);
+        assert_eq!(hands, vec![(qs_qd.1, qs_qd.0), (ah_kh.1, ah_kh.0)]);
+        assert_eq!(weights, vec![1.0, 0.5]);
+
+        assert!(Range::from_combos(&[((52, 0), 1.0)]).is_err());
+        assert!(Range::from_combos(&[((3, 3), 1.0)]).is_err());
+        assert!(Range::from_combos(&[(ah_kh, 1.5)]).is_err());
+        assert!(Range::from_combos(&[(ah_kh, 1.0), ((ah_kh.1, ah_kh.0), 0.5)]).is_err());
+    }
 }
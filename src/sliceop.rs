@@ -1,6 +1,11 @@
 use crate::utility::*;
 use std::mem::MaybeUninit;
 
+#[inline]
+pub(crate) fn add_slice(lhs: &mut [f32], rhs: &[f32]) {
+    lhs.iter_mut().zip(rhs).for_each(|(l, r)| *l += *r);
+}
+
 #[inline]
 pub(crate) fn sub_slice(lhs: &mut [f32], rhs: &[f32]) {
     lhs.iter_mut().zip(rhs).for_each(|(l, r)| *l -= *r);
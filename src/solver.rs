@@ -4,18 +4,243 @@ use crate::sliceop::*;
 use crate::utility::*;
 use std::io::{self, Write};
 use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(feature = "custom-alloc")]
 use crate::alloc::*;
 
-struct DiscountParams {
+/// Parameters of the Discounted CFR algorithm.
+///
+/// At iteration `t`, the positive cumulative regrets are multiplied by `t^α / (t^α + 1)`, the
+/// negative cumulative regrets by `t^β / (t^β + 1)`, and the cumulative strategy by
+/// `(t / (t + 1))^γ`. Note that the cumulative strategy is reset when the number of iterations is
+/// a power of 4 regardless of the parameters.
+///
+/// The default values are α = 1.5, β = 0.0, and γ = 3.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiscountParams {
+    /// Discount exponent for the positive cumulative regrets.
+    pub alpha: f64,
+
+    /// Discount exponent for the negative cumulative regrets.
+    pub beta: f64,
+
+    /// Discount exponent for the cumulative strategy.
+    pub gamma: f64,
+}
+
+impl Default for DiscountParams {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            alpha: 1.5,
+            beta: 0.0,
+            gamma: 3.0,
+        }
+    }
+}
+
+/// Variants of the CFR algorithm used for the solving.
+///
+/// All variants are implemented by discounting the cumulative regrets and the cumulative strategy
+/// before adding the values of the current iteration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CfrVariant {
+    /// Discounted CFR with the given parameters (default).
+    Discounted(DiscountParams),
+
+    /// Linear CFR: the regrets and the strategy of iteration `t` are weighted by `t`. This is
+    /// equivalent to multiplying both the cumulative regrets and the cumulative strategy by
+    /// `(t - 1) / t` at iteration `t` (1-indexed).
+    Linear,
+
+    /// Vanilla CFR: the cumulative values are never discounted.
+    Vanilla,
+}
+
+impl Default for CfrVariant {
+    #[inline]
+    fn default() -> Self {
+        Self::Discounted(DiscountParams::default())
+    }
+}
+
+/// Weighting schemes of the iterations in the average strategy.
+///
+/// The scheme replaces the discounting of the cumulative strategy of the [`CfrVariant`], while
+/// the cumulative regrets are still updated by the variant. Weighting the later iterations more
+/// heavily usually makes the average strategy converge faster because the strategies of the early
+/// iterations are far from the equilibrium.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AveragingScheme {
+    /// All iterations are weighted equally.
+    Uniform,
+
+    /// The strategy of iteration `t` is weighted by `t`.
+    Linear,
+
+    /// The strategy of iteration `t` is weighted by `t^2`.
+    Quadratic,
+}
+
+impl AveragingScheme {
+    /// Returns the factor multiplied to the cumulative strategy at the given iteration.
+    #[inline]
+    fn discount_factor(self, current_iteration: u32) -> f32 {
+        let t = current_iteration as f64;
+        let ratio = t / (t + 1.0);
+        match self {
+            Self::Uniform => 1.0,
+            Self::Linear => ratio as f32,
+            Self::Quadratic => (ratio * ratio) as f32,
+        }
+    }
+}
+
+/// Metric used for the interim convergence checks of [`solve_with_config`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConvergenceMetric {
+    /// The exploitability computed by a full best-response pass (default).
+    #[default]
+    Exploitability,
+
+    /// An estimate of the exploitability computed from the cumulative regrets, i.e., the sum of
+    /// the positive cumulative regrets of all information sets divided by the number of
+    /// iterations.
+    ///
+    /// This is much cheaper than the best-response pass because no terminal node is evaluated,
+    /// but it is only an estimate since the cumulative regrets are discounted by the solver.
+    RegretBound,
+
+    /// The larger one of the exploitabilities of the two players computed by
+    /// [`compute_exploitability_per_player`].
+    ///
+    /// The solving stops only when both players are converged, whereas the average of the two
+    /// values (i.e., [`ConvergenceMetric::Exploitability`]) can hide one player being much more
+    /// exploitable than the other.
+    PerPlayerExploitability,
+}
+
+/// Parameters of [`solve_with_config`].
+///
+/// The other solving functions (e.g., [`solve`], [`solve_with_variant`], and [`solve_mccfr`]) are
+/// shorthands for [`solve_with_config`] with some of these parameters.
+#[derive(Debug, Clone, Copy)]
+pub struct SolveParams<'a> {
+    /// Maximum number of iterations.
+    pub max_iterations: u32,
+
+    /// The solving stops when the convergence metric is below or equal to this value.
+    pub target_exploitability: f32,
+
+    /// Minimum number of iterations before the solving can stop by `target_exploitability`.
+    ///
+    /// The convergence metric can transiently fall below the target in the early iterations, so
+    /// the target is ignored until this number of iterations is completed. Defaults to `0`.
+    pub min_iterations: u32,
+
+    /// Number of iterations between the convergence checks. Must be positive.
+    pub eval_interval: u32,
+
+    /// Whether to print the progress to the standard output.
+    pub print_progress: bool,
+
+    /// Metric used for the convergence checks during the solving.
+    pub convergence_metric: ConvergenceMetric,
+
+    /// Variant of the CFR algorithm. Defaults to Discounted CFR with the default parameters.
+    pub variant: CfrVariant,
+
+    /// Weighting scheme of the iterations in the average strategy. `None` (the default) uses the
+    /// discounting of the `variant`.
+    pub averaging: Option<AveragingScheme>,
+
+    /// Threshold of the pruning (see [`solve_with_pruning`]). `0.0` (the default) disables the
+    /// pruning.
+    pub prune_threshold: f32,
+
+    /// Seed of the external-sampling Monte Carlo CFR (see [`solve_mccfr`]). `None` (the default)
+    /// traverses the full game tree in each iteration.
+    ///
+    /// The cumulative values of the nodes not sampled in an iteration are not discounted in that
+    /// iteration, so the discounting of the `variant` is applied unevenly across the nodes. Use
+    /// [`CfrVariant::Vanilla`] with the sampling, as [`solve_mccfr`] does.
+    pub seed: Option<u64>,
+
+    /// Flag to interrupt the solving (see [`solve_with_cancel`]). `None` (the default) disables
+    /// the interruption.
+    pub cancel: Option<&'a AtomicBool>,
+
+    /// Whether to check the reach probabilities with [`validate_reach`] at every convergence
+    /// check. The solving panics if an invalid reach probability is found. Defaults to `false`
+    /// because the check traverses the full game tree.
+    pub validate_reach: bool,
+}
+
+impl Default for SolveParams<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_iterations: 1000,
+            target_exploitability: 0.0,
+            min_iterations: 0,
+            eval_interval: 10,
+            print_progress: false,
+            convergence_metric: ConvergenceMetric::Exploitability,
+            variant: CfrVariant::default(),
+            averaging: None,
+            prune_threshold: 0.0,
+            seed: None,
+            cancel: None,
+            validate_reach: false,
+        }
+    }
+}
+
+struct DiscountFactors {
     alpha_t: f32,
     beta_t: f32,
     gamma_t: f32,
+    prune_threshold: f32,
 }
 
-impl DiscountParams {
-    pub fn new(current_iteration: u32) -> Self {
+impl DiscountFactors {
+    pub fn new(
+        current_iteration: u32,
+        variant: &CfrVariant,
+        averaging: Option<AveragingScheme>,
+        prune_threshold: f32,
+    ) -> Self {
+        let mut factors = Self::from_variant(current_iteration, variant, prune_threshold);
+        if let Some(averaging) = averaging {
+            factors.gamma_t = averaging.discount_factor(current_iteration);
+        }
+        factors
+    }
+
+    fn from_variant(current_iteration: u32, variant: &CfrVariant, prune_threshold: f32) -> Self {
+        let params = match variant {
+            CfrVariant::Discounted(params) => params,
+            CfrVariant::Linear => {
+                let t = current_iteration as f64;
+                let factor = (t / (t + 1.0)) as f32;
+                return Self {
+                    alpha_t: factor,
+                    beta_t: factor,
+                    gamma_t: factor,
+                    prune_threshold,
+                };
+            }
+            CfrVariant::Vanilla => {
+                return Self {
+                    alpha_t: 1.0,
+                    beta_t: 1.0,
+                    gamma_t: 1.0,
+                    prune_threshold,
+                };
+            }
+        };
+
         // 0, 1, 4, 16, 64, 256, ...
         let msb_even = match current_iteration {
             0 => 0,
@@ -25,13 +250,15 @@ impl DiscountParams {
         let t_alpha = (current_iteration as i32 - 1).max(0) as f64;
         let t_gamma = (current_iteration - msb_even) as f64;
 
-        let pow_alpha = t_alpha * t_alpha.sqrt();
-        let pow_gamma = (t_gamma / (t_gamma + 1.0)).powi(3);
+        let pow_alpha = t_alpha.powf(params.alpha);
+        let pow_beta = t_alpha.powf(params.beta);
+        let pow_gamma = (t_gamma / (t_gamma + 1.0)).powf(params.gamma);
 
         Self {
             alpha_t: (pow_alpha / (pow_alpha + 1.0)) as f32,
-            beta_t: 0.5,
+            beta_t: (pow_beta / (pow_beta + 1.0)) as f32,
             gamma_t: pow_gamma as f32,
+            prune_threshold,
         }
     }
 }
@@ -40,12 +267,264 @@ impl DiscountParams {
 /// satisfied.
 ///
 /// This method returns the exploitability of the obtained strategy.
+#[inline]
 pub fn solve<T: Game>(
     game: &mut T,
     max_num_iterations: u32,
     target_exploitability: f32,
     print_progress: bool,
 ) -> f32 {
+    solve_with_params(
+        game,
+        max_num_iterations,
+        target_exploitability,
+        print_progress,
+        &DiscountParams::default(),
+    )
+}
+
+/// Performs Discounted CFR algorithm in a thread pool with `num_threads` threads until the given
+/// number of iterations or exploitability is satisfied.
+///
+/// Only the solving process (including the exploitability computation and [`finalize`]) runs in
+/// the pool created by [`with_num_threads`]; the game itself may have been built and allocated
+/// with another number of threads, e.g., in the global pool of [rayon]. This is useful because
+/// the optimal number of threads for the solving is often smaller than that for the building due
+/// to the memory bandwidth. The global pool is not affected.
+///
+/// This method returns the exploitability of the obtained strategy, or an error if the thread
+/// pool cannot be created.
+///
+/// [rayon]: https://github.com/rayon-rs/rayon
+#[cfg(feature = "rayon")]
+#[inline]
+pub fn solve_in_pool<T: Game + Send>(
+    game: &mut T,
+    num_threads: usize,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+) -> Result<f32, String> {
+    with_num_threads(num_threads, || {
+        solve(
+            game,
+            max_num_iterations,
+            target_exploitability,
+            print_progress,
+        )
+    })
+}
+
+/// Performs Discounted CFR algorithm with the given parameters until the given number of
+/// iterations or exploitability is satisfied.
+///
+/// This method returns the exploitability of the obtained strategy.
+#[inline]
+pub fn solve_with_params<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+    params: &DiscountParams,
+) -> f32 {
+    solve_with_variant(
+        game,
+        max_num_iterations,
+        target_exploitability,
+        print_progress,
+        &CfrVariant::Discounted(*params),
+    )
+}
+
+/// Performs the given variant of CFR algorithm until the given number of iterations or
+/// exploitability is satisfied.
+///
+/// This method returns the exploitability of the obtained strategy.
+#[inline]
+pub fn solve_with_variant<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+    variant: &CfrVariant,
+) -> f32 {
+    let params = SolveParams {
+        max_iterations: max_num_iterations,
+        target_exploitability,
+        print_progress,
+        variant: *variant,
+        ..Default::default()
+    };
+    solve_internal(game, &params).0
+}
+
+/// Performs Discounted CFR algorithm until the given number of iterations or exploitability is
+/// satisfied, and records the convergence history.
+///
+/// This method returns a list of `(iteration, exploitability)` pairs, where the exploitability is
+/// sampled at the start and whenever it is computed during the solving process (i.e., every 10
+/// iterations and at the last iteration). The last element is the exploitability of the obtained
+/// strategy.
+#[inline]
+pub fn solve_with_history<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+) -> Vec<(u32, f32)> {
+    let params = SolveParams {
+        max_iterations: max_num_iterations,
+        target_exploitability,
+        print_progress,
+        ..Default::default()
+    };
+    solve_internal(game, &params).1
+}
+
+/// Performs Discounted CFR algorithm with the pruning of the subtrees with negligible reach
+/// probabilities until the given number of iterations or exploitability is satisfied.
+///
+/// In each iteration, the evaluation of the subtree is skipped if the sum of the reach
+/// probabilities of the opponent into the subtree is below `prune_threshold` (e.g., `1e-6`), since
+/// the counterfactual values of such a subtree are almost zero. The counterfactual values of the
+/// skipped subtree are treated as zero, but its cumulative regrets are still discounted and its
+/// cumulative strategy is still updated, so the skipped subtree is averaged in the same way as the
+/// other subtrees. A too large threshold may result in an inaccurate strategy in rarely reached
+/// subtrees because their regrets are not updated. Passing `0.0` disables the pruning, which is
+/// equivalent to [`solve`].
+///
+/// This method returns the exploitability of the obtained strategy.
+#[inline]
+pub fn solve_with_pruning<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+    prune_threshold: f32,
+) -> f32 {
+    let params = SolveParams {
+        max_iterations: max_num_iterations,
+        target_exploitability,
+        print_progress,
+        prune_threshold,
+        ..Default::default()
+    };
+    solve_internal(game, &params).0
+}
+
+/// Performs Discounted CFR algorithm until the given number of iterations or exploitability is
+/// satisfied, or until `cancel` is set.
+///
+/// The `cancel` flag is checked between iterations, so it can be set from another thread (e.g.,
+/// by a stop button of a GUI) to interrupt the solving. Even if the solving is interrupted, the
+/// obtained strategy is finalized in the same way as [`solve`], so the game can be used as a
+/// partially converged solution.
+///
+/// This method returns the number of completed iterations and the exploitability of the obtained
+/// strategy.
+#[inline]
+pub fn solve_with_cancel<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+    cancel: &AtomicBool,
+) -> (u32, f32) {
+    let params = SolveParams {
+        max_iterations: max_num_iterations,
+        target_exploitability,
+        print_progress,
+        cancel: Some(cancel),
+        ..Default::default()
+    };
+    solve_with_config(game, &params)
+}
+
+/// Performs CFR algorithm with the given [`SolveParams`].
+///
+/// Like the other solving functions, each iteration updates the two players in turn (alternating
+/// updates): the update of IP uses the regrets of OOP already updated in the same iteration.
+///
+/// The convergence is checked every `eval_interval` iterations and at the last iteration with the
+/// given `convergence_metric`, and the solving stops when the metric is below or equal to
+/// `target_exploitability`. Regardless of the metric, the exploitability at the start and the end
+/// of the solving is computed by a full best-response pass.
+///
+/// This method returns the number of completed iterations and the exploitability of the obtained
+/// strategy.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let mut game = GameBuilder::new()
+///     .ranges("AA,QQ", "KK")
+///     .flop("Td9d6h")
+///     .turn("2c")
+///     .river("3s")
+///     .pot(100)
+///     .stack(100)
+///     .river_bets("100%", "")
+///     .build()
+///     .unwrap();
+/// game.allocate_memory(false);
+///
+/// let params = SolveParams {
+///     max_iterations: 1000,
+///     target_exploitability: 100.0 * 0.005,
+///     eval_interval: 50,
+///     convergence_metric: ConvergenceMetric::RegretBound,
+///     ..Default::default()
+/// };
+/// let (num_iterations, exploitability) = solve_with_config(&mut game, &params);
+/// assert!(num_iterations <= 1000);
+/// assert!(exploitability.is_finite());
+/// ```
+#[inline]
+pub fn solve_with_config<T: Game>(game: &mut T, params: &SolveParams) -> (u32, f32) {
+    let (exploitability, _, num_iterations) = solve_internal(game, params);
+    (num_iterations, exploitability)
+}
+
+/// Performs external-sampling Monte Carlo CFR algorithm until the given number of iterations or
+/// exploitability is satisfied.
+///
+/// In each iteration, only one chance outcome is sampled at each chance node, and only one action
+/// of the opponent is sampled at each opponent's node, whereas all actions of the traversing
+/// player are traversed. Although each iteration is much cheaper than that of [`solve`], more
+/// iterations are needed to reach the same exploitability. The sampling is deterministic given
+/// the `seed`.
+///
+/// Unlike [`solve`], the cumulative regrets and strategies are not discounted (i.e.,
+/// [`CfrVariant::Vanilla`] is used): a node is updated only in the iterations in which it is
+/// sampled, so discounting only the sampled nodes would weight the iterations differently from
+/// node to node.
+///
+/// Since the exploitability computation traverses the full game tree, the exploitability is
+/// checked only every 100 iterations.
+///
+/// This method returns the exploitability of the obtained strategy.
+#[inline]
+pub fn solve_mccfr<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+    seed: u64,
+) -> f32 {
+    let params = SolveParams {
+        max_iterations: max_num_iterations,
+        target_exploitability,
+        eval_interval: 100,
+        print_progress,
+        variant: CfrVariant::Vanilla,
+        seed: Some(seed),
+        ..Default::default()
+    };
+    solve_internal(game, &params).0
+}
+
+fn solve_internal<T: Game>(game: &mut T, params: &SolveParams) -> (f32, Vec<(u32, f32)>, u32) {
     if game.is_solved() {
         panic!("the game is already solved");
     }
@@ -54,59 +533,278 @@ pub fn solve<T: Game>(
         panic!("the game is not ready");
     }
 
+    if params.eval_interval == 0 {
+        panic!("eval_interval must be positive");
+    }
+
+    let max_num_iterations = params.max_iterations;
+    let metric_name = match params.convergence_metric {
+        ConvergenceMetric::Exploitability => "exploitability",
+        ConvergenceMetric::RegretBound => "regret bound",
+        ConvergenceMetric::PerPlayerExploitability => "max exploitability",
+    };
+
     let mut root = game.root();
-    let mut exploitability = compute_exploitability(game);
+    let mut exploitability = compute_convergence_metric(game, params.convergence_metric, 0);
+    let mut history = vec![(0, exploitability)];
 
-    if print_progress {
+    if params.print_progress {
         print!("iteration: 0 / {max_num_iterations} ");
-        print!("(exploitability = {exploitability:.4e})");
+        print!("({metric_name} = {exploitability:.4e})");
         io::stdout().flush().unwrap();
     }
 
+    let mut num_iterations = 0;
+
     for t in 0..max_num_iterations {
-        if exploitability <= target_exploitability {
+        if t >= params.min_iterations && exploitability <= params.target_exploitability {
             break;
         }
 
-        let params = DiscountParams::new(t);
-
-        // alternating updates
-        for player in 0..2 {
-            let mut result = Vec::with_capacity(game.num_private_hands(player));
-            solve_recursive(
-                result.spare_capacity_mut(),
-                game,
-                &mut root,
-                player,
-                game.initial_weights(player ^ 1),
-                &params,
-            );
+        if params
+            .cancel
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+        {
+            break;
         }
 
-        if (t + 1) % 10 == 0 || t + 1 == max_num_iterations {
-            exploitability = compute_exploitability(game);
+        let factors =
+            DiscountFactors::new(t, &params.variant, params.averaging, params.prune_threshold);
+        solve_iteration(game, &mut root, t, &factors, params.seed);
+        num_iterations = t + 1;
+
+        if (t + 1) % params.eval_interval == 0 || t + 1 == max_num_iterations {
+            if params.validate_reach {
+                if let Err(e) = validate_reach(game) {
+                    panic!("{e}");
+                }
+            }
+
+            exploitability = compute_convergence_metric(game, params.convergence_metric, t + 1);
+            history.push((t + 1, exploitability));
         }
 
-        if print_progress {
+        if params.print_progress {
             print!("\riteration: {} / {} ", t + 1, max_num_iterations);
-            print!("(exploitability = {exploitability:.4e})");
+            print!("({metric_name} = {exploitability:.4e})");
             io::stdout().flush().unwrap();
         }
     }
 
-    if print_progress {
+    if params.print_progress {
         println!();
         io::stdout().flush().unwrap();
     }
 
+    // the exploitability has not been computed yet if the solving was canceled or the regret
+    // bound was used for the checks
+    let is_computed = history.last().unwrap().0 == num_iterations;
+    if !is_computed || params.convergence_metric != ConvergenceMetric::Exploitability {
+        exploitability = compute_exploitability(game);
+        if is_computed && num_iterations > 0 {
+            history.pop();
+        }
+        if !is_computed || num_iterations > 0 {
+            history.push((num_iterations, exploitability));
+        }
+    }
+
     finalize(game);
 
-    exploitability
+    (exploitability, history, num_iterations)
+}
+
+/// Computes the value of the given convergence metric after `num_iterations` iterations.
+///
+/// The regret bound is not defined before the first iteration, so the exploitability is returned
+/// instead in that case.
+fn compute_convergence_metric<T: Game>(
+    game: &T,
+    metric: ConvergenceMetric,
+    num_iterations: u32,
+) -> f32 {
+    match metric {
+        ConvergenceMetric::RegretBound if num_iterations > 0 => {
+            compute_regret_bound(game, num_iterations)
+        }
+        ConvergenceMetric::PerPlayerExploitability => {
+            let exploitability = compute_exploitability_per_player(game);
+            exploitability[0].max(exploitability[1])
+        }
+        _ => compute_exploitability(game),
+    }
+}
+
+/// Computes the regret bound of the current strategy after `num_iterations` iterations.
+///
+/// The regret bound is the sum of the maximum positive cumulative regrets of all information sets
+/// divided by the number of iterations, halved to be on the same scale as
+/// [`compute_exploitability`]. It is a cheap proxy for the convergence because no terminal node
+/// is evaluated. For vanilla CFR, the average regret upper-bounds the exploitability of the
+/// average strategy; since the solver discounts the cumulative regrets, however, this value is
+/// only an estimate that is neither guaranteed to upper-bound the exploitability nor to decrease
+/// monotonically. In practice, it is usually much larger than the true exploitability. This is
+/// the metric used by [`ConvergenceMetric::RegretBound`].
+///
+/// `num_iterations` must be the number of iterations performed so far. Panics if the game is not
+/// ready (e.g., the memory is not allocated or the game is already solved) or if
+/// `num_iterations` is zero.
+pub fn compute_regret_bound<T: Game>(game: &T, num_iterations: u32) -> f32 {
+    if !game.is_ready() {
+        panic!("the game is not ready");
+    }
+
+    if num_iterations == 0 {
+        panic!("num_iterations must be positive");
+    }
+
+    let mut regret_sum = 0.0;
+    regret_sum_recursive(game, &game.root(), 1.0, &mut regret_sum);
+    (regret_sum * 0.5 / num_iterations as f64) as f32
+}
+
+/// Recursive function to sum up the positive cumulative regrets of all information sets.
+///
+/// The regrets of each private hand are weighted by its initial weight, and the regrets of the
+/// representative runouts are counted as many times as the isomorphic runouts they represent.
+fn regret_sum_recursive<T: Game>(game: &T, node: &T::Node, multiplier: f64, sum: &mut f64) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if node.is_chance() {
+        let isomorphic_chances = game.isomorphic_chances(node);
+        for action in 0..node.num_actions() {
+            let num_isomorphic = isomorphic_chances
+                .iter()
+                .filter(|&&index| index as usize == action)
+                .count();
+            let multiplier = multiplier * (1 + num_isomorphic) as f64;
+            regret_sum_recursive(game, &node.play(action), multiplier, sum);
+        }
+        return;
+    }
+
+    let player = node.player();
+    let num_actions = node.num_actions();
+    let weights = game.initial_weights(player);
+    let num_hands = weights.len();
+
+    let regrets = if game.is_compression_enabled() {
+        let decoder = node.regret_scale() / i16::MAX as f32;
+        let src = node.regrets_compressed();
+        src.iter().map(|&x| x as f32 * decoder).collect()
+    } else {
+        node.regrets().to_vec()
+    };
+
+    for (hand, &weight) in weights.iter().enumerate() {
+        let max_regret = (0..num_actions)
+            .map(|action| regrets[action * num_hands + hand])
+            .fold(0.0, f32::max);
+        *sum += multiplier * weight as f64 * max_regret as f64;
+    }
+
+    for action in 0..num_actions {
+        regret_sum_recursive(game, &node.play(action), multiplier, sum);
+    }
+}
+
+/// Checks that the reach probabilities of both players under the current strategy are finite
+/// and between 0 and 1 at every node.
+///
+/// The current strategy is the one obtained by the regret matching of the cumulative regrets with
+/// the locked strategies applied, i.e., the strategy played by the next iteration of the solver.
+/// Returns an error describing the first invalid reach probability found, along with the path to
+/// the node as the indices of the children taken from the root.
+///
+/// If [`SolveParams::validate_reach`] is set, [`solve_with_config`] runs this check at every
+/// convergence check and panics on the error, so that a corrupted strategy (e.g., NaN) is caught
+/// before it silently corrupts the expected values. Panics if the game is not ready.
+pub fn validate_reach<T: Game>(game: &T) -> Result<(), String> {
+    if !game.is_ready() {
+        panic!("the game is not ready");
+    }
+
+    let reach = [0, 1].map(|player| game.initial_weights(player).to_vec());
+    validate_reach_recursive(game, &game.root(), &reach, &mut Vec::new())
+}
+
+/// Recursive function to check the reach probabilities of the subtree.
+fn validate_reach_recursive<T: Game>(
+    game: &T,
+    node: &T::Node,
+    reach: &[Vec<f32>; 2],
+    path: &mut Vec<usize>,
+) -> Result<(), String> {
+    for (player, reach) in reach.iter().enumerate() {
+        if let Some(&r) = reach.iter().find(|&&r| !(0.0..=1.0).contains(&r)) {
+            return Err(format!(
+                "Invalid reach probability of player {player}: {r} (path = {path:?})"
+            ));
+        }
+    }
+
+    if node.is_terminal() {
+        return Ok(());
+    }
+
+    let num_actions = node.num_actions();
+
+    if node.is_chance() {
+        for action in 0..num_actions {
+            path.push(action);
+            validate_reach_recursive(game, &node.play(action), reach, path)?;
+            path.pop();
+        }
+        return Ok(());
+    }
+
+    let player = node.player();
+    let num_hands = game.num_private_hands(player);
+
+    let mut strategy = if game.is_compression_enabled() {
+        regret_matching_compressed(node.regrets_compressed(), num_actions)
+    } else {
+        regret_matching(node.regrets(), num_actions)
+    };
+    apply_locking_strategy(&mut strategy, game.locking_strategy(node));
+
+    for action in 0..num_actions {
+        let mut reach_updated = reach.clone();
+        mul_slice(
+            &mut reach_updated[player],
+            row(&strategy, action, num_hands),
+        );
+        path.push(action);
+        validate_reach_recursive(game, &node.play(action), &reach_updated, path)?;
+        path.pop();
+    }
+
+    Ok(())
 }
 
 /// Proceeds Discounted CFR algorithm for one iteration.
 #[inline]
 pub fn solve_step<T: Game>(game: &T, current_iteration: u32) {
+    solve_step_with_params(game, current_iteration, &DiscountParams::default());
+}
+
+/// Proceeds Discounted CFR algorithm with the given parameters for one iteration.
+#[inline]
+pub fn solve_step_with_params<T: Game>(game: &T, current_iteration: u32, params: &DiscountParams) {
+    solve_step_with_variant(game, current_iteration, &CfrVariant::Discounted(*params));
+}
+
+/// Proceeds the given variant of CFR algorithm for one iteration.
+///
+/// `current_iteration` is the zero-based number of the iteration and determines the discount
+/// factors of the variant. Calling this function with `current_iteration = 0, 1, ..., n - 1` and
+/// then calling [`finalize`] produces exactly the same result as [`solve_with_variant`] with
+/// `max_num_iterations = n` and `target_exploitability = 0.0`, so the caller can schedule the
+/// iterations (e.g., inspect the intermediate strategy or interleave other work) at will.
+#[inline]
+pub fn solve_step_with_variant<T: Game>(game: &T, current_iteration: u32, variant: &CfrVariant) {
     if game.is_solved() {
         panic!("the game is already solved");
     }
@@ -116,30 +814,128 @@ pub fn solve_step<T: Game>(game: &T, current_iteration: u32) {
     }
 
     let mut root = game.root();
-    let params = DiscountParams::new(current_iteration);
+    let factors = DiscountFactors::new(current_iteration, variant, None, 0.0);
+    solve_iteration(game, &mut root, current_iteration, &factors, None);
+}
+
+/// Performs the given number of iterations of Discounted CFR algorithm on the subtree rooted at
+/// `node`, with the reach probabilities of both players at the node fixed to `reach`.
+///
+/// `reach[player]` must include the chance probabilities along the line to the node. The
+/// cumulative regrets and strategies of the subtree are cleared before the first iteration, and
+/// the nodes outside the subtree are not updated.
+pub(crate) fn solve_subtree<T: Game>(
+    game: &T,
+    node: &mut T::Node,
+    reach: &[Vec<f32>; 2],
+    num_iterations: u32,
+) {
+    clear_subtree_recursive(game, node);
+
+    let variant = CfrVariant::Discounted(DiscountParams::default());
+
+    for t in 0..num_iterations {
+        let factors = DiscountFactors::new(t, &variant, None, 0.0);
 
+        // alternating updates
+        for player in 0..2 {
+            let mut result = Vec::with_capacity(game.num_private_hands(player));
+            solve_recursive(
+                result.spare_capacity_mut(),
+                game,
+                node,
+                player,
+                &reach[player ^ 1],
+                &factors,
+                None,
+            );
+        }
+    }
+}
+
+/// Recursive function to clear the cumulative regrets and strategies of the subtree.
+fn clear_subtree_recursive<T: Game>(game: &T, node: &mut T::Node) {
+    if node.is_terminal() || (node.is_chance() && is_runout_only(node)) {
+        return;
+    }
+
+    if !node.is_chance() {
+        if game.is_compression_enabled() {
+            node.strategy_compressed_mut().fill(0);
+            node.regrets_compressed_mut().fill(0);
+            node.set_strategy_scale(0.0);
+            node.set_regret_scale(0.0);
+        } else {
+            node.strategy_mut().fill(0.0);
+            node.regrets_mut().fill(0.0);
+        }
+    }
+
+    for_each_child(node, |action| {
+        clear_subtree_recursive(game, &mut node.play(action));
+    });
+}
+
+/// Performs one iteration of CFR algorithm with alternating updates.
+#[inline]
+fn solve_iteration<T: Game>(
+    game: &T,
+    root: &mut T::Node,
+    current_iteration: u32,
+    factors: &DiscountFactors,
+    seed: Option<u64>,
+) {
     // alternating updates
     for player in 0..2 {
         let mut result = Vec::with_capacity(game.num_private_hands(player));
+        let seed =
+            seed.map(|seed| splitmix64(seed ^ ((current_iteration as u64) << 1 | player as u64)));
         solve_recursive(
             result.spare_capacity_mut(),
             game,
-            &mut root,
+            root,
             player,
             game.initial_weights(player ^ 1),
-            &params,
+            factors,
+            seed,
         );
     }
 }
 
+/// SplitMix64 hash function used for the deterministic sampling.
+#[inline]
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Returns the seed for the child node of the given index.
+#[inline]
+fn child_seed(seed: Option<u64>, index: usize) -> Option<u64> {
+    seed.map(|seed| splitmix64(seed.wrapping_add(index as u64 + 1)))
+}
+
+/// Returns whether the subtree of the chance `node` consists only of chance and terminal nodes
+/// (i.e., the players are all-in).
+#[inline]
+fn is_runout_only<T: GameNode>(node: &T) -> bool {
+    let child = node.play(0);
+    child.is_terminal() || child.is_chance()
+}
+
 /// Recursively solves the counterfactual values.
+///
+/// If `seed` is `Some`, the chance outcomes and the opponent's actions are sampled.
 fn solve_recursive<T: Game>(
     result: &mut [MaybeUninit<f32>],
     game: &T,
     node: &mut T::Node,
     player: usize,
     cfreach: &[f32],
-    params: &DiscountParams,
+    params: &DiscountFactors,
+    seed: Option<u64>,
 ) {
     // return the counterfactual values when the `node` is terminal
     if node.is_terminal() {
@@ -147,16 +943,68 @@ fn solve_recursive<T: Game>(
         return;
     }
 
+    // skip the evaluation of the subtree if the reach probability of the opponent is negligible
+    if params.prune_threshold > 0.0 {
+        let cfreach_sum = cfreach.iter().fold(0.0, |acc, &x| acc + x as f64);
+        if cfreach_sum < params.prune_threshold as f64 {
+            prune_recursive(game, node, player, params);
+            result.iter_mut().for_each(|r| {
+                r.write(0.0);
+            });
+            return;
+        }
+    }
+
     let num_actions = node.num_actions();
     let num_hands = result.len();
 
     // simply recurse when the number of actions is one
     if num_actions == 1 && !node.is_chance() {
         let child = &mut node.play(0);
-        solve_recursive(result, game, child, player, cfreach, params);
+        solve_recursive(result, game, child, player, cfreach, params, seed);
         return;
     }
 
+    // if the `node` is chance and sampling is enabled
+    if node.is_chance() {
+        if let Some(seed) = seed {
+            let isomorphic_chances = game.isomorphic_chances(node);
+            let num_outcomes = num_actions + isomorphic_chances.len();
+            let index = (splitmix64(seed) % num_outcomes as u64) as usize;
+            let action = match index.checked_sub(num_actions) {
+                Some(i) => isomorphic_chances[i] as usize,
+                None => index,
+            };
+
+            // the sampled outcome represents all `num_outcomes` outcomes
+            let mut cfreach_updated = cfreach.to_vec();
+            let scale = num_outcomes as f32 / game.chance_factor(node) as f32;
+            cfreach_updated.iter_mut().for_each(|r| *r *= scale);
+
+            let mut cfv = Vec::with_capacity(num_hands);
+            solve_recursive(
+                cfv.spare_capacity_mut(),
+                game,
+                &mut node.play(action),
+                player,
+                &cfreach_updated,
+                params,
+                child_seed(Some(seed), index),
+            );
+            unsafe { cfv.set_len(num_hands) };
+
+            if index >= num_actions {
+                let swap_list = &game.isomorphic_swap(node, index - num_actions)[player];
+                apply_swap(&mut cfv, swap_list);
+            }
+
+            result.iter_mut().zip(&cfv).for_each(|(r, &v)| {
+                r.write(v);
+            });
+            return;
+        }
+    }
+
     // allocate memory for storing the counterfactual values
     #[cfg(feature = "custom-alloc")]
     let cfv_actions = MutexLike::new(Vec::with_capacity_in(num_actions * num_hands, StackAlloc));
@@ -186,6 +1034,7 @@ fn solve_recursive<T: Game>(
                 player,
                 &cfreach_updated,
                 params,
+                None,
             );
         });
 
@@ -233,6 +1082,7 @@ fn solve_recursive<T: Game>(
                 player,
                 cfreach,
                 params,
+                child_seed(seed, action),
             );
         });
 
@@ -244,78 +1094,14 @@ fn solve_recursive<T: Game>(
         };
 
         // node-locking
-        let locking = game.locking_strategy(node);
-        apply_locking_strategy(&mut strategy, locking);
+        apply_locking_strategy(&mut strategy, game.locking_strategy(node));
 
         // sum up the counterfactual values
         let mut cfv_actions = cfv_actions.lock();
         unsafe { cfv_actions.set_len(num_actions * num_hands) };
         let result = fma_slices_uninit(result, &strategy, &cfv_actions);
 
-        if game.is_compression_enabled() {
-            // update the cumulative strategy
-            let scale = node.strategy_scale();
-            let decoder = params.gamma_t * scale / u16::MAX as f32;
-            let cum_strategy = node.strategy_compressed_mut();
-
-            strategy.iter_mut().zip(&*cum_strategy).for_each(|(x, y)| {
-                *x += (*y as f32) * decoder;
-            });
-
-            if !locking.is_empty() {
-                strategy.iter_mut().zip(locking).for_each(|(d, s)| {
-                    if s.is_sign_positive() {
-                        *d = 0.0;
-                    }
-                })
-            }
-
-            let new_scale = encode_unsigned_slice(cum_strategy, &strategy);
-            node.set_strategy_scale(new_scale);
-
-            // update the cumulative regret
-            let scale = node.regret_scale();
-            let alpha_decoder = params.alpha_t * scale / i16::MAX as f32;
-            let beta_decoder = params.beta_t * scale / i16::MAX as f32;
-            let cum_regret = node.regrets_compressed_mut();
-
-            cfv_actions.iter_mut().zip(&*cum_regret).for_each(|(x, y)| {
-                *x += *y as f32 * if *y >= 0 { alpha_decoder } else { beta_decoder };
-            });
-
-            cfv_actions.chunks_exact_mut(num_hands).for_each(|row| {
-                sub_slice(row, result);
-            });
-
-            if !locking.is_empty() {
-                cfv_actions.iter_mut().zip(locking).for_each(|(d, s)| {
-                    if s.is_sign_positive() {
-                        *d = 0.0;
-                    }
-                })
-            }
-
-            let new_scale = encode_signed_slice(cum_regret, &cfv_actions);
-            node.set_regret_scale(new_scale);
-        } else {
-            // update the cumulative strategy
-            let gamma = params.gamma_t;
-            let cum_strategy = node.strategy_mut();
-            cum_strategy.iter_mut().zip(&strategy).for_each(|(x, y)| {
-                *x = *x * gamma + *y;
-            });
-
-            // update the cumulative regret
-            let (alpha, beta) = (params.alpha_t, params.beta_t);
-            let cum_regret = node.regrets_mut();
-            cum_regret.iter_mut().zip(&*cfv_actions).for_each(|(x, y)| {
-                let coef = if x.is_sign_positive() { alpha } else { beta };
-                *x = *x * coef + *y;
-            });
-            cum_regret.chunks_exact_mut(num_hands).for_each(|row| {
-                sub_slice(row, result);
-            });
-        }
+        update_cumulative(game, node, &mut strategy, &mut cfv_actions, result, params);
     }
     // if the current player is not `player`
     else {
@@ -336,6 +1122,48 @@ fn solve_recursive<T: Game>(
             mul_slice(row, cfreach);
         });
 
+        // sample an action in proportion to the total reach probability
+        if let Some(seed) = seed {
+            let reach_sums = cfreach_actions
+                .chunks_exact(row_size)
+                .map(|row| row.iter().fold(0.0, |acc, &x| acc + x as f64))
+                .collect::<Vec<_>>();
+            let total = reach_sums.iter().sum::<f64>();
+
+            if total == 0.0 {
+                result.iter_mut().for_each(|r| {
+                    r.write(0.0);
+                });
+                return;
+            }
+
+            let mut threshold = (splitmix64(seed) >> 11) as f64 / (1u64 << 53) as f64 * total;
+            let mut action = reach_sums.iter().rposition(|&sum| sum > 0.0).unwrap();
+            for (i, &sum) in reach_sums.iter().enumerate() {
+                if sum > 0.0 && threshold < sum {
+                    action = i;
+                    break;
+                }
+                threshold -= sum;
+            }
+
+            // importance weighting by the sampling probability
+            let scale = (total / reach_sums[action]) as f32;
+            let mut cfreach_sampled = row(&cfreach_actions, action, row_size).to_vec();
+            cfreach_sampled.iter_mut().for_each(|r| *r *= scale);
+
+            solve_recursive(
+                result,
+                game,
+                &mut node.play(action),
+                player,
+                &cfreach_sampled,
+                params,
+                child_seed(Some(seed), action),
+            );
+            return;
+        }
+
         // compute the counterfactual values of each action
         for_each_child(node, |action| {
             solve_recursive(
@@ -345,6 +1173,7 @@ fn solve_recursive<T: Game>(
                 player,
                 row(&cfreach_actions, action, row_size),
                 params,
+                None,
             );
         });
 
@@ -355,6 +1184,121 @@ fn solve_recursive<T: Game>(
     }
 }
 
+/// Updates the cumulative strategy and the cumulative regrets of the player `node`.
+///
+/// `strategy` is the current strategy with the locked strategy applied, `cfv_actions` is the
+/// counterfactual values of each action, and `result` is the counterfactual values of the node.
+/// Both `strategy` and `cfv_actions` are used as scratch buffers.
+fn update_cumulative<T: Game>(
+    game: &T,
+    node: &mut T::Node,
+    strategy: &mut [f32],
+    cfv_actions: &mut [f32],
+    result: &[f32],
+    params: &DiscountFactors,
+) {
+    let num_hands = result.len();
+    let locking = game.locking_strategy(node);
+
+    if game.is_compression_enabled() {
+        // update the cumulative strategy
+        let scale = node.strategy_scale();
+        let decoder = params.gamma_t * scale / u16::MAX as f32;
+        let cum_strategy = node.strategy_compressed_mut();
+
+        strategy.iter_mut().zip(&*cum_strategy).for_each(|(x, y)| {
+            *x += (*y as f32) * decoder;
+        });
+
+        if !locking.is_empty() {
+            strategy.iter_mut().zip(locking).for_each(|(d, s)| {
+                if s.is_sign_positive() {
+                    *d = 0.0;
+                }
+            })
+        }
+
+        let new_scale = encode_unsigned_slice(cum_strategy, strategy);
+        node.set_strategy_scale(new_scale);
+
+        // update the cumulative regret
+        let scale = node.regret_scale();
+        let alpha_decoder = params.alpha_t * scale / i16::MAX as f32;
+        let beta_decoder = params.beta_t * scale / i16::MAX as f32;
+        let cum_regret = node.regrets_compressed_mut();
+
+        cfv_actions.iter_mut().zip(&*cum_regret).for_each(|(x, y)| {
+            *x += *y as f32 * if *y >= 0 { alpha_decoder } else { beta_decoder };
+        });
+
+        cfv_actions.chunks_exact_mut(num_hands).for_each(|row| {
+            sub_slice(row, result);
+        });
+
+        if !locking.is_empty() {
+            cfv_actions.iter_mut().zip(locking).for_each(|(d, s)| {
+                if s.is_sign_positive() {
+                    *d = 0.0;
+                }
+            })
+        }
+
+        let new_scale = encode_signed_slice(cum_regret, cfv_actions);
+        node.set_regret_scale(new_scale);
+    } else {
+        // update the cumulative strategy
+        let gamma = params.gamma_t;
+        let cum_strategy = node.strategy_mut();
+        cum_strategy.iter_mut().zip(&*strategy).for_each(|(x, y)| {
+            *x = *x * gamma + *y;
+        });
+
+        // update the cumulative regret
+        let (alpha, beta) = (params.alpha_t, params.beta_t);
+        let cum_regret = node.regrets_mut();
+        cum_regret.iter_mut().zip(&*cfv_actions).for_each(|(x, y)| {
+            let coef = if x.is_sign_positive() { alpha } else { beta };
+            *x = *x * coef + *y;
+        });
+        cum_regret.chunks_exact_mut(num_hands).for_each(|row| {
+            sub_slice(row, result);
+        });
+    }
+}
+
+/// Updates the player nodes of `player` in the pruned subtree rooted at `node`.
+///
+/// The counterfactual values of the pruned subtree are treated as zero, so the cumulative regrets
+/// are only discounted, while the current strategy is accumulated into the cumulative strategy as
+/// usual. This keeps the discounting and the averaging consistent with the unpruned nodes; only
+/// the evaluation of the terminal nodes is skipped.
+fn prune_recursive<T: Game>(game: &T, node: &mut T::Node, player: usize, params: &DiscountFactors) {
+    if node.is_terminal() || (node.is_chance() && is_runout_only(node)) {
+        return;
+    }
+
+    let num_actions = node.num_actions();
+
+    if !node.is_chance() && node.player() == player && num_actions > 1 {
+        let num_hands = game.num_private_hands(player);
+
+        let mut strategy = if game.is_compression_enabled() {
+            regret_matching_compressed(node.regrets_compressed(), num_actions)
+        } else {
+            regret_matching(node.regrets(), num_actions)
+        };
+        apply_locking_strategy(&mut strategy, game.locking_strategy(node));
+
+        let mut cfv_actions = vec![0.0; num_actions * num_hands];
+        let result = vec![0.0; num_hands];
+        update_cumulative(game, node, &mut strategy, &mut cfv_actions, &result, params);
+    }
+
+    for_each_child(node, |action| {
+        prune_recursive(game, &mut node.play(action), player, params);
+    });
+}
+
 /// Computes the strategy by regret-matching algorithm.
 #[cfg(feature = "custom-alloc")]
 #[inline]
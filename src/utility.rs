@@ -38,6 +38,37 @@ pub(crate) fn into_par_iter(range: std::ops::Range<usize>) -> std::ops::Range<us
     range
 }
 
+/// Executes `op` in a dedicated thread pool with the given number of threads.
+///
+/// All the parallel computations invoked in `op` (e.g., building the game tree, allocating the
+/// memory, and solving) use this thread pool instead of the global one. The setting is scoped, not
+/// per-game: it does not affect the computations outside of `op`, and the thread pool is dropped
+/// when `op` returns. If `num_threads` is `0`, the default number of threads of [rayon] is used.
+///
+/// Returns an error if the thread pool could not be created.
+///
+/// [rayon]: https://github.com/rayon-rs/rayon
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let num_threads = with_num_threads(2, rayon::current_num_threads).unwrap();
+/// assert_eq!(num_threads, 2);
+/// ```
+#[cfg(feature = "rayon")]
+pub fn with_num_threads<R, OP>(num_threads: usize, op: OP) -> Result<R, String>
+where
+    R: Send,
+    OP: FnOnce() -> R + Send,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(pool.install(op))
+}
+
 #[inline]
 pub(crate) fn max(x: f32, y: f32) -> f32 {
     if x > y {
@@ -290,9 +321,27 @@ pub fn compute_exploitability<T: Game>(game: &T) -> f32 {
     }
 }
 
+/// Computes the exploitability of the current strategy for each player.
+///
+/// The `i`-th element is the amount the player `i` can gain by deviating to the best response
+/// against the current strategy of the opponent, i.e., the difference between the best-response
+/// EV and the current EV of the player `i`. When the game is not raked, the average of the two
+/// values equals the return value of [`compute_exploitability`].
+#[inline]
+pub fn compute_exploitability_per_player<T: Game>(game: &T) -> [f32; 2] {
+    if !game.is_ready() && !game.is_solved() {
+        panic!("the game is not ready");
+    }
+
+    let mes_ev = compute_mes_ev(game);
+    let current_ev = compute_current_ev(game);
+    [mes_ev[0] - current_ev[0], mes_ev[1] - current_ev[1]]
+}
+
 /// Computes the expected values of the current strategy of each player.
 ///
-/// The bias, i.e., (starting pot) / 2, is already subtracted to increase the significant figures.
+/// The bias, i.e., the amount of the starting pot invested by each player ((starting pot) / 2 by
+/// default), is already subtracted to increase the significant figures.
 /// This treatment makes the return value zero-sum when not raked.
 #[inline]
 pub fn compute_current_ev<T: Game>(game: &T) -> [f32; 2] {
@@ -325,7 +374,8 @@ pub fn compute_current_ev<T: Game>(game: &T) -> [f32; 2] {
 
 /// Computes the expected values of the MES (Maximally Exploitative Strategy) of each player.
 ///
-/// The bias, i.e., (starting pot) / 2, is already subtracted to increase the significant figures.
+/// The bias, i.e., the amount of the starting pot invested by each player ((starting pot) / 2 by
+/// default), is already subtracted to increase the significant figures.
 /// Therefore, the average of the return value corresponds to the exploitability value if not raked.
 #[inline]
 pub fn compute_mes_ev<T: Game>(game: &T) -> [f32; 2] {
@@ -550,8 +600,8 @@ fn compute_cfvalue_recursive<T: Game>(
         sum_slices_uninit(result, &cfv_actions);
     }
 
-    // save the counterfactual values for IP
-    if save_cfvalues && node.has_cfvalues_ip() && player == 1 {
+    // save the counterfactual values for the player who is not acting first on the street
+    if save_cfvalues && node.has_cfvalues_ip() && player != node.player() {
         let result = unsafe { &*(result as *const _ as *const [f32]) };
         if game.is_compression_enabled() {
             let cfv_scale = encode_signed_slice(node.cfvalues_ip_compressed_mut(), result);
@@ -563,7 +613,7 @@ fn compute_cfvalue_recursive<T: Game>(
 }
 
 /// The recursive helper function for computing the counterfactual values of best response.
-fn compute_best_cfv_recursive<T: Game>(
+pub(crate) fn compute_best_cfv_recursive<T: Game>(
     result: &mut [MaybeUninit<f32>],
     game: &T,
     node: &T::Node,
@@ -41,7 +41,7 @@ impl Game for KuhnGame {
     type Node = KuhnNode;
 
     #[inline]
-    fn root(&self) -> MutexGuardLike<Self::Node> {
+    fn root(&self) -> MutexGuardLike<'_, Self::Node> {
         self.root.lock()
     }
 
@@ -55,6 +55,7 @@ impl Game for KuhnGame {
         &self.initial_weight
     }
 
+    #[allow(clippy::needless_range_loop)]
     fn evaluate(
         &self,
         result: &mut [MaybeUninit<f32>],
@@ -206,7 +207,7 @@ impl GameNode for KuhnNode {
     }
 
     #[inline]
-    fn play(&self, action: usize) -> MutexGuardLike<Self> {
+    fn play(&self, action: usize) -> MutexGuardLike<'_, Self> {
         self.children[action].1.lock()
     }
 